@@ -0,0 +1,13 @@
+CREATE TABLE IF NOT EXISTS payments (
+    id UUID PRIMARY KEY,
+    group_id UUID NOT NULL,
+    from_person_id UUID NOT NULL,
+    to_person_id UUID NOT NULL,
+    amount INT NOT NULL CHECK (amount > 0),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK (from_person_id <> to_person_id),
+    FOREIGN KEY (group_id) REFERENCES groups(id) ON DELETE CASCADE,
+    FOREIGN KEY (from_person_id) REFERENCES persons(id) ON DELETE CASCADE,
+    FOREIGN KEY (to_person_id) REFERENCES persons(id) ON DELETE CASCADE
+);
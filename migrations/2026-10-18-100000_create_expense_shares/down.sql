@@ -0,0 +1,2 @@
+DROP TABLE expense_shares;
+ALTER TABLE expenses DROP COLUMN split_mode;
\ No newline at end of file
@@ -0,0 +1,14 @@
+ALTER TABLE expenses
+    ADD COLUMN split_mode VARCHAR(20) NOT NULL DEFAULT 'RESOURCES'
+    CHECK (split_mode IN ('EQUAL', 'RESOURCES', 'SHARES', 'PERCENTAGE', 'EXACT'));
+
+CREATE TABLE IF NOT EXISTS expense_shares (
+    expense_id UUID NOT NULL,
+    person_id UUID NOT NULL,
+    value INT NOT NULL CHECK (value >= 0),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (expense_id, person_id),
+    FOREIGN KEY (expense_id) REFERENCES expenses(id) ON DELETE CASCADE,
+    FOREIGN KEY (person_id) REFERENCES persons(id) ON DELETE CASCADE
+);
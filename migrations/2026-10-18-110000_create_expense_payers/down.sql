@@ -0,0 +1,13 @@
+ALTER TABLE expenses ADD COLUMN person_id UUID REFERENCES persons(id) ON DELETE CASCADE;
+
+-- Keep the biggest payer of each expense
+UPDATE expenses SET person_id = (
+    SELECT person_id FROM expense_payers
+    WHERE expense_id = expenses.id
+    ORDER BY amount DESC, person_id
+    LIMIT 1
+);
+
+DELETE FROM expenses WHERE person_id IS NULL;
+ALTER TABLE expenses ALTER COLUMN person_id SET NOT NULL;
+DROP TABLE expense_payers;
@@ -0,0 +1,16 @@
+CREATE TABLE IF NOT EXISTS expense_payers (
+    expense_id UUID NOT NULL,
+    person_id UUID NOT NULL,
+    amount INT NOT NULL CHECK (amount > 0),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (expense_id, person_id),
+    FOREIGN KEY (expense_id) REFERENCES expenses(id) ON DELETE CASCADE,
+    FOREIGN KEY (person_id) REFERENCES persons(id) ON DELETE CASCADE
+);
+
+-- Every existing expense was paid entirely by its single payer
+INSERT INTO expense_payers (expense_id, person_id, amount, created_at, updated_at)
+SELECT id, person_id, amount, created_at, updated_at FROM expenses;
+
+ALTER TABLE expenses DROP COLUMN person_id;
@@ -0,0 +1,3 @@
+DROP TABLE exchange_rates;
+ALTER TABLE expenses DROP COLUMN currency, DROP COLUMN exchange_rate;
+ALTER TABLE groups DROP COLUMN currency;
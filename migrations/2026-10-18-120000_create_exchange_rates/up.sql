@@ -0,0 +1,17 @@
+ALTER TABLE groups
+    ADD COLUMN currency VARCHAR(3) NOT NULL DEFAULT 'EUR' CHECK (currency ~ '^[A-Z]{3}$');
+
+-- The rate converts the expense's amount to its group's currency, as it was when the expense was added
+ALTER TABLE expenses
+    ADD COLUMN currency VARCHAR(3) NOT NULL DEFAULT 'EUR' CHECK (currency ~ '^[A-Z]{3}$'),
+    ADD COLUMN exchange_rate DOUBLE PRECISION NOT NULL DEFAULT 1 CHECK (exchange_rate > 0);
+
+CREATE TABLE IF NOT EXISTS exchange_rates (
+    from_currency VARCHAR(3) NOT NULL CHECK (from_currency ~ '^[A-Z]{3}$'),
+    to_currency VARCHAR(3) NOT NULL CHECK (to_currency ~ '^[A-Z]{3}$'),
+    rate DOUBLE PRECISION NOT NULL CHECK (rate > 0),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (from_currency, to_currency),
+    CHECK (from_currency <> to_currency)
+);
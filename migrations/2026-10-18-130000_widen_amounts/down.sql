@@ -0,0 +1,5 @@
+ALTER TABLE persons ALTER COLUMN resources TYPE INT;
+ALTER TABLE payments ALTER COLUMN amount TYPE INT;
+ALTER TABLE expense_shares ALTER COLUMN value TYPE INT;
+ALTER TABLE expense_payers ALTER COLUMN amount TYPE INT;
+ALTER TABLE expenses ALTER COLUMN amount TYPE INT;
@@ -0,0 +1,6 @@
+-- Amounts are stored in the minor unit of their currency (e.g. cents)
+ALTER TABLE expenses ALTER COLUMN amount TYPE BIGINT;
+ALTER TABLE expense_payers ALTER COLUMN amount TYPE BIGINT;
+ALTER TABLE expense_shares ALTER COLUMN value TYPE BIGINT;
+ALTER TABLE payments ALTER COLUMN amount TYPE BIGINT;
+ALTER TABLE persons ALTER COLUMN resources TYPE BIGINT;
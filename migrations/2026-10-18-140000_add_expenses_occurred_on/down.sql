@@ -0,0 +1,2 @@
+DROP INDEX expenses_group_id_occurred_on_idx;
+ALTER TABLE expenses DROP COLUMN occurred_on;
@@ -0,0 +1,6 @@
+ALTER TABLE expenses ADD COLUMN occurred_on DATE NOT NULL DEFAULT CURRENT_DATE;
+
+-- The existing expenses were typed in when they occurred, as far as we know
+UPDATE expenses SET occurred_on = created_at::date;
+
+CREATE INDEX expenses_group_id_occurred_on_idx ON expenses (group_id, occurred_on);
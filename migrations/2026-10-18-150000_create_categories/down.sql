@@ -0,0 +1,2 @@
+ALTER TABLE expenses DROP COLUMN category_id;
+DROP TABLE categories;
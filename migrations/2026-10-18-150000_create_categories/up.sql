@@ -0,0 +1,25 @@
+-- The built-in categories have no group, the custom ones belong to a single group
+CREATE TABLE IF NOT EXISTS categories (
+    id UUID PRIMARY KEY,
+    group_id UUID,
+    name VARCHAR(50) NOT NULL CHECK (char_length(name) > 0),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    FOREIGN KEY (group_id) REFERENCES groups(id) ON DELETE CASCADE
+);
+
+CREATE UNIQUE INDEX categories_group_id_name_idx ON categories (group_id, name);
+CREATE UNIQUE INDEX categories_built_in_name_idx ON categories (name) WHERE group_id IS NULL;
+
+INSERT INTO categories (id, name) VALUES
+    ('00000000-0000-0000-0000-000000000001', 'Food'),
+    ('00000000-0000-0000-0000-000000000002', 'Transport'),
+    ('00000000-0000-0000-0000-000000000003', 'Housing'),
+    ('00000000-0000-0000-0000-000000000004', 'Leisure'),
+    ('00000000-0000-0000-0000-000000000005', 'Health'),
+    ('00000000-0000-0000-0000-000000000006', 'Shopping'),
+    ('00000000-0000-0000-0000-000000000007', 'Other');
+
+-- A category can't be deleted while expenses use it
+ALTER TABLE expenses
+    ADD COLUMN category_id UUID REFERENCES categories(id) ON DELETE RESTRICT;
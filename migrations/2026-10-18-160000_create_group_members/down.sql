@@ -0,0 +1,2 @@
+ALTER TABLE persons DROP COLUMN user_id;
+DROP TABLE group_members;
@@ -0,0 +1,20 @@
+-- The users sharing a group, its creator included
+CREATE TABLE IF NOT EXISTS group_members (
+    group_id UUID NOT NULL,
+    user_id UUID NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (group_id, user_id),
+    FOREIGN KEY (group_id) REFERENCES groups(id) ON DELETE CASCADE,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE INDEX group_members_user_id_idx ON group_members (user_id);
+
+INSERT INTO group_members (group_id, user_id)
+SELECT id, user_id FROM groups;
+
+-- The account of the person, if they have one
+ALTER TABLE persons ADD COLUMN user_id UUID REFERENCES users(id) ON DELETE SET NULL;
+
+CREATE UNIQUE INDEX persons_group_id_user_id_idx ON persons (group_id, user_id);
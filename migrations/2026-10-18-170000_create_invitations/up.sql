@@ -0,0 +1,18 @@
+-- Only a hash of the invitations' secret is stored
+CREATE TABLE IF NOT EXISTS invitations (
+    id UUID PRIMARY KEY,
+    group_id UUID NOT NULL,
+    created_by UUID NOT NULL,
+    secret_hash VARCHAR(255) NOT NULL CHECK (char_length(secret_hash) > 0),
+    expires_at TIMESTAMPTZ NOT NULL,
+    accepted_by UUID,
+    accepted_at TIMESTAMPTZ,
+    revoked_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    FOREIGN KEY (group_id) REFERENCES groups(id) ON DELETE CASCADE,
+    FOREIGN KEY (created_by) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (accepted_by) REFERENCES users(id) ON DELETE SET NULL
+);
+
+CREATE INDEX invitations_group_id_idx ON invitations (group_id);
@@ -0,0 +1,2 @@
+ALTER TABLE invitations DROP COLUMN role;
+ALTER TABLE group_members DROP COLUMN role;
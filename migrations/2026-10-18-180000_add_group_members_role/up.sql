@@ -0,0 +1,13 @@
+ALTER TABLE group_members
+    ADD COLUMN role VARCHAR(10) NOT NULL DEFAULT 'EDITOR'
+    CHECK (role IN ('OWNER', 'EDITOR', 'VIEWER'));
+
+-- The creators own their groups
+UPDATE group_members m SET role = 'OWNER'
+FROM groups g
+WHERE g.id = m.group_id AND g.user_id = m.user_id;
+
+-- The role given to whoever accepts an invitation
+ALTER TABLE invitations
+    ADD COLUMN role VARCHAR(10) NOT NULL DEFAULT 'EDITOR'
+    CHECK (role IN ('OWNER', 'EDITOR', 'VIEWER'));
@@ -0,0 +1,16 @@
+-- An append-only log of the changes made to the groups.
+-- The entries outlive the rows they describe, so the entity isn't a foreign key.
+CREATE TABLE IF NOT EXISTS activities (
+    id BIGSERIAL PRIMARY KEY,
+    group_id UUID NOT NULL,
+    user_id UUID,
+    action VARCHAR(10) NOT NULL CHECK (action IN ('CREATE', 'UPDATE', 'DELETE')),
+    entity_type VARCHAR(20) NOT NULL CHECK (char_length(entity_type) > 0),
+    entity_id UUID NOT NULL,
+    before JSONB,
+    after JSONB,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE SET NULL
+);
+
+CREATE INDEX activities_group_id_idx ON activities (group_id, id DESC);
@@ -0,0 +1,8 @@
+DELETE FROM activities WHERE action = 'RESTORE';
+ALTER TABLE activities DROP CONSTRAINT activities_action_check;
+ALTER TABLE activities ADD CONSTRAINT activities_action_check
+    CHECK (action IN ('CREATE', 'UPDATE', 'DELETE'));
+
+ALTER TABLE expenses DROP COLUMN deleted_at;
+ALTER TABLE persons DROP COLUMN deleted_at;
+ALTER TABLE groups DROP COLUMN deleted_at;
@@ -0,0 +1,12 @@
+-- The removed groups, persons and expenses stay in the trash until they're restored or purged
+ALTER TABLE groups ADD COLUMN deleted_at TIMESTAMPTZ;
+ALTER TABLE persons ADD COLUMN deleted_at TIMESTAMPTZ;
+ALTER TABLE expenses ADD COLUMN deleted_at TIMESTAMPTZ;
+
+CREATE INDEX groups_deleted_at_idx ON groups (deleted_at) WHERE deleted_at IS NOT NULL;
+CREATE INDEX persons_deleted_at_idx ON persons (deleted_at) WHERE deleted_at IS NOT NULL;
+CREATE INDEX expenses_deleted_at_idx ON expenses (deleted_at) WHERE deleted_at IS NOT NULL;
+
+ALTER TABLE activities DROP CONSTRAINT activities_action_check;
+ALTER TABLE activities ADD CONSTRAINT activities_action_check
+    CHECK (action IN ('CREATE', 'UPDATE', 'DELETE', 'RESTORE'));
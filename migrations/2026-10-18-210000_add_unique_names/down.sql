@@ -0,0 +1,2 @@
+DROP INDEX persons_group_id_name_idx;
+DROP INDEX groups_user_id_name_idx;
@@ -0,0 +1,19 @@
+-- The names made unique by the application may have been duplicated by concurrent requests:
+-- the latest duplicates get a numbered suffix before the indexes are created
+UPDATE groups SET name = left(groups.name, 90) || ' (' || duplicates.rank || ')'
+FROM (
+    SELECT id, ROW_NUMBER() OVER (PARTITION BY user_id, name ORDER BY created_at, id) AS rank
+    FROM groups WHERE deleted_at IS NULL
+) AS duplicates
+WHERE groups.id = duplicates.id AND duplicates.rank > 1;
+
+UPDATE persons SET name = left(persons.name, 90) || ' (' || duplicates.rank || ')'
+FROM (
+    SELECT id, ROW_NUMBER() OVER (PARTITION BY group_id, name ORDER BY created_at, id) AS rank
+    FROM persons WHERE deleted_at IS NULL
+) AS duplicates
+WHERE persons.id = duplicates.id AND duplicates.rank > 1;
+
+-- A name is only taken by the rows out of the trash
+CREATE UNIQUE INDEX groups_user_id_name_idx ON groups (user_id, name) WHERE deleted_at IS NULL;
+CREATE UNIQUE INDEX persons_group_id_name_idx ON persons (group_id, name) WHERE deleted_at IS NULL;
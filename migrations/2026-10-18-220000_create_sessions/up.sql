@@ -0,0 +1,16 @@
+-- A session lasts as long as its refresh token is rotated before expiring.
+-- Only a hash of the refresh token's secret is stored, along with the one it had before its last
+-- rotation, so that a stolen refresh token being replayed can be told apart from a mistyped one
+CREATE TABLE IF NOT EXISTS sessions (
+    id UUID PRIMARY KEY,
+    user_id UUID NOT NULL,
+    secret_hash VARCHAR(255) NOT NULL CHECK (char_length(secret_hash) > 0),
+    previous_secret_hash VARCHAR(255),
+    expires_at TIMESTAMPTZ NOT NULL,
+    revoked_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE INDEX sessions_user_id_idx ON sessions (user_id);
@@ -0,0 +1,4 @@
+-- The passwords hashed before were all salted with the same global salt: they're left without a
+-- date, to be hashed again with a salt of their own when their users log in
+ALTER TABLE users ADD COLUMN password_hashed_at TIMESTAMPTZ;
+ALTER TABLE users ALTER COLUMN password_hashed_at SET DEFAULT NOW();
@@ -0,0 +1,20 @@
+-- The users who signed up before couldn't verify their email address: it's taken as verified,
+-- so that they can still invite others and join groups
+ALTER TABLE users ADD COLUMN email_verified_at TIMESTAMPTZ;
+UPDATE users SET email_verified_at = created_at;
+
+-- A verification proves its user reads the emails sent to this address, once, before it expires.
+-- Only a hash of the token's secret is stored, the user receives the token by email
+CREATE TABLE IF NOT EXISTS email_verifications (
+    id UUID PRIMARY KEY,
+    user_id UUID NOT NULL,
+    email VARCHAR(255) NOT NULL,
+    secret_hash VARCHAR(255) NOT NULL CHECK (char_length(secret_hash) > 0),
+    expires_at TIMESTAMPTZ NOT NULL,
+    used_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE INDEX email_verifications_user_id_idx ON email_verifications (user_id);
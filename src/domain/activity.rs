@@ -0,0 +1,130 @@
+use std::{fmt, str::FromStr};
+
+/// What was done to a row of a group.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Create,
+    Update,
+    /// Moved to the trash.
+    Delete,
+    /// Moved back from the trash.
+    Restore,
+}
+
+impl Action {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Action::Create => "CREATE",
+            Action::Update => "UPDATE",
+            Action::Delete => "DELETE",
+            Action::Restore => "RESTORE",
+        }
+    }
+}
+
+impl FromStr for Action {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "CREATE" => Ok(Action::Create),
+            "UPDATE" => Ok(Action::Update),
+            "DELETE" => Ok(Action::Delete),
+            "RESTORE" => Ok(Action::Restore),
+            _ => Err(anyhow::anyhow!("Unknown action ({})", s)),
+        }
+    }
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The kind of row an action was done to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Entity {
+    Group,
+    Member,
+    Invitation,
+    Person,
+    Category,
+    Expense,
+    /// The persons who paid an expense, changed together.
+    ExpensePayers,
+    /// The persons covered by an expense, changed together.
+    ExpenseShares,
+    Payment,
+}
+
+impl Entity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Entity::Group => "GROUP",
+            Entity::Member => "MEMBER",
+            Entity::Invitation => "INVITATION",
+            Entity::Person => "PERSON",
+            Entity::Category => "CATEGORY",
+            Entity::Expense => "EXPENSE",
+            Entity::ExpensePayers => "EXPENSE_PAYERS",
+            Entity::ExpenseShares => "EXPENSE_SHARES",
+            Entity::Payment => "PAYMENT",
+        }
+    }
+}
+
+impl FromStr for Entity {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "GROUP" => Ok(Entity::Group),
+            "MEMBER" => Ok(Entity::Member),
+            "INVITATION" => Ok(Entity::Invitation),
+            "PERSON" => Ok(Entity::Person),
+            "CATEGORY" => Ok(Entity::Category),
+            "EXPENSE" => Ok(Entity::Expense),
+            "EXPENSE_PAYERS" => Ok(Entity::ExpensePayers),
+            "EXPENSE_SHARES" => Ok(Entity::ExpenseShares),
+            "PAYMENT" => Ok(Entity::Payment),
+            _ => Err(anyhow::anyhow!("Unknown entity type ({})", s)),
+        }
+    }
+}
+
+impl fmt::Display for Entity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn activities_should_be_stored_as_strings() {
+        for action in &[
+            Action::Create,
+            Action::Update,
+            Action::Delete,
+            Action::Restore,
+        ] {
+            assert_eq!(Ok(*action), action.to_string().parse().map_err(|_| ()));
+        }
+        for entity in &[
+            Entity::Group,
+            Entity::Member,
+            Entity::Invitation,
+            Entity::Person,
+            Entity::Category,
+            Entity::Expense,
+            Entity::ExpensePayers,
+            Entity::ExpenseShares,
+            Entity::Payment,
+        ] {
+            assert_eq!(Ok(*entity), entity.to_string().parse().map_err(|_| ()));
+        }
+    }
+}
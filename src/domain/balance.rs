@@ -0,0 +1,510 @@
+use super::{
+    currency,
+    expense::{Participant, Payer, SplitMode},
+};
+
+/// A person sharing the group's expenses.
+#[derive(Debug, Clone, Copy)]
+pub struct Member {
+    pub id: uuid::Uuid,
+    /// Weight of this person's share in the expenses split according to resources.
+    pub resources: i64,
+}
+
+/// An amount paid by some members for some members of the group.
+#[derive(Debug, Clone, Default)]
+pub struct Spending {
+    pub payers: Vec<Payer>,
+    pub amount: i64,
+    pub mode: SplitMode,
+    /// The members covered by this spending. Every member when empty.
+    pub participants: Vec<Participant>,
+}
+
+impl Spending {
+    /// Convert this spending into another currency.
+    /// The payers' amounts and the exact amounts still add up to the converted amount.
+    pub fn convert(&self, rate: f64) -> Spending {
+        let amount = currency::convert(self.amount, rate);
+        let payers = self
+            .payers
+            .iter()
+            .map(|p| (p.person_id, p.amount))
+            .collect::<Vec<_>>();
+        let participants = self
+            .participants
+            .iter()
+            .map(|p| (p.person_id, p.value))
+            .collect::<Vec<_>>();
+
+        Spending {
+            payers: self
+                .payers
+                .iter()
+                .zip(split(amount, &payers))
+                .map(|(p, amount)| Payer { amount, ..*p })
+                .collect(),
+            amount,
+            mode: self.mode,
+            participants: match self.mode {
+                SplitMode::Exact => self
+                    .participants
+                    .iter()
+                    .zip(split(amount, &participants))
+                    .map(|(p, value)| Participant { value, ..*p })
+                    .collect(),
+                _ => self.participants.clone(),
+            },
+        }
+    }
+}
+
+/// An amount paid back by a person to another one.
+#[derive(Debug, Clone, Copy)]
+pub struct Payment {
+    pub from: uuid::Uuid,
+    pub to: uuid::Uuid,
+    pub amount: i64,
+}
+
+/// What a person paid for the group, what their fair share is and what they paid back or were paid back.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Balance {
+    pub person_id: uuid::Uuid,
+    pub paid: i64,
+    pub owed: i64,
+    pub sent: i64,
+    pub received: i64,
+}
+
+impl Balance {
+    /// Positive when the group owes money to this person, negative when they are in debt.
+    pub fn net(&self) -> i64 {
+        self.paid - self.owed + self.sent - self.received
+    }
+}
+
+/// Compute the balance of every member, in the same order as `members`.
+/// Every spending is split between its participants according to its mode.
+/// Spendings paid by and payments of persons which aren't members are ignored so that the nets always add up to zero.
+pub fn compute(members: &[Member], spendings: &[Spending], payments: &[Payment]) -> Vec<Balance> {
+    let mut balances = members
+        .iter()
+        .map(|m| Balance {
+            person_id: m.id,
+            ..Balance::default()
+        })
+        .collect::<Vec<_>>();
+    let position =
+        |id: &uuid::Uuid, balances: &[Balance]| balances.iter().position(|b| b.person_id == *id);
+
+    for spending in spendings {
+        let payers = match spending
+            .payers
+            .iter()
+            .map(|p| position(&p.person_id, &balances).map(|i| (i, p.amount)))
+            .collect::<Option<Vec<_>>>()
+        {
+            None => continue,
+            Some(v) => v,
+        };
+        for (i, amount) in payers {
+            balances[i].paid += amount;
+        }
+
+        for (id, share) in owed(spending, members) {
+            if let Some(i) = position(&id, &balances) {
+                balances[i].owed += share;
+            }
+        }
+    }
+
+    for payment in payments {
+        if let (Some(from), Some(to)) = (
+            position(&payment.from, &balances),
+            position(&payment.to, &balances),
+        ) {
+            balances[from].sent += payment.amount;
+            balances[to].received += payment.amount;
+        }
+    }
+
+    balances
+}
+
+/// What each participant of a spending owes. The amounts always add up to the spending's amount.
+/// Participants which aren't members anymore are ignored, their part being split between the others.
+fn owed(spending: &Spending, members: &[Member]) -> Vec<(uuid::Uuid, i64)> {
+    let member = |id: &uuid::Uuid| members.iter().find(|m| m.id == *id);
+    let mut participants = spending
+        .participants
+        .iter()
+        .filter(|p| member(&p.person_id).is_some())
+        .copied()
+        .collect::<Vec<_>>();
+    let mut mode = spending.mode;
+    if participants.is_empty() {
+        participants = members
+            .iter()
+            .map(|m| Participant {
+                person_id: m.id,
+                value: 0,
+            })
+            .collect();
+        if mode != SplitMode::Equal {
+            mode = SplitMode::Resources;
+        }
+    }
+
+    let weights = participants
+        .iter()
+        .map(|p| {
+            let weight = match mode {
+                SplitMode::Equal => 1,
+                SplitMode::Resources => member(&p.person_id).map_or(0, |m| m.resources),
+                SplitMode::Shares | SplitMode::Percentage | SplitMode::Exact => p.value,
+            };
+            (p.person_id, weight)
+        })
+        .collect::<Vec<_>>();
+    let mut shares = match mode {
+        SplitMode::Exact => weights.iter().map(|(_, w)| *w).collect(),
+        _ => split(spending.amount, &weights),
+    };
+
+    // Only exact amounts can leave something to split, when some participants aren't members anymore.
+    let leftover = spending.amount - shares.iter().sum::<i64>();
+    if leftover != 0 {
+        let equally = weights.iter().map(|(id, _)| (*id, 0)).collect::<Vec<_>>();
+        for (share, rest) in shares.iter_mut().zip(split(leftover, &equally)) {
+            *share += rest;
+        }
+    }
+
+    weights.into_iter().map(|(id, _)| id).zip(shares).collect()
+}
+
+/// Split an amount proportionally to the given weights, in the same order as `weights`.
+/// The shares always add up to `amount`: the units left over by the rounding go to the largest remainders,
+/// ties being broken by id so that the result doesn't depend on the order of the weights.
+/// When every weight is zero, the amount is split equally.
+pub fn split(amount: i64, weights: &[(uuid::Uuid, i64)]) -> Vec<i64> {
+    if weights.is_empty() {
+        return Vec::new();
+    }
+    let total = weights.iter().map(|(_, w)| i128::from(*w)).sum::<i128>();
+    let equally = total == 0;
+    let total = if equally {
+        weights.len() as i128
+    } else {
+        total
+    };
+
+    let mut shares = Vec::with_capacity(weights.len());
+    let mut remainders = Vec::with_capacity(weights.len());
+    for (i, (id, w)) in weights.iter().enumerate() {
+        let part = i128::from(amount) * if equally { 1 } else { i128::from(*w) };
+        shares.push(part.div_euclid(total) as i64);
+        remainders.push((part.rem_euclid(total), *id, i));
+    }
+
+    let leftover = amount - shares.iter().sum::<i64>();
+    remainders.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+    for (_, _, i) in remainders.into_iter().take(leftover as usize) {
+        shares[i] += 1;
+    }
+
+    shares
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn member(resources: i64) -> Member {
+        Member {
+            id: uuid::Uuid::new_v4(),
+            resources,
+        }
+    }
+
+    fn paid_by(member: &Member, amount: i64) -> Vec<Payer> {
+        vec![Payer {
+            person_id: member.id,
+            amount,
+        }]
+    }
+
+    #[test]
+    fn should_split_proportionally_to_the_weights() {
+        let (a, b) = (uuid::Uuid::new_v4(), uuid::Uuid::new_v4());
+        assert_eq!(vec![25, 75], split(100, &[(a, 1000), (b, 3000)]));
+        assert_eq!(vec![50, 50], split(100, &[(a, 0), (b, 0)]));
+        assert_eq!(vec![0, 100], split(100, &[(a, 0), (b, 10)]));
+        assert!(split(100, &[]).is_empty());
+    }
+
+    #[test]
+    fn should_round_deterministically() {
+        let mut ids = [
+            uuid::Uuid::new_v4(),
+            uuid::Uuid::new_v4(),
+            uuid::Uuid::new_v4(),
+        ];
+        ids.sort();
+        let weights = ids.iter().map(|id| (*id, 1)).collect::<Vec<_>>();
+        assert_eq!(vec![34, 33, 33], split(100, &weights));
+
+        let reversed = weights.iter().rev().cloned().collect::<Vec<_>>();
+        assert_eq!(vec![33, 33, 34], split(100, &reversed));
+    }
+
+    #[test]
+    fn should_compute_balances_weighted_by_resources() {
+        let members = [member(1000), member(2000), member(0)];
+        let spendings = [
+            Spending {
+                payers: paid_by(&members[0], 90),
+                amount: 90,
+                ..Spending::default()
+            },
+            Spending {
+                payers: paid_by(&members[2], 30),
+                amount: 30,
+                ..Spending::default()
+            },
+        ];
+        let balances = compute(&members, &spendings, &[]);
+
+        assert_eq!(
+            vec![90, 0, 30],
+            balances.iter().map(|b| b.paid).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![40, 80, 0],
+            balances.iter().map(|b| b.owed).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![50, -80, 30],
+            balances.iter().map(Balance::net).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn should_split_spendings_between_their_participants() {
+        let members = [member(0), member(1), member(3)];
+        let participant = |i: usize, value: i64| Participant {
+            person_id: members[i].id,
+            value,
+        };
+        let spending = |mode: SplitMode, participants: Vec<Participant>| Spending {
+            payers: paid_by(&members[0], 100),
+            amount: 100,
+            mode,
+            participants,
+        };
+        let owed = |spending: Spending| {
+            compute(&members, &[spending], &[])
+                .iter()
+                .map(|b| b.owed)
+                .collect::<Vec<_>>()
+        };
+
+        let mut equally = owed(spending(SplitMode::Equal, vec![]));
+        equally.sort();
+        assert_eq!(vec![33, 33, 34], equally);
+        assert_eq!(
+            vec![0, 25, 75],
+            owed(spending(SplitMode::Resources, vec![]))
+        );
+        assert_eq!(
+            vec![50, 0, 50],
+            owed(spending(
+                SplitMode::Equal,
+                vec![participant(0, 0), participant(2, 0)]
+            ))
+        );
+        assert_eq!(
+            vec![80, 20, 0],
+            owed(spending(
+                SplitMode::Shares,
+                vec![participant(0, 4), participant(1, 1)]
+            ))
+        );
+        assert_eq!(
+            vec![10, 0, 90],
+            owed(spending(
+                SplitMode::Percentage,
+                vec![participant(0, 10), participant(2, 90)]
+            ))
+        );
+        assert_eq!(
+            vec![0, 70, 30],
+            owed(spending(
+                SplitMode::Exact,
+                vec![participant(1, 70), participant(2, 30)]
+            ))
+        );
+    }
+
+    #[test]
+    fn should_split_the_part_of_participants_which_are_not_members_anymore() {
+        let members = [member(1), member(1)];
+        let spending = Spending {
+            payers: paid_by(&members[0], 100),
+            amount: 100,
+            mode: SplitMode::Exact,
+            participants: vec![
+                Participant {
+                    person_id: members[0].id,
+                    value: 20,
+                },
+                Participant {
+                    person_id: members[1].id,
+                    value: 20,
+                },
+                Participant {
+                    person_id: uuid::Uuid::new_v4(),
+                    value: 60,
+                },
+            ],
+        };
+        let balances = compute(&members, &[spending], &[]);
+
+        assert_eq!(
+            vec![50, 50],
+            balances.iter().map(|b| b.owed).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn every_payer_should_be_credited() {
+        let members = [member(1), member(1), member(1)];
+        let spending = Spending {
+            payers: vec![
+                Payer {
+                    person_id: members[0].id,
+                    amount: 40,
+                },
+                Payer {
+                    person_id: members[1].id,
+                    amount: 50,
+                },
+            ],
+            amount: 90,
+            ..Spending::default()
+        };
+        let balances = compute(&members, &[spending], &[]);
+
+        assert_eq!(
+            vec![40, 50, 0],
+            balances.iter().map(|b| b.paid).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![10, 20, -30],
+            balances.iter().map(Balance::net).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn payments_should_be_included_in_the_balances() {
+        let members = [member(1), member(1)];
+        let spendings = [Spending {
+            payers: paid_by(&members[0], 100),
+            amount: 100,
+            ..Spending::default()
+        }];
+        let payments = [Payment {
+            from: members[1].id,
+            to: members[0].id,
+            amount: 40,
+        }];
+        let balances = compute(&members, &spendings, &payments);
+
+        assert_eq!(
+            vec![0, 40],
+            balances.iter().map(|b| b.sent).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![40, 0],
+            balances.iter().map(|b| b.received).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![10, -10],
+            balances.iter().map(Balance::net).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn nets_should_always_add_up_to_zero() {
+        let members = [member(7), member(13), member(0), member(29)];
+        let spendings = (1..50)
+            .map(|i| (i, (i * 37 % 101) as i64 + 1))
+            .map(|(i, amount)| Spending {
+                payers: paid_by(&members[i % members.len()], amount),
+                amount,
+                mode: [
+                    SplitMode::Equal,
+                    SplitMode::Resources,
+                    SplitMode::Shares,
+                    SplitMode::Percentage,
+                ][i % 4],
+                participants: members
+                    .iter()
+                    .skip(i % 3)
+                    .map(|m| Participant {
+                        person_id: m.id,
+                        value: if i % 4 == 3 { 50 } else { i as i64 },
+                    })
+                    .take(2)
+                    .collect(),
+            })
+            .collect::<Vec<_>>();
+
+        let payments = (1..10)
+            .map(|i| Payment {
+                from: members[i % members.len()].id,
+                to: members[(i + 1) % members.len()].id,
+                amount: i as i64 * 3,
+            })
+            .collect::<Vec<_>>();
+
+        let balances = compute(&members, &spendings, &payments);
+        assert_eq!(0, balances.iter().map(Balance::net).sum::<i64>());
+    }
+
+    #[test]
+    fn converted_spendings_should_still_add_up() {
+        let (a, b, c) = (member(1), member(1), member(1));
+        let spending = Spending {
+            payers: vec![
+                Payer {
+                    person_id: a.id,
+                    amount: 50,
+                },
+                Payer {
+                    person_id: b.id,
+                    amount: 50,
+                },
+            ],
+            amount: 100,
+            mode: SplitMode::Exact,
+            participants: [a, b, c]
+                .iter()
+                .map(|m| Participant {
+                    person_id: m.id,
+                    value: 33 + (m.id == a.id) as i64,
+                })
+                .collect(),
+        };
+
+        let converted = spending.convert(1.0 / 3.0);
+
+        assert_eq!(33, converted.amount);
+        assert_eq!(33, converted.payers.iter().map(|p| p.amount).sum::<i64>());
+        assert_eq!(
+            33,
+            converted.participants.iter().map(|p| p.value).sum::<i64>()
+        );
+    }
+}
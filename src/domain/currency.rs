@@ -0,0 +1,216 @@
+/// The rate converting a currency into another one: one unit of `from` is worth `rate` units of `to`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rate {
+    pub from: String,
+    pub to: String,
+    pub rate: f64,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum CurrencyError {
+    /// The code isn't an ISO 4217 like code made of three uppercase letters.
+    InvalidCode,
+    /// The rate isn't a finite positive number or converts a currency into itself.
+    InvalidRate,
+    /// This line of a CSV import couldn't be read, starting at 1.
+    InvalidCsvLine(usize),
+}
+
+/// Check that a currency code is made of three uppercase letters, like `EUR`.
+pub fn validate_code(code: &str) -> Result<(), CurrencyError> {
+    if code.len() == 3 && code.bytes().all(|b| b.is_ascii_uppercase()) {
+        Ok(())
+    } else {
+        Err(CurrencyError::InvalidCode)
+    }
+}
+
+/// Check that a rate can be saved.
+pub fn validate_rate(rate: &Rate) -> Result<(), CurrencyError> {
+    validate_code(&rate.from)?;
+    validate_code(&rate.to)?;
+    if rate.from == rate.to || !rate.rate.is_finite() || rate.rate <= 0.0 {
+        return Err(CurrencyError::InvalidRate);
+    }
+
+    Ok(())
+}
+
+/// Find the rate converting `from` into `to`, using the inverse rate when only this one is known.
+pub fn find_rate(from: &str, to: &str, rates: &[Rate]) -> Option<f64> {
+    if from == to {
+        return Some(1.0);
+    }
+
+    rates
+        .iter()
+        .find(|r| r.from == from && r.to == to)
+        .map(|r| r.rate)
+        .or_else(|| {
+            rates
+                .iter()
+                .find(|r| r.from == to && r.to == from)
+                .map(|r| 1.0 / r.rate)
+        })
+}
+
+/// Turn a rate between major units, like euros, into a rate between minor units, like cents.
+pub fn minor_rate(rate: f64, from: &str, to: &str) -> f64 {
+    rate * 10f64.powi(minor_digits(to) as i32 - minor_digits(from) as i32)
+}
+
+/// Convert an amount with a rate, rounding to the nearest unit.
+pub fn convert(amount: i64, rate: f64) -> i64 {
+    (amount as f64 * rate).round() as i64
+}
+
+/// The number of digits of a currency's minor unit, e.g. 2 for the euro's cents.
+pub fn minor_digits(code: &str) -> u32 {
+    match code {
+        "BIF" | "CLP" | "DJF" | "GNF" | "ISK" | "JPY" | "KMF" | "KRW" | "PYG" | "RWF" | "UGX"
+        | "UYI" | "VND" | "VUV" | "XAF" | "XOF" | "XPF" => 0,
+        "BHD" | "IQD" | "JOD" | "KWD" | "LYD" | "OMR" | "TND" => 3,
+        _ => 2,
+    }
+}
+
+/// Format an amount in minor units with its currency, like `-1234.50 EUR`.
+pub fn format(amount: i64, code: &str) -> String {
+    let digits = minor_digits(code);
+    let sign = if amount < 0 { "-" } else { "" };
+    let amount = amount.unsigned_abs();
+    if digits == 0 {
+        return format!("{}{} {}", sign, amount, code);
+    }
+
+    let unit = 10u64.pow(digits);
+    format!(
+        "{}{}.{:0width$} {}",
+        sign,
+        amount / unit,
+        amount % unit,
+        code,
+        width = digits as usize
+    )
+}
+
+/// Read rates from a CSV made of `from,to,rate` lines.
+/// Blank lines and an optional `from,to,rate` header are skipped.
+/// When the same currencies appear several times, the last rate wins.
+pub fn parse_csv(csv: &str) -> Result<Vec<Rate>, CurrencyError> {
+    let rates = csv
+        .lines()
+        .enumerate()
+        .filter(|(i, l)| {
+            let l = l.trim();
+            !l.is_empty() && (*i != 0 || !l.eq_ignore_ascii_case("from,to,rate"))
+        })
+        .map(|(i, l)| {
+            let invalid = || CurrencyError::InvalidCsvLine(i + 1);
+            let fields = l.split(',').map(str::trim).collect::<Vec<_>>();
+            let rate = match fields.as_slice() {
+                [from, to, rate] => Rate {
+                    from: from.to_string(),
+                    to: to.to_string(),
+                    rate: rate.parse().map_err(|_| invalid())?,
+                },
+                _ => return Err(invalid()),
+            };
+            validate_rate(&rate).map_err(|_| invalid())?;
+            Ok(rate)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut unique: Vec<Rate> = Vec::with_capacity(rates.len());
+    for rate in rates {
+        match unique
+            .iter_mut()
+            .find(|r| r.from == rate.from && r.to == rate.to)
+        {
+            Some(r) => r.rate = rate.rate,
+            None => unique.push(rate),
+        }
+    }
+
+    Ok(unique)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rate(from: &str, to: &str, rate: f64) -> Rate {
+        Rate {
+            from: from.to_string(),
+            to: to.to_string(),
+            rate,
+        }
+    }
+
+    #[test]
+    fn should_validate_currency_codes() {
+        assert_eq!(Ok(()), validate_code("EUR"));
+        assert_eq!(Err(CurrencyError::InvalidCode), validate_code("eur"));
+        assert_eq!(Err(CurrencyError::InvalidCode), validate_code("EURO"));
+        assert_eq!(Err(CurrencyError::InvalidCode), validate_code(""));
+        assert_eq!(
+            Err(CurrencyError::InvalidRate),
+            validate_rate(&rate("EUR", "EUR", 1.0))
+        );
+        assert_eq!(
+            Err(CurrencyError::InvalidRate),
+            validate_rate(&rate("EUR", "USD", 0.0))
+        );
+    }
+
+    #[test]
+    fn should_find_direct_and_inverse_rates() {
+        let rates = [rate("EUR", "USD", 1.25)];
+
+        assert_eq!(Some(1.0), find_rate("USD", "USD", &rates));
+        assert_eq!(Some(1.25), find_rate("EUR", "USD", &rates));
+        assert_eq!(Some(0.8), find_rate("USD", "EUR", &rates));
+        assert_eq!(None, find_rate("EUR", "GBP", &rates));
+    }
+
+    #[test]
+    fn should_convert_to_the_nearest_unit() {
+        assert_eq!(125, convert(100, 1.25));
+        assert_eq!(33, convert(100, 1.0 / 3.0));
+        assert_eq!(67, convert(100, 2.0 / 3.0));
+        // 1000 yens are worth 6 euros
+        assert_eq!(600, convert(1000, minor_rate(0.006, "JPY", "EUR")));
+    }
+
+    #[test]
+    fn should_format_amounts_in_minor_units() {
+        assert_eq!("12.34 EUR", format(1234, "EUR"));
+        assert_eq!("-0.05 USD", format(-5, "USD"));
+        assert_eq!("1234 JPY", format(1234, "JPY"));
+        assert_eq!("1.234 KWD", format(1234, "KWD"));
+        assert_eq!("-92233720368547758.08 EUR", format(i64::MIN, "EUR"));
+    }
+
+    #[test]
+    fn should_parse_rates_from_a_csv() {
+        let csv = "from,to,rate\nEUR,USD,1.25\n\n GBP , EUR , 1.15 \n";
+        assert_eq!(
+            Ok(vec![rate("EUR", "USD", 1.25), rate("GBP", "EUR", 1.15)]),
+            parse_csv(csv)
+        );
+
+        assert_eq!(
+            Ok(vec![rate("EUR", "USD", 1.5)]),
+            parse_csv("EUR,USD,1.25\nEUR,USD,1.5")
+        );
+
+        assert_eq!(
+            Err(CurrencyError::InvalidCsvLine(2)),
+            parse_csv("EUR,USD,1.25\nEUR,GBP")
+        );
+        assert_eq!(
+            Err(CurrencyError::InvalidCsvLine(1)),
+            parse_csv("EUR,USD,-1")
+        );
+    }
+}
@@ -0,0 +1,243 @@
+use chrono::NaiveDate;
+use std::{collections::HashSet, fmt, str::FromStr};
+
+/// How an expense is split between its participants.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SplitMode {
+    /// Every participant owes the same amount.
+    Equal,
+    /// Participants owe an amount proportional to their resources.
+    #[default]
+    Resources,
+    /// Participants owe an amount proportional to their explicit number of shares.
+    Shares,
+    /// Participants owe a percentage of the amount. The percentages must add up to 100.
+    Percentage,
+    /// Participants owe an exact amount. The amounts must add up to the expense's amount.
+    Exact,
+}
+
+impl SplitMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SplitMode::Equal => "EQUAL",
+            SplitMode::Resources => "RESOURCES",
+            SplitMode::Shares => "SHARES",
+            SplitMode::Percentage => "PERCENTAGE",
+            SplitMode::Exact => "EXACT",
+        }
+    }
+}
+
+impl FromStr for SplitMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "EQUAL" => Ok(SplitMode::Equal),
+            "RESOURCES" => Ok(SplitMode::Resources),
+            "SHARES" => Ok(SplitMode::Shares),
+            "PERCENTAGE" => Ok(SplitMode::Percentage),
+            "EXACT" => Ok(SplitMode::Exact),
+            _ => Err(anyhow::anyhow!("Unknown split mode ({})", s)),
+        }
+    }
+}
+
+impl fmt::Display for SplitMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A person covered by an expense.
+/// The value is a number of shares, a percentage or an exact amount depending on the split mode
+/// and is ignored for the equal and resources modes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Participant {
+    pub person_id: uuid::Uuid,
+    pub value: i64,
+}
+
+/// A person who paid part of an expense.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Payer {
+    pub person_id: uuid::Uuid,
+    pub amount: i64,
+}
+
+/// The dates expenses occurred on, both bounds included. A missing bound doesn't limit the range.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DateRange {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+}
+
+impl DateRange {
+    /// None when the range ends before it starts.
+    pub fn new(from: Option<NaiveDate>, to: Option<NaiveDate>) -> Option<Self> {
+        match (from, to) {
+            (Some(f), Some(t)) if f > t => None,
+            _ => Some(DateRange { from, to }),
+        }
+    }
+
+    pub fn contains(&self, date: &NaiveDate) -> bool {
+        self.from.is_none_or(|f| f <= *date) && self.to.is_none_or(|t| *date <= t)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum SplitError {
+    /// There is no payer, a payer appears twice or the payers' amounts don't add up to the expense's amount.
+    InvalidPayers,
+    /// A participant appears twice, has a negative value or no participant has any share.
+    InvalidShares,
+    /// The percentages don't add up to 100.
+    InvalidPercentages,
+    /// The exact amounts don't add up to the expense's amount.
+    InvalidExactAmounts,
+}
+
+/// Check that the participants of an expense can split its amount with this mode.
+/// No participants means that the expense covers the whole group, which is only possible
+/// for the equal and resources modes.
+pub fn validate_split(
+    mode: SplitMode,
+    amount: i64,
+    participants: &[Participant],
+) -> Result<(), SplitError> {
+    let mut persons = HashSet::new();
+    if participants
+        .iter()
+        .any(|p| p.value < 0 || !persons.insert(p.person_id))
+    {
+        return Err(SplitError::InvalidShares);
+    }
+
+    let total = participants.iter().map(|p| p.value).sum::<i64>();
+    match mode {
+        SplitMode::Equal | SplitMode::Resources => Ok(()),
+        SplitMode::Shares if total <= 0 => Err(SplitError::InvalidShares),
+        SplitMode::Shares => Ok(()),
+        SplitMode::Percentage if total != 100 => Err(SplitError::InvalidPercentages),
+        SplitMode::Percentage => Ok(()),
+        SplitMode::Exact if total != amount => Err(SplitError::InvalidExactAmounts),
+        SplitMode::Exact => Ok(()),
+    }
+}
+
+/// Check that the payers of an expense paid exactly its amount.
+pub fn validate_payers(amount: i64, payers: &[Payer]) -> Result<(), SplitError> {
+    let mut persons = HashSet::new();
+    if payers.is_empty()
+        || payers
+            .iter()
+            .any(|p| p.amount < 1 || !persons.insert(p.person_id))
+        || payers.iter().map(|p| p.amount).sum::<i64>() != amount
+    {
+        return Err(SplitError::InvalidPayers);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn participants(values: &[i64]) -> Vec<Participant> {
+        values
+            .iter()
+            .map(|v| Participant {
+                person_id: uuid::Uuid::new_v4(),
+                value: *v,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn should_validate_the_split_of_an_expense() {
+        assert_eq!(Ok(()), validate_split(SplitMode::Equal, 100, &[]));
+        assert_eq!(Ok(()), validate_split(SplitMode::Resources, 100, &[]));
+        assert_eq!(
+            Ok(()),
+            validate_split(SplitMode::Shares, 100, &participants(&[1, 0, 2]))
+        );
+        assert_eq!(
+            Ok(()),
+            validate_split(SplitMode::Percentage, 100, &participants(&[25, 75]))
+        );
+        assert_eq!(
+            Ok(()),
+            validate_split(SplitMode::Exact, 100, &participants(&[60, 40]))
+        );
+    }
+
+    #[test]
+    fn should_refuse_invalid_splits() {
+        assert_eq!(
+            Err(SplitError::InvalidShares),
+            validate_split(SplitMode::Shares, 100, &[])
+        );
+        assert_eq!(
+            Err(SplitError::InvalidShares),
+            validate_split(SplitMode::Equal, 100, &participants(&[-1]))
+        );
+        assert_eq!(
+            Err(SplitError::InvalidPercentages),
+            validate_split(SplitMode::Percentage, 100, &participants(&[25, 70]))
+        );
+        assert_eq!(
+            Err(SplitError::InvalidExactAmounts),
+            validate_split(SplitMode::Exact, 100, &participants(&[60, 50]))
+        );
+
+        let participant = participants(&[50]).remove(0);
+        assert_eq!(
+            Err(SplitError::InvalidShares),
+            validate_split(SplitMode::Percentage, 100, &[participant, participant])
+        );
+    }
+
+    #[test]
+    fn date_ranges_should_include_their_bounds() {
+        let date = |d: u32| NaiveDate::from_ymd(2020, 9, d);
+        let range = DateRange::new(Some(date(10)), Some(date(20))).unwrap();
+
+        assert!(range.contains(&date(10)));
+        assert!(range.contains(&date(20)));
+        assert!(!range.contains(&date(9)));
+        assert!(!range.contains(&date(21)));
+        assert!(DateRange::default().contains(&date(1)));
+        assert!(DateRange::new(None, Some(date(10)))
+            .unwrap()
+            .contains(&date(1)));
+        assert_eq!(None, DateRange::new(Some(date(20)), Some(date(10))));
+    }
+
+    #[test]
+    fn should_validate_the_payers_of_an_expense() {
+        let payer = |amount: i64| Payer {
+            person_id: uuid::Uuid::new_v4(),
+            amount,
+        };
+        assert_eq!(Ok(()), validate_payers(100, &[payer(100)]));
+        assert_eq!(Ok(()), validate_payers(100, &[payer(70), payer(30)]));
+
+        assert_eq!(Err(SplitError::InvalidPayers), validate_payers(100, &[]));
+        assert_eq!(
+            Err(SplitError::InvalidPayers),
+            validate_payers(100, &[payer(70), payer(20)])
+        );
+        assert_eq!(
+            Err(SplitError::InvalidPayers),
+            validate_payers(100, &[payer(110), payer(-10)])
+        );
+        let payer = payer(50);
+        assert_eq!(
+            Err(SplitError::InvalidPayers),
+            validate_payers(100, &[payer, payer])
+        );
+    }
+}
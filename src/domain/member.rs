@@ -0,0 +1,67 @@
+use std::{fmt, str::FromStr};
+
+/// What a member is allowed to do in a group. Each role can do everything the previous ones can.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Role {
+    /// Can only read the group.
+    Viewer,
+    /// Can also add, change and remove the persons, expenses, payments and categories.
+    Editor,
+    /// Can also rename and remove the group and manage its members.
+    Owner,
+}
+
+impl Role {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Viewer => "VIEWER",
+            Role::Editor => "EDITOR",
+            Role::Owner => "OWNER",
+        }
+    }
+
+    /// Whether this role can do what the required one can.
+    pub fn allows(&self, required: Role) -> bool {
+        *self >= required
+    }
+}
+
+impl FromStr for Role {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "VIEWER" => Ok(Role::Viewer),
+            "EDITOR" => Ok(Role::Editor),
+            "OWNER" => Ok(Role::Owner),
+            _ => Err(anyhow::anyhow!("Unknown role ({})", s)),
+        }
+    }
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roles_should_include_the_lower_ones() {
+        assert!(Role::Owner.allows(Role::Editor));
+        assert!(Role::Editor.allows(Role::Viewer));
+        assert!(Role::Editor.allows(Role::Editor));
+        assert!(!Role::Viewer.allows(Role::Editor));
+        assert!(!Role::Editor.allows(Role::Owner));
+    }
+
+    #[test]
+    fn roles_should_be_stored_as_strings() {
+        for role in &[Role::Viewer, Role::Editor, Role::Owner] {
+            assert_eq!(Ok(*role), role.to_string().parse().map_err(|_| ()));
+        }
+    }
+}
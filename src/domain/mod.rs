@@ -0,0 +1,8 @@
+pub mod activity;
+pub mod balance;
+pub mod currency;
+pub mod expense;
+pub mod member;
+pub mod services;
+pub mod settlement;
+pub mod statistics;
@@ -0,0 +1,667 @@
+use crate::infrastructure::{
+    config::SecuritySettings,
+    jobs::Worker,
+    mail::{Email, Mailer},
+    repositories::{self, Store},
+    security,
+};
+use anyhow::Context;
+use std::sync::Arc;
+use unicode_segmentation::UnicodeSegmentation;
+
+#[derive(Debug)]
+pub enum AuthError {
+    InvalidEmailAddress,
+    InvalidPassword,
+    /// No user has this email address or this password.
+    InvalidCredentials,
+    AlreadyUsedEmail,
+    UserNotFound,
+    /// The token is unknown, expired or revoked.
+    InvalidToken,
+    /// The user hasn't verified their email address yet.
+    UnverifiedEmail,
+    Internal(anyhow::Error),
+}
+
+impl From<anyhow::Error> for AuthError {
+    fn from(e: anyhow::Error) -> Self {
+        AuthError::Internal(e)
+    }
+}
+
+/// Check that an email address looks like one.
+pub fn is_valid_email(email: &str) -> bool {
+    regex::Regex::new(r"^\S+@\S+\.\S+$")
+        .unwrap()
+        .is_match(email)
+}
+
+/// Passwords are 8 to 64 characters long.
+pub fn is_valid_password(password: &str) -> bool {
+    // https://stackoverflow.com/a/46290728
+    (8..=64).contains(&password.graphemes(true).count())
+}
+
+/// The tokens of a session, handed out when a user signs in.
+pub struct Tokens {
+    /// Short-lived, sent along with every request.
+    pub access_token: String,
+    /// Long-lived, only used to get new tokens. Each use replaces it.
+    pub refresh_token: String,
+}
+
+pub struct AuthService;
+impl AuthService {
+    /// Create a user's account if the email isn't already taken, and sign them in.
+    /// They're emailed a link to verify their address with.
+    pub fn signup(
+        email: String,
+        password: &str,
+        settings: &SecuritySettings,
+        mailer: &dyn Mailer,
+        db: &dyn Store,
+    ) -> Result<Tokens, AuthError> {
+        if !is_valid_email(&email) {
+            return Err(AuthError::InvalidEmailAddress);
+        }
+        if !is_valid_password(password) {
+            return Err(AuthError::InvalidPassword);
+        }
+
+        let new_user = repositories::NewUser {
+            id: uuid::Uuid::new_v4(),
+            email,
+            password: security::hash_password(password.as_bytes(), settings.hashing())?,
+        };
+        let user = repositories::transaction(db, |tx| {
+            if repositories::UserRepository::find_one_by_email(tx, &new_user.email)?.is_some() {
+                return Err(AuthError::AlreadyUsedEmail);
+            }
+
+            repositories::UserRepository::save(tx, &new_user).map_err(|e| {
+                if repositories::is_unique_violation(&e) {
+                    AuthError::AlreadyUsedEmail
+                } else {
+                    AuthError::Internal(e)
+                }
+            })
+        })?;
+        // The account exists anyway, the user can ask for another link
+        if let Err(e) = Self::send_verification(&user, settings, mailer, db) {
+            log::error!("{:?}", e);
+        }
+
+        Self::sign(&user, settings, db)
+    }
+
+    /// Sign a user in with their credentials, starting a new session.
+    pub fn login(
+        email: &str,
+        password: &str,
+        settings: &SecuritySettings,
+        db: &dyn Store,
+    ) -> Result<Tokens, AuthError> {
+        // Malformed credentials can't be anyone's
+        if !is_valid_email(email) || !is_valid_password(password) {
+            return Err(AuthError::InvalidEmailAddress);
+        }
+
+        let user = repositories::UserRepository::find_one_by_email(db, email)?
+            .ok_or(AuthError::InvalidCredentials)?;
+        if !security::verify_password(password.as_bytes(), &user.password)? {
+            return Err(AuthError::InvalidCredentials);
+        }
+        // The password is only known now, to be hashed with the current parameters,
+        // and a salt of its own if it was hashed with the former global one
+        if user.password_hashed_at.is_none()
+            || security::needs_rehash(&user.password, settings.hashing())?
+        {
+            let password = security::hash_password(password.as_bytes(), settings.hashing())?;
+            repositories::UserRepository::update_password(db, &user.id, &password)?;
+        }
+
+        Self::sign(&user, settings, db)
+    }
+
+    /// Exchange a refresh token for new tokens of the same session.
+    /// A refresh token used twice may have been stolen: its session is revoked then.
+    pub fn refresh(
+        refresh_token: &str,
+        settings: &SecuritySettings,
+        db: &dyn Store,
+    ) -> Result<Tokens, AuthError> {
+        let (id, secret) =
+            security::parse_opaque_token(refresh_token).ok_or(AuthError::InvalidToken)?;
+        let session = repositories::SessionRepository::find_one(db, &id)?
+            .filter(|s| s.revoked_at.is_none() && s.expires_at > chrono::Utc::now())
+            .ok_or(AuthError::InvalidToken)?;
+        if !security::verify_password(secret.as_bytes(), &session.secret_hash)? {
+            // A rotated secret coming back means the refresh token was stolen: whoever holds it
+            // is logged out, as well as the client. Anything else is only a wrong token
+            if let Some(previous) = &session.previous_secret_hash {
+                if security::verify_password(secret.as_bytes(), previous)? {
+                    repositories::SessionRepository::revoke(db, &session.id)?;
+                }
+            }
+            return Err(AuthError::InvalidToken);
+        }
+
+        let secret = security::generate_secret();
+        let secret_hash = security::hash_password(secret.as_bytes(), settings.hashing())?;
+        let expires_at = chrono::Utc::now() + settings.refresh_token_expiration();
+        // Another request may have used the same refresh token meanwhile
+        let session =
+            repositories::SessionRepository::rotate(db, &session, &secret_hash, &expires_at)?
+                .ok_or(AuthError::InvalidToken)?;
+
+        Self::tokens(&session, &secret, settings)
+    }
+
+    /// Check that the session an access token was issued for is still valid.
+    pub fn check_session(viewer: &security::Viewer, db: &dyn Store) -> Result<(), AuthError> {
+        match repositories::SessionRepository::find_one(db, viewer.session_id())? {
+            Some(s) if s.user_id == *viewer.id() && s.revoked_at.is_none() => Ok(()),
+            _ => Err(AuthError::InvalidToken),
+        }
+    }
+
+    /// End the viewer's session, revoking its tokens.
+    pub fn logout(viewer: &security::Viewer, db: &dyn Store) -> Result<(), AuthError> {
+        repositories::SessionRepository::revoke(db, viewer.session_id()).map_err(Into::into)
+    }
+
+    /// End all the sessions of the viewer, on every device.
+    pub fn logout_everywhere(viewer_id: &uuid::Uuid, db: &dyn Store) -> Result<(), AuthError> {
+        repositories::SessionRepository::revoke_by_user(db, viewer_id, None).map_err(Into::into)
+    }
+
+    /// Change the viewer's password, ending their other sessions.
+    pub fn change_password(
+        viewer: &security::Viewer,
+        current_password: &str,
+        new_password: &str,
+        settings: &SecuritySettings,
+        db: &dyn Store,
+    ) -> Result<(), AuthError> {
+        if !is_valid_password(new_password) {
+            return Err(AuthError::InvalidPassword);
+        }
+        let user = Self::check_password(viewer.id(), current_password, db)?;
+
+        let password = security::hash_password(new_password.as_bytes(), settings.hashing())?;
+        repositories::transaction(db, |tx| {
+            repositories::UserRepository::update_password(tx, &user.id, &password)?;
+            repositories::SessionRepository::revoke_by_user(tx, &user.id, Some(viewer.session_id()))
+                .map_err(Into::into)
+        })
+    }
+
+    /// Change the viewer's email, if it isn't already taken.
+    /// The new address has to be verified, they're emailed a link to do so.
+    pub fn change_email(
+        viewer_id: &uuid::Uuid,
+        password: &str,
+        new_email: String,
+        settings: &SecuritySettings,
+        mailer: &dyn Mailer,
+        db: &dyn Store,
+    ) -> Result<(), AuthError> {
+        if !is_valid_email(&new_email) {
+            return Err(AuthError::InvalidEmailAddress);
+        }
+        let user = Self::check_password(viewer_id, password, db)?;
+        if user.email == new_email {
+            return Ok(());
+        }
+
+        repositories::transaction(db, |tx| {
+            if repositories::UserRepository::find_one_by_email(tx, &new_email)?.is_some() {
+                return Err(AuthError::AlreadyUsedEmail);
+            }
+
+            repositories::UserRepository::update_email(tx, &user.id, &new_email).map_err(|e| {
+                if repositories::is_unique_violation(&e) {
+                    AuthError::AlreadyUsedEmail
+                } else {
+                    AuthError::Internal(e)
+                }
+            })
+        })?;
+
+        let user = repositories::User {
+            email: new_email,
+            email_verified_at: None,
+            ..user
+        };
+        // The email is changed anyway, the user can ask for another link
+        if let Err(e) = Self::send_verification(&user, settings, mailer, db) {
+            log::error!("{:?}", e);
+        }
+
+        Ok(())
+    }
+
+    /// Email the viewer another link to verify their address with, unless it's already verified.
+    pub fn send_verification_email(
+        viewer_id: &uuid::Uuid,
+        settings: &SecuritySettings,
+        mailer: &dyn Mailer,
+        db: &dyn Store,
+    ) -> Result<(), AuthError> {
+        let user = Self::viewer(viewer_id, db)?;
+        if user.email_verified_at.is_some() {
+            return Ok(());
+        }
+        Self::send_verification(&user, settings, mailer, db).map_err(Into::into)
+    }
+
+    /// Verify an email address with a token emailed by `signup` or `change_email`.
+    /// A token can only be used once, and only while the user still has the address.
+    pub fn verify_email(token: &str, db: &dyn Store) -> Result<(), AuthError> {
+        let (id, secret) = security::parse_opaque_token(token).ok_or(AuthError::InvalidToken)?;
+        let verification = repositories::EmailVerificationRepository::find_one(db, &id)?
+            .filter(|v| v.used_at.is_none() && v.expires_at > chrono::Utc::now())
+            .ok_or(AuthError::InvalidToken)?;
+        if !security::verify_password(secret.as_bytes(), &verification.secret_hash)? {
+            return Err(AuthError::InvalidToken);
+        }
+
+        repositories::transaction(db, |tx| {
+            // Another request may have used the same token meanwhile
+            if !repositories::EmailVerificationRepository::use_one(tx, &verification.id)? {
+                return Err(AuthError::InvalidToken);
+            }
+            if !repositories::UserRepository::verify_email(
+                tx,
+                &verification.user_id,
+                &verification.email,
+            )? {
+                return Err(AuthError::InvalidToken);
+            }
+            Ok(())
+        })
+    }
+
+    /// Check that the viewer verified their email address.
+    pub fn check_verified_email(viewer_id: &uuid::Uuid, db: &dyn Store) -> Result<(), AuthError> {
+        match Self::viewer(viewer_id, db)?.email_verified_at {
+            Some(_) => Ok(()),
+            None => Err(AuthError::UnverifiedEmail),
+        }
+    }
+
+    /// Email a user a token to reset their password with.
+    /// Whether a user has this email address or not, the outcome is the same.
+    pub fn request_password_reset(
+        email: &str,
+        settings: &SecuritySettings,
+        mailer: Arc<dyn Mailer + Send + Sync>,
+        db: Arc<dyn Store + Send + Sync>,
+        worker: &Worker,
+    ) -> Result<(), AuthError> {
+        if !is_valid_email(email) {
+            return Err(AuthError::InvalidEmailAddress);
+        }
+        // Nothing is queued for a flooded address. Known or not, it costs the same query
+        if password_resets_exceeded(email, settings, &*db)? {
+            log::warn!("Too many password resets for an email address");
+            return Ok(());
+        }
+
+        // Answering once the email is sent, or not, would take longer for the existing users
+        let email = email.to_string();
+        let settings = settings.clone();
+        let queued = worker.submit(move || {
+            Self::send_password_reset(&email, &settings, &*mailer, &*db)
+                .context("Couldn't send a password reset email")
+        });
+        if let Err(e) = queued {
+            log::error!("{:?}", e);
+        }
+
+        Ok(())
+    }
+
+    /// Email a new password reset token to the user with this email address, if any,
+    /// unless they were sent too many lately. Only this token can be used then.
+    fn send_password_reset(
+        email: &str,
+        settings: &SecuritySettings,
+        mailer: &dyn Mailer,
+        db: &dyn Store,
+    ) -> anyhow::Result<()> {
+        let user = match repositories::UserRepository::find_one_by_email(db, email)? {
+            Some(u) => u,
+            None => return Ok(()),
+        };
+
+        let secret = security::generate_secret();
+        let new_password_reset = repositories::NewPasswordReset {
+            id: uuid::Uuid::new_v4(),
+            user_id: user.id,
+            secret_hash: security::hash_password(secret.as_bytes(), settings.hashing())?,
+            expires_at: chrono::Utc::now() + settings.password_reset_expiration(),
+        };
+        let password_reset = repositories::transaction(db, |tx| {
+            // Other requests may have been queued meanwhile
+            if password_resets_exceeded(&user.email, settings, tx)? {
+                return Ok(None);
+            }
+
+            repositories::PasswordResetRepository::expire_by_user(tx, &user.id)?;
+            repositories::PasswordResetRepository::save(tx, &new_password_reset).map(Some)
+        })?;
+        let password_reset = match password_reset {
+            Some(r) => r,
+            None => {
+                log::warn!("Too many password resets for this user ({})", user.id);
+                return Ok(());
+            }
+        };
+
+        let email = Email {
+            to: user.email,
+            subject: "Reset your password".to_string(),
+            body: format!(
+                "Someone asked to reset your password. Use this token to choose a new one:\n\n\
+                 {}\n\n\
+                 It expires in {} minutes. If you didn't ask for it, you can ignore this email.",
+                security::opaque_token(&password_reset.id, &secret),
+                settings.password_reset_expiration().num_minutes()
+            ),
+        };
+        mailer.send(&email)
+    }
+
+    /// Choose a new password with a token emailed by `request_password_reset`,
+    /// ending all the sessions of the user. A token can only be used once.
+    pub fn reset_password(
+        token: &str,
+        new_password: &str,
+        settings: &SecuritySettings,
+        db: &dyn Store,
+    ) -> Result<(), AuthError> {
+        if !is_valid_password(new_password) {
+            return Err(AuthError::InvalidPassword);
+        }
+        let (id, secret) = security::parse_opaque_token(token).ok_or(AuthError::InvalidToken)?;
+        let password_reset = repositories::PasswordResetRepository::find_one(db, &id)?
+            .filter(|r| r.used_at.is_none() && r.expires_at > chrono::Utc::now())
+            .ok_or(AuthError::InvalidToken)?;
+        if !security::verify_password(secret.as_bytes(), &password_reset.secret_hash)? {
+            return Err(AuthError::InvalidToken);
+        }
+
+        let password = security::hash_password(new_password.as_bytes(), settings.hashing())?;
+        repositories::transaction(db, |tx| {
+            // Another request may have used the same token meanwhile
+            if !repositories::PasswordResetRepository::use_one(tx, &password_reset.id)? {
+                return Err(AuthError::InvalidToken);
+            }
+            // The tokens emailed before can't be used anymore either
+            repositories::PasswordResetRepository::expire_by_user(tx, &password_reset.user_id)?;
+            repositories::UserRepository::update_password(tx, &password_reset.user_id, &password)?;
+            repositories::SessionRepository::revoke_by_user(tx, &password_reset.user_id, None)
+                .map_err(Into::into)
+        })
+    }
+
+    /// Find the signed in user.
+    pub fn viewer(viewer_id: &uuid::Uuid, db: &dyn Store) -> Result<repositories::User, AuthError> {
+        repositories::UserRepository::find_one(db, viewer_id)?.ok_or(AuthError::UserNotFound)
+    }
+
+    /// Find a user and check that this is their password.
+    fn check_password(
+        id: &uuid::Uuid,
+        password: &str,
+        db: &dyn Store,
+    ) -> Result<repositories::User, AuthError> {
+        let user =
+            repositories::UserRepository::find_one(db, id)?.ok_or(AuthError::UserNotFound)?;
+        if !security::verify_password(password.as_bytes(), &user.password)? {
+            return Err(AuthError::InvalidCredentials);
+        }
+        Ok(user)
+    }
+
+    /// Email a user a link to verify their current address with.
+    fn send_verification(
+        user: &repositories::User,
+        settings: &SecuritySettings,
+        mailer: &dyn Mailer,
+        db: &dyn Store,
+    ) -> anyhow::Result<()> {
+        let secret = security::generate_secret();
+        let new_email_verification = repositories::NewEmailVerification {
+            id: uuid::Uuid::new_v4(),
+            user_id: user.id,
+            email: user.email.clone(),
+            secret_hash: security::hash_password(secret.as_bytes(), settings.hashing())?,
+            expires_at: chrono::Utc::now() + settings.email_verification_expiration(),
+        };
+        let verification =
+            repositories::EmailVerificationRepository::save(db, &new_email_verification)?;
+
+        let url = settings.email_verification_url();
+        let separator = if url.contains('?') { '&' } else { '?' };
+        let email = Email {
+            to: verification.email,
+            subject: "Verify your email address".to_string(),
+            body: format!(
+                "Follow this link to verify your email address:\n\n\
+                 {}{}token={}\n\n\
+                 It expires in {} hours. If you didn't ask for it, you can ignore this email.",
+                url,
+                separator,
+                security::opaque_token(&verification.id, &secret),
+                settings.email_verification_expiration().num_hours()
+            ),
+        };
+        mailer
+            .send(&email)
+            .context("Couldn't send an email verification email")
+    }
+
+    /// Start a new session for a user.
+    fn sign(
+        user: &repositories::User,
+        settings: &SecuritySettings,
+        db: &dyn Store,
+    ) -> Result<Tokens, AuthError> {
+        let secret = security::generate_secret();
+        let new_session = repositories::NewSession {
+            id: uuid::Uuid::new_v4(),
+            user_id: user.id,
+            secret_hash: security::hash_password(secret.as_bytes(), settings.hashing())?,
+            expires_at: chrono::Utc::now() + settings.refresh_token_expiration(),
+        };
+        let session = repositories::SessionRepository::save(db, &new_session)?;
+
+        Self::tokens(&session, &secret, settings)
+    }
+
+    fn tokens(
+        session: &repositories::Session,
+        secret: &str,
+        settings: &SecuritySettings,
+    ) -> Result<Tokens, AuthError> {
+        let access_token = security::sign_token(
+            session.user_id,
+            session.id,
+            settings.token_expiration_time(),
+            settings.secret_key(),
+        )?;
+
+        Ok(Tokens {
+            access_token,
+            refresh_token: security::opaque_token(&session.id, secret),
+        })
+    }
+}
+
+/// Whether the user with this email address, if any, was sent too many password resets lately.
+fn password_resets_exceeded(
+    email: &str,
+    settings: &SecuritySettings,
+    db: &dyn Store,
+) -> anyhow::Result<bool> {
+    let since = chrono::Utc::now() - chrono::Duration::hours(1);
+    let sent = repositories::PasswordResetRepository::count_by_email_since(db, email, &since)?;
+    Ok(sent >= settings.password_resets_per_hour())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::services::fixtures::{self, Outbox};
+
+    #[test]
+    fn email_addresses_should_have_a_domain() {
+        assert!(is_valid_email("someone@example.com"));
+        assert!(!is_valid_email("someone@example"));
+        assert!(!is_valid_email("someone.example.com"));
+        assert!(!is_valid_email("some one@example.com"));
+    }
+
+    #[test]
+    fn passwords_should_be_8_to_64_characters_long() {
+        assert!(!is_valid_password("1234567"));
+        assert!(is_valid_password("12345678"));
+        assert!(is_valid_password(&"é".repeat(64)));
+        assert!(!is_valid_password(&"a".repeat(65)));
+    }
+
+    #[test]
+    fn login_should_rehash_outdated_passwords() {
+        let config = fixtures::settings();
+        let db = repositories::InMemoryStore::new();
+        let outdated =
+            argon2::hash_encoded(b"password", b"randomsalt", &argon2::Config::default()).unwrap();
+        let user_id = fixtures::user_with("user@test.com", &outdated, &db);
+
+        AuthService::login("user@test.com", "password", config.security(), &db).unwrap();
+        let rehashed = repositories::UserRepository::find_one(&db, &user_id)
+            .unwrap()
+            .unwrap()
+            .password;
+
+        assert_ne!(outdated, rehashed);
+        assert!(!security::needs_rehash(&rehashed, config.security().hashing()).unwrap());
+        assert!(AuthService::login("user@test.com", "password", config.security(), &db).is_ok());
+    }
+
+    #[test]
+    fn login_should_rehash_the_passwords_with_the_former_global_salt() {
+        let config = fixtures::settings();
+        let db = repositories::InMemoryStore::new();
+        // The current parameters, but the salt every user used to share
+        let argon2_config = argon2::Config {
+            variant: argon2::Variant::Argon2id,
+            mem_cost: 19 * 1024,
+            time_cost: 2,
+            ..argon2::Config::default()
+        };
+        let legacy = argon2::hash_encoded(b"password", b"globalsalt", &argon2_config).unwrap();
+        assert!(!security::needs_rehash(&legacy, config.security().hashing()).unwrap());
+        let user_id = fixtures::user_with("user@test.com", &legacy, &db);
+        db.forget_password_hashed_at(&user_id);
+
+        AuthService::login("user@test.com", "password", config.security(), &db).unwrap();
+        let user = repositories::UserRepository::find_one(&db, &user_id)
+            .unwrap()
+            .unwrap();
+
+        assert_ne!(legacy, user.password);
+        assert!(user.password_hashed_at.is_some());
+        assert!(AuthService::login("user@test.com", "password", config.security(), &db).is_ok());
+    }
+
+    #[test]
+    fn password_resets_should_be_limited_per_address() {
+        let config = fixtures::settings();
+        let db = repositories::InMemoryStore::new();
+        let outbox = Outbox::new();
+        AuthService::signup(
+            "user@test.com".to_string(),
+            "password",
+            config.security(),
+            &outbox,
+            &db,
+        )
+        .unwrap();
+        let resets = || {
+            outbox
+                .emails_to("user@test.com")
+                .unwrap()
+                .into_iter()
+                .filter(|e| e.subject == "Reset your password")
+                .count() as i64
+        };
+
+        for _ in 0..config.security().password_resets_per_hour() + 1 {
+            AuthService::send_password_reset("user@test.com", config.security(), &outbox, &db)
+                .unwrap();
+        }
+        AuthService::send_password_reset("nobody@test.com", config.security(), &outbox, &db)
+            .unwrap();
+
+        assert_eq!(config.security().password_resets_per_hour(), resets());
+        assert!(outbox.emails_to("nobody@test.com").unwrap().is_empty());
+    }
+
+    #[test]
+    fn emails_should_only_be_verified_while_the_user_has_them() {
+        let config = fixtures::settings();
+        let db = repositories::InMemoryStore::new();
+        let outbox = Outbox::new();
+        let verified = |id: &uuid::Uuid| {
+            repositories::UserRepository::find_one(&db, id)
+                .unwrap()
+                .unwrap()
+                .email_verified_at
+                .is_some()
+        };
+
+        let tokens = AuthService::signup(
+            "old@test.com".to_string(),
+            "password",
+            config.security(),
+            &outbox,
+            &db,
+        )
+        .unwrap();
+        let viewer =
+            security::verify_token(&tokens.access_token, config.security().secret_key()).unwrap();
+        let old_token = outbox.last_token("old@test.com");
+        AuthService::change_email(
+            viewer.id(),
+            "password",
+            "new@test.com".to_string(),
+            config.security(),
+            &outbox,
+            &db,
+        )
+        .unwrap();
+
+        assert!(matches!(
+            AuthService::verify_email(&old_token, &db),
+            Err(AuthError::InvalidToken)
+        ));
+        assert!(!verified(viewer.id()));
+        assert!(matches!(
+            AuthService::check_verified_email(viewer.id(), &db),
+            Err(AuthError::UnverifiedEmail)
+        ));
+
+        let new_token = outbox.last_token("new@test.com");
+        AuthService::verify_email(&new_token, &db).unwrap();
+        assert!(verified(viewer.id()));
+        assert!(AuthService::check_verified_email(viewer.id(), &db).is_ok());
+        assert!(matches!(
+            AuthService::verify_email(&new_token, &db),
+            Err(AuthError::InvalidToken)
+        ));
+    }
+}
@@ -0,0 +1,221 @@
+use super::{access, is_valid_name};
+use crate::{
+    domain::member::Role,
+    infrastructure::repositories::{self, Store},
+};
+
+#[derive(Debug)]
+pub enum CategoryError {
+    InvalidName,
+    GroupNotFound,
+    CategoryNotFound,
+    /// Some expenses, even in the trash, still belong to the category.
+    CategoryInUse,
+    NonUniqueName(String),
+    /// The viewer's role in the group doesn't allow it.
+    Forbidden,
+    Internal(anyhow::Error),
+}
+
+impl From<anyhow::Error> for CategoryError {
+    fn from(e: anyhow::Error) -> Self {
+        CategoryError::Internal(e)
+    }
+}
+
+pub struct CategoryService;
+impl CategoryService {
+    /// Find a custom category of a group the viewer is a member of with at least this role.
+    /// The built-in categories belong to no group.
+    pub fn owned_category(
+        id: &uuid::Uuid,
+        role: Role,
+        viewer_id: &uuid::Uuid,
+        db: &dyn Store,
+    ) -> Result<repositories::Category, CategoryError> {
+        let found = repositories::CategoryRepository::find_one_by_user(db, id, viewer_id)?;
+        access(found, role)?.or(CategoryError::CategoryNotFound, CategoryError::Forbidden)
+    }
+
+    /// Add a custom category to a group, named differently from the ones available to it.
+    /// Reserved to the group's editors and owners.
+    pub fn add_category(
+        group_id: &uuid::Uuid,
+        name: String,
+        viewer_id: &uuid::Uuid,
+        db: &dyn Store,
+    ) -> Result<repositories::Category, CategoryError> {
+        if !is_valid_name(&name) {
+            return Err(CategoryError::InvalidName);
+        }
+
+        repositories::transaction(db, |tx| {
+            let group = owned_group(group_id, viewer_id, tx)?;
+            check_name(&name, &group, None, tx)?;
+
+            let new_category = repositories::NewCategory {
+                id: uuid::Uuid::new_v4(),
+                group_id: *group_id,
+                name,
+            };
+            repositories::CategoryRepository::save(tx, &new_category, viewer_id)
+                .map_err(|e| name_taken(e, &new_category.name))
+        })
+    }
+
+    /// Rename a custom category. Reserved to the group's editors and owners.
+    pub fn update_category(
+        id: &uuid::Uuid,
+        name: Option<String>,
+        viewer_id: &uuid::Uuid,
+        db: &dyn Store,
+    ) -> Result<repositories::Category, CategoryError> {
+        if let Some(n) = &name {
+            if !is_valid_name(n) {
+                return Err(CategoryError::InvalidName);
+            }
+        }
+
+        repositories::transaction(db, |tx| {
+            // Only the custom categories of the viewer's groups can be changed
+            let category = Self::owned_category(id, Role::Editor, viewer_id, tx)?;
+            if let (Some(n), Some(group_id)) = (&name, &category.group_id) {
+                let group = owned_group(group_id, viewer_id, tx)?;
+                check_name(n, &group, Some(id), tx)?;
+            }
+
+            let category = repositories::UpdateCategory { id: *id, name };
+            repositories::CategoryRepository::update_one(tx, &category, viewer_id)
+                .map_err(|e| name_taken(e, category.name.as_deref().unwrap_or_default()))?;
+            repositories::CategoryRepository::find_one(tx, id)?
+                .ok_or(CategoryError::CategoryNotFound)
+        })
+    }
+
+    /// Remove a custom category, unless some expenses, even in the trash, belong to it.
+    /// Reserved to the group's editors and owners.
+    pub fn remove_category(
+        id: &uuid::Uuid,
+        viewer_id: &uuid::Uuid,
+        db: &dyn Store,
+    ) -> Result<(), CategoryError> {
+        repositories::transaction(db, |tx| {
+            // Nothing to do once it's gone
+            if repositories::CategoryRepository::find_one(tx, id)?.is_none() {
+                return Ok(());
+            }
+            // The built-in categories belong to no group, and can't be removed
+            Self::owned_category(id, Role::Editor, viewer_id, tx)?;
+            if repositories::CategoryRepository::is_used(tx, id)? {
+                return Err(CategoryError::CategoryInUse);
+            }
+
+            repositories::CategoryRepository::delete_one(tx, id, viewer_id)
+                .map_err(CategoryError::Internal)
+        })
+    }
+}
+
+/// Find a group the viewer may add categories to.
+fn owned_group(
+    id: &uuid::Uuid,
+    viewer_id: &uuid::Uuid,
+    db: &dyn Store,
+) -> Result<repositories::Group, CategoryError> {
+    let found = repositories::GroupRepository::find_one_by_user(db, id, viewer_id)?;
+    access(found, Role::Editor)?.or(CategoryError::GroupNotFound, CategoryError::Forbidden)
+}
+
+/// Check that no other category available to the group has this name.
+fn check_name(
+    name: &str,
+    group: &repositories::Group,
+    category_id: Option<&uuid::Uuid>,
+    db: &dyn Store,
+) -> Result<(), CategoryError> {
+    let categories = repositories::CategoryRepository::find_by_group(db, group)?;
+    if categories
+        .iter()
+        .any(|c| c.name == name && Some(&c.id) != category_id)
+    {
+        Err(CategoryError::NonUniqueName(name.to_string()))
+    } else {
+        Ok(())
+    }
+}
+
+/// A unique constraint means another category got this name since it was checked.
+fn name_taken(e: anyhow::Error, name: &str) -> CategoryError {
+    if repositories::is_unique_violation(&e) {
+        CategoryError::NonUniqueName(name.to_string())
+    } else {
+        CategoryError::Internal(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::services::{fixtures, GroupService};
+
+    fn trip(db: &dyn Store) -> (uuid::Uuid, repositories::Group) {
+        let owner = fixtures::user(db);
+        let group = GroupService::add_group("Trip".to_string(), None, &owner, db).unwrap();
+        (owner, group)
+    }
+
+    #[test]
+    fn categories_should_be_named_differently_from_the_available_ones() {
+        let db = repositories::InMemoryStore::new();
+        let (owner, group) = trip(&db);
+
+        let gifts =
+            CategoryService::add_category(&group.id, "Gifts".to_string(), &owner, &db).unwrap();
+        assert_eq!(Some(group.id), gifts.group_id);
+        for name in &["Gifts", "Food"] {
+            assert!(matches!(
+                CategoryService::add_category(&group.id, name.to_string(), &owner, &db),
+                Err(CategoryError::NonUniqueName(_))
+            ));
+        }
+        assert!(matches!(
+            CategoryService::update_category(&gifts.id, Some("Food".to_string()), &owner, &db),
+            Err(CategoryError::NonUniqueName(_))
+        ));
+        let presents =
+            CategoryService::update_category(&gifts.id, Some("Presents".to_string()), &owner, &db)
+                .unwrap();
+        assert_eq!("Presents", presents.name);
+    }
+
+    #[test]
+    fn only_custom_categories_should_be_removed() {
+        let db = repositories::InMemoryStore::new();
+        let (owner, group) = trip(&db);
+        let (stranger, _) = trip(&db);
+        let gifts =
+            CategoryService::add_category(&group.id, "Gifts".to_string(), &owner, &db).unwrap();
+        let food = repositories::CategoryRepository::find_by_group(&db, &group)
+            .unwrap()
+            .into_iter()
+            .find(|c| c.name == "Food")
+            .unwrap();
+
+        assert!(matches!(
+            CategoryService::remove_category(&food.id, &owner, &db),
+            Err(CategoryError::CategoryNotFound)
+        ));
+        assert!(matches!(
+            CategoryService::remove_category(&gifts.id, &stranger, &db),
+            Err(CategoryError::CategoryNotFound)
+        ));
+        assert!(CategoryService::owned_category(&gifts.id, Role::Editor, &owner, &db).is_ok());
+
+        CategoryService::remove_category(&gifts.id, &owner, &db).unwrap();
+        CategoryService::remove_category(&gifts.id, &owner, &db).unwrap();
+        assert!(matches!(
+            CategoryService::owned_category(&gifts.id, Role::Editor, &owner, &db),
+            Err(CategoryError::CategoryNotFound)
+        ));
+    }
+}
@@ -0,0 +1,171 @@
+use crate::{
+    domain::currency::{self, CurrencyError},
+    infrastructure::{
+        config::Settings,
+        repositories::{self, Store},
+    },
+};
+
+#[derive(Debug)]
+pub enum ExchangeRateError {
+    Currency(CurrencyError),
+    UserNotFound,
+    /// The viewer isn't an admin, or hasn't verified their admin address yet.
+    Forbidden,
+    Internal(anyhow::Error),
+}
+
+impl From<anyhow::Error> for ExchangeRateError {
+    fn from(e: anyhow::Error) -> Self {
+        ExchangeRateError::Internal(e)
+    }
+}
+
+impl From<CurrencyError> for ExchangeRateError {
+    fn from(e: CurrencyError) -> Self {
+        ExchangeRateError::Currency(e)
+    }
+}
+
+pub struct ExchangeRateService;
+impl ExchangeRateService {
+    /// The rates used to convert the expenses into their group's currency.
+    pub fn find_all(db: &dyn Store) -> Result<Vec<repositories::ExchangeRate>, ExchangeRateError> {
+        repositories::ExchangeRateRepository::find_all(db).map_err(ExchangeRateError::Internal)
+    }
+
+    /// Set the rate converting a currency into another one. Reserved to the admins.
+    pub fn set_rate(
+        rate: currency::Rate,
+        viewer_id: &uuid::Uuid,
+        settings: &Settings,
+        db: &dyn Store,
+    ) -> Result<(), ExchangeRateError> {
+        check_admin(viewer_id, settings, db)?;
+        currency::validate_rate(&rate)?;
+
+        save_all(vec![rate], db)
+    }
+
+    /// Import the rates of a CSV, none of them if a line is invalid. Reserved to the admins.
+    /// Returns the number of imported rates.
+    pub fn import_rates(
+        csv: &str,
+        viewer_id: &uuid::Uuid,
+        settings: &Settings,
+        db: &dyn Store,
+    ) -> Result<usize, ExchangeRateError> {
+        check_admin(viewer_id, settings, db)?;
+        let rates = currency::parse_csv(csv)?;
+        let count = rates.len();
+
+        save_all(rates, db)?;
+        Ok(count)
+    }
+
+    /// Remove the rate converting a currency into another one. Reserved to the admins.
+    pub fn remove_rate(
+        from: &str,
+        to: &str,
+        viewer_id: &uuid::Uuid,
+        settings: &Settings,
+        db: &dyn Store,
+    ) -> Result<(), ExchangeRateError> {
+        check_admin(viewer_id, settings, db)?;
+
+        repositories::ExchangeRateRepository::delete_one(db, from, to)
+            .map_err(ExchangeRateError::Internal)
+    }
+}
+
+/// Check that the viewer is allowed to maintain the exchange rates.
+/// Anyone could sign up with an admin address, until it's verified.
+fn check_admin(
+    viewer_id: &uuid::Uuid,
+    settings: &Settings,
+    db: &dyn Store,
+) -> Result<(), ExchangeRateError> {
+    match repositories::UserRepository::find_one(db, viewer_id)? {
+        None => Err(ExchangeRateError::UserNotFound),
+        Some(u) if u.email_verified_at.is_some() && settings.is_admin(&u.email) => Ok(()),
+        Some(_) => Err(ExchangeRateError::Forbidden),
+    }
+}
+
+/// Save rates, replacing the existing ones for the same currencies.
+fn save_all(rates: Vec<currency::Rate>, db: &dyn Store) -> Result<(), ExchangeRateError> {
+    let new_rates = rates
+        .into_iter()
+        .map(|r| repositories::NewExchangeRate {
+            from_currency: r.from,
+            to_currency: r.to,
+            rate: r.rate,
+        })
+        .collect::<Vec<_>>();
+
+    repositories::ExchangeRateRepository::save_all(db, &new_rates)
+        .map_err(ExchangeRateError::Internal)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::services::fixtures;
+
+    fn euros_to_dollars() -> currency::Rate {
+        currency::Rate {
+            from: "EUR".to_string(),
+            to: "USD".to_string(),
+            rate: 1.1,
+        }
+    }
+
+    #[test]
+    fn rates_should_only_be_set_by_verified_admins() {
+        let db = repositories::InMemoryStore::new();
+        let admin = fixtures::user_with("admin@test.com", "password", &db);
+        let config = fixtures::settings().with_admin_email("admin@test.com");
+
+        assert!(matches!(
+            ExchangeRateService::set_rate(euros_to_dollars(), &admin, &config, &db),
+            Err(ExchangeRateError::Forbidden)
+        ));
+        repositories::UserRepository::verify_email(&db, &admin, "admin@test.com").unwrap();
+        ExchangeRateService::set_rate(euros_to_dollars(), &admin, &config, &db).unwrap();
+        assert_eq!(1, ExchangeRateService::find_all(&db).unwrap().len());
+
+        let other = fixtures::verified_user(&db);
+        assert!(matches!(
+            ExchangeRateService::remove_rate("EUR", "USD", &other, &config, &db),
+            Err(ExchangeRateError::Forbidden)
+        ));
+        ExchangeRateService::remove_rate("EUR", "USD", &admin, &config, &db).unwrap();
+        assert!(ExchangeRateService::find_all(&db).unwrap().is_empty());
+    }
+
+    #[test]
+    fn invalid_imports_should_save_nothing() {
+        let db = repositories::InMemoryStore::new();
+        let admin = fixtures::user_with("admin@test.com", "password", &db);
+        repositories::UserRepository::verify_email(&db, &admin, "admin@test.com").unwrap();
+        let config = fixtures::settings().with_admin_email("admin@test.com");
+
+        assert!(matches!(
+            ExchangeRateService::import_rates("EUR,USD,1.1\nEUR,EUR,1", &admin, &config, &db),
+            Err(ExchangeRateError::Currency(CurrencyError::InvalidCsvLine(
+                2
+            )))
+        ));
+        assert!(ExchangeRateService::find_all(&db).unwrap().is_empty());
+        assert_eq!(
+            2,
+            ExchangeRateService::import_rates(
+                "from,to,rate\nEUR,USD,1.1\nUSD,JPY,150",
+                &admin,
+                &config,
+                &db
+            )
+            .unwrap()
+        );
+    }
+}
@@ -0,0 +1,837 @@
+use super::{access, Money};
+use crate::{
+    domain::{
+        balance, currency,
+        expense::{self, DateRange, Participant, Payer, SplitError, SplitMode},
+        member::Role,
+        settlement, statistics,
+    },
+    infrastructure::repositories::{self, Store},
+};
+
+#[derive(Debug)]
+pub enum ExpenseError {
+    InvalidAmount,
+    /// A payment from a person to themselves.
+    InvalidPayment,
+    InvalidCurrency,
+    Split(SplitError),
+    ExchangeRateNotFound(String, String),
+    GroupNotFound,
+    PersonNotFound,
+    ExpenseNotFound,
+    PaymentNotFound,
+    CategoryNotFound,
+    /// The viewer's role in the group doesn't allow it.
+    Forbidden,
+    Internal(anyhow::Error),
+}
+
+impl From<anyhow::Error> for ExpenseError {
+    fn from(e: anyhow::Error) -> Self {
+        ExpenseError::Internal(e)
+    }
+}
+
+impl From<SplitError> for ExpenseError {
+    fn from(e: SplitError) -> Self {
+        ExpenseError::Split(e)
+    }
+}
+
+/// An amount a person should pay another one back, in the minor unit of their group's currency.
+pub struct Transfer {
+    pub from: repositories::Person,
+    pub to: repositories::Person,
+    pub amount: i64,
+}
+
+/// An expense to add to a group.
+pub struct AddExpense {
+    pub group_id: uuid::Uuid,
+    pub name: String,
+    /// In the minor unit of the expense's currency.
+    pub amount: i64,
+    /// Defaults to the group's currency.
+    pub currency: Option<String>,
+    pub payers: Vec<Payer>,
+    pub split_mode: SplitMode,
+    /// None means the whole group.
+    pub participants: Vec<Participant>,
+    /// Defaults to today.
+    pub occurred_on: Option<chrono::NaiveDate>,
+    pub category_id: Option<uuid::Uuid>,
+}
+
+/// The changes to make to an expense, None leaving a field as it is.
+#[derive(Default)]
+pub struct UpdateExpense {
+    pub name: Option<String>,
+    pub amount: Option<i64>,
+    /// A new currency is converted with the current exchange rate.
+    pub currency: Option<String>,
+    pub payers: Option<Vec<Payer>>,
+    pub split_mode: Option<SplitMode>,
+    pub participants: Option<Vec<Participant>>,
+    pub occurred_on: Option<chrono::NaiveDate>,
+    pub category_id: Option<uuid::Uuid>,
+}
+
+pub struct ExpenseService;
+impl ExpenseService {
+    /// Find an expense of a group the viewer is a member of with at least this role.
+    pub fn owned_expense(
+        id: &uuid::Uuid,
+        role: Role,
+        viewer_id: &uuid::Uuid,
+        db: &dyn Store,
+    ) -> Result<repositories::Expense, ExpenseError> {
+        let found = repositories::ExpenseRepository::find_one_by_user(db, id, viewer_id)?;
+        access(found, role)?.or(ExpenseError::ExpenseNotFound, ExpenseError::Forbidden)
+    }
+
+    /// Find an expense in the trash of a group the viewer is a member of with at least this role.
+    pub fn trashed_expense(
+        id: &uuid::Uuid,
+        role: Role,
+        viewer_id: &uuid::Uuid,
+        db: &dyn Store,
+    ) -> Result<repositories::Expense, ExpenseError> {
+        let found = repositories::ExpenseRepository::find_deleted_one_by_user(db, id, viewer_id)?;
+        access(found, role)?.or(ExpenseError::ExpenseNotFound, ExpenseError::Forbidden)
+    }
+
+    /// Find a payment of a group the viewer is a member of with at least this role.
+    pub fn owned_payment(
+        id: &uuid::Uuid,
+        role: Role,
+        viewer_id: &uuid::Uuid,
+        db: &dyn Store,
+    ) -> Result<repositories::Payment, ExpenseError> {
+        let found = repositories::PaymentRepository::find_one_by_user(db, id, viewer_id)?;
+        access(found, role)?.or(ExpenseError::PaymentNotFound, ExpenseError::Forbidden)
+    }
+
+    /// Add an expense paid and split between persons of its group, converted into the group's
+    /// currency with the current exchange rate. Reserved to the group's editors and owners.
+    pub fn add_expense(
+        new: AddExpense,
+        viewer_id: &uuid::Uuid,
+        db: &dyn Store,
+    ) -> Result<repositories::Expense, ExpenseError> {
+        let AddExpense {
+            group_id,
+            name,
+            amount,
+            currency,
+            payers,
+            split_mode,
+            participants,
+            occurred_on,
+            category_id,
+        } = new;
+        if amount < 1 {
+            return Err(ExpenseError::InvalidAmount);
+        }
+        expense::validate_payers(amount, &payers)?;
+        expense::validate_split(split_mode, amount, &participants)?;
+        if let Some(c) = &currency {
+            currency::validate_code(c).map_err(|_| ExpenseError::InvalidCurrency)?;
+        }
+
+        repositories::transaction(db, |tx| {
+            let group = owned_group(&group_id, viewer_id, tx)?;
+            // Freeze the rate converting the expense into the group's currency
+            let currency = currency.unwrap_or_else(|| group.currency.clone());
+            let exchange_rate = exchange_rate(&currency, &group.currency, tx)?;
+
+            // The category and the persons must belong to the group
+            if let Some(c) = &category_id {
+                check_category(c, &group.id, tx)?;
+            }
+            let persons = repositories::PersonRepository::find_by_group(tx, &group)?;
+            if !payers
+                .iter()
+                .map(|p| &p.person_id)
+                .chain(participants.iter().map(|p| &p.person_id))
+                .all(|id| persons.iter().any(|p| p.id == *id))
+            {
+                return Err(ExpenseError::PersonNotFound);
+            }
+
+            let new_expense = repositories::NewExpense {
+                id: uuid::Uuid::new_v4(),
+                group_id,
+                name,
+                amount,
+                split_mode: split_mode.to_string(),
+                currency,
+                exchange_rate,
+                occurred_on: occurred_on.unwrap_or_else(|| chrono::Utc::today().naive_utc()),
+                category_id,
+            };
+            let expense = repositories::ExpenseRepository::save(tx, &new_expense, viewer_id)?;
+            save_payers(&expense.id, &payers, viewer_id, tx)?;
+            save_shares(&expense.id, &participants, viewer_id, tx)?;
+            Ok(expense)
+        })
+    }
+
+    /// Change an expense, which must still be split between persons of its group.
+    /// Reserved to the group's editors and owners.
+    pub fn update_expense(
+        id: &uuid::Uuid,
+        changes: UpdateExpense,
+        viewer_id: &uuid::Uuid,
+        db: &dyn Store,
+    ) -> Result<repositories::Expense, ExpenseError> {
+        let UpdateExpense {
+            name,
+            amount,
+            currency,
+            mut payers,
+            split_mode,
+            participants,
+            occurred_on,
+            category_id,
+        } = changes;
+        if amount.is_some_and(|a| a < 1) {
+            return Err(ExpenseError::InvalidAmount);
+        }
+        if let Some(c) = &currency {
+            currency::validate_code(c).map_err(|_| ExpenseError::InvalidCurrency)?;
+        }
+        repositories::transaction(db, |tx| {
+            // Checked in the transaction, not to update an expense changed meanwhile
+            let mut exchange_rate = None;
+            let expense = Self::owned_expense(id, Role::Editor, viewer_id, tx)?;
+
+            // Check that the expense can still be split
+            if amount.is_some()
+                || payers.is_some()
+                || split_mode.is_some()
+                || participants.is_some()
+                || currency.is_some()
+                || category_id.is_some()
+            {
+                if let Some(c) = &category_id {
+                    check_category(c, &expense.group_id, tx)?;
+                }
+                let mode = match split_mode {
+                    Some(m) => m,
+                    None => expense.split_mode.parse()?,
+                };
+                let current = match &participants {
+                    Some(p) => p.clone(),
+                    None => repositories::ExpenseShareRepository::find_by_expense(tx, &expense)?
+                        .into_iter()
+                        .map(|s| Participant {
+                            person_id: s.person_id,
+                            value: s.value,
+                        })
+                        .collect(),
+                };
+                // A new currency is converted with today's rate
+                if let Some(c) = currency.as_ref().filter(|c| **c != expense.currency) {
+                    let group = repositories::GroupRepository::find_one(tx, &expense.group_id)?
+                        .ok_or(ExpenseError::GroupNotFound)?;
+                    exchange_rate = Some(self::exchange_rate(c, &group.currency, tx)?);
+                }
+
+                let total = amount.unwrap_or(expense.amount);
+                expense::validate_split(mode, total, &current)?;
+
+                // A single payer keeps paying the whole amount when it changes
+                if payers.is_none() && amount.is_some() {
+                    let mut current =
+                        repositories::ExpensePayerRepository::find_by_expense(tx, &expense)?
+                            .into_iter()
+                            .map(|p| Payer {
+                                person_id: p.person_id,
+                                amount: p.amount,
+                            })
+                            .collect::<Vec<_>>();
+                    if let [payer] = current.as_mut_slice() {
+                        payer.amount = total;
+                    }
+                    payers = Some(current);
+                }
+                if let Some(p) = &payers {
+                    expense::validate_payers(total, p)?;
+                }
+            }
+
+            let update = repositories::UpdateExpense {
+                id: *id,
+                name,
+                amount,
+                split_mode: split_mode.map(|m| m.to_string()),
+                currency: exchange_rate.and(currency),
+                exchange_rate,
+                occurred_on,
+                category_id,
+            };
+
+            // The payers and the participants must belong to the expense's group
+            if payers.is_some() || participants.is_some() {
+                let persons =
+                    repositories::PersonRepository::find_by_group_id(tx, &expense.group_id)?;
+                if !payers
+                    .iter()
+                    .flatten()
+                    .map(|p| p.person_id)
+                    .chain(participants.iter().flatten().map(|p| p.person_id))
+                    .all(|id| persons.iter().any(|q| q.id == id))
+                {
+                    return Err(ExpenseError::PersonNotFound);
+                }
+            }
+
+            repositories::ExpenseRepository::update_one(tx, &update, viewer_id)?;
+            if let Some(p) = &payers {
+                save_payers(id, p, viewer_id, tx)?;
+            }
+            if let Some(p) = &participants {
+                save_shares(id, p, viewer_id, tx)?;
+            }
+            repositories::ExpenseRepository::find_one(tx, id)?.ok_or(ExpenseError::ExpenseNotFound)
+        })
+    }
+
+    /// Move an expense to the trash. Reserved to the group's editors and owners.
+    pub fn remove_expense(
+        id: &uuid::Uuid,
+        viewer_id: &uuid::Uuid,
+        db: &dyn Store,
+    ) -> Result<(), ExpenseError> {
+        repositories::transaction(db, |tx| {
+            // Nothing to do once it's gone
+            if repositories::ExpenseRepository::find_one(tx, id)?.is_none() {
+                return Ok(());
+            }
+            Self::owned_expense(id, Role::Editor, viewer_id, tx)?;
+
+            repositories::ExpenseRepository::delete_one(tx, id, viewer_id)
+                .map_err(ExpenseError::Internal)
+        })
+    }
+
+    /// Move an expense back from the trash, once the persons who paid it are out of it.
+    /// Reserved to the group's editors and owners.
+    pub fn restore_expense(
+        id: &uuid::Uuid,
+        viewer_id: &uuid::Uuid,
+        db: &dyn Store,
+    ) -> Result<(), ExpenseError> {
+        let expense = match Self::trashed_expense(id, Role::Editor, viewer_id, db) {
+            // An expense out of the trash is left as it is
+            Err(ExpenseError::ExpenseNotFound) => {
+                return Self::owned_expense(id, Role::Editor, viewer_id, db).map(|_| ())
+            }
+            Err(e) => return Err(e),
+            Ok(e) => e,
+        };
+
+        repositories::transaction(db, |tx| {
+            let payers = repositories::ExpensePayerRepository::find_by_expense(tx, &expense)?;
+            let persons = repositories::PersonRepository::find_by_group_id(tx, &expense.group_id)?;
+            if !payers
+                .iter()
+                .all(|p| persons.iter().any(|q| q.id == p.person_id))
+            {
+                return Err(ExpenseError::PersonNotFound);
+            }
+
+            repositories::ExpenseRepository::restore_one(tx, id, viewer_id)
+                .map_err(ExpenseError::Internal)
+        })
+    }
+
+    /// Add a payment from a person to another one of the same group, in the group's currency.
+    /// Reserved to the group's editors and owners.
+    pub fn add_payment(
+        group_id: &uuid::Uuid,
+        from_person_id: &uuid::Uuid,
+        to_person_id: &uuid::Uuid,
+        amount: &Money,
+        viewer_id: &uuid::Uuid,
+        db: &dyn Store,
+    ) -> Result<repositories::Payment, ExpenseError> {
+        if from_person_id == to_person_id {
+            return Err(ExpenseError::InvalidPayment);
+        }
+        if amount.amount < 1 {
+            return Err(ExpenseError::InvalidAmount);
+        }
+
+        repositories::transaction(db, |tx| {
+            let group = owned_group(group_id, viewer_id, tx)?;
+            let amount = amount
+                .in_currency(&group.currency)
+                .ok_or(ExpenseError::InvalidCurrency)?;
+
+            // Both persons must belong to the group
+            let persons = repositories::PersonRepository::find_by_group(tx, &group)?;
+            if ![from_person_id, to_person_id]
+                .iter()
+                .all(|id| persons.iter().any(|p| p.id == **id))
+            {
+                return Err(ExpenseError::PersonNotFound);
+            }
+
+            let new_payment = repositories::NewPayment {
+                id: uuid::Uuid::new_v4(),
+                group_id: group.id,
+                from_person_id: *from_person_id,
+                to_person_id: *to_person_id,
+                amount,
+            };
+            repositories::PaymentRepository::save(tx, &new_payment, viewer_id)
+                .map_err(ExpenseError::Internal)
+        })
+    }
+
+    /// Remove a payment. Reserved to the group's editors and owners.
+    pub fn remove_payment(
+        id: &uuid::Uuid,
+        viewer_id: &uuid::Uuid,
+        db: &dyn Store,
+    ) -> Result<(), ExpenseError> {
+        repositories::transaction(db, |tx| {
+            // Nothing to do once it's gone
+            if repositories::PaymentRepository::find_one(tx, id)?.is_none() {
+                return Ok(());
+            }
+            Self::owned_payment(id, Role::Editor, viewer_id, tx)?;
+
+            repositories::PaymentRepository::delete_one(tx, id, viewer_id)
+                .map_err(ExpenseError::Internal)
+        })
+    }
+
+    /// The sum of the expenses which occurred in this range, in the minor unit of the group's currency.
+    pub fn total(
+        group: &repositories::Group,
+        range: &DateRange,
+        db: &dyn Store,
+    ) -> Result<i64, ExpenseError> {
+        Ok(
+            repositories::ExpenseRepository::find_by_group(db, group, range)?
+                .iter()
+                .map(|e| currency::convert(e.amount, minor_rate(e, group)))
+                .sum(),
+        )
+    }
+
+    /// The balances of the group's persons, in its currency, counting the expenses which occurred
+    /// in this range and the payments made in it.
+    pub fn balances(
+        group: &repositories::Group,
+        range: &DateRange,
+        db: &dyn Store,
+    ) -> Result<Vec<(repositories::Person, balance::Balance)>, ExpenseError> {
+        let persons = repositories::PersonRepository::find_by_group(db, group)?;
+        let expenses = repositories::ExpenseRepository::find_by_group(db, group, range)?;
+        let payments = repositories::PaymentRepository::find_by_group(db, group)?
+            .into_iter()
+            .filter(|p| range.contains(&p.created_at.naive_utc().date()))
+            .map(|p| balance::Payment {
+                from: p.from_person_id,
+                to: p.to_person_id,
+                amount: p.amount,
+            })
+            .collect::<Vec<_>>();
+        let payers = repositories::ExpensePayerRepository::find_by_expenses(db, &expenses)?;
+        let shares = repositories::ExpenseShareRepository::find_by_expenses(db, &expenses)?;
+
+        let members = persons
+            .iter()
+            .map(|p| balance::Member {
+                id: p.id,
+                resources: p.resources,
+            })
+            .collect::<Vec<_>>();
+        let spendings = expenses
+            .iter()
+            .zip(payers.into_iter().zip(shares))
+            .map(|(e, (payers, shares))| {
+                Ok(balance::Spending {
+                    payers: payers
+                        .iter()
+                        .map(|p| Payer {
+                            person_id: p.person_id,
+                            amount: p.amount,
+                        })
+                        .collect(),
+                    amount: e.amount,
+                    mode: e.split_mode.parse()?,
+                    participants: shares
+                        .iter()
+                        .map(|s| Participant {
+                            person_id: s.person_id,
+                            value: s.value,
+                        })
+                        .collect(),
+                }
+                .convert(minor_rate(e, group)))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let balances = balance::compute(&members, &spendings, &payments);
+
+        Ok(persons.into_iter().zip(balances).collect())
+    }
+
+    /// The fewest transfers needed to bring every person's balance back to zero, whatever the dates.
+    pub fn settlement_plan(
+        group: &repositories::Group,
+        db: &dyn Store,
+    ) -> Result<Vec<Transfer>, ExpenseError> {
+        let (persons, balances): (Vec<_>, Vec<_>) =
+            Self::balances(group, &DateRange::default(), db)?
+                .into_iter()
+                .unzip();
+        let person = |id: &uuid::Uuid| {
+            persons
+                .iter()
+                .find(|p| p.id == *id)
+                .cloned()
+                .expect("A transfer should only involve the group's persons")
+        };
+
+        Ok(settlement::plan(&balances)
+            .into_iter()
+            .map(|t| Transfer {
+                from: person(&t.from),
+                to: person(&t.to),
+                amount: t.amount,
+            })
+            .collect())
+    }
+
+    /// What was spent on each category in this range, the largest first.
+    /// A None category gathers the uncategorized expenses.
+    pub fn totals_by_category(
+        group: &repositories::Group,
+        range: &DateRange,
+        db: &dyn Store,
+    ) -> Result<Vec<(Option<repositories::Category>, i64)>, ExpenseError> {
+        let subtotals = repositories::StatisticsRepository::by_category(db, group, range)?
+            .into_iter()
+            .map(|s| statistics::Subtotal {
+                key: s.category_id,
+                currency: s.currency,
+                exchange_rate: s.exchange_rate,
+                amount: s.amount,
+            })
+            .collect();
+        let categories = repositories::CategoryRepository::find_by_group(db, group)?;
+
+        let mut totals = statistics::totals(subtotals, &group.currency)
+            .into_iter()
+            .map(|(id, total)| {
+                let category = id.and_then(|id| categories.iter().find(|c| c.id == id));
+                (category.cloned(), total)
+            })
+            .collect::<Vec<_>>();
+        totals.sort_by_key(|(_, total)| std::cmp::Reverse(*total));
+        Ok(totals)
+    }
+
+    /// What each person paid in this range, the largest first.
+    pub fn totals_by_person(
+        group: &repositories::Group,
+        range: &DateRange,
+        db: &dyn Store,
+    ) -> Result<Vec<(repositories::Person, i64)>, ExpenseError> {
+        let subtotals = repositories::StatisticsRepository::by_person(db, group, range)?
+            .into_iter()
+            .map(|s| statistics::Subtotal {
+                key: s.person_id,
+                currency: s.currency,
+                exchange_rate: s.exchange_rate,
+                amount: s.amount,
+            })
+            .collect();
+        let persons = repositories::PersonRepository::find_by_group(db, group)?;
+
+        let mut totals = statistics::totals(subtotals, &group.currency)
+            .into_iter()
+            .filter_map(|(id, total)| {
+                let person = persons.iter().find(|p| p.id == id);
+                person.map(|p| (p.clone(), total))
+            })
+            .collect::<Vec<_>>();
+        totals.sort_by_key(|(_, total)| std::cmp::Reverse(*total));
+        Ok(totals)
+    }
+
+    /// What was spent each month of this range, keyed by their first day, the oldest first.
+    /// The months without expenses are left out.
+    pub fn totals_by_month(
+        group: &repositories::Group,
+        range: &DateRange,
+        db: &dyn Store,
+    ) -> Result<Vec<(chrono::NaiveDate, i64)>, ExpenseError> {
+        let subtotals = repositories::StatisticsRepository::by_month(db, group, range)?
+            .into_iter()
+            .map(|s| statistics::Subtotal {
+                key: s.month,
+                currency: s.currency,
+                exchange_rate: s.exchange_rate,
+                amount: s.amount,
+            })
+            .collect();
+
+        Ok(statistics::totals(subtotals, &group.currency))
+    }
+}
+
+/// The rate converting the minor units of an expense into its group's ones.
+fn minor_rate(expense: &repositories::Expense, group: &repositories::Group) -> f64 {
+    currency::minor_rate(expense.exchange_rate, &expense.currency, &group.currency)
+}
+
+/// Find a group the viewer may add expenses and payments to.
+fn owned_group(
+    id: &uuid::Uuid,
+    viewer_id: &uuid::Uuid,
+    db: &dyn Store,
+) -> Result<repositories::Group, ExpenseError> {
+    let found = repositories::GroupRepository::find_one_by_user(db, id, viewer_id)?;
+    access(found, Role::Editor)?.or(ExpenseError::GroupNotFound, ExpenseError::Forbidden)
+}
+
+/// Check that a category is a built-in one or one of the group's.
+fn check_category(
+    category_id: &uuid::Uuid,
+    group_id: &uuid::Uuid,
+    db: &dyn Store,
+) -> Result<(), ExpenseError> {
+    match repositories::CategoryRepository::find_one(db, category_id)? {
+        Some(c) if c.group_id.is_none_or(|id| id == *group_id) => Ok(()),
+        _ => Err(ExpenseError::CategoryNotFound),
+    }
+}
+
+/// Find the rate converting a currency into another one.
+fn exchange_rate(from: &str, to: &str, db: &dyn Store) -> Result<f64, ExpenseError> {
+    let rates = repositories::ExchangeRateRepository::find_between(db, from, to)?
+        .into_iter()
+        .map(|r| currency::Rate {
+            from: r.from_currency,
+            to: r.to_currency,
+            rate: r.rate,
+        })
+        .collect::<Vec<_>>();
+
+    currency::find_rate(from, to, &rates)
+        .ok_or_else(|| ExpenseError::ExchangeRateNotFound(from.to_string(), to.to_string()))
+}
+
+/// Replace the persons who paid an expense.
+fn save_payers(
+    expense_id: &uuid::Uuid,
+    payers: &[Payer],
+    viewer_id: &uuid::Uuid,
+    db: &dyn Store,
+) -> Result<(), ExpenseError> {
+    let new_payers = payers
+        .iter()
+        .map(|p| repositories::NewExpensePayer {
+            expense_id: *expense_id,
+            person_id: p.person_id,
+            amount: p.amount,
+        })
+        .collect::<Vec<_>>();
+
+    repositories::ExpensePayerRepository::save_all(db, expense_id, &new_payers, viewer_id)
+        .map_err(ExpenseError::Internal)
+}
+
+/// Replace the persons covered by an expense.
+fn save_shares(
+    expense_id: &uuid::Uuid,
+    participants: &[Participant],
+    viewer_id: &uuid::Uuid,
+    db: &dyn Store,
+) -> Result<(), ExpenseError> {
+    let new_shares = participants
+        .iter()
+        .map(|p| repositories::NewExpenseShare {
+            expense_id: *expense_id,
+            person_id: p.person_id,
+            value: p.value,
+        })
+        .collect::<Vec<_>>();
+
+    repositories::ExpenseShareRepository::save_all(db, expense_id, &new_shares, viewer_id)
+        .map_err(ExpenseError::Internal)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::services::{fixtures, GroupService};
+
+    /// A group of the returned user, with two persons.
+    fn trip(db: &dyn Store) -> (uuid::Uuid, repositories::Group, Vec<repositories::Person>) {
+        let owner = fixtures::user(db);
+        let group = GroupService::add_group("Trip".to_string(), None, &owner, db).unwrap();
+        let persons = ["Alice", "Bob"]
+            .iter()
+            .map(|name| {
+                let resources = Money {
+                    amount: 100,
+                    currency: None,
+                };
+                GroupService::add_person(&group.id, name.to_string(), &resources, &owner, db)
+                    .unwrap()
+            })
+            .collect();
+        (owner, group, persons)
+    }
+
+    fn expense(group: &repositories::Group, persons: &[repositories::Person]) -> AddExpense {
+        AddExpense {
+            group_id: group.id,
+            name: "Dinner".to_string(),
+            amount: 3000,
+            currency: None,
+            payers: vec![Payer {
+                person_id: persons[0].id,
+                amount: 3000,
+            }],
+            split_mode: SplitMode::Equal,
+            participants: persons
+                .iter()
+                .map(|p| Participant {
+                    person_id: p.id,
+                    value: 0,
+                })
+                .collect(),
+            occurred_on: None,
+            category_id: None,
+        }
+    }
+
+    #[test]
+    fn expenses_should_be_split_between_the_group_persons() {
+        let db = repositories::InMemoryStore::new();
+        let (owner, group, persons) = trip(&db);
+        let (_, _, others) = trip(&db);
+
+        let added = ExpenseService::add_expense(expense(&group, &persons), &owner, &db).unwrap();
+        assert_eq!(group.currency, added.currency);
+
+        let mut foreign = expense(&group, &persons);
+        foreign.payers[0].person_id = others[0].id;
+        assert!(matches!(
+            ExpenseService::add_expense(foreign, &owner, &db),
+            Err(ExpenseError::PersonNotFound)
+        ));
+        let mut free = expense(&group, &persons);
+        free.amount = 0;
+        assert!(matches!(
+            ExpenseService::add_expense(free, &owner, &db),
+            Err(ExpenseError::InvalidAmount)
+        ));
+        let mut unknown = expense(&group, &persons);
+        unknown.currency = Some("USD".to_string());
+        assert!(matches!(
+            ExpenseService::add_expense(unknown, &owner, &db),
+            Err(ExpenseError::ExchangeRateNotFound(_, _))
+        ));
+    }
+
+    #[test]
+    fn expenses_should_be_restored_from_the_trash() {
+        let db = repositories::InMemoryStore::new();
+        let (owner, group, persons) = trip(&db);
+        let (stranger, _, _) = trip(&db);
+        let added = ExpenseService::add_expense(expense(&group, &persons), &owner, &db).unwrap();
+
+        assert!(matches!(
+            ExpenseService::remove_expense(&added.id, &stranger, &db),
+            Err(ExpenseError::ExpenseNotFound)
+        ));
+        assert!(ExpenseService::owned_expense(&added.id, Role::Editor, &owner, &db).is_ok());
+
+        ExpenseService::remove_expense(&added.id, &owner, &db).unwrap();
+        assert!(matches!(
+            ExpenseService::owned_expense(&added.id, Role::Editor, &owner, &db),
+            Err(ExpenseError::ExpenseNotFound)
+        ));
+        ExpenseService::remove_expense(&added.id, &owner, &db).unwrap();
+
+        ExpenseService::restore_expense(&added.id, &owner, &db).unwrap();
+        assert!(ExpenseService::owned_expense(&added.id, Role::Editor, &owner, &db).is_ok());
+    }
+
+    #[test]
+    fn balances_should_be_settled_by_transfers() {
+        let db = repositories::InMemoryStore::new();
+        let (owner, group, persons) = trip(&db);
+        ExpenseService::add_expense(expense(&group, &persons), &owner, &db).unwrap();
+        let range = DateRange::default();
+
+        assert_eq!(3000, ExpenseService::total(&group, &range, &db).unwrap());
+        let nets = ExpenseService::balances(&group, &range, &db)
+            .unwrap()
+            .into_iter()
+            .map(|(p, b)| (p.name, b.net()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![("Alice".to_string(), 1500), ("Bob".to_string(), -1500)],
+            nets
+        );
+        let plan = ExpenseService::settlement_plan(&group, &db).unwrap();
+        assert_eq!(1, plan.len());
+        assert_eq!(
+            ("Bob", "Alice", 1500),
+            (
+                plan[0].from.name.as_str(),
+                plan[0].to.name.as_str(),
+                plan[0].amount
+            )
+        );
+        assert_eq!(
+            vec![(persons[0].clone(), 3000)],
+            ExpenseService::totals_by_person(&group, &range, &db).unwrap()
+        );
+    }
+
+    #[test]
+    fn payments_should_be_between_two_persons_of_the_group() {
+        let db = repositories::InMemoryStore::new();
+        let (owner, group, persons) = trip(&db);
+        let (stranger, _, others) = trip(&db);
+        let amount = Money {
+            amount: 1500,
+            currency: None,
+        };
+        let pay = |from: &uuid::Uuid, to: &uuid::Uuid| {
+            ExpenseService::add_payment(&group.id, from, to, &amount, &owner, &db)
+        };
+
+        assert!(matches!(
+            pay(&persons[0].id, &persons[0].id),
+            Err(ExpenseError::InvalidPayment)
+        ));
+        assert!(matches!(
+            pay(&persons[0].id, &others[0].id),
+            Err(ExpenseError::PersonNotFound)
+        ));
+        let payment = pay(&persons[1].id, &persons[0].id).unwrap();
+
+        assert!(matches!(
+            ExpenseService::remove_payment(&payment.id, &stranger, &db),
+            Err(ExpenseError::PaymentNotFound)
+        ));
+        assert!(ExpenseService::owned_payment(&payment.id, Role::Editor, &owner, &db).is_ok());
+        ExpenseService::remove_payment(&payment.id, &owner, &db).unwrap();
+        assert!(matches!(
+            ExpenseService::owned_payment(&payment.id, Role::Editor, &owner, &db),
+            Err(ExpenseError::PaymentNotFound)
+        ));
+    }
+}
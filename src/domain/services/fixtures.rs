@@ -0,0 +1,90 @@
+//! The setup shared by the services' tests.
+
+use crate::infrastructure::{
+    config::Settings,
+    mail::{Email, Mailer, OutboxMailer},
+    repositories::{self, Store},
+};
+use std::{ops::Deref, path::PathBuf};
+
+/// The default settings, read without setting any env var.
+pub fn settings() -> Settings {
+    Settings::for_tests()
+}
+
+/// A user who signed up with this address and password, not verified yet.
+pub fn user_with(email: &str, password: &str, db: &dyn Store) -> uuid::Uuid {
+    let new_user = repositories::NewUser {
+        id: uuid::Uuid::new_v4(),
+        email: email.to_string(),
+        password: password.to_string(),
+    };
+    repositories::UserRepository::save(db, &new_user)
+        .unwrap()
+        .id
+}
+
+/// A user with an address of their own, not verified yet.
+pub fn user(db: &dyn Store) -> uuid::Uuid {
+    user_with(
+        &format!("{}@test.com", uuid::Uuid::new_v4()),
+        "password",
+        db,
+    )
+}
+
+/// A user with an address of their own, already verified.
+pub fn verified_user(db: &dyn Store) -> uuid::Uuid {
+    let email = format!("{}@test.com", uuid::Uuid::new_v4());
+    let id = user_with(&email, "password", db);
+    repositories::UserRepository::verify_email(db, &id, &email).unwrap();
+    id
+}
+
+/// An outbox in a temporary directory of its own, removed once dropped.
+pub struct Outbox {
+    mailer: OutboxMailer,
+    dir: PathBuf,
+}
+
+impl Outbox {
+    pub fn new() -> Self {
+        let dir = std::env::temp_dir().join(format!("outbox-test-{}", uuid::Uuid::new_v4()));
+        Outbox {
+            mailer: OutboxMailer::new(&dir).unwrap(),
+            dir,
+        }
+    }
+
+    /// The token of the last link emailed to this address.
+    pub fn last_token(&self, to: &str) -> String {
+        let body = self.mailer.emails_to(to).unwrap().pop().unwrap().body;
+        body.split("token=")
+            .nth(1)
+            .unwrap()
+            .split_whitespace()
+            .next()
+            .unwrap()
+            .to_string()
+    }
+}
+
+impl Deref for Outbox {
+    type Target = OutboxMailer;
+
+    fn deref(&self) -> &OutboxMailer {
+        &self.mailer
+    }
+}
+
+impl Mailer for Outbox {
+    fn send(&self, email: &Email) -> anyhow::Result<()> {
+        self.mailer.send(email)
+    }
+}
+
+impl Drop for Outbox {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
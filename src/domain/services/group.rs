@@ -0,0 +1,416 @@
+use super::{access, is_valid_name, Money};
+use crate::{
+    domain::{currency, member::Role},
+    infrastructure::repositories::{self, Store},
+};
+
+#[derive(Debug)]
+pub enum GroupError {
+    InvalidName,
+    InvalidCurrency,
+    InvalidResources,
+    UserNotFound,
+    GroupNotFound,
+    PersonNotFound,
+    NonUniqueName(String),
+    /// The viewer's role in the group doesn't allow it.
+    Forbidden,
+    Internal(anyhow::Error),
+}
+
+impl From<anyhow::Error> for GroupError {
+    fn from(e: anyhow::Error) -> Self {
+        GroupError::Internal(e)
+    }
+}
+
+/// The currency of the groups created without one.
+pub const DEFAULT_CURRENCY: &str = "EUR";
+
+pub struct GroupService;
+impl GroupService {
+    /// Find a group the viewer is a member of with at least this role.
+    pub fn owned_group(
+        id: &uuid::Uuid,
+        role: Role,
+        viewer_id: &uuid::Uuid,
+        db: &dyn Store,
+    ) -> Result<repositories::Group, GroupError> {
+        let found = repositories::GroupRepository::find_one_by_user(db, id, viewer_id)?;
+        access(found, role)?.or(GroupError::GroupNotFound, GroupError::Forbidden)
+    }
+
+    /// Find a group in the trash the viewer is a member of with at least this role.
+    pub fn trashed_group(
+        id: &uuid::Uuid,
+        role: Role,
+        viewer_id: &uuid::Uuid,
+        db: &dyn Store,
+    ) -> Result<repositories::Group, GroupError> {
+        let found = repositories::GroupRepository::find_deleted_one_by_user(db, id, viewer_id)?;
+        access(found, role)?.or(GroupError::GroupNotFound, GroupError::Forbidden)
+    }
+
+    /// Find a person of a group the viewer is a member of with at least this role.
+    pub fn owned_person(
+        id: &uuid::Uuid,
+        role: Role,
+        viewer_id: &uuid::Uuid,
+        db: &dyn Store,
+    ) -> Result<repositories::Person, GroupError> {
+        let found = repositories::PersonRepository::find_one_by_user(db, id, viewer_id)?;
+        access(found, role)?.or(GroupError::PersonNotFound, GroupError::Forbidden)
+    }
+
+    /// Find a person in the trash of a group the viewer is a member of with at least this role.
+    pub fn trashed_person(
+        id: &uuid::Uuid,
+        role: Role,
+        viewer_id: &uuid::Uuid,
+        db: &dyn Store,
+    ) -> Result<repositories::Person, GroupError> {
+        let found = repositories::PersonRepository::find_deleted_one_by_user(db, id, viewer_id)?;
+        access(found, role)?.or(GroupError::PersonNotFound, GroupError::Forbidden)
+    }
+
+    /// Add a group owned by the viewer, named differently from the other ones they created.
+    pub fn add_group(
+        name: String,
+        currency: Option<String>,
+        viewer_id: &uuid::Uuid,
+        db: &dyn Store,
+    ) -> Result<repositories::Group, GroupError> {
+        if !is_valid_name(&name) {
+            return Err(GroupError::InvalidName);
+        }
+        let currency = currency.unwrap_or_else(|| DEFAULT_CURRENCY.to_string());
+        currency::validate_code(&currency).map_err(|_| GroupError::InvalidCurrency)?;
+
+        repositories::transaction(db, |tx| {
+            // FIXME: Very inefficient query. Should use joins instead ?
+            let user = repositories::UserRepository::find_one(tx, viewer_id)?
+                .ok_or(GroupError::UserNotFound)?;
+            let groups = repositories::GroupRepository::find_by_user(tx, &user)?;
+            // Only the groups the viewer created, not the ones shared with them
+            if groups
+                .iter()
+                .any(|g| g.user_id == user.id && g.name == name)
+            {
+                return Err(GroupError::NonUniqueName(name));
+            }
+
+            let new_group = repositories::NewGroup {
+                id: uuid::Uuid::new_v4(),
+                user_id: user.id,
+                name,
+                currency,
+            };
+            repositories::GroupRepository::save(tx, &new_group)
+                .map_err(|e| name_taken(e, &new_group.name))
+        })
+    }
+
+    /// Rename a group. Reserved to its owners.
+    pub fn update_group(
+        id: &uuid::Uuid,
+        name: Option<String>,
+        viewer_id: &uuid::Uuid,
+        db: &dyn Store,
+    ) -> Result<repositories::Group, GroupError> {
+        if name.as_deref().is_some_and(|n| !is_valid_name(n)) {
+            return Err(GroupError::InvalidName);
+        }
+
+        repositories::transaction(db, |tx| {
+            Self::owned_group(id, Role::Owner, viewer_id, tx)?;
+
+            // The creator's other groups may already have this name
+            let group = repositories::UpdateGroup { id: *id, name };
+            repositories::GroupRepository::update_one(tx, &group, viewer_id)
+                .map_err(|e| name_taken(e, group.name.as_deref().unwrap_or_default()))?;
+            repositories::GroupRepository::find_one(tx, id)?.ok_or(GroupError::GroupNotFound)
+        })
+    }
+
+    /// Move a group to the trash, along with everything it holds. Reserved to its owners.
+    pub fn remove_group(
+        id: &uuid::Uuid,
+        viewer_id: &uuid::Uuid,
+        db: &dyn Store,
+    ) -> Result<(), GroupError> {
+        repositories::transaction(db, |tx| {
+            // Nothing to do once it's gone
+            if repositories::GroupRepository::find_one(tx, id)?.is_none() {
+                return Ok(());
+            }
+            Self::owned_group(id, Role::Owner, viewer_id, tx)?;
+
+            repositories::GroupRepository::delete_one(tx, id, viewer_id)
+                .map_err(GroupError::Internal)
+        })
+    }
+
+    /// Move a group back from the trash, unless its creator reused its name meanwhile.
+    /// Reserved to its owners.
+    pub fn restore_group(
+        id: &uuid::Uuid,
+        viewer_id: &uuid::Uuid,
+        db: &dyn Store,
+    ) -> Result<(), GroupError> {
+        let group = match Self::trashed_group(id, Role::Owner, viewer_id, db) {
+            // A group out of the trash is left as it is
+            Err(GroupError::GroupNotFound) => {
+                return Self::owned_group(id, Role::Owner, viewer_id, db).map(|_| ())
+            }
+            Err(e) => return Err(e),
+            Ok(g) => g,
+        };
+
+        repositories::transaction(db, |tx| {
+            let creator = repositories::UserRepository::find_one(tx, &group.user_id)?
+                .ok_or(GroupError::UserNotFound)?;
+            let groups = repositories::GroupRepository::find_by_user(tx, &creator)?;
+            if groups
+                .iter()
+                .any(|g| g.user_id == group.user_id && g.name == group.name)
+            {
+                return Err(GroupError::NonUniqueName(group.name));
+            }
+
+            repositories::GroupRepository::restore_one(tx, id, viewer_id)
+                .map_err(|e| name_taken(e, &group.name))
+        })
+    }
+
+    /// Add a person to a group, named differently from its other persons.
+    /// Reserved to the group's editors and owners.
+    pub fn add_person(
+        group_id: &uuid::Uuid,
+        name: String,
+        resources: &Money,
+        viewer_id: &uuid::Uuid,
+        db: &dyn Store,
+    ) -> Result<repositories::Person, GroupError> {
+        if !is_valid_name(&name) {
+            return Err(GroupError::InvalidName);
+        }
+        if resources.amount.is_negative() {
+            return Err(GroupError::InvalidResources);
+        }
+
+        repositories::transaction(db, |tx| {
+            let group = Self::owned_group(group_id, Role::Editor, viewer_id, tx)?;
+            let resources = resources
+                .in_currency(&group.currency)
+                .ok_or(GroupError::InvalidCurrency)?;
+            let persons = repositories::PersonRepository::find_by_group(tx, &group)?;
+            if persons.iter().any(|p| p.name == name) {
+                return Err(GroupError::NonUniqueName(name));
+            }
+
+            let new_person = repositories::NewPerson {
+                id: uuid::Uuid::new_v4(),
+                group_id: group.id,
+                name,
+                resources,
+            };
+            repositories::PersonRepository::save(tx, &new_person, viewer_id)
+                .map_err(|e| name_taken(e, &new_person.name))
+        })
+    }
+
+    /// Rename a person or change their resources, in the group's currency.
+    /// Reserved to the group's editors and owners.
+    pub fn update_person(
+        id: &uuid::Uuid,
+        name: Option<String>,
+        resources: Option<&Money>,
+        viewer_id: &uuid::Uuid,
+        db: &dyn Store,
+    ) -> Result<repositories::Person, GroupError> {
+        if name.as_deref().is_some_and(|n| !is_valid_name(n)) {
+            return Err(GroupError::InvalidName);
+        }
+        if resources.is_some_and(|r| r.amount.is_negative()) {
+            return Err(GroupError::InvalidResources);
+        }
+
+        repositories::transaction(db, |tx| {
+            let person = Self::owned_person(id, Role::Editor, viewer_id, tx)?;
+            let resources = match resources {
+                None => None,
+                Some(r) => {
+                    let group = repositories::GroupRepository::find_one(tx, &person.group_id)?
+                        .ok_or(GroupError::GroupNotFound)?;
+                    Some(
+                        r.in_currency(&group.currency)
+                            .ok_or(GroupError::InvalidCurrency)?,
+                    )
+                }
+            };
+
+            // The group's other persons may already have this name
+            let person = repositories::UpdatePerson {
+                id: *id,
+                name,
+                resources,
+            };
+            repositories::PersonRepository::update_one(tx, &person, viewer_id)
+                .map_err(|e| name_taken(e, person.name.as_deref().unwrap_or_default()))?;
+            repositories::PersonRepository::find_one(tx, id)?.ok_or(GroupError::PersonNotFound)
+        })
+    }
+
+    /// Move a person to the trash, along with the expenses they helped pay for.
+    /// Reserved to the group's editors and owners.
+    pub fn remove_person(
+        id: &uuid::Uuid,
+        viewer_id: &uuid::Uuid,
+        db: &dyn Store,
+    ) -> Result<(), GroupError> {
+        repositories::transaction(db, |tx| {
+            // Nothing to do once they're gone
+            if repositories::PersonRepository::find_one(tx, id)?.is_none() {
+                return Ok(());
+            }
+            Self::owned_person(id, Role::Editor, viewer_id, tx)?;
+
+            repositories::PersonRepository::delete_one(tx, id, viewer_id)
+                .map_err(GroupError::Internal)
+        })
+    }
+
+    /// Move a person back from the trash, along with the expenses deleted with them,
+    /// unless someone else took their name meanwhile. Reserved to the group's editors and owners.
+    pub fn restore_person(
+        id: &uuid::Uuid,
+        viewer_id: &uuid::Uuid,
+        db: &dyn Store,
+    ) -> Result<(), GroupError> {
+        let person = match Self::trashed_person(id, Role::Editor, viewer_id, db) {
+            // A person out of the trash is left as they are
+            Err(GroupError::PersonNotFound) => {
+                return Self::owned_person(id, Role::Editor, viewer_id, db).map(|_| ())
+            }
+            Err(e) => return Err(e),
+            Ok(p) => p,
+        };
+
+        repositories::transaction(db, |tx| {
+            let persons = repositories::PersonRepository::find_by_group_id(tx, &person.group_id)?;
+            if persons.iter().any(|p| p.name == person.name) {
+                return Err(GroupError::NonUniqueName(person.name));
+            }
+
+            repositories::PersonRepository::restore_one(tx, id, viewer_id)
+                .map_err(|e| name_taken(e, &person.name))
+        })
+    }
+}
+
+/// A unique constraint means another row got this name since it was checked.
+fn name_taken(e: anyhow::Error, name: &str) -> GroupError {
+    if repositories::is_unique_violation(&e) {
+        GroupError::NonUniqueName(name.to_string())
+    } else {
+        GroupError::Internal(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::services::fixtures::user;
+
+    #[test]
+    fn names_should_be_unique() {
+        let db = repositories::InMemoryStore::new();
+        let owner = user(&db);
+        let group = GroupService::add_group("Trip".to_string(), None, &owner, &db).unwrap();
+        let euros = Money {
+            amount: 100,
+            currency: None,
+        };
+
+        assert_eq!(DEFAULT_CURRENCY, group.currency);
+        assert!(matches!(
+            GroupService::add_group("Trip".to_string(), None, &owner, &db),
+            Err(GroupError::NonUniqueName(_))
+        ));
+        // Another user's groups can have the same names
+        assert!(GroupService::add_group("Trip".to_string(), None, &user(&db), &db).is_ok());
+
+        GroupService::add_person(&group.id, "Alice".to_string(), &euros, &owner, &db).unwrap();
+        let bob =
+            GroupService::add_person(&group.id, "Bob".to_string(), &euros, &owner, &db).unwrap();
+        assert!(matches!(
+            GroupService::add_person(&group.id, "Alice".to_string(), &euros, &owner, &db),
+            Err(GroupError::NonUniqueName(_))
+        ));
+        assert!(matches!(
+            GroupService::update_person(&bob.id, Some("Alice".to_string()), None, &owner, &db),
+            Err(GroupError::NonUniqueName(_))
+        ));
+        let dollars = Money {
+            amount: 100,
+            currency: Some("USD".to_string()),
+        };
+        assert!(matches!(
+            GroupService::add_person(&group.id, "Carol".to_string(), &dollars, &owner, &db),
+            Err(GroupError::InvalidCurrency)
+        ));
+    }
+
+    #[test]
+    fn groups_should_only_be_changed_by_their_members() {
+        let db = repositories::InMemoryStore::new();
+        let owner = user(&db);
+        let stranger = user(&db);
+        let group = GroupService::add_group("Trip".to_string(), None, &owner, &db).unwrap();
+        let person = GroupService::add_person(
+            &group.id,
+            "Alice".to_string(),
+            &Money {
+                amount: 0,
+                currency: None,
+            },
+            &owner,
+            &db,
+        )
+        .unwrap();
+
+        assert!(matches!(
+            GroupService::update_group(&group.id, Some("Mine".to_string()), &stranger, &db),
+            Err(GroupError::GroupNotFound)
+        ));
+        assert!(matches!(
+            GroupService::remove_person(&person.id, &stranger, &db),
+            Err(GroupError::PersonNotFound)
+        ));
+        assert!(matches!(
+            GroupService::remove_group(&group.id, &stranger, &db),
+            Err(GroupError::GroupNotFound)
+        ));
+        assert!(GroupService::owned_person(&person.id, Role::Editor, &owner, &db).is_ok());
+        assert!(GroupService::owned_group(&group.id, Role::Owner, &owner, &db).is_ok());
+    }
+
+    #[test]
+    fn groups_should_be_restored_from_the_trash() {
+        let db = repositories::InMemoryStore::new();
+        let owner = user(&db);
+        let group = GroupService::add_group("Trip".to_string(), None, &owner, &db).unwrap();
+
+        GroupService::remove_group(&group.id, &owner, &db).unwrap();
+        assert!(matches!(
+            GroupService::owned_group(&group.id, Role::Owner, &owner, &db),
+            Err(GroupError::GroupNotFound)
+        ));
+        // Removing it again changes nothing
+        GroupService::remove_group(&group.id, &owner, &db).unwrap();
+
+        GroupService::restore_group(&group.id, &owner, &db).unwrap();
+        assert!(GroupService::owned_group(&group.id, Role::Owner, &owner, &db).is_ok());
+        GroupService::restore_group(&group.id, &owner, &db).unwrap();
+    }
+}
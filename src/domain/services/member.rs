@@ -0,0 +1,393 @@
+use super::access;
+use crate::{
+    domain::member::Role,
+    infrastructure::{
+        config::SecuritySettings,
+        repositories::{self, Store},
+        security,
+    },
+};
+
+#[derive(Debug)]
+pub enum MemberError {
+    InvalidExpiration,
+    UserNotFound,
+    GroupNotFound,
+    PersonNotFound,
+    /// The invitation is unknown, revoked or its group is in the trash.
+    InvitationNotFound,
+    InvitationExpired,
+    InvitationAlreadyUsed,
+    AlreadyMember,
+    MemberNotFound,
+    /// A group can't be left without an owner.
+    LastOwner,
+    PersonAlreadyClaimed,
+    UnverifiedEmail,
+    /// The viewer's role in the group doesn't allow it.
+    Forbidden,
+    Internal(anyhow::Error),
+}
+
+impl From<anyhow::Error> for MemberError {
+    fn from(e: anyhow::Error) -> Self {
+        MemberError::Internal(e)
+    }
+}
+
+/// The longest an invitation can be used, in seconds.
+pub const MAX_INVITATION_LIFETIME: i32 = 30 * 24 * 3600;
+
+pub struct MemberService;
+impl MemberService {
+    /// Find an invitation of a group the viewer is a member of with at least this role.
+    pub fn owned_invitation(
+        id: &uuid::Uuid,
+        role: Role,
+        viewer_id: &uuid::Uuid,
+        db: &dyn Store,
+    ) -> Result<repositories::Invitation, MemberError> {
+        let found = repositories::InvitationRepository::find_one_by_user(db, id, viewer_id)?;
+        access(found, role)?.or(MemberError::InvitationNotFound, MemberError::Forbidden)
+    }
+
+    /// Invite someone to join a group with this role, for `expires_in` seconds.
+    /// Returns the invitation's token, which can't be retrieved later.
+    /// Reserved to the group's owners.
+    pub fn create_invitation(
+        group_id: &uuid::Uuid,
+        expires_in: i32,
+        role: Role,
+        viewer_id: &uuid::Uuid,
+        settings: &SecuritySettings,
+        db: &dyn Store,
+    ) -> Result<String, MemberError> {
+        if !(60..=MAX_INVITATION_LIFETIME).contains(&expires_in) {
+            return Err(MemberError::InvalidExpiration);
+        }
+        owned_group(group_id, Role::Owner, viewer_id, db)?;
+        if settings.verified_email_to_invite() {
+            check_verified_email(viewer_id, db)?;
+        }
+
+        let id = uuid::Uuid::new_v4();
+        let secret = security::generate_secret();
+        let secret_hash = security::hash_password(secret.as_bytes(), settings.hashing())?;
+        let new_invitation = repositories::NewInvitation {
+            id,
+            group_id: *group_id,
+            created_by: *viewer_id,
+            secret_hash,
+            expires_at: chrono::Utc::now() + chrono::Duration::seconds(expires_in.into()),
+            role: role.to_string(),
+        };
+        repositories::InvitationRepository::save(db, &new_invitation)?;
+
+        Ok(security::opaque_token(&id, &secret))
+    }
+
+    /// Join a group with an invitation, which can't be used again,
+    /// optionally claiming one of its persons nobody claimed yet.
+    pub fn accept_invitation(
+        token: &str,
+        person_id: Option<&uuid::Uuid>,
+        viewer_id: &uuid::Uuid,
+        settings: &SecuritySettings,
+        db: &dyn Store,
+    ) -> Result<(), MemberError> {
+        if settings.verified_email_to_join() {
+            check_verified_email(viewer_id, db)?;
+        }
+        let (invitation_id, secret) =
+            security::parse_opaque_token(token).ok_or(MemberError::InvitationNotFound)?;
+        let invitation = repositories::InvitationRepository::find_one(db, &invitation_id)?
+            .ok_or(MemberError::InvitationNotFound)?;
+        if !security::verify_password(secret.as_bytes(), &invitation.secret_hash)? {
+            return Err(MemberError::InvitationNotFound);
+        }
+        // A revoked invitation is as good as missing
+        if invitation.revoked_at.is_some() {
+            return Err(MemberError::InvitationNotFound);
+        }
+        if invitation.accepted_at.is_some() {
+            return Err(MemberError::InvitationAlreadyUsed);
+        }
+        if invitation.expires_at <= chrono::Utc::now() {
+            return Err(MemberError::InvitationExpired);
+        }
+        // The invitations of a group in the trash are as good as missing
+        if repositories::GroupRepository::find_one(db, &invitation.group_id)?.is_none() {
+            return Err(MemberError::InvitationNotFound);
+        }
+        // Members don't need an invitation, which stays available for someone else
+        match owned_group(&invitation.group_id, Role::Viewer, viewer_id, db) {
+            Err(MemberError::GroupNotFound) => (),
+            Err(e) => return Err(e),
+            Ok(_) => return Err(MemberError::AlreadyMember),
+        }
+        // The claimed person must be one of the group's and not be someone else yet
+        if let Some(id) = person_id {
+            match repositories::PersonRepository::find_one(db, id)? {
+                Some(p) if p.group_id == invitation.group_id => {
+                    if p.user_id.is_some() {
+                        return Err(MemberError::PersonAlreadyClaimed);
+                    }
+                }
+                _ => return Err(MemberError::PersonNotFound),
+            }
+        }
+
+        if repositories::InvitationRepository::accept(db, &invitation, viewer_id, person_id)? {
+            Ok(())
+        } else {
+            // Someone else was quicker
+            Err(MemberError::InvitationAlreadyUsed)
+        }
+    }
+
+    /// Make an invitation unusable. Reserved to the group's owners.
+    pub fn revoke_invitation(
+        id: &uuid::Uuid,
+        viewer_id: &uuid::Uuid,
+        db: &dyn Store,
+    ) -> Result<(), MemberError> {
+        Self::owned_invitation(id, Role::Owner, viewer_id, db)?;
+
+        repositories::InvitationRepository::revoke(db, id, viewer_id).map_err(MemberError::Internal)
+    }
+
+    /// Change what a member can do in a group. Reserved to the group's owners.
+    pub fn change_member_role(
+        group_id: &uuid::Uuid,
+        user_id: &uuid::Uuid,
+        role: Role,
+        viewer_id: &uuid::Uuid,
+        db: &dyn Store,
+    ) -> Result<(), MemberError> {
+        repositories::transaction(db, |tx| {
+            owned_group(group_id, Role::Owner, viewer_id, tx)?;
+            check_owners(group_id, user_id, Some(role), tx)?;
+
+            repositories::GroupMemberRepository::update_role(
+                tx,
+                group_id,
+                user_id,
+                role.as_str(),
+                viewer_id,
+            )
+            .map_err(MemberError::Internal)
+        })
+    }
+
+    /// Remove a member from a group. The person they were is kept.
+    /// Reserved to the group's owners, but members can remove themselves.
+    pub fn remove_member(
+        group_id: &uuid::Uuid,
+        user_id: &uuid::Uuid,
+        viewer_id: &uuid::Uuid,
+        db: &dyn Store,
+    ) -> Result<(), MemberError> {
+        // Leaving a group only requires being one of its members
+        let role = if user_id == viewer_id {
+            Role::Viewer
+        } else {
+            Role::Owner
+        };
+        repositories::transaction(db, |tx| {
+            owned_group(group_id, role, viewer_id, tx)?;
+            check_owners(group_id, user_id, None, tx)?;
+
+            repositories::GroupMemberRepository::delete_one(tx, group_id, user_id, viewer_id)
+                .map_err(MemberError::Internal)
+        })
+    }
+}
+
+/// Find a group the viewer is a member of with at least this role.
+fn owned_group(
+    id: &uuid::Uuid,
+    role: Role,
+    viewer_id: &uuid::Uuid,
+    db: &dyn Store,
+) -> Result<repositories::Group, MemberError> {
+    let found = repositories::GroupRepository::find_one_by_user(db, id, viewer_id)?;
+    access(found, role)?.or(MemberError::GroupNotFound, MemberError::Forbidden)
+}
+
+/// Check that the viewer verified their email address.
+fn check_verified_email(viewer_id: &uuid::Uuid, db: &dyn Store) -> Result<(), MemberError> {
+    match repositories::UserRepository::find_one(db, viewer_id)? {
+        None => Err(MemberError::UserNotFound),
+        Some(u) if u.email_verified_at.is_none() => Err(MemberError::UnverifiedEmail),
+        Some(_) => Ok(()),
+    }
+}
+
+/// Check that this user is a member of the group and that it keeps an owner once they get
+/// this role, or once they leave it when there's none.
+/// The members stay locked until the end of the transaction, so that two owners can't step down at once.
+fn check_owners(
+    group_id: &uuid::Uuid,
+    user_id: &uuid::Uuid,
+    role: Option<Role>,
+    tx: &dyn Store,
+) -> Result<(), MemberError> {
+    let members = repositories::GroupMemberRepository::lock_by_group_id(tx, group_id)?;
+    let owner = Role::Owner.as_str();
+    let member = members
+        .iter()
+        .find(|m| m.user_id == *user_id)
+        .ok_or(MemberError::MemberNotFound)?;
+    if member.role == owner
+        && role != Some(Role::Owner)
+        && !members
+            .iter()
+            .any(|m| m.user_id != *user_id && m.role == owner)
+    {
+        return Err(MemberError::LastOwner);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::services::{
+        fixtures::{self, verified_user},
+        GroupError, GroupService, Money,
+    };
+
+    #[test]
+    fn invitations_should_give_their_role_once() {
+        let config = fixtures::settings();
+        let db = repositories::InMemoryStore::new();
+        let owner = verified_user(&db);
+        let viewer = verified_user(&db);
+        let group = GroupService::add_group("Trip".to_string(), None, &owner, &db).unwrap();
+        let invite = |expires_in| {
+            MemberService::create_invitation(
+                &group.id,
+                expires_in,
+                Role::Viewer,
+                &owner,
+                config.security(),
+                &db,
+            )
+        };
+
+        assert!(matches!(invite(1), Err(MemberError::InvalidExpiration)));
+        let token = invite(3600).unwrap();
+        MemberService::accept_invitation(&token, None, &viewer, config.security(), &db).unwrap();
+        assert!(matches!(
+            MemberService::accept_invitation(
+                &token,
+                None,
+                &verified_user(&db),
+                config.security(),
+                &db
+            ),
+            Err(MemberError::InvitationAlreadyUsed)
+        ));
+
+        let resources = Money {
+            amount: 0,
+            currency: None,
+        };
+        assert!(matches!(
+            GroupService::add_person(&group.id, "Bob".to_string(), &resources, &viewer, &db),
+            Err(GroupError::Forbidden)
+        ));
+        // Other members can't be invited by a viewer
+        assert!(matches!(
+            MemberService::create_invitation(
+                &group.id,
+                3600,
+                Role::Viewer,
+                &viewer,
+                config.security(),
+                &db
+            ),
+            Err(MemberError::Forbidden)
+        ));
+    }
+
+    #[test]
+    fn revoked_invitations_should_be_unusable() {
+        let config = fixtures::settings();
+        let db = repositories::InMemoryStore::new();
+        let owner = verified_user(&db);
+        let group = GroupService::add_group("Trip".to_string(), None, &owner, &db).unwrap();
+        let token = MemberService::create_invitation(
+            &group.id,
+            3600,
+            Role::Editor,
+            &owner,
+            config.security(),
+            &db,
+        )
+        .unwrap();
+        let (id, _) = security::parse_opaque_token(&token).unwrap();
+
+        MemberService::revoke_invitation(&id, &owner, &db).unwrap();
+        assert!(matches!(
+            MemberService::accept_invitation(
+                &token,
+                None,
+                &verified_user(&db),
+                config.security(),
+                &db
+            ),
+            Err(MemberError::InvitationNotFound)
+        ));
+        assert!(matches!(
+            MemberService::accept_invitation(
+                "unknown",
+                None,
+                &verified_user(&db),
+                config.security(),
+                &db
+            ),
+            Err(MemberError::InvitationNotFound)
+        ));
+    }
+
+    #[test]
+    fn groups_should_keep_an_owner() {
+        let config = fixtures::settings();
+        let db = repositories::InMemoryStore::new();
+        let owner = verified_user(&db);
+        let editor = verified_user(&db);
+        let group = GroupService::add_group("Trip".to_string(), None, &owner, &db).unwrap();
+        let token = MemberService::create_invitation(
+            &group.id,
+            3600,
+            Role::Editor,
+            &owner,
+            config.security(),
+            &db,
+        )
+        .unwrap();
+        MemberService::accept_invitation(&token, None, &editor, config.security(), &db).unwrap();
+
+        assert!(matches!(
+            MemberService::remove_member(&group.id, &owner, &owner, &db),
+            Err(MemberError::LastOwner)
+        ));
+        assert!(matches!(
+            MemberService::change_member_role(&group.id, &owner, Role::Editor, &owner, &db),
+            Err(MemberError::LastOwner)
+        ));
+        assert!(matches!(
+            MemberService::change_member_role(&group.id, &owner, Role::Owner, &editor, &db),
+            Err(MemberError::Forbidden)
+        ));
+
+        MemberService::change_member_role(&group.id, &editor, Role::Owner, &owner, &db).unwrap();
+        MemberService::remove_member(&group.id, &owner, &owner, &db).unwrap();
+        assert!(matches!(
+            MemberService::remove_member(&group.id, &owner, &editor, &db),
+            Err(MemberError::MemberNotFound)
+        ));
+    }
+}
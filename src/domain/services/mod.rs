@@ -0,0 +1,108 @@
+//! The use cases of the application, whatever interface exposes them.
+//! They validate their input, check what the viewer may do and save the changes,
+//! leaving the interfaces to parse the requests and to report the errors their own way.
+
+mod auth;
+mod category;
+mod exchange_rate;
+mod expense;
+#[cfg(test)]
+mod fixtures;
+mod group;
+mod member;
+
+pub use self::{auth::*, category::*, exchange_rate::*, expense::*, group::*, member::*};
+use crate::domain::member::Role;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// An amount of money, in the group's currency unless another one is given.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Money {
+    /// In the currency's minor unit, e.g. cents for euros.
+    pub amount: i64,
+    pub currency: Option<String>,
+}
+
+impl Money {
+    /// The amount in the given currency, None when the money is in another one.
+    pub fn in_currency(&self, currency: &str) -> Option<i64> {
+        match &self.currency {
+            Some(c) if c != currency => None,
+            _ => Some(self.amount),
+        }
+    }
+}
+
+/// What a viewer may do with a row of the groups they're a member of.
+pub enum Access<T> {
+    Granted(T),
+    /// The row doesn't exist or the viewer isn't a member of its group.
+    Missing,
+    /// The viewer's role in the row's group doesn't allow it.
+    Denied,
+}
+
+impl<T> Access<T> {
+    /// The row, or the given error when it's missing or denied.
+    pub fn or<E>(self, missing: E, denied: E) -> Result<T, E> {
+        match self {
+            Access::Granted(row) => Ok(row),
+            Access::Missing => Err(missing),
+            Access::Denied => Err(denied),
+        }
+    }
+}
+
+/// Check the viewer's role in the group of a row found along with it.
+pub fn access<T>(found: Option<(T, String)>, required: Role) -> anyhow::Result<Access<T>> {
+    match found {
+        None => Ok(Access::Missing),
+        Some((row, role)) if role.parse::<Role>()?.allows(required) => Ok(Access::Granted(row)),
+        Some(_) => Ok(Access::Denied),
+    }
+}
+
+/// The names of the groups, persons and categories are 1 to 50 characters long.
+pub fn is_valid_name(name: &str) -> bool {
+    (1..=50).contains(&name.graphemes(true).count())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn money_should_default_to_the_given_currency() {
+        let money = |currency: Option<&str>| Money {
+            amount: 100,
+            currency: currency.map(String::from),
+        };
+
+        assert_eq!(Some(100), money(None).in_currency("EUR"));
+        assert_eq!(Some(100), money(Some("EUR")).in_currency("EUR"));
+        assert_eq!(None, money(Some("USD")).in_currency("EUR"));
+    }
+
+    #[test]
+    fn access_should_depend_on_the_role() {
+        let granted = |found, required| matches!(access(found, required), Ok(Access::Granted(_)));
+        let denied = |found, required| matches!(access(found, required), Ok(Access::Denied));
+
+        assert!(granted(Some(((), "EDITOR".to_string())), Role::Editor));
+        assert!(granted(Some(((), "OWNER".to_string())), Role::Viewer));
+        assert!(denied(Some(((), "VIEWER".to_string())), Role::Editor));
+        assert!(matches!(
+            access(None::<((), String)>, Role::Viewer),
+            Ok(Access::Missing)
+        ));
+        assert!(access(Some(((), "ADMIN".to_string())), Role::Viewer).is_err());
+    }
+
+    #[test]
+    fn names_should_be_1_to_50_characters_long() {
+        assert!(!is_valid_name(""));
+        assert!(is_valid_name("a"));
+        assert!(is_valid_name(&"é".repeat(50)));
+        assert!(!is_valid_name(&"a".repeat(51)));
+    }
+}
@@ -0,0 +1,208 @@
+use super::balance::Balance;
+
+/// Above this number of persons in debt or in credit, the plan is computed greedily
+/// because finding the optimal one is exponential.
+const MAX_OPTIMAL_PERSONS: usize = 16;
+
+/// An amount one person has to pay to another one to settle the group.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Transfer {
+    pub from: uuid::Uuid,
+    pub to: uuid::Uuid,
+    pub amount: i64,
+}
+
+/// Compute the fewest transfers bringing every balance's net back to zero.
+/// The nets must add up to zero. The plan only depends on the balances, not on their order.
+///
+/// The persons are partitioned in as many zero-sum subsets as possible, each of them then being
+/// settled with one transfer less than its number of persons, which is the minimum.
+pub fn plan(balances: &[Balance]) -> Vec<Transfer> {
+    let mut nets = balances
+        .iter()
+        .filter(|b| b.net() != 0)
+        .map(|b| (b.person_id, b.net()))
+        .collect::<Vec<_>>();
+    nets.sort();
+
+    if nets.len() > MAX_OPTIMAL_PERSONS {
+        return settle(nets);
+    }
+
+    zero_sum_subsets(&nets)
+        .into_iter()
+        .flat_map(|subset| settle(subset.into_iter().map(|i| nets[i]).collect()))
+        .collect()
+}
+
+/// Partition the nets in the maximum number of zero-sum subsets, as lists of indices.
+fn zero_sum_subsets(nets: &[(uuid::Uuid, i64)]) -> Vec<Vec<usize>> {
+    let full = (1usize << nets.len()) - 1;
+    let mut sums = vec![0i64; full + 1];
+    // The maximum number of zero-sum subsets partitioning each mask (plus one if its sum isn't zero).
+    let mut counts = vec![0usize; full + 1];
+    for mask in 1..=full {
+        let lowest = mask.trailing_zeros() as usize;
+        sums[mask] = sums[mask & (mask - 1)] + nets[lowest].1;
+        counts[mask] = bits(mask)
+            .map(|i| counts[mask ^ (1 << i)])
+            .max()
+            .unwrap_or(0)
+            + (sums[mask] == 0) as usize;
+    }
+
+    // Remove the persons one by one, closing a subset whenever the remaining ones add up to zero.
+    let mut subsets = Vec::new();
+    let mut subset = Vec::new();
+    let mut mask = full;
+    while mask != 0 {
+        let target = counts[mask] - (sums[mask] == 0) as usize;
+        let i = bits(mask)
+            .find(|i| counts[mask ^ (1 << i)] == target)
+            .expect("a person should lead to the best count");
+        subset.push(i);
+        mask ^= 1 << i;
+        if sums[mask] == 0 {
+            subsets.push(std::mem::take(&mut subset));
+        }
+    }
+
+    subsets
+}
+
+/// The indices of the bits set in a mask.
+fn bits(mut mask: usize) -> impl Iterator<Item = usize> {
+    std::iter::from_fn(move || {
+        if mask == 0 {
+            return None;
+        }
+        let i = mask.trailing_zeros() as usize;
+        mask &= mask - 1;
+        Some(i)
+    })
+}
+
+/// Settle the given nets by repeatedly making the biggest debtor pay the biggest creditor.
+/// This takes at most one transfer less than the number of nets.
+fn settle(mut nets: Vec<(uuid::Uuid, i64)>) -> Vec<Transfer> {
+    let mut transfers = Vec::new();
+    loop {
+        let debtor = nets
+            .iter()
+            .enumerate()
+            .filter(|(_, n)| n.1 < 0)
+            .min_by(|(_, a), (_, b)| a.1.cmp(&b.1).then_with(|| a.0.cmp(&b.0)))
+            .map(|(i, _)| i);
+        let creditor = nets
+            .iter()
+            .enumerate()
+            .filter(|(_, n)| n.1 > 0)
+            .max_by(|(_, a), (_, b)| a.1.cmp(&b.1).then_with(|| b.0.cmp(&a.0)))
+            .map(|(i, _)| i);
+
+        match (debtor, creditor) {
+            (Some(d), Some(c)) => {
+                let amount = (-nets[d].1).min(nets[c].1);
+                nets[d].1 += amount;
+                nets[c].1 -= amount;
+                transfers.push(Transfer {
+                    from: nets[d].0,
+                    to: nets[c].0,
+                    amount,
+                });
+            }
+            _ => return transfers,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn id(i: usize) -> uuid::Uuid {
+        let mut bytes = [0u8; 16];
+        bytes[8..].copy_from_slice(&(i as u64).to_be_bytes());
+        uuid::Uuid::from_bytes(&bytes).unwrap()
+    }
+
+    fn balances(nets: &[i64]) -> Vec<Balance> {
+        nets.iter()
+            .enumerate()
+            .map(|(i, n)| Balance {
+                person_id: id(i),
+                paid: (*n).max(0),
+                owed: (-n).max(0),
+                ..Balance::default()
+            })
+            .collect()
+    }
+
+    fn apply(balances: &[Balance], transfers: &[Transfer]) -> Vec<i64> {
+        balances
+            .iter()
+            .map(|b| {
+                transfers
+                    .iter()
+                    .map(|t| match (t.from == b.person_id, t.to == b.person_id) {
+                        (true, _) => t.amount,
+                        (_, true) => -t.amount,
+                        _ => 0,
+                    })
+                    .sum::<i64>()
+                    + b.net()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn should_not_plan_anything_for_a_settled_group() {
+        assert!(plan(&balances(&[])).is_empty());
+        assert!(plan(&balances(&[0, 0])).is_empty());
+    }
+
+    #[test]
+    fn should_plan_the_fewest_transfers() {
+        // Greedily, the biggest debtor would pay the biggest creditor first and need 4 transfers.
+        let balances = balances(&[6, 4, -4, -3, -3]);
+        let transfers = plan(&balances);
+
+        assert_eq!(3, transfers.len());
+        assert!(transfers.contains(&Transfer {
+            from: id(2),
+            to: id(1),
+            amount: 4
+        }));
+        assert!(apply(&balances, &transfers).iter().all(|n| *n == 0));
+    }
+
+    fn nets() -> impl Strategy<Value = Vec<i64>> {
+        prop::collection::vec(-1000i64..1000, 0..24).prop_map(|mut v| {
+            let sum = v.iter().sum::<i64>();
+            v.push(-sum);
+            v
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn transfers_should_always_balance(nets in nets()) {
+            let balances = balances(&nets);
+            let transfers = plan(&balances);
+
+            prop_assert!(apply(&balances, &transfers).iter().all(|n| *n == 0));
+            prop_assert!(transfers.iter().all(|t| t.amount > 0 && t.from != t.to));
+            let persons = nets.iter().filter(|n| **n != 0).count();
+            prop_assert!(transfers.len() <= persons.saturating_sub(1));
+        }
+
+        #[test]
+        fn plan_should_be_deterministic(nets in nets()) {
+            let balances = balances(&nets);
+            let reversed = balances.iter().rev().cloned().collect::<Vec<_>>();
+
+            prop_assert_eq!(plan(&balances), plan(&reversed));
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use super::currency;
+
+/// The sum of some expenses sharing a key, a currency and an exchange rate.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Subtotal<K> {
+    pub key: K,
+    pub currency: String,
+    /// The rate converting one major unit of `currency` into the group's currency.
+    pub exchange_rate: f64,
+    pub amount: i64,
+}
+
+/// Convert the subtotals into the group's currency and add up the ones sharing a key.
+/// The keys are kept in the order they first appear.
+pub fn totals<K: PartialEq>(subtotals: Vec<Subtotal<K>>, currency: &str) -> Vec<(K, i64)> {
+    let mut totals: Vec<(K, i64)> = Vec::new();
+    for s in subtotals {
+        let rate = currency::minor_rate(s.exchange_rate, &s.currency, currency);
+        let amount = currency::convert(s.amount, rate);
+        match totals.iter_mut().find(|(k, _)| *k == s.key) {
+            Some((_, total)) => *total += amount,
+            None => totals.push((s.key, amount)),
+        }
+    }
+
+    totals
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn subtotal(key: &str, currency: &str, exchange_rate: f64, amount: i64) -> Subtotal<String> {
+        Subtotal {
+            key: key.to_string(),
+            currency: currency.to_string(),
+            exchange_rate,
+            amount,
+        }
+    }
+
+    #[test]
+    fn subtotals_should_be_added_up_in_the_group_currency() {
+        let subtotals = vec![
+            subtotal("Food", "EUR", 1.0, 1000),
+            subtotal("Transport", "EUR", 1.0, 250),
+            subtotal("Food", "USD", 0.8, 500),
+            // 1000 yens are worth 6 euros
+            subtotal("Food", "JPY", 0.006, 1000),
+        ];
+
+        assert_eq!(
+            vec![("Food".to_string(), 2000), ("Transport".to_string(), 250)],
+            totals(subtotals, "EUR")
+        );
+    }
+}
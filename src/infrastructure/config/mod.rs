@@ -6,14 +6,36 @@ pub struct Settings {
     application_port: u16,
     database: DatabaseSettings,
     security: SecuritySettings,
+    /// The users allowed to maintain the exchange rates, once they verified these addresses.
+    admin_emails: Vec<String>,
+    /// How long the removed groups, persons and expenses stay in the trash, in days.
+    trash_retention_days: i64,
+    mail: MailSettings,
 }
 
 impl Settings {
     pub fn new() -> anyhow::Result<Self> {
         // Necessary env vars
-        let hash_salt = env::var("HASH_SALT").context("HASH_SALT env var is not defined")?;
         let secret_key = env::var("SECRET_KEY").context("SECRET_KEY env var is not defined")?;
 
+        Ok(Self::from_env(secret_key))
+    }
+
+    /// The settings of the unit tests, which run in parallel and so can't set the SECRET_KEY env var.
+    #[cfg(test)]
+    pub(crate) fn for_tests() -> Self {
+        Self::from_env("mysupersecretkey".to_string())
+    }
+
+    /// The test settings, with this address allowed to maintain the exchange rates.
+    #[cfg(test)]
+    pub(crate) fn with_admin_email(mut self, email: &str) -> Self {
+        self.admin_emails.push(email.to_string());
+        self
+    }
+
+    /// The default settings, overridden by the optional env vars.
+    fn from_env(secret_key: String) -> Self {
         // Default settings
         let mut settings = Settings {
             application_port: 8000,
@@ -25,9 +47,30 @@ impl Settings {
                 name: "group-expenses".to_string(),
             },
             security: SecuritySettings {
-                hash_salt,
                 secret_key,
                 token_expiration_time: 3600,
+                refresh_token_expiration_time: 30 * 24 * 3600,
+                password_reset_expiration_time: 3600,
+                password_resets_per_hour: 3,
+                email_verification_url: "http://localhost:3000/verify-email".to_string(),
+                email_verification_expiration_time: 2 * 24 * 3600,
+                verified_email_to_invite: true,
+                verified_email_to_join: true,
+                hashing: HashSettings::default(),
+            },
+            admin_emails: Vec::new(),
+            trash_retention_days: 30,
+            mail: MailSettings {
+                transport: MailTransport::Smtp,
+                from: "no-reply@localhost".to_string(),
+                outbox_dir: "outbox".to_string(),
+                smtp: SmtpSettings {
+                    host: "localhost".to_string(),
+                    port: 587,
+                    username: None,
+                    password: None,
+                    starttls: true,
+                },
             },
         };
 
@@ -57,7 +100,134 @@ impl Settings {
             settings.database.host = host;
         }
 
-        Ok(settings)
+        if let Ok(emails) = env::var("ADMIN_EMAILS") {
+            settings.admin_emails = emails
+                .split(',')
+                .map(str::trim)
+                .filter(|e| !e.is_empty())
+                .map(String::from)
+                .collect();
+        }
+
+        if let Ok(days) = env::var("TRASH_RETENTION_DAYS")
+            .context("Couldn't read trash retention env variable")
+            .and_then(|d| d.parse().context("Couldn't parse trash retention env var"))
+        {
+            settings.trash_retention_days = days;
+        }
+
+        if let Ok(variant) = env::var("ARGON2_VARIANT")
+            .context("Couldn't read argon2 variant env variable")
+            .and_then(|v| {
+                argon2::Variant::from_str(&v)
+                    .map(|v| v.as_lowercase_str().to_string())
+                    .context("Couldn't parse argon2 variant env var")
+            })
+        {
+            settings.security.hashing.variant = variant;
+        }
+
+        if let Ok(memory_cost) = env::var("ARGON2_MEMORY_COST")
+            .context("Couldn't read argon2 memory cost env variable")
+            .and_then(|m| {
+                m.parse()
+                    .context("Couldn't parse argon2 memory cost env var")
+            })
+        {
+            settings.security.hashing.memory_cost = memory_cost;
+        }
+
+        if let Ok(iterations) = env::var("ARGON2_ITERATIONS")
+            .context("Couldn't read argon2 iterations env variable")
+            .and_then(|i| {
+                i.parse()
+                    .context("Couldn't parse argon2 iterations env var")
+            })
+        {
+            settings.security.hashing.iterations = iterations;
+        }
+
+        if let Ok(parallelism) = env::var("ARGON2_PARALLELISM")
+            .context("Couldn't read argon2 parallelism env variable")
+            .and_then(|p| {
+                p.parse()
+                    .context("Couldn't parse argon2 parallelism env var")
+            })
+        {
+            settings.security.hashing.parallelism = parallelism;
+        }
+
+        if let Ok(limit) = env::var("PASSWORD_RESETS_PER_HOUR")
+            .context("Couldn't read password resets per hour env variable")
+            .and_then(|l| {
+                l.parse()
+                    .context("Couldn't parse password resets per hour env var")
+            })
+        {
+            settings.security.password_resets_per_hour = limit;
+        }
+
+        if let Ok(url) = env::var("EMAIL_VERIFICATION_URL") {
+            settings.security.email_verification_url = url;
+        }
+
+        if let Ok(required) = env::var("REQUIRE_VERIFIED_EMAIL_TO_INVITE")
+            .context("Couldn't read verified email to invite env variable")
+            .and_then(|r| {
+                r.parse()
+                    .context("Couldn't parse verified email to invite env var")
+            })
+        {
+            settings.security.verified_email_to_invite = required;
+        }
+
+        if let Ok(required) = env::var("REQUIRE_VERIFIED_EMAIL_TO_JOIN")
+            .context("Couldn't read verified email to join env variable")
+            .and_then(|r| {
+                r.parse()
+                    .context("Couldn't parse verified email to join env var")
+            })
+        {
+            settings.security.verified_email_to_join = required;
+        }
+
+        if let Ok(transport) = env::var("MAIL_TRANSPORT")
+            .context("Couldn't read mail transport env variable")
+            .and_then(|t| t.parse().context("Couldn't parse mail transport env var"))
+        {
+            settings.mail.transport = transport;
+        }
+
+        if let Ok(from) = env::var("MAIL_FROM") {
+            settings.mail.from = from;
+        }
+
+        if let Ok(dir) = env::var("MAIL_OUTBOX_DIR") {
+            settings.mail.outbox_dir = dir;
+        }
+
+        if let Ok(host) = env::var("SMTP_HOST") {
+            settings.mail.smtp.host = host;
+        }
+
+        if let Ok(port) = env::var("SMTP_PORT")
+            .context("Couldn't read smtp port env variable")
+            .and_then(|p| p.parse().context("Couldn't parse smtp port env var"))
+        {
+            settings.mail.smtp.port = port;
+        }
+
+        settings.mail.smtp.username = env::var("SMTP_USERNAME").ok();
+        settings.mail.smtp.password = env::var("SMTP_PASSWORD").ok();
+
+        if let Ok(starttls) = env::var("SMTP_STARTTLS")
+            .context("Couldn't read smtp starttls env variable")
+            .and_then(|s| s.parse().context("Couldn't parse smtp starttls env var"))
+        {
+            settings.mail.smtp.starttls = starttls;
+        }
+
+        settings
     }
 
     pub fn application_port(&self) -> u16 {
@@ -75,6 +245,18 @@ impl Settings {
     pub fn security(&self) -> &SecuritySettings {
         &self.security
     }
+
+    pub fn is_admin(&self, email: &str) -> bool {
+        self.admin_emails.iter().any(|e| e == email)
+    }
+
+    pub fn trash_retention(&self) -> chrono::Duration {
+        chrono::Duration::days(self.trash_retention_days)
+    }
+
+    pub fn mail(&self) -> &MailSettings {
+        &self.mail
+    }
 }
 
 #[derive(serde::Deserialize, Clone)]
@@ -97,16 +279,27 @@ impl DatabaseSettings {
 
 #[derive(serde::Deserialize, Clone)]
 pub struct SecuritySettings {
-    hash_salt: String,
     secret_key: String,
     token_expiration_time: i64,
+    /// How long a session lasts without its refresh token being used, in seconds.
+    refresh_token_expiration_time: i64,
+    /// How long a password reset token can be used, in seconds.
+    password_reset_expiration_time: i64,
+    /// How many password reset emails an address can be sent in an hour, not to flood it.
+    password_resets_per_hour: i64,
+    /// The page the email verification links lead to, with the token in their `token` parameter.
+    /// It's up to the page to send the token to `verifyEmail`.
+    email_verification_url: String,
+    /// How long an email verification link can be used, in seconds.
+    email_verification_expiration_time: i64,
+    /// Whether only the users who verified their email can invite others to their groups.
+    verified_email_to_invite: bool,
+    /// Whether only the users who verified their email can accept invitations.
+    verified_email_to_join: bool,
+    hashing: HashSettings,
 }
 
 impl SecuritySettings {
-    pub fn hash_salt(&self) -> &[u8] {
-        self.hash_salt.as_bytes()
-    }
-
     pub fn secret_key(&self) -> &[u8] {
         self.secret_key.as_bytes()
     }
@@ -114,4 +307,160 @@ impl SecuritySettings {
     pub fn token_expiration_time(&self) -> i64 {
         self.token_expiration_time
     }
+
+    pub fn refresh_token_expiration(&self) -> chrono::Duration {
+        chrono::Duration::seconds(self.refresh_token_expiration_time)
+    }
+
+    pub fn password_reset_expiration(&self) -> chrono::Duration {
+        chrono::Duration::seconds(self.password_reset_expiration_time)
+    }
+
+    pub fn password_resets_per_hour(&self) -> i64 {
+        self.password_resets_per_hour
+    }
+
+    pub fn email_verification_url(&self) -> &str {
+        &self.email_verification_url
+    }
+
+    pub fn email_verification_expiration(&self) -> chrono::Duration {
+        chrono::Duration::seconds(self.email_verification_expiration_time)
+    }
+
+    pub fn verified_email_to_invite(&self) -> bool {
+        self.verified_email_to_invite
+    }
+
+    pub fn verified_email_to_join(&self) -> bool {
+        self.verified_email_to_join
+    }
+
+    pub fn hashing(&self) -> &HashSettings {
+        &self.hashing
+    }
+}
+
+/// The Argon2 parameters the passwords and the secrets are hashed with.
+/// The users' passwords are hashed again with the new ones when they log in.
+#[derive(serde::Deserialize, Clone, PartialEq, Debug)]
+pub struct HashSettings {
+    /// `argon2d`, `argon2i` or `argon2id`.
+    variant: String,
+    /// In KiB.
+    memory_cost: u32,
+    iterations: u32,
+    parallelism: u32,
+}
+
+impl HashSettings {
+    pub fn variant(&self) -> &str {
+        &self.variant
+    }
+
+    pub fn memory_cost(&self) -> u32 {
+        self.memory_cost
+    }
+
+    pub fn iterations(&self) -> u32 {
+        self.iterations
+    }
+
+    pub fn parallelism(&self) -> u32 {
+        self.parallelism
+    }
+}
+
+impl Default for HashSettings {
+    /// The OWASP recommendation.
+    fn default() -> Self {
+        HashSettings {
+            variant: "argon2id".to_string(),
+            memory_cost: 19 * 1024,
+            iterations: 2,
+            parallelism: 1,
+        }
+    }
+}
+
+#[derive(serde::Deserialize, Clone)]
+pub struct MailSettings {
+    transport: MailTransport,
+    /// The address the emails are sent from.
+    from: String,
+    /// Where the outbox transport writes the emails.
+    outbox_dir: String,
+    smtp: SmtpSettings,
+}
+
+impl MailSettings {
+    pub fn transport(&self) -> MailTransport {
+        self.transport
+    }
+
+    pub fn from(&self) -> &str {
+        &self.from
+    }
+
+    pub fn outbox_dir(&self) -> &str {
+        &self.outbox_dir
+    }
+
+    pub fn smtp(&self) -> &SmtpSettings {
+        &self.smtp
+    }
+}
+
+/// How the emails are delivered.
+#[derive(serde::Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum MailTransport {
+    Smtp,
+    /// Written to files instead of being sent, for the development and the tests.
+    /// Never the default, not to lose the emails of a server missing its configuration.
+    Outbox,
+}
+
+impl std::str::FromStr for MailTransport {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "smtp" => Ok(MailTransport::Smtp),
+            "outbox" => Ok(MailTransport::Outbox),
+            _ => Err(anyhow::anyhow!("Unknown mail transport {}", s)),
+        }
+    }
+}
+
+#[derive(serde::Deserialize, Clone)]
+pub struct SmtpSettings {
+    host: String,
+    port: u16,
+    username: Option<String>,
+    password: Option<String>,
+    /// Whether to switch to TLS before authenticating and sending anything.
+    /// The credentials are refused without it.
+    starttls: bool,
+}
+
+impl SmtpSettings {
+    pub fn host(&self) -> &str {
+        &self.host
+    }
+
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    /// The username and the password, when the server requires them.
+    pub fn credentials(&self) -> Option<(&str, &str)> {
+        match (&self.username, &self.password) {
+            (Some(u), Some(p)) => Some((u, p)),
+            _ => None,
+        }
+    }
+
+    pub fn starttls(&self) -> bool {
+        self.starttls
+    }
 }
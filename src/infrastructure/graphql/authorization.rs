@@ -0,0 +1,19 @@
+//! The rows a viewer may read or change are the ones of the groups they're a member of,
+//! depending on their role in the group.
+//! A row of any other group is reported as not found, so that its existence isn't leaked.
+//! The rows are checked by their services.
+
+use super::{errors::GraphQLError, schema::Context};
+use crate::{
+    domain::{member::Role, services::GroupService},
+    infrastructure::repositories,
+};
+
+/// Find a group the viewer is a member of with at least this role.
+pub fn owned_group(
+    id: &uuid::Uuid,
+    role: Role,
+    context: &Context,
+) -> Result<repositories::Group, GraphQLError> {
+    GroupService::owned_group(id, role, context.viewer.id(), &*context.db).map_err(Into::into)
+}
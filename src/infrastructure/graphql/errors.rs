@@ -1,3 +1,10 @@
+use crate::domain::{
+    currency::CurrencyError,
+    expense::SplitError,
+    services::{
+        AuthError, CategoryError, ExchangeRateError, ExpenseError, GroupError, MemberError,
+    },
+};
 use juniper::graphql_value;
 
 pub enum GraphQLError {
@@ -8,14 +15,165 @@ pub enum GraphQLError {
     InvalidResources,
     InvalidId,
     InvalidAmount,
+    InvalidPayment,
+    InvalidPayers,
+    InvalidShares,
+    InvalidPercentages,
+    InvalidExactAmounts,
+    InvalidCurrency,
+    InvalidExchangeRate,
+    InvalidCsv(usize),
+    InvalidDateRange,
+    InvalidExpiration,
+    InvalidPagination,
     AlreadyUsedEmail,
+    InvalidToken,
+    UnverifiedEmail,
     UserNotFound,
     GroupNotFound,
     PersonNotFound,
+    ExpenseNotFound,
+    PaymentNotFound,
+    CategoryNotFound,
+    CategoryInUse,
+    InvitationNotFound,
+    InvitationExpired,
+    InvitationAlreadyUsed,
+    AlreadyMember,
+    MemberNotFound,
+    LastOwner,
+    PersonAlreadyClaimed,
+    ExchangeRateNotFound(String, String),
+    Forbidden,
     NonUniqueName(String),
     InternalServerError(anyhow::Error),
 }
 
+impl From<SplitError> for GraphQLError {
+    fn from(e: SplitError) -> Self {
+        match e {
+            SplitError::InvalidPayers => GraphQLError::InvalidPayers,
+            SplitError::InvalidShares => GraphQLError::InvalidShares,
+            SplitError::InvalidPercentages => GraphQLError::InvalidPercentages,
+            SplitError::InvalidExactAmounts => GraphQLError::InvalidExactAmounts,
+        }
+    }
+}
+
+impl From<CurrencyError> for GraphQLError {
+    fn from(e: CurrencyError) -> Self {
+        match e {
+            CurrencyError::InvalidCode => GraphQLError::InvalidCurrency,
+            CurrencyError::InvalidRate => GraphQLError::InvalidExchangeRate,
+            CurrencyError::InvalidCsvLine(l) => GraphQLError::InvalidCsv(l),
+        }
+    }
+}
+
+impl From<AuthError> for GraphQLError {
+    fn from(e: AuthError) -> Self {
+        match e {
+            AuthError::InvalidEmailAddress => GraphQLError::InvalidEmailAddress,
+            AuthError::InvalidPassword => GraphQLError::InvalidPassword,
+            AuthError::InvalidCredentials => GraphQLError::InvalidCredentials,
+            AuthError::AlreadyUsedEmail => GraphQLError::AlreadyUsedEmail,
+            AuthError::UserNotFound => GraphQLError::UserNotFound,
+            AuthError::InvalidToken => GraphQLError::InvalidToken,
+            AuthError::UnverifiedEmail => GraphQLError::UnverifiedEmail,
+            AuthError::Internal(e) => GraphQLError::InternalServerError(e),
+        }
+    }
+}
+
+impl From<GroupError> for GraphQLError {
+    fn from(e: GroupError) -> Self {
+        match e {
+            GroupError::InvalidName => GraphQLError::InvalidName,
+            GroupError::InvalidCurrency => GraphQLError::InvalidCurrency,
+            GroupError::InvalidResources => GraphQLError::InvalidResources,
+            GroupError::UserNotFound => GraphQLError::UserNotFound,
+            GroupError::GroupNotFound => GraphQLError::GroupNotFound,
+            GroupError::PersonNotFound => GraphQLError::PersonNotFound,
+            GroupError::NonUniqueName(name) => GraphQLError::NonUniqueName(name),
+            GroupError::Forbidden => GraphQLError::Forbidden,
+            GroupError::Internal(e) => GraphQLError::InternalServerError(e),
+        }
+    }
+}
+
+impl From<ExpenseError> for GraphQLError {
+    fn from(e: ExpenseError) -> Self {
+        match e {
+            ExpenseError::InvalidAmount => GraphQLError::InvalidAmount,
+            ExpenseError::InvalidPayment => GraphQLError::InvalidPayment,
+            ExpenseError::InvalidCurrency => GraphQLError::InvalidCurrency,
+            ExpenseError::Split(e) => e.into(),
+            ExpenseError::ExchangeRateNotFound(from, to) => {
+                GraphQLError::ExchangeRateNotFound(from, to)
+            }
+            ExpenseError::GroupNotFound => GraphQLError::GroupNotFound,
+            ExpenseError::PersonNotFound => GraphQLError::PersonNotFound,
+            ExpenseError::ExpenseNotFound => GraphQLError::ExpenseNotFound,
+            ExpenseError::PaymentNotFound => GraphQLError::PaymentNotFound,
+            ExpenseError::CategoryNotFound => GraphQLError::CategoryNotFound,
+            ExpenseError::Forbidden => GraphQLError::Forbidden,
+            ExpenseError::Internal(e) => GraphQLError::InternalServerError(e),
+        }
+    }
+}
+
+impl From<CategoryError> for GraphQLError {
+    fn from(e: CategoryError) -> Self {
+        match e {
+            CategoryError::InvalidName => GraphQLError::InvalidName,
+            CategoryError::GroupNotFound => GraphQLError::GroupNotFound,
+            CategoryError::CategoryNotFound => GraphQLError::CategoryNotFound,
+            CategoryError::CategoryInUse => GraphQLError::CategoryInUse,
+            CategoryError::NonUniqueName(name) => GraphQLError::NonUniqueName(name),
+            CategoryError::Forbidden => GraphQLError::Forbidden,
+            CategoryError::Internal(e) => GraphQLError::InternalServerError(e),
+        }
+    }
+}
+
+impl From<ExchangeRateError> for GraphQLError {
+    fn from(e: ExchangeRateError) -> Self {
+        match e {
+            ExchangeRateError::Currency(e) => e.into(),
+            ExchangeRateError::UserNotFound => GraphQLError::UserNotFound,
+            ExchangeRateError::Forbidden => GraphQLError::Forbidden,
+            ExchangeRateError::Internal(e) => GraphQLError::InternalServerError(e),
+        }
+    }
+}
+
+impl From<MemberError> for GraphQLError {
+    fn from(e: MemberError) -> Self {
+        match e {
+            MemberError::InvalidExpiration => GraphQLError::InvalidExpiration,
+            MemberError::UserNotFound => GraphQLError::UserNotFound,
+            MemberError::GroupNotFound => GraphQLError::GroupNotFound,
+            MemberError::PersonNotFound => GraphQLError::PersonNotFound,
+            MemberError::InvitationNotFound => GraphQLError::InvitationNotFound,
+            MemberError::InvitationExpired => GraphQLError::InvitationExpired,
+            MemberError::InvitationAlreadyUsed => GraphQLError::InvitationAlreadyUsed,
+            MemberError::AlreadyMember => GraphQLError::AlreadyMember,
+            MemberError::MemberNotFound => GraphQLError::MemberNotFound,
+            MemberError::LastOwner => GraphQLError::LastOwner,
+            MemberError::PersonAlreadyClaimed => GraphQLError::PersonAlreadyClaimed,
+            MemberError::UnverifiedEmail => GraphQLError::UnverifiedEmail,
+            MemberError::Forbidden => GraphQLError::Forbidden,
+            MemberError::Internal(e) => GraphQLError::InternalServerError(e),
+        }
+    }
+}
+
+impl From<anyhow::Error> for GraphQLError {
+    fn from(e: anyhow::Error) -> Self {
+        GraphQLError::InternalServerError(e)
+    }
+}
+
 impl juniper::IntoFieldError for GraphQLError {
     fn into_field_error(self) -> juniper::FieldError {
         match self {
@@ -61,12 +219,90 @@ impl juniper::IntoFieldError for GraphQLError {
                     "code": "INVALID_AMOUNT"
                 }),
             ),
+            GraphQLError::InvalidPayment => juniper::FieldError::new(
+                "A person can't pay themselves back!",
+                graphql_value!({
+                    "code": "INVALID_PAYMENT"
+                }),
+            ),
+            GraphQLError::InvalidPayers => juniper::FieldError::new(
+                "The payers' amounts must add up to the expense's amount!",
+                graphql_value!({
+                    "code": "INVALID_PAYERS"
+                }),
+            ),
+            GraphQLError::InvalidShares => juniper::FieldError::new(
+                "The expense's shares are invalid!",
+                graphql_value!({
+                    "code": "INVALID_SHARES"
+                }),
+            ),
+            GraphQLError::InvalidPercentages => juniper::FieldError::new(
+                "The percentages must add up to 100!",
+                graphql_value!({
+                    "code": "INVALID_PERCENTAGES"
+                }),
+            ),
+            GraphQLError::InvalidExactAmounts => juniper::FieldError::new(
+                "The exact amounts must add up to the expense's amount!",
+                graphql_value!({
+                    "code": "INVALID_EXACT_AMOUNTS"
+                }),
+            ),
+            GraphQLError::InvalidCurrency => juniper::FieldError::new(
+                "The currency must be a code made of three uppercase letters!",
+                graphql_value!({
+                    "code": "INVALID_CURRENCY"
+                }),
+            ),
+            GraphQLError::InvalidExchangeRate => juniper::FieldError::new(
+                "The exchange rate is invalid!",
+                graphql_value!({
+                    "code": "INVALID_EXCHANGE_RATE"
+                }),
+            ),
+            GraphQLError::InvalidCsv(l) => juniper::FieldError::new(
+                format!("The CSV's line {} is invalid!", l),
+                graphql_value!({
+                    "code": "INVALID_CSV"
+                }),
+            ),
+            GraphQLError::InvalidDateRange => juniper::FieldError::new(
+                "The date range ends before it starts!",
+                graphql_value!({
+                    "code": "INVALID_DATE_RANGE"
+                }),
+            ),
+            GraphQLError::InvalidExpiration => juniper::FieldError::new(
+                "The expiration must be between a minute and 30 days!",
+                graphql_value!({
+                    "code": "INVALID_EXPIRATION"
+                }),
+            ),
+            GraphQLError::InvalidPagination => juniper::FieldError::new(
+                "A page must hold between 1 and 100 entries, after a valid cursor!",
+                graphql_value!({
+                    "code": "INVALID_PAGINATION"
+                }),
+            ),
             GraphQLError::AlreadyUsedEmail => juniper::FieldError::new(
                 "The email address is already used!",
                 graphql_value!({
                     "code": "ALREADY_USED_EMAIL"
                 }),
             ),
+            GraphQLError::InvalidToken => juniper::FieldError::new(
+                "The token is invalid or expired!",
+                graphql_value!({
+                    "code": "INVALID_TOKEN"
+                }),
+            ),
+            GraphQLError::UnverifiedEmail => juniper::FieldError::new(
+                "The email address must be verified first!",
+                graphql_value!({
+                    "code": "UNVERIFIED_EMAIL"
+                }),
+            ),
             GraphQLError::UserNotFound => juniper::FieldError::new(
                 "The viewer was not found!",
                 graphql_value!({
@@ -85,8 +321,86 @@ impl juniper::IntoFieldError for GraphQLError {
                     "code": "PERSON_NOT_FOUND"
                 }),
             ),
+            GraphQLError::ExpenseNotFound => juniper::FieldError::new(
+                "The expense was not found!",
+                graphql_value!({
+                    "code": "EXPENSE_NOT_FOUND"
+                }),
+            ),
+            GraphQLError::PaymentNotFound => juniper::FieldError::new(
+                "The payment was not found!",
+                graphql_value!({
+                    "code": "PAYMENT_NOT_FOUND"
+                }),
+            ),
+            GraphQLError::CategoryNotFound => juniper::FieldError::new(
+                "The category was not found!",
+                graphql_value!({
+                    "code": "CATEGORY_NOT_FOUND"
+                }),
+            ),
+            GraphQLError::CategoryInUse => juniper::FieldError::new(
+                "The category still has expenses!",
+                graphql_value!({
+                    "code": "CATEGORY_IN_USE"
+                }),
+            ),
+            GraphQLError::InvitationNotFound => juniper::FieldError::new(
+                "The invitation was not found!",
+                graphql_value!({
+                    "code": "INVITATION_NOT_FOUND"
+                }),
+            ),
+            GraphQLError::InvitationExpired => juniper::FieldError::new(
+                "The invitation has expired!",
+                graphql_value!({
+                    "code": "INVITATION_EXPIRED"
+                }),
+            ),
+            GraphQLError::InvitationAlreadyUsed => juniper::FieldError::new(
+                "The invitation was already used!",
+                graphql_value!({
+                    "code": "INVITATION_ALREADY_USED"
+                }),
+            ),
+            GraphQLError::AlreadyMember => juniper::FieldError::new(
+                "The viewer is already a member of this group!",
+                graphql_value!({
+                    "code": "ALREADY_MEMBER"
+                }),
+            ),
+            GraphQLError::MemberNotFound => juniper::FieldError::new(
+                "The member was not found!",
+                graphql_value!({
+                    "code": "MEMBER_NOT_FOUND"
+                }),
+            ),
+            GraphQLError::LastOwner => juniper::FieldError::new(
+                "A group must keep at least one owner!",
+                graphql_value!({
+                    "code": "LAST_OWNER"
+                }),
+            ),
+            GraphQLError::PersonAlreadyClaimed => juniper::FieldError::new(
+                "The person is already linked to another user!",
+                graphql_value!({
+                    "code": "PERSON_ALREADY_CLAIMED"
+                }),
+            ),
+            GraphQLError::ExchangeRateNotFound(from, to) => juniper::FieldError::new(
+                format!("There is no exchange rate from {} to {}!", from, to),
+                graphql_value!({
+                    "code": "EXCHANGE_RATE_NOT_FOUND"
+                }),
+            ),
+            GraphQLError::Forbidden => juniper::FieldError::new(
+                "The viewer isn't allowed to do this!",
+                graphql_value!({
+                    "code": "FORBIDDEN"
+                }),
+            ),
             GraphQLError::NonUniqueName(n) => juniper::FieldError::new(
-                format!("The person's name ({}) is not unique!", n),
+                format!("The name ({}) is already taken in this group!", n),
                 graphql_value!({
                     "code": "NAME_NOT_UNIQUE"
                 }),
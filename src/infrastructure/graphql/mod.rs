@@ -1,3 +1,4 @@
+mod authorization;
 mod errors;
 mod schema;
 
@@ -1,438 +1,952 @@
 mod types;
 
-use super::errors::*;
-use crate::infrastructure::{config, repositories, security};
+use super::{authorization, errors::*};
+use crate::{
+    domain::{
+        currency, expense,
+        member::Role,
+        services::{
+            self, AuthService, CategoryService, ExchangeRateService, ExpenseService, GroupError,
+            GroupService, MemberService,
+        },
+    },
+    infrastructure::{config, jobs, mail, repositories, security},
+};
 use types::*;
-use unicode_segmentation::UnicodeSegmentation;
 
 pub struct Query;
 #[juniper::object(Context = Context)]
 impl Query {
-    // FIXME: Extract domain and repository logic to own module
     /// Login a user.
+    #[graphql(deprecated = "Use the loginV2 mutation, which also returns a refresh token")]
     fn login(context: &Context, email: String, password: String) -> Result<String, GraphQLError> {
-        // Check email validity and password validity
-        if !regex::Regex::new(r"^\S+@\S+\.\S+$")
-            .unwrap()
-            .is_match(&email)
-            // https://stackoverflow.com/a/46290728
-            || !(8..=64).contains(&password.graphemes(true).count())
-        {
-            return Err(GraphQLError::InvalidEmailAddress);
-        }
-
-        match repositories::UserRepository::find_one_by_email(&email[..], &context.db_pool) {
-            Err(e) => Err(GraphQLError::InternalServerError(e)),
-            Ok(None) => Err(GraphQLError::InvalidCredentials),
-            Ok(Some(user)) => {
-                match security::verify_password(password.as_bytes(), &user.password[..]) {
-                    Err(e) => Err(GraphQLError::InternalServerError(e)),
-                    Ok(verified) => {
-                        if !verified {
-                            Err(GraphQLError::InvalidCredentials)
-                        } else {
-                            // Sign token
-                            let token = match security::sign_token(
-                                user.id,
-                                context.config.security().token_expiration_time(),
-                                context.config.security().secret_key(),
-                            ) {
-                                Err(e) => return Err(GraphQLError::InternalServerError(e)),
-                                Ok(token) => token,
-                            };
-
-                            Ok(token)
-                        }
-                    }
-                }
-            }
-        }
+        AuthService::login(&email, &password, context.config.security(), &*context.db)
+            .map_err(Into::into)
+            .map(|t| t.access_token)
     }
 
-    // FIXME: Extract domain and repository logic to own module
     /// The authenticated user.
     /// This is a user context dependant query.
     fn viewer(context: &Context) -> Result<User, GraphQLError> {
-        match repositories::UserRepository::find_one(context.viewer.id(), &context.db_pool) {
-            Err(e) => Err(GraphQLError::InternalServerError(e)),
-            Ok(None) => Err(GraphQLError::UserNotFound),
-            Ok(Some(user)) => Ok(user.into()),
-        }
+        AuthService::viewer(context.viewer.id(), &*context.db)
+            .map(Into::into)
+            .map_err(Into::into)
     }
 
-    // FIXME: Extract domain and repository logic to own module
     /// Get a group of the use.
     /// This is a user context dependant query.
     fn group(context: &Context, id: String) -> Result<Option<Group>, GraphQLError> {
-        // Check id validity
-        let id = match uuid::Uuid::parse_str(id.as_str()) {
-            Err(e) => return Err(GraphQLError::InvalidId),
-            Ok(u) => u,
-        };
-        // FIXME: Very inefficient quering. Should use joins instead ?
-        let viewer = repositories::UserRepository::find_one(context.viewer.id(), &context.db_pool)
-            .map_err(GraphQLError::InternalServerError)
-            .and_then(|o| match o {
-                None => Err(GraphQLError::UserNotFound),
-                Some(u) => Ok(u),
-            });
-        viewer.and_then(|u| {
-            repositories::GroupRepository::find_by_user(&u, &context.db_pool)
-                .map_err(GraphQLError::InternalServerError)
-                .map(|v| v.into_iter().find(|g| g.id == id).map(|g| g.into()))
-        })
+        let id = parse_id(id)?;
+        // Another user's group is as good as missing
+        match GroupService::owned_group(&id, Role::Viewer, context.viewer.id(), &*context.db) {
+            Err(GroupError::GroupNotFound) => Ok(None),
+            Err(e) => Err(e.into()),
+            Ok(g) => Ok(Some(g.into())),
+        }
+    }
+
+    /// The exchange rates used to convert the expenses into their group's currency.
+    fn exchangeRates(context: &Context) -> Result<Vec<ExchangeRate>, GraphQLError> {
+        ExchangeRateService::find_all(&*context.db)
+            .map_err(Into::into)
+            .map(|v| v.into_iter().map(Into::into).collect())
     }
 }
 
 pub struct Mutation;
 #[juniper::object(Context = Context)]
 impl Mutation {
-    // FIXME: Extract domain and repository logic to own module
     /// Signup a new user. Check if the email isn't already taken or valid and that the password is valid and proceed to create his account.
+    #[graphql(deprecated = "Use signupV2, which also returns a refresh token")]
     fn signup(context: &Context, input: SignupInput) -> Result<String, GraphQLError> {
         let SignupInput { email, password } = input;
 
-        // Check email validity
-        if !regex::Regex::new(r"^\S+@\S+\.\S+$")
-            .unwrap()
-            .is_match(&email)
-        {
-            return Err(GraphQLError::InvalidEmailAddress);
-        }
-
-        // Check password validity
-        if !(8..=64).contains(&password.graphemes(true).count()) {
-            return Err(GraphQLError::InvalidPassword);
-        }
+        AuthService::signup(
+            email,
+            &password,
+            context.config.security(),
+            &*context.mailer,
+            &*context.db,
+        )
+        .map_err(Into::into)
+        .map(|t| t.access_token)
+    }
 
-        // Check email availability
-        match repositories::UserRepository::find_one_by_email(&email[..], &context.db_pool) {
-            Err(e) => return Err(GraphQLError::InternalServerError(e)),
-            Ok(Some(_)) => return Err(GraphQLError::AlreadyUsedEmail),
-            _ => (),
-        }
+    /// Signup a new user and start their first session.
+    fn signupV2(context: &Context, input: SignupInput) -> Result<AuthPayload, GraphQLError> {
+        let SignupInput { email, password } = input;
 
-        // Prepare the new user's data
-        let user_id = uuid::Uuid::new_v4();
-        let hash = match security::hash_password(
-            password.as_bytes(),
-            context.config.security().hash_salt(),
-        ) {
-            Err(e) => return Err(GraphQLError::InternalServerError(e)),
-            Ok(hash) => hash,
-        };
-        let new_user = repositories::NewUser {
-            id: user_id,
+        AuthService::signup(
             email,
-            password: hash,
-        };
+            &password,
+            context.config.security(),
+            &*context.mailer,
+            &*context.db,
+        )
+        .map_err(Into::into)
+        .map(Into::into)
+    }
 
-        let user = match repositories::UserRepository::save(&new_user, &context.db_pool) {
-            Err(e) => return Err(GraphQLError::InternalServerError(e)),
-            Ok(u) => u,
-        };
+    /// Login a user, starting a new session.
+    fn loginV2(
+        context: &Context,
+        email: String,
+        password: String,
+    ) -> Result<AuthPayload, GraphQLError> {
+        AuthService::login(&email, &password, context.config.security(), &*context.db)
+            .map_err(Into::into)
+            .map(Into::into)
+    }
 
-        // Sign token
-        let token = match security::sign_token(
-            user_id,
-            context.config.security().token_expiration_time(),
-            context.config.security().secret_key(),
-        ) {
-            Err(e) => return Err(GraphQLError::InternalServerError(e)),
-            Ok(token) => token,
-        };
+    /// Exchange a refresh token for new tokens. The refresh token can't be used again.
+    fn refreshToken(context: &Context, refresh_token: String) -> Result<AuthPayload, GraphQLError> {
+        AuthService::refresh(&refresh_token, context.config.security(), &*context.db)
+            .map_err(Into::into)
+            .map(Into::into)
+    }
+
+    /// End the viewer's session: its access and refresh tokens can't be used anymore.
+    /// This is a user context dependant mutation.
+    fn logout(context: &Context) -> Result<bool, GraphQLError> {
+        AuthService::logout(&context.viewer, &*context.db)
+            .map_err(Into::into)
+            .map(|_| true)
+    }
 
-        Ok(token)
+    /// End all the viewer's sessions, on every device.
+    /// This is a user context dependant mutation.
+    fn logoutEverywhere(context: &Context) -> Result<bool, GraphQLError> {
+        AuthService::logout_everywhere(context.viewer.id(), &*context.db)
+            .map_err(Into::into)
+            .map(|_| true)
+    }
+
+    /// Change the viewer's password. Their other sessions are ended.
+    /// This is a user context dependant mutation.
+    fn changePassword(
+        context: &Context,
+        current_password: String,
+        new_password: String,
+    ) -> Result<bool, GraphQLError> {
+        AuthService::change_password(
+            &context.viewer,
+            &current_password,
+            &new_password,
+            context.config.security(),
+            &*context.db,
+        )
+        .map_err(Into::into)
+        .map(|_| true)
+    }
+
+    /// Change the viewer's email, checking their password first.
+    /// The new address has to be verified again.
+    /// This is a user context dependant mutation.
+    fn changeEmail(
+        context: &Context,
+        password: String,
+        new_email: String,
+    ) -> Result<bool, GraphQLError> {
+        AuthService::change_email(
+            context.viewer.id(),
+            &password,
+            new_email,
+            context.config.security(),
+            &*context.mailer,
+            &*context.db,
+        )
+        .map_err(Into::into)
+        .map(|_| true)
+    }
+
+    /// Verify the email address a token was sent to. The token can't be used again.
+    fn verifyEmail(context: &Context, token: String) -> Result<bool, GraphQLError> {
+        AuthService::verify_email(&token, &*context.db)
+            .map_err(Into::into)
+            .map(|_| true)
+    }
+
+    /// Email the viewer another verification link, unless their address is already verified.
+    /// This is a user context dependant mutation.
+    fn sendVerificationEmail(context: &Context) -> Result<bool, GraphQLError> {
+        AuthService::send_verification_email(
+            context.viewer.id(),
+            context.config.security(),
+            &*context.mailer,
+            &*context.db,
+        )
+        .map_err(Into::into)
+        .map(|_| true)
+    }
+
+    /// Email a token to reset the password with, if a user has this email address.
+    /// The answer is the same either way.
+    fn requestPasswordReset(context: &Context, email: String) -> Result<bool, GraphQLError> {
+        AuthService::request_password_reset(
+            &email,
+            context.config.security(),
+            context.mailer.clone(),
+            context.db.clone(),
+            &context.worker,
+        )
+        .map_err(Into::into)
+        .map(|_| true)
+    }
+
+    /// Choose a new password with a token from `requestPasswordReset`.
+    /// All the user's sessions are ended.
+    fn resetPassword(
+        context: &Context,
+        token: String,
+        new_password: String,
+    ) -> Result<bool, GraphQLError> {
+        AuthService::reset_password(
+            &token,
+            &new_password,
+            context.config.security(),
+            &*context.db,
+        )
+        .map_err(Into::into)
+        .map(|_| true)
     }
 
-    // FIXME: Extract domain and repository logic to own module
     /// Adds a group.
     /// This is a user context dependant mutation.
+    #[graphql(deprecated = "Use addGroupV2, which returns the added group")]
     fn addGroup(context: &Context, input: AddGroupInput) -> Result<bool, GraphQLError> {
-        let AddGroupInput { name } = input;
-        // Check name validity
-        if !(1..=50).contains(&name.graphemes(true).count()) {
-            return Err(GraphQLError::InvalidName);
-        }
-        // Check name uniqueness
-        // FIXME: Very inefficient query. Should use joins instead ?
-        let result = repositories::UserRepository::find_one(context.viewer.id(), &context.db_pool)
-            .and_then(|o| {
-                o.map(|u| {
-                    repositories::GroupRepository::find_by_user(&u, &context.db_pool)
-                        .map(|g| (u.id, g))
-                })
-                .transpose()
-            });
-
-        match result {
-            Err(e) => Err(GraphQLError::InternalServerError(e)),
-            Ok(None) => Err(GraphQLError::UserNotFound),
-            Ok(Some((user_id, v))) => {
-                if v.iter().any(|g| g.name == name) {
-                    Err(GraphQLError::NonUniqueName(name))
-                } else {
-                    // Add this group to viewer's
-                    let new_group = repositories::NewGroup {
-                        id: uuid::Uuid::new_v4(),
-                        user_id,
-                        name,
-                    };
-                    repositories::GroupRepository::save(&new_group, &context.db_pool)
-                        .map_err(GraphQLError::InternalServerError)
-                        .map(|_| true)
-                }
-            }
-        }
+        add_group(input, context).map(|_| true)
     }
 
-    // FIXME: Extract domain and repository logic to own module
-    /// Adds a person to the specified group.
+    /// Adds a group.
     /// This is a user context dependant mutation.
+    fn addGroupV2(
+        context: &Context,
+        input: AddGroupInput,
+    ) -> Result<AddGroupPayload, GraphQLError> {
+        add_group(input, context).map(AddGroupPayload::new)
+    }
+
+    /// Adds a person to the specified group.
+    /// This is a user context dependant mutation, reserved to the group's editors and owners.
+    #[graphql(deprecated = "Use addPersonV2, which returns the added person")]
     fn addPerson(context: &Context, input: AddPersonInput) -> Result<bool, GraphQLError> {
-        let AddPersonInput {
-            group_id,
-            name,
-            resources,
-        } = input;
-        // Check name validity
-        if !(1..=50).contains(&name.graphemes(true).count()) {
-            return Err(GraphQLError::InvalidName);
-        }
-        // Check resources validity
-        if resources.is_negative() {
-            return Err(GraphQLError::InvalidResources);
-        }
-        // Check id validity
-        let group_id = match uuid::Uuid::parse_str(group_id.as_str()) {
-            Err(e) => return Err(GraphQLError::InvalidId),
-            Ok(u) => u,
-        };
-        // FIXME: Very inefficient quering. Should use joins instead ?
-        let viewer = repositories::UserRepository::find_one(context.viewer.id(), &context.db_pool)
-            .map_err(GraphQLError::InternalServerError)
-            .and_then(|o| match o {
-                None => Err(GraphQLError::UserNotFound),
-                Some(u) => Ok(u),
-            });
-        let group = viewer.and_then(|u| {
-            repositories::GroupRepository::find_by_user(&u, &context.db_pool)
-                .map_err(GraphQLError::InternalServerError)
-                .map(|v| v.into_iter().find(|g| g.id == group_id))
-                .and_then(|o| match o {
-                    None => Err(GraphQLError::GroupNotFound),
-                    Some(g) => Ok(g),
-                })
-        });
-        let person = group.and_then(|g| {
-            repositories::PersonRepository::find_by_group(&g, &context.db_pool)
-                .map_err(GraphQLError::InternalServerError)
-                .map(|v| v.into_iter().find(|p| p.name == name))
-                // Check name uniqueness
-                .and_then(|o| match o {
-                    Some(p) => Err(GraphQLError::NonUniqueName(name.clone())),
-                    None => Ok(()),
-                })
-        });
-        // Add this person to viewer's group
-        person.and_then(|_| {
-            let new_person = repositories::NewPerson {
-                id: uuid::Uuid::new_v4(),
-                group_id,
-                name,
-                resources,
-            };
-            repositories::PersonRepository::save(&new_person, &context.db_pool)
-                .map_err(GraphQLError::InternalServerError)
-                .map(|_| true)
-        })
+        add_person(input, context).map(|_| true)
+    }
+
+    /// Adds a person to the specified group.
+    /// This is a user context dependant mutation, reserved to the group's editors and owners.
+    fn addPersonV2(
+        context: &Context,
+        input: AddPersonInput,
+    ) -> Result<AddPersonPayload, GraphQLError> {
+        let person = add_person(input, context)?;
+        let group = authorization::owned_group(&person.group_id, Role::Viewer, context)?;
+        Ok(AddPersonPayload::new(person, group))
     }
 
-    // FIXME: Extract domain and repository logic to own module
     /// Adds an expense to the specified group.
-    /// This is a user context dependant mutation.
+    /// This is a user context dependant mutation, reserved to the group's editors and owners.
+    #[graphql(deprecated = "Use addExpenseV2, which returns the added expense")]
     fn addExpense(context: &Context, input: AddExpenseInput) -> Result<bool, GraphQLError> {
-        let AddExpenseInput {
-            group_id,
-            person_id,
-            name,
-            amount,
-        } = input;
-        // Check input validity
-        let group_id = match uuid::Uuid::parse_str(group_id.as_str()) {
-            Err(e) => return Err(GraphQLError::InvalidId),
-            Ok(u) => u,
-        };
-        let person_id = match uuid::Uuid::parse_str(person_id.as_str()) {
-            Err(e) => return Err(GraphQLError::InvalidId),
-            Ok(u) => u,
-        };
-        // Check amount validity
-        if amount < 1 {
-            return Err(GraphQLError::InvalidAmount);
-        }
-        // FIXME: Very inefficient quering. Should use joins instead ?
-        let viewer = repositories::UserRepository::find_one(context.viewer.id(), &context.db_pool)
-            .map_err(GraphQLError::InternalServerError)
-            .and_then(|o| match o {
-                None => Err(GraphQLError::UserNotFound),
-                Some(u) => Ok(u),
-            });
-        let group = viewer.and_then(|u| {
-            repositories::GroupRepository::find_by_user(&u, &context.db_pool)
-                .map_err(GraphQLError::InternalServerError)
-                .map(|v| v.into_iter().find(|g| g.id == group_id))
-                .and_then(|o| match o {
-                    None => Err(GraphQLError::GroupNotFound),
-                    Some(g) => Ok(g),
-                })
-        });
-        let person = group.and_then(|g| {
-            repositories::PersonRepository::find_by_group(&g, &context.db_pool)
-                .map_err(GraphQLError::InternalServerError)
-                .map(|v| v.into_iter().find(|p| p.id == person_id))
-                .and_then(|o| match o {
-                    None => Err(GraphQLError::PersonNotFound),
-                    Some(p) => Ok(p),
-                })
-        });
-        // Add this expense to the viewer's group if the person exists
-        person.and_then(|p| {
-            let new_expense = repositories::NewExpense {
-                id: uuid::Uuid::new_v4(),
-                group_id,
-                person_id,
-                name,
-                amount,
-            };
-            repositories::ExpenseRepository::save(&new_expense, &context.db_pool)
-                .map_err(GraphQLError::InternalServerError)
-                .map(|_| true)
-        })
+        add_expense(input, context).map(|_| true)
+    }
+
+    /// Adds an expense to the specified group.
+    /// This is a user context dependant mutation, reserved to the group's editors and owners.
+    fn addExpenseV2(
+        context: &Context,
+        input: AddExpenseInput,
+    ) -> Result<AddExpensePayload, GraphQLError> {
+        let expense = add_expense(input, context)?;
+        let group = authorization::owned_group(&expense.group_id, Role::Viewer, context)?;
+        Ok(AddExpensePayload::new(expense, group))
+    }
+
+    /// Adds a payment from a person to another one of the specified group.
+    /// This is a user context dependant mutation, reserved to the group's editors and owners.
+    #[graphql(deprecated = "Use addPaymentV2, which returns the added payment")]
+    fn addPayment(context: &Context, input: AddPaymentInput) -> Result<bool, GraphQLError> {
+        add_payment(input, context).map(|_| true)
+    }
+
+    /// Adds a payment from a person to another one of the specified group.
+    /// This is a user context dependant mutation, reserved to the group's editors and owners.
+    fn addPaymentV2(
+        context: &Context,
+        input: AddPaymentInput,
+    ) -> Result<AddPaymentPayload, GraphQLError> {
+        let payment = add_payment(input, context)?;
+        let group = authorization::owned_group(&payment.group_id, Role::Viewer, context)?;
+        Ok(AddPaymentPayload::new(payment, group))
     }
 
-    // FIXME: Extract domain and repository logic to own module
     /// Update a group. Idempotent mutation.
-    /// This is a user context dependant mutation.
+    /// This is a user context dependant mutation, reserved to the group's owners.
+    #[graphql(deprecated = "Use updateGroupV2, which returns the updated group")]
     fn updateGroup(context: &Context, input: UpdateGroupInput) -> Result<bool, GraphQLError> {
-        let UpdateGroupInput { person_id, name } = input;
-        // Check input validity
-        let person_id = match uuid::Uuid::parse_str(person_id.as_str()) {
-            Err(e) => return Err(GraphQLError::InvalidId),
-            Ok(u) => u,
-        };
-
-        let person = repositories::UpdateGroup {
-            id: person_id,
-            name,
-        };
-        repositories::GroupRepository::update_one(&person, &context.db_pool)
-            .map_err(GraphQLError::InternalServerError)
-            .map(|_| true)
+        update_group(input, context).map(|_| true)
+    }
+
+    /// Update a group. Idempotent mutation.
+    /// This is a user context dependant mutation, reserved to the group's owners.
+    fn updateGroupV2(
+        context: &Context,
+        input: UpdateGroupInput,
+    ) -> Result<UpdateGroupPayload, GraphQLError> {
+        update_group(input, context).map(UpdateGroupPayload::new)
     }
 
-    // FIXME: Extract domain and repository logic to own module
     /// Update a person. Idempotent mutation.
-    /// This is a user context dependant mutation.
+    /// This is a user context dependant mutation, reserved to the group's editors and owners.
+    #[graphql(deprecated = "Use updatePersonV2, which returns the updated person")]
     fn updatePerson(context: &Context, input: UpdatePersonInput) -> Result<bool, GraphQLError> {
-        let UpdatePersonInput {
-            person_id,
-            name,
-            resources,
-        } = input;
-        // Check input validity
-        let person_id = match uuid::Uuid::parse_str(person_id.as_str()) {
-            Err(e) => return Err(GraphQLError::InvalidId),
-            Ok(u) => u,
-        };
-
-        let person = repositories::UpdatePerson {
-            id: person_id,
-            name,
-            resources,
-        };
-        repositories::PersonRepository::update_one(&person, &context.db_pool)
-            .map_err(GraphQLError::InternalServerError)
-            .map(|_| true)
+        update_person(input, context).map(|_| true)
+    }
+
+    /// Update a person. Idempotent mutation.
+    /// This is a user context dependant mutation, reserved to the group's editors and owners.
+    fn updatePersonV2(
+        context: &Context,
+        input: UpdatePersonInput,
+    ) -> Result<UpdatePersonPayload, GraphQLError> {
+        let person = update_person(input, context)?;
+        let group = authorization::owned_group(&person.group_id, Role::Viewer, context)?;
+        Ok(UpdatePersonPayload::new(person, group))
     }
 
-    // FIXME: Extract domain and repository logic to own module
     /// Update an expense. Idempotent mutation.
-    /// This is a user context dependant mutation.
+    /// This is a user context dependant mutation, reserved to the group's editors and owners.
+    #[graphql(deprecated = "Use updateExpenseV2, which returns the updated expense")]
     fn updateExpense(context: &Context, input: UpdateExpenseInput) -> Result<bool, GraphQLError> {
-        let UpdateExpenseInput {
-            expense_id,
-            name,
-            amount,
+        update_expense(input, context).map(|_| true)
+    }
+
+    /// Update an expense. Idempotent mutation.
+    /// This is a user context dependant mutation, reserved to the group's editors and owners.
+    fn updateExpenseV2(
+        context: &Context,
+        input: UpdateExpenseInput,
+    ) -> Result<UpdateExpensePayload, GraphQLError> {
+        let expense = update_expense(input, context)?;
+        let group = authorization::owned_group(&expense.group_id, Role::Viewer, context)?;
+        Ok(UpdateExpensePayload::new(expense, group))
+    }
+
+    /// Move a group to the trash, along with everything it holds. Idempotent mutation.
+    /// This is a user context dependant mutation, reserved to the group's owners.
+    #[graphql(deprecated = "Use removeGroupV2, which returns the removed group's id")]
+    fn removeGroup(context: &Context, input: RemoveGroupInput) -> Result<bool, GraphQLError> {
+        remove_group(input, context).map(|_| true)
+    }
+
+    /// Move a group to the trash, along with everything it holds. Idempotent mutation.
+    /// This is a user context dependant mutation, reserved to the group's owners.
+    fn removeGroupV2(
+        context: &Context,
+        input: RemoveGroupInput,
+    ) -> Result<RemoveGroupPayload, GraphQLError> {
+        remove_group(input, context).map(RemoveGroupPayload::new)
+    }
+
+    /// Move a person to the trash, along with the expenses they helped pay for. Idempotent mutation.
+    /// This is a user context dependant mutation, reserved to the group's editors and owners.
+    #[graphql(deprecated = "Use removePersonV2, which returns the removed person's id")]
+    fn removePerson(context: &Context, input: RemovePersonInput) -> Result<bool, GraphQLError> {
+        remove_person(input, context).map(|_| true)
+    }
+
+    /// Move a person to the trash, along with the expenses they helped pay for. Idempotent mutation.
+    /// This is a user context dependant mutation, reserved to the group's editors and owners.
+    fn removePersonV2(
+        context: &Context,
+        input: RemovePersonInput,
+    ) -> Result<RemovePersonPayload, GraphQLError> {
+        remove_person(input, context).map(RemovePersonPayload::new)
+    }
+
+    /// Move an expense to the trash. Idempotent mutation.
+    /// This is a user context dependant mutation, reserved to the group's editors and owners.
+    #[graphql(deprecated = "Use removeExpenseV2, which returns the removed expense's id")]
+    fn removeExpense(context: &Context, input: RemoveExpenseInput) -> Result<bool, GraphQLError> {
+        remove_expense(input, context).map(|_| true)
+    }
+
+    /// Move an expense to the trash. Idempotent mutation.
+    /// This is a user context dependant mutation, reserved to the group's editors and owners.
+    fn removeExpenseV2(
+        context: &Context,
+        input: RemoveExpenseInput,
+    ) -> Result<RemoveExpensePayload, GraphQLError> {
+        remove_expense(input, context).map(RemoveExpensePayload::new)
+    }
+
+    /// Remove a payment. Idempotent mutation.
+    /// This is a user context dependant mutation, reserved to the group's editors and owners.
+    #[graphql(deprecated = "Use removePaymentV2, which returns the removed payment's id")]
+    fn removePayment(context: &Context, input: RemovePaymentInput) -> Result<bool, GraphQLError> {
+        remove_payment(input, context).map(|_| true)
+    }
+
+    /// Remove a payment. Idempotent mutation.
+    /// This is a user context dependant mutation, reserved to the group's editors and owners.
+    fn removePaymentV2(
+        context: &Context,
+        input: RemovePaymentInput,
+    ) -> Result<RemovePaymentPayload, GraphQLError> {
+        remove_payment(input, context).map(RemovePaymentPayload::new)
+    }
+
+    /// Move a group back from the trash. Idempotent mutation.
+    /// This is a user context dependant mutation, reserved to the group's owners.
+    #[graphql(deprecated = "Use restoreGroupV2, which returns the restored group")]
+    fn restoreGroup(context: &Context, input: RestoreGroupInput) -> Result<bool, GraphQLError> {
+        restore_group(input, context).map(|_| true)
+    }
+
+    /// Move a group back from the trash. Idempotent mutation.
+    /// This is a user context dependant mutation, reserved to the group's owners.
+    fn restoreGroupV2(
+        context: &Context,
+        input: RestoreGroupInput,
+    ) -> Result<RestoreGroupPayload, GraphQLError> {
+        restore_group(input, context).map(RestoreGroupPayload::new)
+    }
+
+    /// Move a person back from the trash, along with the expenses deleted with them. Idempotent mutation.
+    /// This is a user context dependant mutation, reserved to the group's editors and owners.
+    #[graphql(deprecated = "Use restorePersonV2, which returns the restored person")]
+    fn restorePerson(context: &Context, input: RestorePersonInput) -> Result<bool, GraphQLError> {
+        restore_person(input, context).map(|_| true)
+    }
+
+    /// Move a person back from the trash, along with the expenses deleted with them. Idempotent mutation.
+    /// This is a user context dependant mutation, reserved to the group's editors and owners.
+    fn restorePersonV2(
+        context: &Context,
+        input: RestorePersonInput,
+    ) -> Result<RestorePersonPayload, GraphQLError> {
+        let person = restore_person(input, context)?;
+        let group = authorization::owned_group(&person.group_id, Role::Viewer, context)?;
+        Ok(RestorePersonPayload::new(person, group))
+    }
+
+    /// Move an expense back from the trash. Its payers must be out of it. Idempotent mutation.
+    /// This is a user context dependant mutation, reserved to the group's editors and owners.
+    #[graphql(deprecated = "Use restoreExpenseV2, which returns the restored expense")]
+    fn restoreExpense(context: &Context, input: RestoreExpenseInput) -> Result<bool, GraphQLError> {
+        restore_expense(input, context).map(|_| true)
+    }
+
+    /// Move an expense back from the trash. Its payers must be out of it. Idempotent mutation.
+    /// This is a user context dependant mutation, reserved to the group's editors and owners.
+    fn restoreExpenseV2(
+        context: &Context,
+        input: RestoreExpenseInput,
+    ) -> Result<RestoreExpensePayload, GraphQLError> {
+        let expense = restore_expense(input, context)?;
+        let group = authorization::owned_group(&expense.group_id, Role::Viewer, context)?;
+        Ok(RestoreExpensePayload::new(expense, group))
+    }
+
+    /// Adds a custom category to the specified group.
+    /// This is a user context dependant mutation, reserved to the group's editors and owners.
+    #[graphql(deprecated = "Use addCategoryV2, which returns the added category")]
+    fn addCategory(context: &Context, input: AddCategoryInput) -> Result<bool, GraphQLError> {
+        add_category(input, context).map(|_| true)
+    }
+
+    /// Adds a custom category to the specified group.
+    /// This is a user context dependant mutation, reserved to the group's editors and owners.
+    fn addCategoryV2(
+        context: &Context,
+        input: AddCategoryInput,
+    ) -> Result<AddCategoryPayload, GraphQLError> {
+        add_category(input, context).map(AddCategoryPayload::new)
+    }
+
+    /// Rename a custom category. Idempotent mutation.
+    /// This is a user context dependant mutation, reserved to the group's editors and owners.
+    #[graphql(deprecated = "Use updateCategoryV2, which returns the updated category")]
+    fn updateCategory(context: &Context, input: UpdateCategoryInput) -> Result<bool, GraphQLError> {
+        update_category(input, context).map(|_| true)
+    }
+
+    /// Rename a custom category. Idempotent mutation.
+    /// This is a user context dependant mutation, reserved to the group's editors and owners.
+    fn updateCategoryV2(
+        context: &Context,
+        input: UpdateCategoryInput,
+    ) -> Result<UpdateCategoryPayload, GraphQLError> {
+        update_category(input, context).map(UpdateCategoryPayload::new)
+    }
+
+    /// Remove a custom category. Idempotent mutation.
+    /// A category can't be removed while some expenses, even in the trash, belong to it.
+    /// This is a user context dependant mutation, reserved to the group's editors and owners.
+    #[graphql(deprecated = "Use removeCategoryV2, which returns the removed category's id")]
+    fn removeCategory(context: &Context, input: RemoveCategoryInput) -> Result<bool, GraphQLError> {
+        remove_category(input, context).map(|_| true)
+    }
+
+    /// Remove a custom category. Idempotent mutation.
+    /// A category can't be removed while some expenses, even in the trash, belong to it.
+    /// This is a user context dependant mutation, reserved to the group's editors and owners.
+    fn removeCategoryV2(
+        context: &Context,
+        input: RemoveCategoryInput,
+    ) -> Result<RemoveCategoryPayload, GraphQLError> {
+        remove_category(input, context).map(RemoveCategoryPayload::new)
+    }
+
+    /// Invite someone to join a group. Returns the invitation's token, which can't be retrieved later.
+    /// This is a user context dependant mutation, reserved to the group's owners.
+    fn createInvitation(
+        context: &Context,
+        input: CreateInvitationInput,
+    ) -> Result<String, GraphQLError> {
+        let CreateInvitationInput {
+            group_id,
+            expires_in,
+            role,
         } = input;
-        // Check input validity
-        let expense_id = match uuid::Uuid::parse_str(expense_id.as_str()) {
-            Err(e) => return Err(GraphQLError::InvalidId),
-            Ok(u) => u,
-        };
-
-        let expense = repositories::UpdateExpense {
-            id: expense_id,
-            name,
-            amount,
-        };
-        repositories::ExpenseRepository::update_one(&expense, &context.db_pool)
-            .map_err(GraphQLError::InternalServerError)
-            .map(|_| true)
+        let group_id = parse_id(group_id)?;
+        let role = role.map_or(Role::Editor, Into::into);
+
+        MemberService::create_invitation(
+            &group_id,
+            expires_in,
+            role,
+            context.viewer.id(),
+            context.config.security(),
+            &*context.db,
+        )
+        .map_err(Into::into)
     }
 
-    // FIXME: Extract domain and repository logic to own module
-    /// Remove a group. Idempotent mutation.
+    /// Join a group with an invitation, which can't be used again.
     /// This is a user context dependant mutation.
-    fn removeGroup(context: &Context, input: RemoveGroupInput) -> Result<bool, GraphQLError> {
-        let RemoveGroupInput { group_id } = input;
-        // Check input validity
-        let group_id = match uuid::Uuid::parse_str(group_id.as_str()) {
-            Err(e) => return Err(GraphQLError::InvalidId),
-            Ok(u) => u,
-        };
-        // Delete the group
-        repositories::GroupRepository::delete_one(&group_id, &context.db_pool)
-            .map_err(GraphQLError::InternalServerError)
+    fn acceptInvitation(
+        context: &Context,
+        input: AcceptInvitationInput,
+    ) -> Result<bool, GraphQLError> {
+        let AcceptInvitationInput { token, person_id } = input;
+        let person_id = person_id.map(parse_id).transpose()?;
+
+        MemberService::accept_invitation(
+            &token,
+            person_id.as_ref(),
+            context.viewer.id(),
+            context.config.security(),
+            &*context.db,
+        )
+        .map_err(Into::into)
+        .map(|_| true)
+    }
+
+    /// Make an invitation unusable. Idempotent mutation.
+    /// This is a user context dependant mutation, reserved to the group's owners.
+    fn revokeInvitation(
+        context: &Context,
+        input: RevokeInvitationInput,
+    ) -> Result<bool, GraphQLError> {
+        let invitation_id = parse_id(input.invitation_id)?;
+
+        MemberService::revoke_invitation(&invitation_id, context.viewer.id(), &*context.db)
+            .map_err(Into::into)
             .map(|_| true)
     }
 
-    // FIXME: Extract domain and repository logic to own module
-    /// Remove a person. Idempotent mutation.
-    /// This is a user context dependant mutation.
-    fn removePerson(context: &Context, input: RemovePersonInput) -> Result<bool, GraphQLError> {
-        let RemovePersonInput { person_id } = input;
-        // Check input validity
-        let person_id = match uuid::Uuid::parse_str(person_id.as_str()) {
-            Err(e) => return Err(GraphQLError::InvalidId),
-            Ok(u) => u,
-        };
-        // Delete the person
-        repositories::PersonRepository::delete_one(&person_id, &context.db_pool)
-            .map_err(GraphQLError::InternalServerError)
+    /// Change what a member can do in a group. Idempotent mutation.
+    /// This is a user context dependant mutation, reserved to the group's owners.
+    fn changeMemberRole(
+        context: &Context,
+        input: ChangeMemberRoleInput,
+    ) -> Result<bool, GraphQLError> {
+        let ChangeMemberRoleInput {
+            group_id,
+            user_id,
+            role,
+        } = input;
+        let group_id = parse_id(group_id)?;
+        let user_id = parse_id(user_id)?;
+
+        MemberService::change_member_role(
+            &group_id,
+            &user_id,
+            role.into(),
+            context.viewer.id(),
+            &*context.db,
+        )
+        .map_err(Into::into)
+        .map(|_| true)
+    }
+
+    /// Remove a member from a group. The person they were is kept. Members can remove themselves.
+    /// This is a user context dependant mutation, reserved to the group's owners.
+    fn removeMember(context: &Context, input: RemoveMemberInput) -> Result<bool, GraphQLError> {
+        let RemoveMemberInput { group_id, user_id } = input;
+        let group_id = parse_id(group_id)?;
+        let user_id = parse_id(user_id)?;
+
+        MemberService::remove_member(&group_id, &user_id, context.viewer.id(), &*context.db)
+            .map_err(Into::into)
             .map(|_| true)
     }
 
-    // FIXME: Extract domain and repository logic to own module
-    /// Remove an expense. Idempotent mutation.
-    /// This is a user context dependant mutation.
-    fn removeExpense(context: &Context, input: RemoveExpenseInput) -> Result<bool, GraphQLError> {
-        let RemoveExpenseInput { expense_id } = input;
-        // Check input validity
-        let expense_id = match uuid::Uuid::parse_str(expense_id.as_str()) {
-            Err(e) => return Err(GraphQLError::InvalidId),
-            Ok(u) => u,
-        };
-        // Delete the expense
-        repositories::ExpenseRepository::delete_one(&expense_id, &context.db_pool)
-            .map_err(GraphQLError::InternalServerError)
+    /// Set the rate converting a currency into another one. Idempotent mutation.
+    /// The expenses already added keep the rate they were added with.
+    /// This is an admin mutation.
+    fn setExchangeRate(context: &Context, input: ExchangeRateInput) -> Result<bool, GraphQLError> {
+        let ExchangeRateInput { from, to, rate } = input;
+        let rate = currency::Rate { from, to, rate };
+
+        ExchangeRateService::set_rate(rate, context.viewer.id(), &context.config, &*context.db)
+            .map_err(Into::into)
             .map(|_| true)
     }
+
+    /// Import exchange rates from a CSV made of `from,to,rate` lines, with an optional header.
+    /// Nothing is imported if a line is invalid. Returns the number of imported rates.
+    /// This is an admin mutation.
+    fn importExchangeRates(context: &Context, csv: String) -> Result<i32, GraphQLError> {
+        ExchangeRateService::import_rates(&csv, context.viewer.id(), &context.config, &*context.db)
+            .map_err(Into::into)
+            .map(|count| count as i32)
+    }
+
+    /// Remove the rate converting a currency into another one. Idempotent mutation.
+    /// This is an admin mutation.
+    fn removeExchangeRate(
+        context: &Context,
+        input: RemoveExchangeRateInput,
+    ) -> Result<bool, GraphQLError> {
+        let RemoveExchangeRateInput { from, to } = input;
+
+        ExchangeRateService::remove_rate(
+            &from,
+            &to,
+            context.viewer.id(),
+            &context.config,
+            &*context.db,
+        )
+        .map_err(Into::into)
+        .map(|_| true)
+    }
+}
+
+fn add_group(input: AddGroupInput, context: &Context) -> Result<repositories::Group, GraphQLError> {
+    let AddGroupInput { name, currency } = input;
+
+    GroupService::add_group(name, currency, context.viewer.id(), &*context.db).map_err(Into::into)
+}
+
+fn add_person(
+    input: AddPersonInput,
+    context: &Context,
+) -> Result<repositories::Person, GraphQLError> {
+    let AddPersonInput {
+        group_id,
+        name,
+        resources,
+    } = input;
+    let group_id = parse_id(group_id)?;
+
+    GroupService::add_person(
+        &group_id,
+        name,
+        &resources.into(),
+        context.viewer.id(),
+        &*context.db,
+    )
+    .map_err(Into::into)
+}
+
+fn add_expense(
+    input: AddExpenseInput,
+    context: &Context,
+) -> Result<repositories::Expense, GraphQLError> {
+    let AddExpenseInput {
+        group_id,
+        person_id,
+        payers,
+        name,
+        amount,
+        split_mode,
+        shares,
+        occurred_on,
+        category_id,
+    } = input;
+    let MoneyInput { amount, currency } = amount;
+    let amount = amount.0;
+    // Check input validity
+    let group_id = parse_id(group_id)?;
+    let category_id = category_id.map(parse_id).transpose()?;
+    // A single person paying the whole amount is a shorthand for one payer
+    let payers = match (person_id, payers) {
+        (Some(person_id), None) => parse_payers(vec![ExpensePayerInput {
+            person_id,
+            amount: BigInt(amount),
+        }])?,
+        (None, Some(payers)) => parse_payers(payers)?,
+        _ => return Err(GraphQLError::InvalidPayers),
+    };
+    let participants = parse_shares(shares.unwrap_or_default())?;
+
+    let new_expense = services::AddExpense {
+        group_id,
+        name,
+        amount,
+        currency,
+        payers,
+        split_mode: split_mode.map(expense::SplitMode::from).unwrap_or_default(),
+        participants,
+        occurred_on,
+        category_id,
+    };
+    ExpenseService::add_expense(new_expense, context.viewer.id(), &*context.db).map_err(Into::into)
+}
+
+fn add_payment(
+    input: AddPaymentInput,
+    context: &Context,
+) -> Result<repositories::Payment, GraphQLError> {
+    let AddPaymentInput {
+        group_id,
+        from_person_id,
+        to_person_id,
+        amount,
+    } = input;
+    // Check input validity
+    let group_id = parse_id(group_id)?;
+    let from_person_id = parse_id(from_person_id)?;
+    let to_person_id = parse_id(to_person_id)?;
+
+    ExpenseService::add_payment(
+        &group_id,
+        &from_person_id,
+        &to_person_id,
+        &amount.into(),
+        context.viewer.id(),
+        &*context.db,
+    )
+    .map_err(Into::into)
+}
+
+fn update_group(
+    input: UpdateGroupInput,
+    context: &Context,
+) -> Result<repositories::Group, GraphQLError> {
+    let UpdateGroupInput { person_id, name } = input;
+    let group_id = parse_id(person_id)?;
+
+    GroupService::update_group(&group_id, name, context.viewer.id(), &*context.db)
+        .map_err(Into::into)
+}
+
+fn update_person(
+    input: UpdatePersonInput,
+    context: &Context,
+) -> Result<repositories::Person, GraphQLError> {
+    let UpdatePersonInput {
+        person_id,
+        name,
+        resources,
+    } = input;
+    let person_id = parse_id(person_id)?;
+
+    GroupService::update_person(
+        &person_id,
+        name,
+        resources.map(Into::into).as_ref(),
+        context.viewer.id(),
+        &*context.db,
+    )
+    .map_err(Into::into)
+}
+
+fn update_expense(
+    input: UpdateExpenseInput,
+    context: &Context,
+) -> Result<repositories::Expense, GraphQLError> {
+    let UpdateExpenseInput {
+        expense_id,
+        name,
+        amount,
+        payers,
+        split_mode,
+        shares,
+        occurred_on,
+        category_id,
+    } = input;
+    let (amount, currency) = match amount {
+        None => (None, None),
+        Some(MoneyInput { amount, currency }) => (Some(amount.0), currency),
+    };
+    // Check input validity
+    let expense_id = parse_id(expense_id)?;
+    let category_id = category_id.map(parse_id).transpose()?;
+    let payers = payers.map(parse_payers).transpose()?;
+    let participants = shares.map(parse_shares).transpose()?;
+
+    let changes = services::UpdateExpense {
+        name,
+        amount,
+        currency,
+        payers,
+        split_mode: split_mode.map(expense::SplitMode::from),
+        participants,
+        occurred_on,
+        category_id,
+    };
+    ExpenseService::update_expense(&expense_id, changes, context.viewer.id(), &*context.db)
+        .map_err(Into::into)
+}
+
+/// Remove a group, returning its id.
+fn remove_group(input: RemoveGroupInput, context: &Context) -> Result<uuid::Uuid, GraphQLError> {
+    let group_id = parse_id(input.group_id)?;
+
+    GroupService::remove_group(&group_id, context.viewer.id(), &*context.db)?;
+    Ok(group_id)
+}
+
+/// Remove a person, returning their id.
+fn remove_person(input: RemovePersonInput, context: &Context) -> Result<uuid::Uuid, GraphQLError> {
+    let person_id = parse_id(input.person_id)?;
+
+    GroupService::remove_person(&person_id, context.viewer.id(), &*context.db)?;
+    Ok(person_id)
+}
+
+/// Remove an expense, returning its id.
+fn remove_expense(
+    input: RemoveExpenseInput,
+    context: &Context,
+) -> Result<uuid::Uuid, GraphQLError> {
+    let expense_id = parse_id(input.expense_id)?;
+
+    ExpenseService::remove_expense(&expense_id, context.viewer.id(), &*context.db)?;
+    Ok(expense_id)
+}
+
+/// Remove a payment, returning its id.
+fn remove_payment(
+    input: RemovePaymentInput,
+    context: &Context,
+) -> Result<uuid::Uuid, GraphQLError> {
+    let payment_id = parse_id(input.payment_id)?;
+
+    ExpenseService::remove_payment(&payment_id, context.viewer.id(), &*context.db)?;
+    Ok(payment_id)
+}
+
+/// Move a group back from the trash, returning it.
+fn restore_group(
+    input: RestoreGroupInput,
+    context: &Context,
+) -> Result<repositories::Group, GraphQLError> {
+    let group_id = parse_id(input.group_id)?;
+
+    GroupService::restore_group(&group_id, context.viewer.id(), &*context.db)?;
+    authorization::owned_group(&group_id, Role::Viewer, context)
+}
+
+/// Move a person back from the trash, returning them.
+fn restore_person(
+    input: RestorePersonInput,
+    context: &Context,
+) -> Result<repositories::Person, GraphQLError> {
+    let person_id = parse_id(input.person_id)?;
+
+    GroupService::restore_person(&person_id, context.viewer.id(), &*context.db)?;
+    GroupService::owned_person(&person_id, Role::Viewer, context.viewer.id(), &*context.db)
+        .map_err(Into::into)
+}
+
+/// Move an expense back from the trash, returning it.
+fn restore_expense(
+    input: RestoreExpenseInput,
+    context: &Context,
+) -> Result<repositories::Expense, GraphQLError> {
+    let expense_id = parse_id(input.expense_id)?;
+
+    ExpenseService::restore_expense(&expense_id, context.viewer.id(), &*context.db)?;
+    ExpenseService::owned_expense(&expense_id, Role::Viewer, context.viewer.id(), &*context.db)
+        .map_err(Into::into)
+}
+
+fn add_category(
+    input: AddCategoryInput,
+    context: &Context,
+) -> Result<repositories::Category, GraphQLError> {
+    let AddCategoryInput { group_id, name } = input;
+    let group_id = parse_id(group_id)?;
+
+    CategoryService::add_category(&group_id, name, context.viewer.id(), &*context.db)
+        .map_err(Into::into)
+}
+
+fn update_category(
+    input: UpdateCategoryInput,
+    context: &Context,
+) -> Result<repositories::Category, GraphQLError> {
+    let UpdateCategoryInput { category_id, name } = input;
+    let category_id = parse_id(category_id)?;
+
+    CategoryService::update_category(&category_id, name, context.viewer.id(), &*context.db)
+        .map_err(Into::into)
+}
+
+/// Remove a category, returning its id.
+fn remove_category(
+    input: RemoveCategoryInput,
+    context: &Context,
+) -> Result<uuid::Uuid, GraphQLError> {
+    let category_id = parse_id(input.category_id)?;
+
+    CategoryService::remove_category(&category_id, context.viewer.id(), &*context.db)?;
+    Ok(category_id)
+}
+
+/// Parse the persons covered by an expense.
+fn parse_shares(shares: Vec<ExpenseShareInput>) -> Result<Vec<expense::Participant>, GraphQLError> {
+    shares
+        .into_iter()
+        .map(|s| match uuid::Uuid::parse_str(s.person_id.as_str()) {
+            Err(_) => Err(GraphQLError::InvalidId),
+            Ok(person_id) => Ok(expense::Participant {
+                person_id,
+                value: s.value.map_or(0, |v| v.0),
+            }),
+        })
+        .collect()
+}
+
+fn parse_id(id: String) -> Result<uuid::Uuid, GraphQLError> {
+    uuid::Uuid::parse_str(id.as_str()).map_err(|_| GraphQLError::InvalidId)
+}
+
+/// Parse the persons who paid an expense.
+fn parse_payers(payers: Vec<ExpensePayerInput>) -> Result<Vec<expense::Payer>, GraphQLError> {
+    payers
+        .into_iter()
+        .map(|p| match uuid::Uuid::parse_str(p.person_id.as_str()) {
+            Err(_) => Err(GraphQLError::InvalidId),
+            Ok(person_id) => Ok(expense::Payer {
+                person_id,
+                amount: p.amount.0,
+            }),
+        })
+        .collect()
 }
 
 pub struct Context {
-    pub db_pool: repositories::PostgresPool,
+    /// The Postgres database, or an in-memory store for the tests which shouldn't need one.
+    pub db: std::sync::Arc<dyn repositories::Store + Send + Sync>,
     pub config: config::Settings,
+    pub mailer: std::sync::Arc<dyn mail::Mailer + Send + Sync>,
+    /// Where the slow work which shouldn't delay the answers is queued.
+    pub worker: jobs::Worker,
     pub viewer: security::Viewer,
 }
 
@@ -1,142 +1,1514 @@
 use super::*;
+use crate::domain::{activity, balance, currency, expense, member};
+use juniper::{parser::ScalarToken, ParseScalarResult, ParseScalarValue, Value};
 
 pub struct User(repositories::User);
 
 impl User {
     fn groups(&self, context: &Context) -> Result<Vec<Group>, GraphQLError> {
-        match repositories::GroupRepository::find_by_user(&self.0, &context.db_pool) {
+        match repositories::GroupRepository::find_by_user(&*context.db, &self.0) {
             Err(e) => Err(GraphQLError::InternalServerError(e)),
             Ok(v) => Ok(v.into_iter().map(Into::into).collect()),
         }
     }
+
+    fn deleted_groups(&self, context: &Context) -> Result<Vec<Group>, GraphQLError> {
+        repositories::GroupRepository::find_deleted_by_user(&*context.db, &self.0)
+            .map_err(GraphQLError::InternalServerError)
+            .map(|v| v.into_iter().map(Into::into).collect())
+    }
+}
+
+/// A user.
+#[juniper::object(Context = Context)]
+impl User {
+    fn email(&self) -> &str {
+        &self.0.email[..]
+    }
+
+    /// When the user verified their current email address, if they did.
+    fn email_verified_at(&self) -> Option<&chrono::DateTime<chrono::Utc>> {
+        self.0.email_verified_at.as_ref()
+    }
+
+    fn groups(&self, context: &Context) -> Result<Vec<Group>, GraphQLError> {
+        self.groups(context)
+    }
+
+    /// The groups in the trash, the latest deleted first, until they're purged.
+    fn deleted_groups(&self, context: &Context) -> Result<Vec<Group>, GraphQLError> {
+        self.deleted_groups(context)
+    }
+}
+
+impl From<repositories::User> for User {
+    fn from(row: repositories::User) -> Self {
+        User(row)
+    }
+}
+
+pub struct Account(repositories::User);
+
+/// A user as seen by the other members of their groups.
+#[juniper::object(Context = Context)]
+impl Account {
+    fn id(&self) -> String {
+        self.0.id.to_string()
+    }
+
+    fn email(&self) -> &str {
+        &self.0.email[..]
+    }
+}
+
+impl From<repositories::User> for Account {
+    fn from(row: repositories::User) -> Self {
+        Account(row)
+    }
+}
+
+/// A group shared by its members.
+pub struct Group(repositories::Group);
+
+impl Group {
+    fn expenses(
+        &self,
+        range: &expense::DateRange,
+        context: &Context,
+    ) -> Result<Vec<Expense>, GraphQLError> {
+        match repositories::ExpenseRepository::find_by_group(&*context.db, &self.0, range)
+            .map(|v| v.into_iter().map(Into::into).collect())
+        {
+            Err(e) => Err(GraphQLError::InternalServerError(e)),
+            Ok(v) => Ok(v),
+        }
+    }
+
+    fn persons(&self, context: &Context) -> Result<Vec<Person>, GraphQLError> {
+        match repositories::PersonRepository::find_by_group(&*context.db, &self.0)
+            .map(|v| v.into_iter().map(Into::into).collect())
+        {
+            Err(e) => Err(GraphQLError::InternalServerError(e)),
+            Ok(v) => Ok(v),
+        }
+    }
+
+    fn payments(&self, context: &Context) -> Result<Vec<Payment>, GraphQLError> {
+        match repositories::PaymentRepository::find_by_group(&*context.db, &self.0).map(|v| {
+            v.into_iter()
+                .map(|p| Payment(p, self.0.currency.clone()))
+                .collect()
+        }) {
+            Err(e) => Err(GraphQLError::InternalServerError(e)),
+            Ok(v) => Ok(v),
+        }
+    }
+
+    fn total(&self, range: &expense::DateRange, context: &Context) -> Result<Money, GraphQLError> {
+        let total = ExpenseService::total(&self.0, range, &*context.db)?;
+        Ok(Money::new(total, &self.0.currency))
+    }
+
+    fn activity(
+        &self,
+        first: i32,
+        after: Option<String>,
+        context: &Context,
+    ) -> Result<ActivityPage, GraphQLError> {
+        if !(1..=MAX_ACTIVITY_PAGE_SIZE).contains(&first) {
+            return Err(GraphQLError::InvalidPagination);
+        }
+        let after = after
+            .map(|c| c.parse::<i64>())
+            .transpose()
+            .map_err(|_| GraphQLError::InvalidPagination)?;
+        // One more entry tells whether there's a next page
+        let mut entries = repositories::ActivityRepository::find_by_group(
+            &*context.db,
+            &self.0,
+            after,
+            i64::from(first) + 1,
+        )
+        .map_err(GraphQLError::InternalServerError)?;
+        let has_next_page = entries.len() > first as usize;
+        entries.truncate(first as usize);
+
+        Ok(ActivityPage {
+            entries: entries.into_iter().map(Activity).collect(),
+            has_next_page,
+        })
+    }
+
+    fn balances(
+        &self,
+        range: &expense::DateRange,
+        context: &Context,
+    ) -> Result<Vec<Balance>, GraphQLError> {
+        Ok(ExpenseService::balances(&self.0, range, &*context.db)?
+            .into_iter()
+            .map(|(p, b)| Balance::new(p, &b, &self.0.currency))
+            .collect())
+    }
+
+    fn settlement_plan(&self, context: &Context) -> Result<Vec<Transfer>, GraphQLError> {
+        Ok(ExpenseService::settlement_plan(&self.0, &*context.db)?
+            .into_iter()
+            .map(|t| Transfer {
+                from: t.from.into(),
+                to: t.to.into(),
+                amount: Money::new(t.amount, &self.0.currency),
+            })
+            .collect())
+    }
+}
+
+#[juniper::object(Context = Context)]
+impl Group {
+    fn id(&self) -> String {
+        self.0.id.to_string()
+    }
+
+    fn name(&self) -> &str {
+        self.0.name.as_str()
+    }
+
+    /// The expenses which occurred between these dates, both included, the oldest first.
+    fn expenses(
+        &self,
+        context: &Context,
+        from: Option<chrono::NaiveDate>,
+        to: Option<chrono::NaiveDate>,
+    ) -> Result<Vec<Expense>, GraphQLError> {
+        self.expenses(&date_range(from, to)?, context)
+    }
+
+    /// The group's persons, the oldest first.
+    fn persons(&self, context: &Context) -> Result<Vec<Person>, GraphQLError> {
+        self.persons(context)
+    }
+
+    /// The users sharing this group, the oldest first.
+    fn members(&self, context: &Context) -> Result<Vec<GroupMember>, GraphQLError> {
+        repositories::GroupMemberRepository::find_by_group(&*context.db, &self.0)
+            .map_err(GraphQLError::InternalServerError)
+            .map(|v| v.into_iter().map(Into::into).collect())
+    }
+
+    /// The invitations which can still be accepted, the oldest first.
+    fn invitations(&self, context: &Context) -> Result<Vec<Invitation>, GraphQLError> {
+        repositories::InvitationRepository::find_pending_by_group(&*context.db, &self.0)
+            .map_err(GraphQLError::InternalServerError)
+            .map(|v| v.into_iter().map(Into::into).collect())
+    }
+
+    /// The built-in categories, then the group's custom ones.
+    fn categories(&self, context: &Context) -> Result<Vec<Category>, GraphQLError> {
+        repositories::CategoryRepository::find_by_group(&*context.db, &self.0)
+            .map_err(GraphQLError::InternalServerError)
+            .map(|v| v.into_iter().map(Into::into).collect())
+    }
+
+    /// The currency the balances and the total are computed in.
+    fn currency(&self) -> &str {
+        self.0.currency.as_str()
+    }
+
+    /// The sum of the expenses which occurred between these dates, converted into the group's currency.
+    fn total(
+        &self,
+        context: &Context,
+        from: Option<chrono::NaiveDate>,
+        to: Option<chrono::NaiveDate>,
+    ) -> Result<Money, GraphQLError> {
+        self.total(&date_range(from, to)?, context)
+    }
+
+    /// The amounts the persons paid back to each other.
+    fn payments(&self, context: &Context) -> Result<Vec<Payment>, GraphQLError> {
+        self.payments(context)
+    }
+
+    /// What each person paid, owes and their net amount, in the group's currency.
+    /// Only the expenses which occurred and the payments made between these dates are counted.
+    fn balances(
+        &self,
+        context: &Context,
+        from: Option<chrono::NaiveDate>,
+        to: Option<chrono::NaiveDate>,
+    ) -> Result<Vec<Balance>, GraphQLError> {
+        self.balances(&date_range(from, to)?, context)
+    }
+
+    /// The fewest transfers needed to bring every person's balance back to zero, whatever the dates.
+    fn settlement_plan(&self, context: &Context) -> Result<Vec<Transfer>, GraphQLError> {
+        self.settlement_plan(context)
+    }
+
+    /// The persons and expenses removed from the group, until they're purged.
+    fn trash(&self) -> Trash {
+        Trash(self.0.clone())
+    }
+
+    /// When the group was moved to the trash, if it was.
+    fn deleted_at(&self) -> Option<&chrono::DateTime<chrono::Utc>> {
+        self.0.deleted_at.as_ref()
+    }
+
+    /// The changes made to the group, the latest first.
+    /// The next page starts after the previous one's `endCursor`.
+    fn activity(
+        &self,
+        context: &Context,
+        first: Option<i32>,
+        after: Option<String>,
+    ) -> Result<ActivityPage, GraphQLError> {
+        self.activity(first.unwrap_or(ACTIVITY_PAGE_SIZE), after, context)
+    }
+
+    /// The totals of the expenses which occurred between these dates, in the group's currency.
+    fn statistics(
+        &self,
+        from: Option<chrono::NaiveDate>,
+        to: Option<chrono::NaiveDate>,
+    ) -> Result<Statistics, GraphQLError> {
+        Ok(Statistics {
+            group: self.0.clone(),
+            range: date_range(from, to)?,
+        })
+    }
+}
+
+impl From<repositories::Group> for Group {
+    fn from(row: repositories::Group) -> Self {
+        Group(row)
+    }
+}
+
+fn date_range(
+    from: Option<chrono::NaiveDate>,
+    to: Option<chrono::NaiveDate>,
+) -> Result<expense::DateRange, GraphQLError> {
+    expense::DateRange::new(from, to).ok_or(GraphQLError::InvalidDateRange)
+}
+
+pub struct GroupMember(repositories::GroupMember);
+
+/// A user sharing a group.
+#[juniper::object(Context = Context)]
+impl GroupMember {
+    fn user(&self, context: &Context) -> Result<Account, GraphQLError> {
+        match repositories::UserRepository::find_one(&*context.db, &self.0.user_id) {
+            Err(e) => Err(GraphQLError::InternalServerError(e)),
+            Ok(None) => Err(GraphQLError::UserNotFound),
+            Ok(Some(u)) => Ok(u.into()),
+        }
+    }
+
+    fn role(&self) -> Result<MemberRole, GraphQLError> {
+        self.0
+            .role
+            .parse::<member::Role>()
+            .map(Into::into)
+            .map_err(GraphQLError::InternalServerError)
+    }
+}
+
+impl From<repositories::GroupMember> for GroupMember {
+    fn from(row: repositories::GroupMember) -> Self {
+        GroupMember(row)
+    }
+}
+
+pub struct Invitation(repositories::Invitation);
+
+/// An invitation to join a group. Its token is only given when it's created.
+#[juniper::object(Context = Context)]
+impl Invitation {
+    fn id(&self) -> String {
+        self.0.id.to_string()
+    }
+
+    fn expires_at(&self) -> &chrono::DateTime<chrono::Utc> {
+        &self.0.expires_at
+    }
+
+    fn created_at(&self) -> &chrono::DateTime<chrono::Utc> {
+        &self.0.created_at
+    }
+}
+
+impl From<repositories::Invitation> for Invitation {
+    fn from(row: repositories::Invitation) -> Self {
+        Invitation(row)
+    }
+}
+
+pub struct Trash(repositories::Group);
+
+/// What was removed from a group and can be restored.
+#[juniper::object(Context = Context)]
+impl Trash {
+    /// The latest deleted first.
+    fn persons(&self, context: &Context) -> Result<Vec<Person>, GraphQLError> {
+        repositories::PersonRepository::find_deleted_by_group(&*context.db, &self.0)
+            .map_err(GraphQLError::InternalServerError)
+            .map(|v| v.into_iter().map(Into::into).collect())
+    }
+
+    /// The latest deleted first, including the ones deleted along with a person.
+    fn expenses(&self, context: &Context) -> Result<Vec<Expense>, GraphQLError> {
+        repositories::ExpenseRepository::find_deleted_by_group(&*context.db, &self.0)
+            .map_err(GraphQLError::InternalServerError)
+            .map(|v| v.into_iter().map(Into::into).collect())
+    }
+}
+
+/// The number of activity entries in a page, by default and at most.
+const ACTIVITY_PAGE_SIZE: i32 = 20;
+const MAX_ACTIVITY_PAGE_SIZE: i32 = 100;
+
+pub struct ActivityPage {
+    entries: Vec<Activity>,
+    has_next_page: bool,
+}
+
+/// A page of a group's activity.
+#[juniper::object(Context = Context)]
+impl ActivityPage {
+    fn entries(&self) -> &Vec<Activity> {
+        &self.entries
+    }
+
+    /// The cursor of the page's last entry.
+    fn end_cursor(&self) -> Option<String> {
+        self.entries.last().map(|a| a.0.id.to_string())
+    }
+
+    fn has_next_page(&self) -> bool {
+        self.has_next_page
+    }
+}
+
+pub struct Activity(repositories::Activity);
+
+/// A change made to a group by one of its members.
+#[juniper::object(Context = Context)]
+impl Activity {
+    fn id(&self) -> String {
+        self.0.id.to_string()
+    }
+
+    /// The member who made the change. Null once their account is gone.
+    fn actor(&self, context: &Context) -> Result<Option<Account>, GraphQLError> {
+        match self.0.user_id {
+            None => Ok(None),
+            Some(id) => repositories::UserRepository::find_one(&*context.db, &id)
+                .map_err(GraphQLError::InternalServerError)
+                .map(|o| o.map(Into::into)),
+        }
+    }
+
+    fn action(&self) -> Result<ActivityAction, GraphQLError> {
+        self.0
+            .action
+            .parse::<activity::Action>()
+            .map(Into::into)
+            .map_err(GraphQLError::InternalServerError)
+    }
+
+    fn entity_type(&self) -> Result<EntityType, GraphQLError> {
+        self.0
+            .entity_type
+            .parse::<activity::Entity>()
+            .map(Into::into)
+            .map_err(GraphQLError::InternalServerError)
+    }
+
+    /// The changed row's id. The expense's one for its payers and shares, the user's one for a member.
+    fn entity_id(&self) -> String {
+        self.0.entity_id.to_string()
+    }
+
+    /// The JSON snapshot of the row before the change. Null when it was created.
+    fn before(&self) -> Option<String> {
+        self.0.before.as_ref().map(|v| v.to_string())
+    }
+
+    /// The JSON snapshot of the row after the change. Null when it was deleted.
+    fn after(&self) -> Option<String> {
+        self.0.after.as_ref().map(|v| v.to_string())
+    }
+
+    fn created_at(&self) -> &chrono::DateTime<chrono::Utc> {
+        &self.0.created_at
+    }
+}
+
+pub struct Statistics {
+    group: repositories::Group,
+    range: expense::DateRange,
+}
+
+impl Statistics {
+    fn by_category(&self, context: &Context) -> Result<Vec<CategoryTotal>, GraphQLError> {
+        Ok(
+            ExpenseService::totals_by_category(&self.group, &self.range, &*context.db)?
+                .into_iter()
+                .map(|(category, total)| CategoryTotal {
+                    category: category.map(Into::into),
+                    total: Money::new(total, &self.group.currency),
+                })
+                .collect(),
+        )
+    }
+
+    fn by_person(&self, context: &Context) -> Result<Vec<PersonTotal>, GraphQLError> {
+        Ok(
+            ExpenseService::totals_by_person(&self.group, &self.range, &*context.db)?
+                .into_iter()
+                .map(|(person, total)| PersonTotal {
+                    person: person.into(),
+                    total: Money::new(total, &self.group.currency),
+                })
+                .collect(),
+        )
+    }
+
+    fn by_month(&self, context: &Context) -> Result<Vec<MonthTotal>, GraphQLError> {
+        Ok(
+            ExpenseService::totals_by_month(&self.group, &self.range, &*context.db)?
+                .into_iter()
+                .map(|(month, total)| MonthTotal {
+                    month,
+                    total: Money::new(total, &self.group.currency),
+                })
+                .collect(),
+        )
+    }
+}
+
+/// The totals of a group's expenses, computed by the database.
+#[juniper::object(Context = Context)]
+impl Statistics {
+    /// What was spent on each category, the largest first. A null category gathers the
+    /// uncategorized expenses.
+    fn by_category(&self, context: &Context) -> Result<Vec<CategoryTotal>, GraphQLError> {
+        self.by_category(context)
+    }
+
+    /// What each person paid, the largest first.
+    fn by_person(&self, context: &Context) -> Result<Vec<PersonTotal>, GraphQLError> {
+        self.by_person(context)
+    }
+
+    /// What was spent each month, the oldest first. Months without expenses are left out.
+    fn by_month(&self, context: &Context) -> Result<Vec<MonthTotal>, GraphQLError> {
+        self.by_month(context)
+    }
+}
+
+pub struct CategoryTotal {
+    category: Option<Category>,
+    total: Money,
+}
+
+#[juniper::object(Context = Context)]
+impl CategoryTotal {
+    fn category(&self) -> Option<&Category> {
+        self.category.as_ref()
+    }
+
+    fn total(&self) -> &Money {
+        &self.total
+    }
+}
+
+pub struct PersonTotal {
+    person: Person,
+    total: Money,
+}
+
+#[juniper::object(Context = Context)]
+impl PersonTotal {
+    fn person(&self) -> &Person {
+        &self.person
+    }
+
+    fn total(&self) -> &Money {
+        &self.total
+    }
+}
+
+pub struct MonthTotal {
+    /// The first day of the month.
+    month: chrono::NaiveDate,
+    total: Money,
+}
+
+#[juniper::object(Context = Context)]
+impl MonthTotal {
+    /// The first day of the month.
+    fn month(&self) -> &chrono::NaiveDate {
+        &self.month
+    }
+
+    fn total(&self) -> &Money {
+        &self.total
+    }
+}
+
+pub struct Expense(repositories::Expense);
+
+impl Expense {
+    fn split_mode(&self) -> Result<SplitMode, GraphQLError> {
+        self.0
+            .split_mode
+            .parse::<expense::SplitMode>()
+            .map(Into::into)
+            .map_err(GraphQLError::InternalServerError)
+    }
+
+    fn payers(&self, context: &Context) -> Result<Vec<ExpensePayer>, GraphQLError> {
+        match repositories::ExpensePayerRepository::find_by_expense(&*context.db, &self.0).map(
+            |v| {
+                v.into_iter()
+                    .map(|p| ExpensePayer(p, self.0.currency.clone()))
+                    .collect()
+            },
+        ) {
+            Err(e) => Err(GraphQLError::InternalServerError(e)),
+            Ok(v) => Ok(v),
+        }
+    }
+
+    fn shares(&self, context: &Context) -> Result<Vec<ExpenseShare>, GraphQLError> {
+        match repositories::ExpenseShareRepository::find_by_expense(&*context.db, &self.0)
+            .map(|v| v.into_iter().map(Into::into).collect())
+        {
+            Err(e) => Err(GraphQLError::InternalServerError(e)),
+            Ok(v) => Ok(v),
+        }
+    }
+
+    fn category(&self, context: &Context) -> Result<Option<Category>, GraphQLError> {
+        match self.0.category_id {
+            None => Ok(None),
+            Some(id) => repositories::CategoryRepository::find_one(&*context.db, &id)
+                .map_err(GraphQLError::InternalServerError)
+                .map(|o| o.map(Into::into)),
+        }
+    }
+}
+
+/// A unique group expense.
+#[juniper::object(Context = Context)]
+impl Expense {
+    fn id(&self) -> String {
+        self.0.id.to_string()
+    }
+
+    fn name(&self) -> &str {
+        self.0.name.as_str()
+    }
+
+    /// The payers' amounts and the exact shares are in the same currency.
+    fn amount(&self) -> Money {
+        Money::new(self.0.amount, &self.0.currency)
+    }
+
+    /// When the money was spent.
+    fn occurred_on(&self) -> &chrono::NaiveDate {
+        &self.0.occurred_on
+    }
+
+    /// The rate converting this expense into its group's currency, frozen when the expense was added
+    /// or its currency changed.
+    fn exchange_rate(&self) -> f64 {
+        self.0.exchange_rate
+    }
+
+    /// The persons who paid this expense and how much each of them paid.
+    fn payers(&self, context: &Context) -> Result<Vec<ExpensePayer>, GraphQLError> {
+        self.payers(context)
+    }
+
+    fn split_mode(&self) -> Result<SplitMode, GraphQLError> {
+        self.split_mode()
+    }
+
+    /// The persons covered by this expense. Empty when it covers the whole group.
+    fn shares(&self, context: &Context) -> Result<Vec<ExpenseShare>, GraphQLError> {
+        self.shares(context)
+    }
+
+    fn category(&self, context: &Context) -> Result<Option<Category>, GraphQLError> {
+        self.category(context)
+    }
+
+    /// When the expense was moved to the trash, if it was.
+    fn deleted_at(&self) -> Option<&chrono::DateTime<chrono::Utc>> {
+        self.0.deleted_at.as_ref()
+    }
+}
+
+impl From<repositories::Expense> for Expense {
+    fn from(row: repositories::Expense) -> Self {
+        Expense(row)
+    }
+}
+
+pub struct Category(repositories::Category);
+
+/// What an expense was spent on.
+#[juniper::object(Context = Context)]
+impl Category {
+    fn id(&self) -> String {
+        self.0.id.to_string()
+    }
+
+    fn name(&self) -> &str {
+        self.0.name.as_str()
+    }
+
+    /// Built-in categories are available to every group and can't be changed.
+    fn built_in(&self) -> bool {
+        self.0.is_built_in()
+    }
+}
+
+impl From<repositories::Category> for Category {
+    fn from(row: repositories::Category) -> Self {
+        Category(row)
+    }
+}
+
+pub struct ExchangeRate(repositories::ExchangeRate);
+
+/// The rate converting a currency into another one: one unit of `from` is worth `rate` units of `to`.
+#[juniper::object(Context = Context)]
+impl ExchangeRate {
+    fn from(&self) -> &str {
+        self.0.from_currency.as_str()
+    }
+
+    fn to(&self) -> &str {
+        self.0.to_currency.as_str()
+    }
+
+    fn rate(&self) -> f64 {
+        self.0.rate
+    }
+}
+
+impl From<repositories::ExchangeRate> for ExchangeRate {
+    fn from(row: repositories::ExchangeRate) -> Self {
+        ExchangeRate(row)
+    }
+}
+
+/// What a member is allowed to do in a group. Each role can do everything the next ones can.
+#[derive(juniper::GraphQLEnum, Clone, Copy)]
+pub enum MemberRole {
+    /// Can also rename and remove the group and manage its members.
+    Owner,
+    /// Can also add, change and remove the persons, expenses, payments and categories.
+    Editor,
+    /// Can only read the group.
+    Viewer,
+}
+
+impl From<MemberRole> for member::Role {
+    fn from(role: MemberRole) -> Self {
+        match role {
+            MemberRole::Owner => member::Role::Owner,
+            MemberRole::Editor => member::Role::Editor,
+            MemberRole::Viewer => member::Role::Viewer,
+        }
+    }
+}
+
+impl From<member::Role> for MemberRole {
+    fn from(role: member::Role) -> Self {
+        match role {
+            member::Role::Owner => MemberRole::Owner,
+            member::Role::Editor => MemberRole::Editor,
+            member::Role::Viewer => MemberRole::Viewer,
+        }
+    }
+}
+
+/// What was done to a row of a group.
+#[derive(juniper::GraphQLEnum, Clone, Copy)]
+pub enum ActivityAction {
+    Create,
+    Update,
+    /// Moved to the trash.
+    Delete,
+    /// Moved back from the trash.
+    Restore,
+}
+
+impl From<activity::Action> for ActivityAction {
+    fn from(action: activity::Action) -> Self {
+        match action {
+            activity::Action::Create => ActivityAction::Create,
+            activity::Action::Update => ActivityAction::Update,
+            activity::Action::Delete => ActivityAction::Delete,
+            activity::Action::Restore => ActivityAction::Restore,
+        }
+    }
+}
+
+/// The kind of row an activity is about.
+#[derive(juniper::GraphQLEnum, Clone, Copy)]
+pub enum EntityType {
+    Group,
+    Member,
+    Invitation,
+    Person,
+    Category,
+    Expense,
+    /// The persons who paid an expense, changed together.
+    ExpensePayers,
+    /// The persons covered by an expense, changed together.
+    ExpenseShares,
+    Payment,
+}
+
+impl From<activity::Entity> for EntityType {
+    fn from(entity: activity::Entity) -> Self {
+        match entity {
+            activity::Entity::Group => EntityType::Group,
+            activity::Entity::Member => EntityType::Member,
+            activity::Entity::Invitation => EntityType::Invitation,
+            activity::Entity::Person => EntityType::Person,
+            activity::Entity::Category => EntityType::Category,
+            activity::Entity::Expense => EntityType::Expense,
+            activity::Entity::ExpensePayers => EntityType::ExpensePayers,
+            activity::Entity::ExpenseShares => EntityType::ExpenseShares,
+            activity::Entity::Payment => EntityType::Payment,
+        }
+    }
+}
+
+/// How an expense is split between its participants.
+#[derive(juniper::GraphQLEnum, Clone, Copy)]
+pub enum SplitMode {
+    /// Every participant owes the same amount.
+    Equal,
+    /// Participants owe an amount proportional to their resources.
+    Resources,
+    /// Participants owe an amount proportional to their number of shares.
+    Shares,
+    /// Participants owe a percentage of the amount. The percentages must add up to 100.
+    Percentage,
+    /// Participants owe an exact amount. The amounts must add up to the expense's amount.
+    Exact,
+}
+
+impl From<SplitMode> for expense::SplitMode {
+    fn from(mode: SplitMode) -> Self {
+        match mode {
+            SplitMode::Equal => expense::SplitMode::Equal,
+            SplitMode::Resources => expense::SplitMode::Resources,
+            SplitMode::Shares => expense::SplitMode::Shares,
+            SplitMode::Percentage => expense::SplitMode::Percentage,
+            SplitMode::Exact => expense::SplitMode::Exact,
+        }
+    }
+}
+
+impl From<expense::SplitMode> for SplitMode {
+    fn from(mode: expense::SplitMode) -> Self {
+        match mode {
+            expense::SplitMode::Equal => SplitMode::Equal,
+            expense::SplitMode::Resources => SplitMode::Resources,
+            expense::SplitMode::Shares => SplitMode::Shares,
+            expense::SplitMode::Percentage => SplitMode::Percentage,
+            expense::SplitMode::Exact => SplitMode::Exact,
+        }
+    }
+}
+
+pub struct ExpensePayer(repositories::ExpensePayer, String);
+
+/// A person who paid part of an expense.
+#[juniper::object(Context = Context)]
+impl ExpensePayer {
+    fn person(&self, context: &Context) -> Result<Person, GraphQLError> {
+        match repositories::PersonRepository::find_one(&*context.db, &self.0.person_id) {
+            Err(e) => Err(GraphQLError::InternalServerError(e)),
+            Ok(None) => Err(GraphQLError::PersonNotFound),
+            Ok(Some(p)) => Ok(p.into()),
+        }
+    }
+
+    fn amount(&self) -> Money {
+        Money::new(self.0.amount, &self.1)
+    }
+}
+
+pub struct ExpenseShare(repositories::ExpenseShare);
+
+/// A person covered by an expense.
+#[juniper::object(Context = Context)]
+impl ExpenseShare {
+    fn person(&self, context: &Context) -> Result<Person, GraphQLError> {
+        match repositories::PersonRepository::find_one(&*context.db, &self.0.person_id) {
+            Err(e) => Err(GraphQLError::InternalServerError(e)),
+            Ok(None) => Err(GraphQLError::PersonNotFound),
+            Ok(Some(p)) => Ok(p.into()),
+        }
+    }
+
+    /// A number of shares, a percentage or an exact amount depending on the expense's split mode.
+    fn value(&self) -> BigInt {
+        BigInt(self.0.value)
+    }
+}
+
+impl From<repositories::ExpenseShare> for ExpenseShare {
+    fn from(row: repositories::ExpenseShare) -> Self {
+        ExpenseShare(row)
+    }
+}
+
+/// A payment and its group's currency.
+pub struct Payment(repositories::Payment, String);
+
+impl Payment {
+    fn person(&self, id: &uuid::Uuid, context: &Context) -> Result<Person, GraphQLError> {
+        match repositories::PersonRepository::find_one(&*context.db, id) {
+            Err(e) => Err(GraphQLError::InternalServerError(e)),
+            Ok(None) => Err(GraphQLError::PersonNotFound),
+            Ok(Some(p)) => Ok(p.into()),
+        }
+    }
+}
+
+/// An amount a person paid back to another one.
+#[juniper::object(Context = Context)]
+impl Payment {
+    fn id(&self) -> String {
+        self.0.id.to_string()
+    }
+
+    fn from(&self, context: &Context) -> Result<Person, GraphQLError> {
+        self.person(&self.0.from_person_id, context)
+    }
+
+    fn to(&self, context: &Context) -> Result<Person, GraphQLError> {
+        self.person(&self.0.to_person_id, context)
+    }
+
+    fn amount(&self) -> Money {
+        Money::new(self.0.amount, &self.1)
+    }
+}
+
+pub struct Person(repositories::Person);
+
+impl Person {
+    fn expenses(&self, context: &Context) -> Result<Vec<Expense>, GraphQLError> {
+        match repositories::ExpenseRepository::find_by_person(&*context.db, &self.0)
+            .map(|v| v.into_iter().map(Into::into).collect())
+        {
+            Err(e) => Err(GraphQLError::InternalServerError(e)),
+            Ok(v) => Ok(v),
+        }
+    }
+
+    fn resources(&self, context: &Context) -> Result<Money, GraphQLError> {
+        // The persons of the groups in the trash can still be read
+        match repositories::GroupRepository::find_one_including_deleted(
+            &*context.db,
+            &self.0.group_id,
+        ) {
+            Err(e) => Err(GraphQLError::InternalServerError(e)),
+            Ok(None) => Err(GraphQLError::GroupNotFound),
+            Ok(Some(g)) => Ok(Money::new(self.0.resources, &g.currency)),
+        }
+    }
+
+    fn account(&self, context: &Context) -> Result<Option<Account>, GraphQLError> {
+        match self.0.user_id {
+            None => Ok(None),
+            Some(id) => repositories::UserRepository::find_one(&*context.db, &id)
+                .map_err(GraphQLError::InternalServerError)
+                .map(|o| o.map(Into::into)),
+        }
+    }
+}
+
+/// A unique group person.
+#[juniper::object(Context = Context)]
+impl Person {
+    fn id(&self) -> String {
+        self.0.id.to_string()
+    }
+
+    fn name(&self) -> &str {
+        self.0.name.as_str()
+    }
+
+    /// In the group's currency.
+    fn resources(&self, context: &Context) -> Result<Money, GraphQLError> {
+        self.resources(context)
+    }
+
+    fn expenses(&self, context: &Context) -> Result<Vec<Expense>, GraphQLError> {
+        self.expenses(context)
+    }
+
+    /// The user this person is, when they joined the group.
+    fn account(&self, context: &Context) -> Result<Option<Account>, GraphQLError> {
+        self.account(context)
+    }
+
+    /// When the person was moved to the trash, if they were.
+    fn deleted_at(&self) -> Option<&chrono::DateTime<chrono::Utc>> {
+        self.0.deleted_at.as_ref()
+    }
+}
+
+impl From<repositories::Person> for Person {
+    fn from(row: repositories::Person) -> Self {
+        Person(row)
+    }
+}
+
+pub struct Balance {
+    person: Person,
+    paid: Money,
+    owed: Money,
+    sent: Money,
+    received: Money,
+    net: Money,
+}
+
+impl Balance {
+    fn new(person: repositories::Person, balance: &balance::Balance, currency: &str) -> Self {
+        Balance {
+            paid: Money::new(balance.paid, currency),
+            owed: Money::new(balance.owed, currency),
+            sent: Money::new(balance.sent, currency),
+            received: Money::new(balance.received, currency),
+            net: Money::new(balance.net(), currency),
+            person: person.into(),
+        }
+    }
+}
+
+/// The balance of a person within their group.
+#[juniper::object(Context = Context)]
+impl Balance {
+    fn person(&self) -> &Person {
+        &self.person
+    }
+
+    /// The total amount of the expenses this person paid.
+    fn paid(&self) -> &Money {
+        &self.paid
+    }
+
+    /// This person's share of the group's expenses.
+    fn owed(&self) -> &Money {
+        &self.owed
+    }
+
+    /// The total amount this person paid back to the others.
+    fn sent(&self) -> &Money {
+        &self.sent
+    }
+
+    /// The total amount the others paid back to this person.
+    fn received(&self) -> &Money {
+        &self.received
+    }
+
+    /// Positive when the group owes this person money, negative when they are in debt.
+    fn net(&self) -> &Money {
+        &self.net
+    }
+}
+
+pub struct Transfer {
+    from: Person,
+    to: Person,
+    amount: Money,
+}
+
+/// An amount a person has to pay to another one to settle their group.
+#[juniper::object(Context = Context)]
+impl Transfer {
+    fn from(&self) -> &Person {
+        &self.from
+    }
+
+    fn to(&self) -> &Person {
+        &self.to
+    }
+
+    fn amount(&self) -> &Money {
+        &self.amount
+    }
+}
+
+/// A 64-bit integer. It is serialized as a string since JSON parsers can't safely read all of them as numbers,
+/// but integers are accepted as inputs too.
+pub struct BigInt(pub i64);
+
+juniper::graphql_scalar!(BigInt where Scalar = <S> {
+    description: "A 64-bit integer, serialized as a string."
+
+    resolve(&self) -> Value {
+        Value::scalar(self.0.to_string())
+    }
+
+    from_input_value(v: &InputValue) -> Option<BigInt> {
+        v.as_scalar_value::<i32>()
+            .map(|i| i64::from(*i))
+            .or_else(|| v.as_scalar_value::<String>().and_then(|s| s.parse().ok()))
+            .map(BigInt)
+    }
+
+    from_str<'a>(value: ScalarToken<'a>) -> ParseScalarResult<'a, S> {
+        match value {
+            ScalarToken::Int(_) => <i32 as ParseScalarValue<S>>::from_str(value),
+            _ => <String as ParseScalarValue<S>>::from_str(value),
+        }
+    }
+});
+
+pub struct Money {
+    amount: i64,
+    currency: String,
+}
+
+impl Money {
+    pub fn new(amount: i64, currency: &str) -> Self {
+        Money {
+            amount,
+            currency: currency.to_string(),
+        }
+    }
+}
+
+/// An amount of money.
+#[juniper::object(Context = Context)]
+impl Money {
+    /// In the currency's minor unit, e.g. cents for euros.
+    fn amount(&self) -> BigInt {
+        BigInt(self.amount)
+    }
+
+    fn currency(&self) -> &str {
+        self.currency.as_str()
+    }
+
+    /// The amount in the currency's major unit followed by the currency, e.g. `12.34 EUR`.
+    fn formatted(&self) -> String {
+        currency::format(self.amount, &self.currency)
+    }
+}
+
+pub struct AuthPayload(services::Tokens);
+
+/// The tokens of a session.
+#[juniper::object(Context = Context)]
+impl AuthPayload {
+    /// Sent as a bearer token with every request, until it expires.
+    fn access_token(&self) -> &str {
+        &self.0.access_token
+    }
+
+    /// Exchanged for new tokens with `refreshToken` once the access token expired.
+    fn refresh_token(&self) -> &str {
+        &self.0.refresh_token
+    }
 }
 
-/// A user.
+impl From<services::Tokens> for AuthPayload {
+    fn from(tokens: services::Tokens) -> Self {
+        AuthPayload(tokens)
+    }
+}
+
+pub struct AddGroupPayload {
+    group: Group,
+}
+
+impl AddGroupPayload {
+    pub fn new(group: repositories::Group) -> Self {
+        AddGroupPayload {
+            group: group.into(),
+        }
+    }
+}
+
+/// The group added by `addGroupV2`.
 #[juniper::object(Context = Context)]
-impl User {
-    fn email(&self) -> &str {
-        &self.0.email[..]
+impl AddGroupPayload {
+    fn group(&self) -> &Group {
+        &self.group
     }
+}
 
-    fn groups(&self, context: &Context) -> Result<Vec<Group>, GraphQLError> {
-        self.groups(context)
+pub struct UpdateGroupPayload {
+    group: Group,
+}
+
+impl UpdateGroupPayload {
+    pub fn new(group: repositories::Group) -> Self {
+        UpdateGroupPayload {
+            group: group.into(),
+        }
     }
 }
 
-impl From<repositories::User> for User {
-    fn from(row: repositories::User) -> Self {
-        User(row)
+/// The group changed by `updateGroupV2`.
+#[juniper::object(Context = Context)]
+impl UpdateGroupPayload {
+    fn group(&self) -> &Group {
+        &self.group
     }
 }
 
-/// A user's group.
-pub struct Group(repositories::Group);
+pub struct AddPersonPayload {
+    person: Person,
+    group: Group,
+}
 
-impl Group {
-    fn expenses(&self, context: &Context) -> Result<Vec<Expense>, GraphQLError> {
-        match repositories::ExpenseRepository::find_by_group(&self.0, &context.db_pool)
-            .map(|v| v.into_iter().map(Into::into).collect())
-        {
-            Err(e) => Err(GraphQLError::InternalServerError(e)),
-            Ok(v) => Ok(v),
+impl AddPersonPayload {
+    pub fn new(person: repositories::Person, group: repositories::Group) -> Self {
+        AddPersonPayload {
+            person: person.into(),
+            group: group.into(),
         }
     }
+}
 
-    fn persons(&self, context: &Context) -> Result<Vec<Person>, GraphQLError> {
-        match repositories::PersonRepository::find_by_group(&self.0, &context.db_pool)
-            .map(|v| v.into_iter().map(Into::into).collect())
-        {
-            Err(e) => Err(GraphQLError::InternalServerError(e)),
-            Ok(v) => Ok(v),
+/// The person added by `addPersonV2`, and their group.
+#[juniper::object(Context = Context)]
+impl AddPersonPayload {
+    fn person(&self) -> &Person {
+        &self.person
+    }
+
+    fn group(&self) -> &Group {
+        &self.group
+    }
+}
+
+pub struct UpdatePersonPayload {
+    person: Person,
+    group: Group,
+}
+
+impl UpdatePersonPayload {
+    pub fn new(person: repositories::Person, group: repositories::Group) -> Self {
+        UpdatePersonPayload {
+            person: person.into(),
+            group: group.into(),
         }
     }
 }
 
+/// The person changed by `updatePersonV2`, and their group.
 #[juniper::object(Context = Context)]
-impl Group {
-    fn id(&self) -> String {
-        self.0.id.to_string()
+impl UpdatePersonPayload {
+    fn person(&self) -> &Person {
+        &self.person
     }
 
-    fn name(&self) -> &str {
-        self.0.name.as_str()
+    /// With the balances the new resources lead to.
+    fn group(&self) -> &Group {
+        &self.group
     }
+}
 
-    fn expenses(&self, context: &Context) -> Result<Vec<Expense>, GraphQLError> {
-        self.expenses(context)
+pub struct AddExpensePayload {
+    expense: Expense,
+    group: Group,
+}
+
+impl AddExpensePayload {
+    pub fn new(expense: repositories::Expense, group: repositories::Group) -> Self {
+        AddExpensePayload {
+            expense: expense.into(),
+            group: group.into(),
+        }
     }
+}
 
-    fn persons(&self, context: &Context) -> Result<Vec<Person>, GraphQLError> {
-        self.persons(context)
+/// The expense added by `addExpenseV2`, and its group.
+#[juniper::object(Context = Context)]
+impl AddExpensePayload {
+    fn expense(&self) -> &Expense {
+        &self.expense
+    }
+
+    /// With the balances the expense leads to.
+    fn group(&self) -> &Group {
+        &self.group
     }
 }
 
-impl From<repositories::Group> for Group {
-    fn from(row: repositories::Group) -> Self {
-        Group(row)
+pub struct UpdateExpensePayload {
+    expense: Expense,
+    group: Group,
+}
+
+impl UpdateExpensePayload {
+    pub fn new(expense: repositories::Expense, group: repositories::Group) -> Self {
+        UpdateExpensePayload {
+            expense: expense.into(),
+            group: group.into(),
+        }
     }
 }
 
-pub struct Expense(repositories::Expense);
+/// The expense changed by `updateExpenseV2`, and its group.
+#[juniper::object(Context = Context)]
+impl UpdateExpensePayload {
+    fn expense(&self) -> &Expense {
+        &self.expense
+    }
 
-/// A unique group expense.
+    /// With the balances the change leads to.
+    fn group(&self) -> &Group {
+        &self.group
+    }
+}
+
+pub struct AddPaymentPayload {
+    payment: Payment,
+    group: Group,
+}
+
+impl AddPaymentPayload {
+    pub fn new(payment: repositories::Payment, group: repositories::Group) -> Self {
+        AddPaymentPayload {
+            payment: Payment(payment, group.currency.clone()),
+            group: group.into(),
+        }
+    }
+}
+
+/// The payment added by `addPaymentV2`, and its group.
 #[juniper::object(Context = Context)]
-impl Expense {
-    fn id(&self) -> String {
-        self.0.id.to_string()
+impl AddPaymentPayload {
+    fn payment(&self) -> &Payment {
+        &self.payment
     }
 
-    fn name(&self) -> &str {
-        self.0.name.as_str()
+    /// With the balances the payment settles.
+    fn group(&self) -> &Group {
+        &self.group
     }
+}
+
+pub struct RemoveGroupPayload {
+    deleted_id: uuid::Uuid,
+}
 
-    fn amount(&self) -> &i32 {
-        &self.0.amount
+impl RemoveGroupPayload {
+    pub fn new(deleted_id: uuid::Uuid) -> Self {
+        RemoveGroupPayload { deleted_id }
     }
 }
 
-impl From<repositories::Expense> for Expense {
-    fn from(row: repositories::Expense) -> Self {
-        Expense(row)
+/// The group moved to the trash by `removeGroupV2`.
+#[juniper::object(Context = Context)]
+impl RemoveGroupPayload {
+    fn deleted_id(&self) -> String {
+        self.deleted_id.to_string()
     }
 }
 
-pub struct Person(repositories::Person);
+pub struct RemovePersonPayload {
+    deleted_id: uuid::Uuid,
+}
 
-impl Person {
-    fn expenses(&self, context: &Context) -> Result<Vec<Expense>, GraphQLError> {
-        match repositories::ExpenseRepository::find_by_person(&self.0, &context.db_pool)
-            .map(|v| v.into_iter().map(Into::into).collect())
-        {
-            Err(e) => Err(GraphQLError::InternalServerError(e)),
-            Ok(v) => Ok(v),
+impl RemovePersonPayload {
+    pub fn new(deleted_id: uuid::Uuid) -> Self {
+        RemovePersonPayload { deleted_id }
+    }
+}
+
+/// The person moved to the trash by `removePersonV2`.
+#[juniper::object(Context = Context)]
+impl RemovePersonPayload {
+    fn deleted_id(&self) -> String {
+        self.deleted_id.to_string()
+    }
+}
+
+pub struct RemoveExpensePayload {
+    deleted_id: uuid::Uuid,
+}
+
+impl RemoveExpensePayload {
+    pub fn new(deleted_id: uuid::Uuid) -> Self {
+        RemoveExpensePayload { deleted_id }
+    }
+}
+
+/// The expense moved to the trash by `removeExpenseV2`.
+#[juniper::object(Context = Context)]
+impl RemoveExpensePayload {
+    fn deleted_id(&self) -> String {
+        self.deleted_id.to_string()
+    }
+}
+
+pub struct RemovePaymentPayload {
+    deleted_id: uuid::Uuid,
+}
+
+impl RemovePaymentPayload {
+    pub fn new(deleted_id: uuid::Uuid) -> Self {
+        RemovePaymentPayload { deleted_id }
+    }
+}
+
+/// The payment removed by `removePaymentV2`.
+#[juniper::object(Context = Context)]
+impl RemovePaymentPayload {
+    fn deleted_id(&self) -> String {
+        self.deleted_id.to_string()
+    }
+}
+
+pub struct RestoreGroupPayload {
+    group: Group,
+}
+
+impl RestoreGroupPayload {
+    pub fn new(group: repositories::Group) -> Self {
+        RestoreGroupPayload {
+            group: group.into(),
         }
     }
 }
 
-/// A unique group person.
+/// The group moved back from the trash by `restoreGroupV2`.
 #[juniper::object(Context = Context)]
-impl Person {
-    fn id(&self) -> String {
-        self.0.id.to_string()
+impl RestoreGroupPayload {
+    fn group(&self) -> &Group {
+        &self.group
     }
+}
 
-    fn name(&self) -> &str {
-        self.0.name.as_str()
+pub struct RestorePersonPayload {
+    person: Person,
+    group: Group,
+}
+
+impl RestorePersonPayload {
+    pub fn new(person: repositories::Person, group: repositories::Group) -> Self {
+        RestorePersonPayload {
+            person: person.into(),
+            group: group.into(),
+        }
     }
+}
 
-    fn resources(&self) -> &i32 {
-        &self.0.resources
+/// The person moved back from the trash by `restorePersonV2`, and their group.
+#[juniper::object(Context = Context)]
+impl RestorePersonPayload {
+    fn person(&self) -> &Person {
+        &self.person
     }
 
-    fn expenses(&self, context: &Context) -> Result<Vec<Expense>, GraphQLError> {
-        self.expenses(context)
+    /// With the expenses restored along with the person.
+    fn group(&self) -> &Group {
+        &self.group
     }
 }
 
-impl From<repositories::Person> for Person {
-    fn from(row: repositories::Person) -> Self {
-        Person(row)
+pub struct RestoreExpensePayload {
+    expense: Expense,
+    group: Group,
+}
+
+impl RestoreExpensePayload {
+    pub fn new(expense: repositories::Expense, group: repositories::Group) -> Self {
+        RestoreExpensePayload {
+            expense: expense.into(),
+            group: group.into(),
+        }
+    }
+}
+
+/// The expense moved back from the trash by `restoreExpenseV2`, and its group.
+#[juniper::object(Context = Context)]
+impl RestoreExpensePayload {
+    fn expense(&self) -> &Expense {
+        &self.expense
+    }
+
+    /// With the balances the expense leads to again.
+    fn group(&self) -> &Group {
+        &self.group
+    }
+}
+
+pub struct AddCategoryPayload {
+    category: Category,
+}
+
+impl AddCategoryPayload {
+    pub fn new(category: repositories::Category) -> Self {
+        AddCategoryPayload {
+            category: category.into(),
+        }
+    }
+}
+
+/// The category added by `addCategoryV2`.
+#[juniper::object(Context = Context)]
+impl AddCategoryPayload {
+    fn category(&self) -> &Category {
+        &self.category
+    }
+}
+
+pub struct UpdateCategoryPayload {
+    category: Category,
+}
+
+impl UpdateCategoryPayload {
+    pub fn new(category: repositories::Category) -> Self {
+        UpdateCategoryPayload {
+            category: category.into(),
+        }
+    }
+}
+
+/// The category changed by `updateCategoryV2`.
+#[juniper::object(Context = Context)]
+impl UpdateCategoryPayload {
+    fn category(&self) -> &Category {
+        &self.category
+    }
+}
+
+pub struct RemoveCategoryPayload {
+    deleted_id: uuid::Uuid,
+}
+
+impl RemoveCategoryPayload {
+    pub fn new(deleted_id: uuid::Uuid) -> Self {
+        RemoveCategoryPayload { deleted_id }
+    }
+}
+
+/// The category removed by `removeCategoryV2`.
+#[juniper::object(Context = Context)]
+impl RemoveCategoryPayload {
+    fn deleted_id(&self) -> String {
+        self.deleted_id.to_string()
     }
 }
 
@@ -149,6 +1521,8 @@ pub struct SignupInput {
 #[derive(juniper::GraphQLInputObject)]
 pub struct AddGroupInput {
     pub name: String,
+    /// The currency the balances are computed in, `EUR` by default. It can't be changed afterwards.
+    pub currency: Option<String>,
 }
 
 #[derive(juniper::GraphQLInputObject)]
@@ -157,33 +1531,142 @@ pub struct UpdateGroupInput {
     pub name: Option<String>,
 }
 
+#[derive(juniper::GraphQLInputObject)]
+pub struct MoneyInput {
+    /// In the currency's minor unit, e.g. cents for euros.
+    pub amount: BigInt,
+    /// Defaults to the group's currency.
+    pub currency: Option<String>,
+}
+
+impl From<MoneyInput> for services::Money {
+    fn from(money: MoneyInput) -> Self {
+        services::Money {
+            amount: money.amount.0,
+            currency: money.currency,
+        }
+    }
+}
+
 #[derive(juniper::GraphQLInputObject)]
 pub struct AddPersonInput {
     pub group_id: String,
     pub name: String,
-    pub resources: i32,
+    /// In the group's currency.
+    pub resources: MoneyInput,
 }
 
 #[derive(juniper::GraphQLInputObject)]
 pub struct AddExpenseInput {
     pub group_id: String,
-    pub person_id: String,
+    /// The person who paid the whole amount. Use `payers` instead when there are several.
+    pub person_id: Option<String>,
+    /// The persons who paid this expense. Their amounts must add up to the expense's amount.
+    pub payers: Option<Vec<ExpensePayerInput>>,
     pub name: String,
-    pub amount: i32,
+    /// There must be an exchange rate from its currency into the group's one.
+    pub amount: MoneyInput,
+    /// Defaults to the persons' resources.
+    pub split_mode: Option<SplitMode>,
+    /// The persons covered by this expense. Defaults to the whole group.
+    pub shares: Option<Vec<ExpenseShareInput>>,
+    /// When the money was spent. Defaults to today.
+    pub occurred_on: Option<chrono::NaiveDate>,
+    /// A built-in category or one of the group's.
+    pub category_id: Option<String>,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct ExpensePayerInput {
+    pub person_id: String,
+    /// In the minor unit of the expense's currency.
+    pub amount: BigInt,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct ExpenseShareInput {
+    pub person_id: String,
+    /// A number of shares, a percentage or an exact amount depending on the expense's split mode.
+    pub value: Option<BigInt>,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct AddPaymentInput {
+    pub group_id: String,
+    pub from_person_id: String,
+    pub to_person_id: String,
+    /// In the group's currency.
+    pub amount: MoneyInput,
 }
 
 #[derive(juniper::GraphQLInputObject)]
 pub struct UpdatePersonInput {
     pub person_id: String,
     pub name: Option<String>,
-    pub resources: Option<i32>,
+    /// In the group's currency.
+    pub resources: Option<MoneyInput>,
 }
 
 #[derive(juniper::GraphQLInputObject)]
 pub struct UpdateExpenseInput {
     pub expense_id: String,
     pub name: Option<String>,
-    pub amount: Option<i32>,
+    /// Defaults to the expense's currency. A new currency is converted with the current exchange rate.
+    pub amount: Option<MoneyInput>,
+    /// Replaces all the persons who paid this expense.
+    /// Required when changing the amount of an expense with several payers.
+    pub payers: Option<Vec<ExpensePayerInput>>,
+    pub split_mode: Option<SplitMode>,
+    /// Replaces all the persons covered by this expense. An empty list means the whole group.
+    pub shares: Option<Vec<ExpenseShareInput>>,
+    pub occurred_on: Option<chrono::NaiveDate>,
+    pub category_id: Option<String>,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct AddCategoryInput {
+    pub group_id: String,
+    pub name: String,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct UpdateCategoryInput {
+    pub category_id: String,
+    pub name: Option<String>,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct CreateInvitationInput {
+    pub group_id: String,
+    /// In seconds, from a minute to 30 days.
+    pub expires_in: i32,
+    /// The role given to whoever accepts the invitation. Defaults to editor.
+    pub role: Option<MemberRole>,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct AcceptInvitationInput {
+    pub token: String,
+    /// A person of the group who isn't linked to a user yet, to become them.
+    pub person_id: Option<String>,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct RevokeInvitationInput {
+    pub invitation_id: String,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct ChangeMemberRoleInput {
+    pub group_id: String,
+    pub user_id: String,
+    pub role: MemberRole,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct RemoveMemberInput {
+    pub group_id: String,
+    pub user_id: String,
 }
 
 #[derive(juniper::GraphQLInputObject)]
@@ -191,6 +1674,21 @@ pub struct RemoveGroupInput {
     pub group_id: String,
 }
 
+#[derive(juniper::GraphQLInputObject)]
+pub struct RestoreGroupInput {
+    pub group_id: String,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct RestorePersonInput {
+    pub person_id: String,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct RestoreExpenseInput {
+    pub expense_id: String,
+}
+
 #[derive(juniper::GraphQLInputObject)]
 pub struct RemovePersonInput {
     pub person_id: String,
@@ -200,3 +1698,26 @@ pub struct RemovePersonInput {
 pub struct RemoveExpenseInput {
     pub expense_id: String,
 }
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct RemovePaymentInput {
+    pub payment_id: String,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct RemoveCategoryInput {
+    pub category_id: String,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct ExchangeRateInput {
+    pub from: String,
+    pub to: String,
+    pub rate: f64,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+pub struct RemoveExchangeRateInput {
+    pub from: String,
+    pub to: String,
+}
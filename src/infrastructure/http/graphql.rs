@@ -1,35 +1,63 @@
-use crate::infrastructure::{config, graphql, repositories, security};
-use actix_web::{dev, error, web, Error, FromRequest, HttpRequest, HttpResponse, Result};
+use crate::{
+    domain::services::{AuthError, AuthService},
+    infrastructure::{config, graphql, jobs, mail, repositories, security},
+};
+use actix_web::{
+    dev, error, error::BlockingError, web, Error, FromRequest, HttpRequest, HttpResponse, Result,
+};
 use futures_util::future::{FutureExt, LocalBoxFuture};
 use graphql_parser::query;
 use juniper::{http, DefaultScalarValue, InputValue, ScalarValue};
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
 pub async fn handler(
-    db_pool: web::Data<repositories::PostgresPool>,
+    db: web::Data<Arc<dyn repositories::Store + Send + Sync>>,
+    mailer: web::Data<Arc<dyn mail::Mailer + Send + Sync>>,
+    worker: web::Data<jobs::Worker>,
     schema: web::Data<graphql::Schema>,
     req: GraphQLAuthentication,
 ) -> Result<HttpResponse> {
     let config = req.config();
     let viewer = req.viewer();
+    let authenticated = req.is_authenticated();
     let ctx = graphql::Context {
-        db_pool: db_pool.get_ref().to_owned(),
+        db: db.get_ref().clone(),
         config,
+        mailer: mailer.get_ref().clone(),
+        worker: worker.get_ref().clone(),
         viewer,
     };
 
     let res = web::block(move || {
+        // The token's session may have been revoked before it expired
+        if authenticated {
+            AuthService::check_session(&ctx.viewer, &*ctx.db).map_err(HandlerError::Session)?;
+        }
         let res = req.graphql().execute(&schema, &ctx);
-        Ok::<_, serde_json::error::Error>(serde_json::to_string(&res)?)
+        serde_json::to_string(&res).map_err(HandlerError::Json)
     })
     .await
-    .map_err(Error::from)?;
+    .map_err(|e| match e {
+        BlockingError::Error(HandlerError::Session(AuthError::InvalidToken)) => {
+            error::ErrorUnauthorized("Unauthorized")
+        }
+        BlockingError::Error(HandlerError::Json(e)) => error::ErrorInternalServerError(e),
+        e => error::ErrorInternalServerError(format!("{:?}", e)),
+    })?;
 
     Ok(HttpResponse::Ok()
         .content_type("application/json")
         .body(res))
 }
 
+/// What can go wrong while a request is handled off the async runtime.
+#[derive(Debug)]
+enum HandlerError {
+    Session(AuthError),
+    Json(serde_json::Error),
+}
+
 pub async fn graphiql(config: web::Data<config::Settings>) -> HttpResponse {
     let html = http::graphiql::graphiql_source(&format!("{}/graphql", config.base_url()));
     HttpResponse::Ok()
@@ -40,7 +68,8 @@ pub async fn graphiql(config: web::Data<config::Settings>) -> HttpResponse {
 pub struct GraphQLAuthentication {
     gql: http::GraphQLRequest,
     config: config::Settings,
-    viewer: security::Viewer,
+    /// None for the operations which don't need a token.
+    viewer: Option<security::Viewer>,
 }
 
 impl GraphQLAuthentication {
@@ -62,18 +91,18 @@ impl GraphQLAuthentication {
                     return Ok(GraphQLAuthentication {
                         gql,
                         config,
-                        viewer: security::Viewer::default(),
+                        viewer: None,
                     });
                 }
 
-                extract_and_check_token(&http)
+                extract_token(&http)
                     .map(|viewer| {
                         log::debug!("GraphQL request - user authorized for {}", op);
 
                         GraphQLAuthentication {
                             gql,
                             config,
-                            viewer,
+                            viewer: Some(viewer),
                         }
                     })
                     .map_err(|e| {
@@ -92,7 +121,12 @@ impl GraphQLAuthentication {
     }
 
     pub fn viewer(&self) -> security::Viewer {
-        self.viewer.clone()
+        self.viewer.clone().unwrap_or_default()
+    }
+
+    /// Whether the request came with an access token, whose session has to be checked.
+    pub fn is_authenticated(&self) -> bool {
+        self.viewer.is_some()
     }
 }
 
@@ -109,7 +143,17 @@ impl FromRequest for GraphQLAuthentication {
     }
 }
 
-const GRAPHQL_OPERATIONS_AUTH_EXCEPTION: [&str; 3] = ["signup", "login", "__schema"];
+const GRAPHQL_OPERATIONS_AUTH_EXCEPTION: [&str; 9] = [
+    "signup",
+    "signupV2",
+    "login",
+    "loginV2",
+    "refreshToken",
+    "requestPasswordReset",
+    "resetPassword",
+    "verifyEmail",
+    "__schema",
+];
 
 fn extract_graphql_operation<'a>(
     ast: query::Document<'a, &'a str>,
@@ -147,7 +191,8 @@ fn extract_graphql_operation<'a>(
         .collect::<String>()
 }
 
-fn extract_and_check_token(req: &HttpRequest) -> Result<security::Viewer> {
+/// Check the access token's signature, leaving its session to the handler since it needs the database.
+fn extract_token(req: &HttpRequest) -> Result<security::Viewer> {
     let secret_key = req
         .app_data::<web::Data<config::Settings>>()
         .expect("Couldn't extract settings")
@@ -1,21 +1,27 @@
 mod graphql;
 mod ops;
 
-use crate::infrastructure::{config, graphql as gql, repositories};
+use crate::infrastructure::{config, graphql as gql, jobs, mail, repositories};
 use actix_web::{dev::Server, http, middleware, web, App, HttpServer};
+use std::sync::Arc;
 
 pub fn run(
     listener: std::net::TcpListener,
     config: config::Settings,
-    db_pool: repositories::PostgresPool,
+    db: Arc<dyn repositories::Store + Send + Sync>,
+    mailer: Arc<dyn mail::Mailer + Send + Sync>,
 ) -> std::result::Result<Server, std::io::Error> {
     let config = web::Data::new(config);
-    let db_pool = web::Data::new(db_pool);
+    let db = web::Data::new(db);
+    let mailer = web::Data::new(mailer);
+    let worker = web::Data::new(jobs::Worker::spawn());
     let schema = web::Data::new(gql::create_schema());
 
     let server = HttpServer::new(move || {
         App::new()
-            .app_data(db_pool.clone())
+            .app_data(db.clone())
+            .app_data(mailer.clone())
+            .app_data(worker.clone())
             .app_data(config.clone())
             .app_data(schema.clone())
             .wrap(middleware::Compress::default())
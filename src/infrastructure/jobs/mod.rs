@@ -0,0 +1,104 @@
+use crate::infrastructure::{config, repositories};
+
+/// How long the trash purge waits between two runs.
+const PURGE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(3600);
+
+/// How many jobs can wait for the worker. The next ones are dropped.
+const WORKER_CAPACITY: usize = 100;
+
+type Job = Box<dyn FnOnce() -> anyhow::Result<()> + Send>;
+
+/// Run the jobs which shouldn't delay the answers, one at a time on a thread of its own.
+#[derive(Clone)]
+pub struct Worker {
+    sender: std::sync::mpsc::SyncSender<Job>,
+}
+
+impl Worker {
+    pub fn spawn() -> Self {
+        let (sender, receiver) = std::sync::mpsc::sync_channel::<Job>(WORKER_CAPACITY);
+        std::thread::spawn(move || {
+            for job in receiver {
+                if let Err(e) = job() {
+                    log::error!("{:?}", e);
+                }
+            }
+        });
+        Worker { sender }
+    }
+
+    /// Queue a job, unless too many are waiting already.
+    pub fn submit(
+        &self,
+        job: impl FnOnce() -> anyhow::Result<()> + Send + 'static,
+    ) -> anyhow::Result<()> {
+        self.sender
+            .try_send(Box::new(job))
+            .map_err(|_| anyhow::anyhow!("Too many jobs are waiting for the worker"))
+    }
+}
+
+/// Permanently delete the groups, persons and expenses which stayed in the trash longer than
+/// the retention period.
+pub fn purge_trash(
+    retention: chrono::Duration,
+    db: &dyn repositories::Store,
+) -> anyhow::Result<()> {
+    let purged = repositories::TrashRepository::purge(db, &(chrono::Utc::now() - retention))?;
+    log::info!(
+        "Purged {} groups, {} persons and {} expenses from the trash",
+        purged.groups,
+        purged.persons,
+        purged.expenses
+    );
+
+    Ok(())
+}
+
+/// Purge the trash periodically, on a thread of its own since the database calls are blocking.
+pub fn spawn_trash_purge(
+    config: config::Settings,
+    db_pool: repositories::PostgresPool,
+) -> std::thread::JoinHandle<()> {
+    std::thread::spawn(move || loop {
+        if let Err(e) = purge_trash(config.trash_retention(), &db_pool) {
+            log::error!("{:?}", e);
+        }
+        std::thread::sleep(PURGE_INTERVAL);
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_worker_should_drop_the_jobs_beyond_its_capacity() {
+        let worker = Worker::spawn();
+        let (release, blocked) = std::sync::mpsc::channel::<()>();
+        let (done, finished) = std::sync::mpsc::channel();
+
+        // The first job keeps the worker busy while the queue fills up
+        let (start, started) = std::sync::mpsc::channel();
+        worker
+            .submit(move || {
+                start.send(())?;
+                blocked.recv().map_err(Into::into)
+            })
+            .unwrap();
+        started.recv().unwrap();
+        for i in 0..WORKER_CAPACITY {
+            let done = done.clone();
+            worker
+                .submit(move || done.send(i).map_err(Into::into))
+                .unwrap();
+        }
+        assert!(worker.submit(|| Ok(())).is_err());
+
+        release.send(()).unwrap();
+        assert_eq!(
+            (0..WORKER_CAPACITY).collect::<Vec<_>>(),
+            finished.iter().take(WORKER_CAPACITY).collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,29 @@
+mod outbox;
+mod smtp;
+
+pub use self::{outbox::OutboxMailer, smtp::SmtpMailer};
+use crate::infrastructure::config;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// A plain text email.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct Email {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+/// Whatever delivers the emails the application sends.
+pub trait Mailer {
+    fn send(&self, email: &Email) -> anyhow::Result<()>;
+}
+
+/// Create the mailer of the configured transport.
+pub fn get_mailer(config: &config::Settings) -> anyhow::Result<Arc<dyn Mailer + Send + Sync>> {
+    let settings = config.mail();
+    Ok(match settings.transport() {
+        config::MailTransport::Smtp => Arc::new(SmtpMailer::new(settings.from(), settings.smtp())?),
+        config::MailTransport::Outbox => Arc::new(OutboxMailer::new(settings.outbox_dir())?),
+    })
+}
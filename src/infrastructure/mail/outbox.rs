@@ -0,0 +1,86 @@
+use super::{Email, Mailer};
+use anyhow::Context;
+use std::{fs, path::PathBuf};
+
+/// Write the emails to a directory instead of sending them, one JSON file each.
+/// Meant for the development and the tests, which read them back.
+pub struct OutboxMailer {
+    dir: PathBuf,
+}
+
+impl OutboxMailer {
+    pub fn new(dir: impl Into<PathBuf>) -> anyhow::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)
+            .context(format!("Couldn't create the outbox ({})", dir.display()))?;
+        Ok(OutboxMailer { dir })
+    }
+
+    /// The emails sent to an address, the oldest first.
+    pub fn emails_to(&self, to: &str) -> anyhow::Result<Vec<Email>> {
+        let mut paths = fs::read_dir(&self.dir)
+            .context(format!("Couldn't read the outbox ({})", self.dir.display()))?
+            .map(|entry| entry.map(|e| e.path()))
+            .collect::<Result<Vec<_>, _>>()
+            .context("Couldn't list the outbox's emails")?;
+        // The file names start with the time they were written at
+        paths.sort();
+
+        let mut emails = Vec::new();
+        for path in paths {
+            let content = fs::read(&path)
+                .context(format!("Couldn't read this email ({})", path.display()))?;
+            let email: Email = serde_json::from_slice(&content)
+                .context(format!("Couldn't parse this email ({})", path.display()))?;
+            if email.to == to {
+                emails.push(email);
+            }
+        }
+
+        Ok(emails)
+    }
+}
+
+impl Mailer for OutboxMailer {
+    fn send(&self, email: &Email) -> anyhow::Result<()> {
+        let now = chrono::Utc::now();
+        let path = self.dir.join(format!(
+            "{}-{}.json",
+            now.format("%Y%m%d%H%M%S%.9f"),
+            uuid::Uuid::new_v4()
+        ));
+        let content = serde_json::to_vec_pretty(email).context("Couldn't serialize this email")?;
+        fs::write(&path, content).context(format!("Couldn't write this email ({})", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn emails_should_be_read_back_in_the_order_they_were_sent() {
+        let dir = std::env::temp_dir().join(format!("outbox-test-{}", uuid::Uuid::new_v4()));
+        let outbox = OutboxMailer::new(&dir).unwrap();
+        let email = |to: &str, subject: &str| Email {
+            to: to.to_string(),
+            subject: subject.to_string(),
+            body: "Hello".to_string(),
+        };
+
+        outbox.send(&email("a@email.com", "First")).unwrap();
+        outbox.send(&email("b@email.com", "Other")).unwrap();
+        outbox.send(&email("a@email.com", "Second")).unwrap();
+
+        assert_eq!(
+            outbox.emails_to("a@email.com").unwrap(),
+            vec![
+                email("a@email.com", "First"),
+                email("a@email.com", "Second")
+            ]
+        );
+        assert!(outbox.emails_to("c@email.com").unwrap().is_empty());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,186 @@
+use super::{Email, Mailer};
+use crate::infrastructure::config::SmtpSettings;
+use anyhow::{bail, Context};
+use lettre::{
+    message::{header::ContentType, Mailbox},
+    transport::smtp::{
+        authentication::Credentials,
+        client::{Tls, TlsParameters},
+        extension::ClientId,
+    },
+    Message, SmtpTransport, Transport,
+};
+use std::time::Duration;
+
+/// How long the server has to answer each command.
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Send the emails through an SMTP server, switching to TLS with STARTTLS when configured to.
+pub struct SmtpMailer {
+    from: Mailbox,
+    transport: SmtpTransport,
+}
+
+impl SmtpMailer {
+    /// Fails when the credentials would be sent in plain text.
+    pub fn new(from: &str, settings: &SmtpSettings) -> anyhow::Result<Self> {
+        let from = from.parse().context(format!(
+            "Invalid address to send the emails from ({})",
+            from
+        ))?;
+
+        let tls = if settings.starttls() {
+            let params = TlsParameters::new(settings.host().to_string())
+                .context("Couldn't create the TLS parameters")?;
+            Tls::Required(params)
+        } else {
+            Tls::None
+        };
+        let mut transport = SmtpTransport::builder_dangerous(settings.host())
+            .port(settings.port())
+            .tls(tls)
+            .timeout(Some(TIMEOUT))
+            .hello_name(ClientId::Domain("localhost".to_string()));
+        if let Some((username, password)) = settings.credentials() {
+            if !settings.starttls() {
+                bail!("The SMTP credentials can't be sent without STARTTLS");
+            }
+            transport =
+                transport.credentials(Credentials::new(username.to_string(), password.to_string()));
+        }
+
+        Ok(SmtpMailer {
+            from,
+            transport: transport.build(),
+        })
+    }
+}
+
+impl Mailer for SmtpMailer {
+    fn send(&self, email: &Email) -> anyhow::Result<()> {
+        // Checked before anything is sent to the server
+        let message = message(&self.from, email)?;
+        self.transport
+            .send(&message)
+            .map(|_| ())
+            .context(format!("Couldn't send this email to {}", email.to))
+    }
+}
+
+/// Build a plain text email, refusing the headers which would add others.
+fn message(from: &Mailbox, email: &Email) -> anyhow::Result<Message> {
+    if email.subject.contains(&['\r', '\n'][..]) {
+        bail!("An email's subject can't contain line breaks");
+    }
+    let to: Mailbox = email.to.parse().context(format!(
+        "Invalid address to send an email to ({:?})",
+        email.to
+    ))?;
+
+    Message::builder()
+        .from(from.clone())
+        .to(to)
+        .subject(email.subject.clone())
+        .header(ContentType::TEXT_PLAIN)
+        .body(email.body.clone())
+        .context("Couldn't build this email")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        io::{BufRead, BufReader, Write},
+        net::TcpListener,
+    };
+
+    fn email() -> Email {
+        Email {
+            to: "to@email.com".to_string(),
+            subject: "Réinitialisation".to_string(),
+            body: "Hello\nWorld".to_string(),
+        }
+    }
+
+    fn settings(port: u16, credentials: bool) -> SmtpSettings {
+        let credentials = if credentials { Some("user") } else { None };
+        serde_json::from_value(serde_json::json!({
+            "host": "127.0.0.1",
+            "port": port,
+            "username": credentials,
+            "password": credentials,
+            "starttls": false
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn headers_should_not_be_injected() {
+        let from = "from@email.com".parse().unwrap();
+        let formatted = String::from_utf8(message(&from, &email()).unwrap().formatted()).unwrap();
+        assert!(formatted.contains("From: from@email.com\r\n"));
+        assert!(formatted.contains("To: to@email.com\r\n"));
+        assert!(formatted.contains("Subject: =?utf-8?b?UsOpaW5pdGlhbGlzYXRpb24=?=\r\n"));
+
+        let mut injected = email();
+        injected.to = "to@email.com\r\nBcc: other@email.com".to_string();
+        assert!(message(&from, &injected).is_err());
+        let mut injected = email();
+        injected.subject = "Hello\r\nBcc: other@email.com".to_string();
+        assert!(message(&from, &injected).is_err());
+    }
+
+    #[test]
+    fn credentials_should_need_tls() {
+        assert!(SmtpMailer::new("from@email.com", &settings(25, true)).is_err());
+        assert!(SmtpMailer::new("from@email.com", &settings(25, false)).is_ok());
+    }
+
+    #[test]
+    fn emails_should_be_sent_to_the_server() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut writer = stream;
+            let mut received = Vec::new();
+            let mut data = false;
+            writer.write_all(b"220 localhost ready\r\n").unwrap();
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).unwrap() == 0 {
+                    return received;
+                }
+                let line = line.trim_end().to_string();
+                let reply: &[u8] = match line.as_str() {
+                    _ if data && line != "." => b"",
+                    "EHLO localhost" => b"250 localhost\r\n",
+                    "DATA" => b"354 go ahead\r\n",
+                    "." => b"250 queued\r\n",
+                    "QUIT" => b"221 bye\r\n",
+                    l if l.starts_with("MAIL") || l.starts_with("RCPT") => b"250 ok\r\n",
+                    _ => b"500 unknown\r\n",
+                };
+                data = (data || line == "DATA") && line != ".";
+                writer.write_all(reply).unwrap();
+                received.push(line);
+                if received.last().unwrap() == "QUIT" {
+                    return received;
+                }
+            }
+        });
+
+        let mailer = SmtpMailer::new("from@email.com", &settings(port, false)).unwrap();
+        mailer.send(&email()).unwrap();
+        drop(mailer);
+
+        let received = server.join().unwrap();
+        assert_eq!(received[0], "EHLO localhost");
+        assert_eq!(received[1], "MAIL FROM:<from@email.com>");
+        assert_eq!(received[2], "RCPT TO:<to@email.com>");
+        assert_eq!(received[3], "DATA");
+        assert!(received.contains(&"To: to@email.com".to_string()));
+        assert!(received.contains(&".".to_string()));
+    }
+}
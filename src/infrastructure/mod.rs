@@ -1,5 +1,7 @@
 pub mod config;
 mod graphql;
 pub mod http;
+pub mod jobs;
+pub mod mail;
 pub mod repositories;
-mod security;
+pub(crate) mod security;
@@ -0,0 +1,163 @@
+use super::{group::Group, schema::activities, Database};
+use crate::domain::activity::{Action, Entity};
+use anyhow::Context;
+use diesel::{pg::PgConnection, prelude::*};
+use serde::Serialize;
+
+/// A change made to a group, with snapshots of the changed row before and after it.
+#[derive(Identifiable, Queryable, PartialEq, Debug, Clone)]
+#[table_name = "activities"]
+pub struct Activity {
+    /// Increases with every entry, even the ones recorded in the same transaction.
+    pub id: i64,
+    pub group_id: uuid::Uuid,
+    /// The user who made the change. None once they're gone.
+    pub user_id: Option<uuid::Uuid>,
+    pub action: String,
+    pub entity_type: String,
+    pub entity_id: uuid::Uuid,
+    pub before: Option<serde_json::Value>,
+    pub after: Option<serde_json::Value>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// A row whose changes are recorded in its group's activity.
+pub trait Audited: Serialize {
+    const ENTITY: Entity;
+
+    fn entity_id(&self) -> uuid::Uuid;
+
+    /// None for the rows belonging to no group, which aren't recorded.
+    fn group_id(&self) -> Option<uuid::Uuid>;
+}
+
+pub trait ActivityRepository {
+    /// Find the group's latest entries, older than the `before` one when given.
+    fn find_by_group(
+        &self,
+        group: &Group,
+        before: Option<i64>,
+        limit: i64,
+    ) -> anyhow::Result<Vec<Activity>>;
+}
+
+impl<D: Database> ActivityRepository for D {
+    fn find_by_group(
+        &self,
+        group: &Group,
+        before: Option<i64>,
+        limit: i64,
+    ) -> anyhow::Result<Vec<Activity>> {
+        let mut query = activities::table
+            .filter(activities::group_id.eq(group.id))
+            .order(activities::id.desc())
+            .limit(limit)
+            .into_boxed();
+        if let Some(before) = before {
+            query = query.filter(activities::id.lt(before));
+        }
+
+        query.load(&*self.get()?).context(format!(
+            "Couldn't find this group's ({}) activity",
+            group.id
+        ))
+    }
+}
+
+/// An entry about to be recorded, numbered and dated by the store.
+#[derive(Insertable)]
+#[table_name = "activities"]
+pub(super) struct NewActivity {
+    pub group_id: uuid::Uuid,
+    pub user_id: uuid::Uuid,
+    pub action: String,
+    pub entity_type: String,
+    pub entity_id: uuid::Uuid,
+    pub before: Option<serde_json::Value>,
+    pub after: Option<serde_json::Value>,
+}
+
+impl NewActivity {
+    /// The entry telling that a user created (no `before`), updated or deleted (no `after`) a row.
+    /// None when there's nothing to record.
+    pub fn change<T: Audited>(
+        user_id: &uuid::Uuid,
+        before: Option<&T>,
+        after: Option<&T>,
+    ) -> serde_json::Result<Option<Self>> {
+        let action = match (before, after) {
+            (None, Some(_)) => Action::Create,
+            (Some(_), Some(_)) => Action::Update,
+            (Some(_), None) => Action::Delete,
+            (None, None) => return Ok(None),
+        };
+
+        Self::new(user_id, action, before, after)
+    }
+
+    /// The entry telling that a user restored a row from the trash.
+    pub fn restore<T: Audited>(
+        user_id: &uuid::Uuid,
+        before: &T,
+        after: &T,
+    ) -> serde_json::Result<Option<Self>> {
+        Self::new(user_id, Action::Restore, Some(before), Some(after))
+    }
+
+    fn new<T: Audited>(
+        user_id: &uuid::Uuid,
+        action: Action,
+        before: Option<&T>,
+        after: Option<&T>,
+    ) -> serde_json::Result<Option<Self>> {
+        let row = match before.or(after) {
+            Some(row) => row,
+            None => return Ok(None),
+        };
+        let group_id = match row.group_id() {
+            Some(id) => id,
+            None => return Ok(None),
+        };
+
+        Ok(Some(NewActivity {
+            group_id,
+            user_id: *user_id,
+            action: action.as_str().to_string(),
+            entity_type: T::ENTITY.as_str().to_string(),
+            entity_id: row.entity_id(),
+            before: before.map(serde_json::to_value).transpose()?,
+            after: after.map(serde_json::to_value).transpose()?,
+        }))
+    }
+}
+
+/// Record that a user created (no `before`), updated or deleted (no `after`) a row.
+/// It must be called within the transaction making the change, on its connection.
+pub(super) fn record<T: Audited>(
+    user_id: &uuid::Uuid,
+    before: Option<&T>,
+    after: Option<&T>,
+    conn: &PgConnection,
+) -> QueryResult<()> {
+    insert(NewActivity::change(user_id, before, after), conn)
+}
+
+/// Record that a user restored a row from the trash, within the same transaction.
+pub(super) fn record_restore<T: Audited>(
+    user_id: &uuid::Uuid,
+    before: &T,
+    after: &T,
+    conn: &PgConnection,
+) -> QueryResult<()> {
+    insert(NewActivity::restore(user_id, before, after), conn)
+}
+
+fn insert(entry: serde_json::Result<Option<NewActivity>>, conn: &PgConnection) -> QueryResult<()> {
+    match entry.map_err(|e| diesel::result::Error::SerializationError(Box::new(e)))? {
+        Some(entry) => diesel::insert_into(activities::table)
+            .values(&entry)
+            .execute(conn)
+            .map(|_| ()),
+        None => Ok(()),
+    }
+}
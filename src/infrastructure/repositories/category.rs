@@ -0,0 +1,175 @@
+use super::{
+    activity::{self, Audited},
+    group::Group,
+    schema::{categories, expenses, group_members, groups},
+    Database,
+};
+use crate::domain::activity::Entity;
+use anyhow::Context;
+use diesel::prelude::*;
+use serde::Serialize;
+
+#[derive(Identifiable, Queryable, Serialize, PartialEq, Debug, Clone)]
+#[table_name = "categories"]
+pub struct Category {
+    pub id: uuid::Uuid,
+    /// None for the built-in categories, shared by every group.
+    pub group_id: Option<uuid::Uuid>,
+    pub name: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl Category {
+    pub fn is_built_in(&self) -> bool {
+        self.group_id.is_none()
+    }
+}
+
+impl Audited for Category {
+    const ENTITY: Entity = Entity::Category;
+
+    fn entity_id(&self) -> uuid::Uuid {
+        self.id
+    }
+
+    fn group_id(&self) -> Option<uuid::Uuid> {
+        self.group_id
+    }
+}
+
+pub trait CategoryRepository {
+    fn find_one(&self, id: &uuid::Uuid) -> anyhow::Result<Option<Category>>;
+
+    /// Find a custom category if this user is a member of its group, along with their role.
+    fn find_one_by_user(
+        &self,
+        id: &uuid::Uuid,
+        user_id: &uuid::Uuid,
+    ) -> anyhow::Result<Option<(Category, String)>>;
+
+    /// Find the categories available to a group: the built-in ones, then its custom ones.
+    fn find_by_group(&self, group: &Group) -> anyhow::Result<Vec<Category>>;
+
+    /// Whether some expenses belong to this category, including the ones in the trash.
+    fn is_used(&self, id: &uuid::Uuid) -> anyhow::Result<bool>;
+
+    fn save(&self, new_category: &NewCategory, user_id: &uuid::Uuid) -> anyhow::Result<Category>;
+
+    fn update_one(&self, category: &UpdateCategory, user_id: &uuid::Uuid) -> anyhow::Result<()>;
+
+    fn delete_one(&self, id: &uuid::Uuid, user_id: &uuid::Uuid) -> anyhow::Result<()>;
+}
+
+impl<D: Database> CategoryRepository for D {
+    fn find_one(&self, id: &uuid::Uuid) -> anyhow::Result<Option<Category>> {
+        categories::table
+            .find(id)
+            .first(&*self.get()?)
+            .optional()
+            .context(format!("Couldn't find this category ({})", id))
+    }
+
+    fn find_one_by_user(
+        &self,
+        id: &uuid::Uuid,
+        user_id: &uuid::Uuid,
+    ) -> anyhow::Result<Option<(Category, String)>> {
+        categories::table
+            .inner_join(groups::table)
+            .inner_join(
+                group_members::table
+                    .on(group_members::group_id.nullable().eq(categories::group_id)),
+            )
+            .filter(categories::id.eq(id))
+            .filter(groups::deleted_at.is_null())
+            .filter(group_members::user_id.eq(user_id))
+            .select((categories::all_columns, group_members::role))
+            .first(&*self.get()?)
+            .optional()
+            .context(format!(
+                "Couldn't find this user's ({}) category ({})",
+                user_id, id
+            ))
+    }
+
+    fn find_by_group(&self, group: &Group) -> anyhow::Result<Vec<Category>> {
+        categories::table
+            .filter(categories::group_id.is_null())
+            .or_filter(categories::group_id.eq(group.id))
+            .order((categories::group_id.desc(), categories::name))
+            .load(&*self.get()?)
+            .context(format!(
+                "Couldn't find this group's ({}) categories",
+                group.id
+            ))
+    }
+
+    fn is_used(&self, id: &uuid::Uuid) -> anyhow::Result<bool> {
+        diesel::select(diesel::dsl::exists(
+            expenses::table.filter(expenses::category_id.eq(id)),
+        ))
+        .get_result(&*self.get()?)
+        .context(format!("Couldn't check if this category ({}) is used", id))
+    }
+
+    fn save(&self, new_category: &NewCategory, user_id: &uuid::Uuid) -> anyhow::Result<Category> {
+        let conn = self.get()?;
+        conn.transaction::<_, diesel::result::Error, _>(|| {
+            let category = diesel::insert_into(categories::table)
+                .values(new_category)
+                .get_result::<Category>(&*conn)?;
+            activity::record(user_id, None, Some(&category), &conn)?;
+            Ok(category)
+        })
+        .context("Couldn't save this category to the database")
+    }
+
+    fn update_one(&self, category: &UpdateCategory, user_id: &uuid::Uuid) -> anyhow::Result<()> {
+        if category.name.is_none() {
+            return Ok(());
+        }
+
+        let conn = self.get()?;
+        conn.transaction::<_, diesel::result::Error, _>(|| {
+            let before = categories::table
+                .find(category.id)
+                .for_update()
+                .first::<Category>(&*conn)
+                .optional()?;
+            let after = diesel::update(categories::table.filter(categories::id.eq(category.id)))
+                .set(category)
+                .get_result::<Category>(&*conn)
+                .optional()?;
+            activity::record(user_id, before.as_ref(), after.as_ref(), &conn)
+        })
+        .context("Couldn't update this category to the database")
+    }
+
+    fn delete_one(&self, id: &uuid::Uuid, user_id: &uuid::Uuid) -> anyhow::Result<()> {
+        let conn = self.get()?;
+        conn.transaction::<_, diesel::result::Error, _>(|| {
+            let before = diesel::delete(categories::table)
+                .filter(categories::id.eq(id))
+                .get_result::<Category>(&*conn)
+                .optional()?;
+            activity::record(user_id, before.as_ref(), None, &conn)
+        })
+        .context(format!("Couldn't delete this category ({})", id))
+    }
+}
+
+#[derive(Insertable)]
+#[table_name = "categories"]
+pub struct NewCategory {
+    pub id: uuid::Uuid,
+    pub group_id: uuid::Uuid,
+    pub name: String,
+}
+
+#[derive(AsChangeset)]
+#[table_name = "categories"]
+pub struct UpdateCategory {
+    pub id: uuid::Uuid,
+    pub name: Option<String>,
+}
@@ -0,0 +1,76 @@
+use super::{schema::email_verifications, Database};
+use anyhow::Context;
+use diesel::prelude::*;
+
+#[derive(Identifiable, Queryable, PartialEq, Debug, Clone)]
+pub struct EmailVerification {
+    pub id: uuid::Uuid,
+    pub user_id: uuid::Uuid,
+    /// The address the token was sent to, which may not be the user's anymore.
+    pub email: String,
+    /// The token's secret is only known by whoever reads the emails sent to this address.
+    pub secret_hash: String,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+    pub used_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+pub trait EmailVerificationRepository {
+    fn find_one(&self, id: &uuid::Uuid) -> anyhow::Result<Option<EmailVerification>>;
+
+    fn save(
+        &self,
+        new_email_verification: &NewEmailVerification,
+    ) -> anyhow::Result<EmailVerification>;
+
+    /// Mark an email verification as used.
+    /// Returns false, changing nothing, when it was already used or has expired.
+    fn use_one(&self, id: &uuid::Uuid) -> anyhow::Result<bool>;
+}
+
+impl<D: Database> EmailVerificationRepository for D {
+    fn find_one(&self, id: &uuid::Uuid) -> anyhow::Result<Option<EmailVerification>> {
+        email_verifications::table
+            .find(id)
+            .first(&*self.get()?)
+            .optional()
+            .context(format!("Couldn't find this email verification ({})", id))
+    }
+
+    fn save(
+        &self,
+        new_email_verification: &NewEmailVerification,
+    ) -> anyhow::Result<EmailVerification> {
+        diesel::insert_into(email_verifications::table)
+            .values(new_email_verification)
+            .get_result::<EmailVerification>(&*self.get()?)
+            .context("Couldn't save this email verification to the database")
+    }
+
+    fn use_one(&self, id: &uuid::Uuid) -> anyhow::Result<bool> {
+        diesel::update(
+            email_verifications::table
+                .filter(email_verifications::id.eq(id))
+                .filter(email_verifications::used_at.is_null())
+                .filter(email_verifications::expires_at.gt(diesel::dsl::now)),
+        )
+        .set((
+            email_verifications::used_at.eq(diesel::dsl::now),
+            email_verifications::updated_at.eq(diesel::dsl::now),
+        ))
+        .execute(&*self.get()?)
+        .map(|n| n > 0)
+        .context(format!("Couldn't use this email verification ({})", id))
+    }
+}
+
+#[derive(Insertable)]
+#[table_name = "email_verifications"]
+pub struct NewEmailVerification {
+    pub id: uuid::Uuid,
+    pub user_id: uuid::Uuid,
+    pub email: String,
+    pub secret_hash: String,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+}
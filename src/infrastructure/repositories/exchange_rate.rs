@@ -0,0 +1,90 @@
+use super::{schema::exchange_rates, Database};
+use anyhow::Context;
+use diesel::{pg::upsert::excluded, prelude::*};
+
+#[derive(Queryable, PartialEq, Debug, Clone)]
+pub struct ExchangeRate {
+    pub from_currency: String,
+    pub to_currency: String,
+    pub rate: f64,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+pub trait ExchangeRateRepository {
+    fn find_all(&self) -> anyhow::Result<Vec<ExchangeRate>>;
+
+    /// Find the rates between two currencies, in both directions.
+    fn find_between(&self, currency: &str, other: &str) -> anyhow::Result<Vec<ExchangeRate>>;
+
+    /// Save the rates, replacing the existing ones for the same currencies.
+    fn save_all(&self, new_rates: &[NewExchangeRate]) -> anyhow::Result<()>;
+
+    fn delete_one(&self, from_currency: &str, to_currency: &str) -> anyhow::Result<()>;
+}
+
+impl<D: Database> ExchangeRateRepository for D {
+    fn find_all(&self) -> anyhow::Result<Vec<ExchangeRate>> {
+        exchange_rates::table
+            .order((exchange_rates::from_currency, exchange_rates::to_currency))
+            .load(&*self.get()?)
+            .context("Couldn't find the exchange rates")
+    }
+
+    fn find_between(&self, currency: &str, other: &str) -> anyhow::Result<Vec<ExchangeRate>> {
+        exchange_rates::table
+            .filter(
+                exchange_rates::from_currency
+                    .eq(currency)
+                    .and(exchange_rates::to_currency.eq(other)),
+            )
+            .or_filter(
+                exchange_rates::from_currency
+                    .eq(other)
+                    .and(exchange_rates::to_currency.eq(currency)),
+            )
+            .load(&*self.get()?)
+            .context(format!(
+                "Couldn't find the exchange rates between {} and {}",
+                currency, other
+            ))
+    }
+
+    fn save_all(&self, new_rates: &[NewExchangeRate]) -> anyhow::Result<()> {
+        if new_rates.is_empty() {
+            return Ok(());
+        }
+
+        diesel::insert_into(exchange_rates::table)
+            .values(new_rates)
+            .on_conflict((exchange_rates::from_currency, exchange_rates::to_currency))
+            .do_update()
+            .set((
+                exchange_rates::rate.eq(excluded(exchange_rates::rate)),
+                exchange_rates::updated_at.eq(diesel::dsl::now),
+            ))
+            .execute(&*self.get()?)
+            .context("Couldn't save these exchange rates to the database")
+            .map(|_| ())
+    }
+
+    fn delete_one(&self, from_currency: &str, to_currency: &str) -> anyhow::Result<()> {
+        diesel::delete(exchange_rates::table)
+            .filter(exchange_rates::from_currency.eq(from_currency))
+            .filter(exchange_rates::to_currency.eq(to_currency))
+            .execute(&*self.get()?)
+            .context(format!(
+                "Couldn't delete this exchange rate ({} to {})",
+                from_currency, to_currency
+            ))
+            .map(|_| ())
+    }
+}
+
+#[derive(Insertable)]
+#[table_name = "exchange_rates"]
+pub struct NewExchangeRate {
+    pub from_currency: String,
+    pub to_currency: String,
+    pub rate: f64,
+}
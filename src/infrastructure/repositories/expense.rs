@@ -1,65 +1,261 @@
-use super::{group::Group, person::Person, schema::expenses, PostgresPool};
+use super::{
+    activity::{self, Audited},
+    group::Group,
+    person::Person,
+    schema::{expense_payers, expenses, group_members, groups},
+    Database,
+};
+use crate::domain::{activity::Entity, expense::DateRange};
 use anyhow::Context;
 use diesel::prelude::*;
+use serde::Serialize;
 
-#[derive(Identifiable, Queryable, Associations, PartialEq, Debug)]
+#[derive(Identifiable, Queryable, Associations, Serialize, PartialEq, Debug, Clone)]
 #[belongs_to(Group)]
-#[belongs_to(Person)]
 pub struct Expense {
     pub id: uuid::Uuid,
     pub group_id: uuid::Uuid,
-    pub person_id: uuid::Uuid,
     pub name: String,
-    pub amount: i32,
+    pub amount: i64,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub updated_at: chrono::DateTime<chrono::Utc>,
+    pub split_mode: String,
+    pub currency: String,
+    /// The rate converting the amount to the group's currency when the expense was added.
+    pub exchange_rate: f64,
+    /// When the money was spent, as opposed to when the expense was added.
+    pub occurred_on: chrono::NaiveDate,
+    pub category_id: Option<uuid::Uuid>,
+    /// When the expense was moved to the trash, if it was.
+    pub deleted_at: Option<chrono::DateTime<chrono::Utc>>,
 }
 
-pub struct ExpenseRepository;
-impl ExpenseRepository {
-    pub fn find_by_person(person: &Person, pool: &PostgresPool) -> anyhow::Result<Vec<Expense>> {
-        Expense::belonging_to(person)
-            .load(&pool.get()?)
+impl Audited for Expense {
+    const ENTITY: Entity = Entity::Expense;
+
+    fn entity_id(&self) -> uuid::Uuid {
+        self.id
+    }
+
+    fn group_id(&self) -> Option<uuid::Uuid> {
+        Some(self.group_id)
+    }
+}
+
+pub trait ExpenseRepository {
+    fn find_one(&self, id: &uuid::Uuid) -> anyhow::Result<Option<Expense>>;
+
+    /// Find an expense if this user is a member of its group, along with their role.
+    fn find_one_by_user(
+        &self,
+        id: &uuid::Uuid,
+        user_id: &uuid::Uuid,
+    ) -> anyhow::Result<Option<(Expense, String)>>;
+
+    /// Find an expense in the trash if its group, out of it, belongs to this user.
+    fn find_deleted_one_by_user(
+        &self,
+        id: &uuid::Uuid,
+        user_id: &uuid::Uuid,
+    ) -> anyhow::Result<Option<(Expense, String)>>;
+
+    /// Find every expense this person helped pay for.
+    fn find_by_person(&self, person: &Person) -> anyhow::Result<Vec<Expense>>;
+
+    /// Find the group's expenses which occurred in this range, the oldest first.
+    fn find_by_group(&self, group: &Group, range: &DateRange) -> anyhow::Result<Vec<Expense>>;
+
+    /// Find the group's expenses in the trash, the latest deleted first.
+    fn find_deleted_by_group(&self, group: &Group) -> anyhow::Result<Vec<Expense>>;
+
+    fn save(&self, new_expense: &NewExpense, user_id: &uuid::Uuid) -> anyhow::Result<Expense>;
+
+    fn update_one(&self, expense: &UpdateExpense, user_id: &uuid::Uuid) -> anyhow::Result<()>;
+
+    /// Move an expense to the trash.
+    fn delete_one(&self, id: &uuid::Uuid, user_id: &uuid::Uuid) -> anyhow::Result<()>;
+
+    /// Move an expense back from the trash.
+    fn restore_one(&self, id: &uuid::Uuid, user_id: &uuid::Uuid) -> anyhow::Result<()>;
+}
+
+impl<D: Database> ExpenseRepository for D {
+    fn find_one(&self, id: &uuid::Uuid) -> anyhow::Result<Option<Expense>> {
+        expenses::table
+            .find(id)
+            .filter(expenses::deleted_at.is_null())
+            .first(&*self.get()?)
+            .optional()
+            .context(format!("Couldn't find this expense ({})", id))
+    }
+
+    fn find_one_by_user(
+        &self,
+        id: &uuid::Uuid,
+        user_id: &uuid::Uuid,
+    ) -> anyhow::Result<Option<(Expense, String)>> {
+        expenses::table
+            .inner_join(groups::table)
+            .inner_join(group_members::table.on(group_members::group_id.eq(expenses::group_id)))
+            .filter(expenses::id.eq(id))
+            .filter(expenses::deleted_at.is_null())
+            .filter(groups::deleted_at.is_null())
+            .filter(group_members::user_id.eq(user_id))
+            .select((expenses::all_columns, group_members::role))
+            .first(&*self.get()?)
+            .optional()
+            .context(format!(
+                "Couldn't find this user's ({}) expense ({})",
+                user_id, id
+            ))
+    }
+
+    fn find_deleted_one_by_user(
+        &self,
+        id: &uuid::Uuid,
+        user_id: &uuid::Uuid,
+    ) -> anyhow::Result<Option<(Expense, String)>> {
+        expenses::table
+            .inner_join(groups::table)
+            .inner_join(group_members::table.on(group_members::group_id.eq(expenses::group_id)))
+            .filter(expenses::id.eq(id))
+            .filter(expenses::deleted_at.is_not_null())
+            .filter(groups::deleted_at.is_null())
+            .filter(group_members::user_id.eq(user_id))
+            .select((expenses::all_columns, group_members::role))
+            .first(&*self.get()?)
+            .optional()
+            .context(format!(
+                "Couldn't find this user's ({}) deleted expense ({})",
+                user_id, id
+            ))
+    }
+
+    fn find_by_person(&self, person: &Person) -> anyhow::Result<Vec<Expense>> {
+        expenses::table
+            .inner_join(expense_payers::table)
+            .filter(expense_payers::person_id.eq(person.id))
+            .filter(expenses::deleted_at.is_null())
+            .select(expenses::all_columns)
+            .load(&*self.get()?)
             .context(format!(
                 "Couldn't find this person's ({}) expenses",
                 person.id
             ))
     }
 
-    pub fn find_by_group(group: &Group, pool: &PostgresPool) -> anyhow::Result<Vec<Expense>> {
+    fn find_by_group(&self, group: &Group, range: &DateRange) -> anyhow::Result<Vec<Expense>> {
+        let mut query = Expense::belonging_to(group)
+            .filter(expenses::deleted_at.is_null())
+            .order((expenses::occurred_on, expenses::created_at))
+            .into_boxed();
+        if let Some(from) = range.from {
+            query = query.filter(expenses::occurred_on.ge(from));
+        }
+        if let Some(to) = range.to {
+            query = query.filter(expenses::occurred_on.le(to));
+        }
+
+        query.load(&*self.get()?).context(format!(
+            "Couldn't find this group's ({}) expenses",
+            group.id
+        ))
+    }
+
+    fn find_deleted_by_group(&self, group: &Group) -> anyhow::Result<Vec<Expense>> {
         Expense::belonging_to(group)
-            .load(&pool.get()?)
+            .filter(expenses::deleted_at.is_not_null())
+            .order(expenses::deleted_at.desc())
+            .load(&*self.get()?)
             .context(format!(
-                "Couldn't find this group's ({}) expenses",
+                "Couldn't find this group's ({}) deleted expenses",
                 group.id
             ))
     }
 
-    pub fn save(new_expense: &NewExpense, pool: &PostgresPool) -> anyhow::Result<Expense> {
-        diesel::insert_into(expenses::table)
-            .values(new_expense)
-            .get_result::<Expense>(&pool.get()?)
-            .context("Couldn't save this expense to the database")
+    fn save(&self, new_expense: &NewExpense, user_id: &uuid::Uuid) -> anyhow::Result<Expense> {
+        let conn = self.get()?;
+        conn.transaction::<_, diesel::result::Error, _>(|| {
+            let expense = diesel::insert_into(expenses::table)
+                .values(new_expense)
+                .get_result::<Expense>(&*conn)?;
+            activity::record(user_id, None, Some(&expense), &conn)?;
+            Ok(expense)
+        })
+        .context("Couldn't save this expense to the database")
     }
 
-    pub fn update_one(expense: &UpdateExpense, pool: &PostgresPool) -> anyhow::Result<()> {
-        if expense.name.is_none() && expense.amount.is_none() {
+    fn update_one(&self, expense: &UpdateExpense, user_id: &uuid::Uuid) -> anyhow::Result<()> {
+        if expense.name.is_none()
+            && expense.amount.is_none()
+            && expense.split_mode.is_none()
+            && expense.currency.is_none()
+            && expense.occurred_on.is_none()
+            && expense.category_id.is_none()
+        {
             return Ok(());
         }
 
-        diesel::update(expenses::table.filter(expenses::id.eq(expense.id)))
-            .set(expense)
-            .execute(&pool.get()?)
-            .context("Couldn't update this expense to the database")
-            .map(|_| ())
+        let conn = self.get()?;
+        conn.transaction::<_, diesel::result::Error, _>(|| {
+            let before = expenses::table
+                .find(expense.id)
+                .for_update()
+                .first::<Expense>(&*conn)
+                .optional()?;
+            let after = diesel::update(expenses::table.filter(expenses::id.eq(expense.id)))
+                .set(expense)
+                .get_result::<Expense>(&*conn)
+                .optional()?;
+            activity::record(user_id, before.as_ref(), after.as_ref(), &conn)
+        })
+        .context("Couldn't update this expense to the database")
     }
 
-    pub fn delete_one(id: &uuid::Uuid, pool: &PostgresPool) -> anyhow::Result<()> {
-        diesel::delete(expenses::table)
-            .filter(expenses::id.eq(id))
-            .execute(&pool.get()?)
-            .context(format!("Couldn't delete this expense ({})", id))
-            .map(|_| ())
+    fn delete_one(&self, id: &uuid::Uuid, user_id: &uuid::Uuid) -> anyhow::Result<()> {
+        let conn = self.get()?;
+        conn.transaction::<_, diesel::result::Error, _>(|| {
+            let before = expenses::table
+                .find(id)
+                .filter(expenses::deleted_at.is_null())
+                .for_update()
+                .first::<Expense>(&*conn)
+                .optional()?;
+            diesel::update(
+                expenses::table
+                    .find(id)
+                    .filter(expenses::deleted_at.is_null()),
+            )
+            .set(expenses::deleted_at.eq(diesel::dsl::now))
+            .execute(&*conn)?;
+            activity::record(user_id, before.as_ref(), None, &conn)
+        })
+        .context(format!("Couldn't delete this expense ({})", id))
+    }
+
+    fn restore_one(&self, id: &uuid::Uuid, user_id: &uuid::Uuid) -> anyhow::Result<()> {
+        let conn = self.get()?;
+        conn.transaction::<_, diesel::result::Error, _>(|| {
+            let before = expenses::table
+                .find(id)
+                .filter(expenses::deleted_at.is_not_null())
+                .for_update()
+                .first::<Expense>(&*conn)
+                .optional()?;
+            let after = diesel::update(
+                expenses::table
+                    .find(id)
+                    .filter(expenses::deleted_at.is_not_null()),
+            )
+            .set(expenses::deleted_at.eq(None::<chrono::DateTime<chrono::Utc>>))
+            .get_result::<Expense>(&*conn)
+            .optional()?;
+            match (before, after) {
+                (Some(b), Some(a)) => activity::record_restore(user_id, &b, &a, &conn),
+                _ => Ok(()),
+            }
+        })
+        .context(format!("Couldn't restore this expense ({})", id))
     }
 }
 
@@ -68,9 +264,13 @@ impl ExpenseRepository {
 pub struct NewExpense {
     pub id: uuid::Uuid,
     pub group_id: uuid::Uuid,
-    pub person_id: uuid::Uuid,
     pub name: String,
-    pub amount: i32,
+    pub amount: i64,
+    pub split_mode: String,
+    pub currency: String,
+    pub exchange_rate: f64,
+    pub occurred_on: chrono::NaiveDate,
+    pub category_id: Option<uuid::Uuid>,
 }
 
 #[derive(AsChangeset)]
@@ -78,5 +278,10 @@ pub struct NewExpense {
 pub struct UpdateExpense {
     pub id: uuid::Uuid,
     pub name: Option<String>,
-    pub amount: Option<i32>,
+    pub amount: Option<i64>,
+    pub split_mode: Option<String>,
+    pub currency: Option<String>,
+    pub exchange_rate: Option<f64>,
+    pub occurred_on: Option<chrono::NaiveDate>,
+    pub category_id: Option<uuid::Uuid>,
 }
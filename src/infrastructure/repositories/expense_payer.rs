@@ -0,0 +1,153 @@
+use super::{
+    activity::{self, Audited},
+    expense::Expense,
+    person::Person,
+    schema::{expense_payers, expenses},
+    Database,
+};
+use crate::domain::activity::Entity;
+use anyhow::Context;
+use diesel::prelude::*;
+use serde::Serialize;
+
+#[derive(Identifiable, Queryable, Associations, Serialize, PartialEq, Debug, Clone)]
+#[primary_key(expense_id, person_id)]
+#[belongs_to(Expense)]
+#[belongs_to(Person)]
+pub struct ExpensePayer {
+    pub expense_id: uuid::Uuid,
+    pub person_id: uuid::Uuid,
+    pub amount: i64,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// The payers of an expense, which are replaced together.
+#[derive(Serialize)]
+pub(super) struct ExpensePayers {
+    #[serde(skip)]
+    group_id: uuid::Uuid,
+    #[serde(skip)]
+    expense_id: uuid::Uuid,
+    payers: Vec<ExpensePayer>,
+}
+
+impl Audited for ExpensePayers {
+    const ENTITY: Entity = Entity::ExpensePayers;
+
+    fn entity_id(&self) -> uuid::Uuid {
+        self.expense_id
+    }
+
+    fn group_id(&self) -> Option<uuid::Uuid> {
+        Some(self.group_id)
+    }
+}
+
+impl ExpensePayers {
+    /// The snapshots telling how the payers of an expense were replaced, None when they weren't.
+    /// No payers at all is recorded as a creation or a deletion.
+    pub fn change(
+        group_id: uuid::Uuid,
+        expense_id: uuid::Uuid,
+        before: Vec<ExpensePayer>,
+        after: Vec<ExpensePayer>,
+    ) -> (Option<Self>, Option<Self>) {
+        // Only record an actual change
+        let key = |v: &[ExpensePayer]| {
+            let mut v = v
+                .iter()
+                .map(|r| (r.person_id, r.amount))
+                .collect::<Vec<_>>();
+            v.sort();
+            v
+        };
+        if key(&before) == key(&after) {
+            return (None, None);
+        }
+        let snapshot = |payers: Vec<_>| {
+            if payers.is_empty() {
+                None
+            } else {
+                Some(ExpensePayers {
+                    group_id,
+                    expense_id,
+                    payers,
+                })
+            }
+        };
+        (snapshot(before), snapshot(after))
+    }
+}
+
+pub trait ExpensePayerRepository {
+    fn find_by_expense(&self, expense: &Expense) -> anyhow::Result<Vec<ExpensePayer>>;
+
+    /// Find the payers of every expense, in the same order as the expenses.
+    fn find_by_expenses(&self, expenses: &[Expense]) -> anyhow::Result<Vec<Vec<ExpensePayer>>>;
+
+    /// Replace all the payers of an expense.
+    fn save_all(
+        &self,
+        expense_id: &uuid::Uuid,
+        new_payers: &[NewExpensePayer],
+        user_id: &uuid::Uuid,
+    ) -> anyhow::Result<()>;
+}
+
+impl<D: Database> ExpensePayerRepository for D {
+    fn find_by_expense(&self, expense: &Expense) -> anyhow::Result<Vec<ExpensePayer>> {
+        ExpensePayer::belonging_to(expense)
+            .load(&*self.get()?)
+            .context(format!(
+                "Couldn't find this expense's ({}) payers",
+                expense.id
+            ))
+    }
+
+    fn find_by_expenses(&self, expenses: &[Expense]) -> anyhow::Result<Vec<Vec<ExpensePayer>>> {
+        ExpensePayer::belonging_to(expenses)
+            .load(&*self.get()?)
+            .map(|v| v.grouped_by(expenses))
+            .context("Couldn't find these expenses' payers")
+    }
+
+    fn save_all(
+        &self,
+        expense_id: &uuid::Uuid,
+        new_payers: &[NewExpensePayer],
+        user_id: &uuid::Uuid,
+    ) -> anyhow::Result<()> {
+        let conn = self.get()?;
+        conn.transaction::<_, diesel::result::Error, _>(|| {
+            let group_id = expenses::table
+                .find(expense_id)
+                .select(expenses::group_id)
+                .first(&*conn)?;
+            let before = diesel::delete(expense_payers::table)
+                .filter(expense_payers::expense_id.eq(expense_id))
+                .get_results::<ExpensePayer>(&*conn)?;
+            let after = if new_payers.is_empty() {
+                Vec::new()
+            } else {
+                diesel::insert_into(expense_payers::table)
+                    .values(new_payers)
+                    .get_results::<ExpensePayer>(&*conn)?
+            };
+            let (before, after) = ExpensePayers::change(group_id, *expense_id, before, after);
+            activity::record(user_id, before.as_ref(), after.as_ref(), &conn)
+        })
+        .context(format!(
+            "Couldn't save this expense's ({}) payers to the database",
+            expense_id
+        ))
+    }
+}
+
+#[derive(Insertable)]
+#[table_name = "expense_payers"]
+pub struct NewExpensePayer {
+    pub expense_id: uuid::Uuid,
+    pub person_id: uuid::Uuid,
+    pub amount: i64,
+}
@@ -0,0 +1,166 @@
+use super::{
+    activity::{self, Audited},
+    expense::Expense,
+    person::Person,
+    schema::{expense_shares, expenses, persons},
+    Database,
+};
+use crate::domain::activity::Entity;
+use anyhow::Context;
+use diesel::prelude::*;
+use serde::Serialize;
+
+#[derive(Identifiable, Queryable, Associations, Serialize, PartialEq, Debug, Clone)]
+#[primary_key(expense_id, person_id)]
+#[belongs_to(Expense)]
+#[belongs_to(Person)]
+pub struct ExpenseShare {
+    pub expense_id: uuid::Uuid,
+    pub person_id: uuid::Uuid,
+    pub value: i64,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// The shares of an expense, which are replaced together.
+#[derive(Serialize)]
+pub(super) struct ExpenseShares {
+    #[serde(skip)]
+    group_id: uuid::Uuid,
+    #[serde(skip)]
+    expense_id: uuid::Uuid,
+    shares: Vec<ExpenseShare>,
+}
+
+impl Audited for ExpenseShares {
+    const ENTITY: Entity = Entity::ExpenseShares;
+
+    fn entity_id(&self) -> uuid::Uuid {
+        self.expense_id
+    }
+
+    fn group_id(&self) -> Option<uuid::Uuid> {
+        Some(self.group_id)
+    }
+}
+
+impl ExpenseShares {
+    /// The snapshots telling how the shares of an expense were replaced, None when they weren't.
+    /// No shares at all is recorded as a creation or a deletion.
+    pub fn change(
+        group_id: uuid::Uuid,
+        expense_id: uuid::Uuid,
+        before: Vec<ExpenseShare>,
+        after: Vec<ExpenseShare>,
+    ) -> (Option<Self>, Option<Self>) {
+        // Only record an actual change
+        let key = |v: &[ExpenseShare]| {
+            let mut v = v.iter().map(|r| (r.person_id, r.value)).collect::<Vec<_>>();
+            v.sort();
+            v
+        };
+        if key(&before) == key(&after) {
+            return (None, None);
+        }
+        let snapshot = |shares: Vec<_>| {
+            if shares.is_empty() {
+                None
+            } else {
+                Some(ExpenseShares {
+                    group_id,
+                    expense_id,
+                    shares,
+                })
+            }
+        };
+        (snapshot(before), snapshot(after))
+    }
+}
+
+pub trait ExpenseShareRepository {
+    /// Find the expense's shares, except the ones of the persons in the trash.
+    fn find_by_expense(&self, expense: &Expense) -> anyhow::Result<Vec<ExpenseShare>>;
+
+    /// Find the shares of every expense, in the same order as the expenses.
+    /// The shares of the persons in the trash are left out.
+    fn find_by_expenses(&self, expenses: &[Expense]) -> anyhow::Result<Vec<Vec<ExpenseShare>>>;
+
+    /// Replace all the shares of an expense.
+    fn save_all(
+        &self,
+        expense_id: &uuid::Uuid,
+        new_shares: &[NewExpenseShare],
+        user_id: &uuid::Uuid,
+    ) -> anyhow::Result<()>;
+}
+
+impl<D: Database> ExpenseShareRepository for D {
+    fn find_by_expense(&self, expense: &Expense) -> anyhow::Result<Vec<ExpenseShare>> {
+        ExpenseShare::belonging_to(expense)
+            .filter(
+                expense_shares::person_id.eq_any(
+                    persons::table
+                        .filter(persons::deleted_at.is_null())
+                        .select(persons::id),
+                ),
+            )
+            .load(&*self.get()?)
+            .context(format!(
+                "Couldn't find this expense's ({}) shares",
+                expense.id
+            ))
+    }
+
+    fn find_by_expenses(&self, expenses: &[Expense]) -> anyhow::Result<Vec<Vec<ExpenseShare>>> {
+        ExpenseShare::belonging_to(expenses)
+            .filter(
+                expense_shares::person_id.eq_any(
+                    persons::table
+                        .filter(persons::deleted_at.is_null())
+                        .select(persons::id),
+                ),
+            )
+            .load(&*self.get()?)
+            .map(|v| v.grouped_by(expenses))
+            .context("Couldn't find these expenses' shares")
+    }
+
+    fn save_all(
+        &self,
+        expense_id: &uuid::Uuid,
+        new_shares: &[NewExpenseShare],
+        user_id: &uuid::Uuid,
+    ) -> anyhow::Result<()> {
+        let conn = self.get()?;
+        conn.transaction::<_, diesel::result::Error, _>(|| {
+            let group_id = expenses::table
+                .find(expense_id)
+                .select(expenses::group_id)
+                .first(&*conn)?;
+            let before = diesel::delete(expense_shares::table)
+                .filter(expense_shares::expense_id.eq(expense_id))
+                .get_results::<ExpenseShare>(&*conn)?;
+            let after = if new_shares.is_empty() {
+                Vec::new()
+            } else {
+                diesel::insert_into(expense_shares::table)
+                    .values(new_shares)
+                    .get_results::<ExpenseShare>(&*conn)?
+            };
+            let (before, after) = ExpenseShares::change(group_id, *expense_id, before, after);
+            activity::record(user_id, before.as_ref(), after.as_ref(), &conn)
+        })
+        .context(format!(
+            "Couldn't save this expense's ({}) shares to the database",
+            expense_id
+        ))
+    }
+}
+
+#[derive(Insertable)]
+#[table_name = "expense_shares"]
+pub struct NewExpenseShare {
+    pub expense_id: uuid::Uuid,
+    pub person_id: uuid::Uuid,
+    pub value: i64,
+}
@@ -1,50 +1,242 @@
-use super::{schema::groups, user::User, PostgresPool};
+use super::{
+    activity::{self, Audited},
+    group_member::GroupMember,
+    schema::{group_members, groups},
+    user::User,
+    Database,
+};
+use crate::domain::{activity::Entity, member::Role};
 use anyhow::Context;
 use diesel::prelude::*;
+use serde::Serialize;
 
-#[derive(Identifiable, Queryable, Associations, PartialEq, Debug)]
+#[derive(Identifiable, Queryable, Associations, Serialize, PartialEq, Debug, Clone)]
 #[belongs_to(User)]
 pub struct Group {
     pub id: uuid::Uuid,
+    /// The user who created the group. Every member can reach it.
     pub user_id: uuid::Uuid,
     pub name: String,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub updated_at: chrono::DateTime<chrono::Utc>,
+    /// The currency the group's balances are computed in.
+    pub currency: String,
+    /// When the group was moved to the trash, if it was.
+    pub deleted_at: Option<chrono::DateTime<chrono::Utc>>,
 }
 
-pub struct GroupRepository;
-impl GroupRepository {
-    pub fn find_by_user(user: &User, pool: &PostgresPool) -> anyhow::Result<Vec<Group>> {
-        Group::belonging_to(user)
-            .load(&pool.get()?)
+impl Audited for Group {
+    const ENTITY: Entity = Entity::Group;
+
+    fn entity_id(&self) -> uuid::Uuid {
+        self.id
+    }
+
+    fn group_id(&self) -> Option<uuid::Uuid> {
+        Some(self.id)
+    }
+}
+
+pub trait GroupRepository {
+    fn find_one(&self, id: &uuid::Uuid) -> anyhow::Result<Option<Group>>;
+
+    /// Find a group, even in the trash, for what it holds which can be reached from the trash.
+    fn find_one_including_deleted(&self, id: &uuid::Uuid) -> anyhow::Result<Option<Group>>;
+
+    /// Find a group if this user is one of its members, along with their role.
+    fn find_one_by_user(
+        &self,
+        id: &uuid::Uuid,
+        user_id: &uuid::Uuid,
+    ) -> anyhow::Result<Option<(Group, String)>>;
+
+    /// Find a group in the trash if this user is one of its members, along with their role.
+    fn find_deleted_one_by_user(
+        &self,
+        id: &uuid::Uuid,
+        user_id: &uuid::Uuid,
+    ) -> anyhow::Result<Option<(Group, String)>>;
+
+    /// Find the groups this user is a member of.
+    fn find_by_user(&self, user: &User) -> anyhow::Result<Vec<Group>>;
+
+    /// Find the groups in the trash this user is a member of, the latest deleted first.
+    fn find_deleted_by_user(&self, user: &User) -> anyhow::Result<Vec<Group>>;
+
+    /// Save a group along with its creator's membership, as its owner.
+    fn save(&self, new_group: &NewGroup) -> anyhow::Result<Group>;
+
+    fn update_one(&self, group: &UpdateGroup, user_id: &uuid::Uuid) -> anyhow::Result<()>;
+
+    /// Move a group to the trash, along with everything it holds.
+    fn delete_one(&self, id: &uuid::Uuid, user_id: &uuid::Uuid) -> anyhow::Result<()>;
+
+    /// Move a group back from the trash.
+    fn restore_one(&self, id: &uuid::Uuid, user_id: &uuid::Uuid) -> anyhow::Result<()>;
+}
+
+impl<D: Database> GroupRepository for D {
+    fn find_one(&self, id: &uuid::Uuid) -> anyhow::Result<Option<Group>> {
+        groups::table
+            .find(id)
+            .filter(groups::deleted_at.is_null())
+            .first(&*self.get()?)
+            .optional()
+            .context(format!("Couldn't find this group ({})", id))
+    }
+
+    fn find_one_including_deleted(&self, id: &uuid::Uuid) -> anyhow::Result<Option<Group>> {
+        groups::table
+            .find(id)
+            .first(&*self.get()?)
+            .optional()
+            .context(format!("Couldn't find this group ({})", id))
+    }
+
+    fn find_one_by_user(
+        &self,
+        id: &uuid::Uuid,
+        user_id: &uuid::Uuid,
+    ) -> anyhow::Result<Option<(Group, String)>> {
+        groups::table
+            .inner_join(group_members::table)
+            .filter(groups::id.eq(id))
+            .filter(groups::deleted_at.is_null())
+            .filter(group_members::user_id.eq(user_id))
+            .select((groups::all_columns, group_members::role))
+            .first(&*self.get()?)
+            .optional()
+            .context(format!(
+                "Couldn't find this user's ({}) group ({})",
+                user_id, id
+            ))
+    }
+
+    fn find_deleted_one_by_user(
+        &self,
+        id: &uuid::Uuid,
+        user_id: &uuid::Uuid,
+    ) -> anyhow::Result<Option<(Group, String)>> {
+        groups::table
+            .inner_join(group_members::table)
+            .filter(groups::id.eq(id))
+            .filter(groups::deleted_at.is_not_null())
+            .filter(group_members::user_id.eq(user_id))
+            .select((groups::all_columns, group_members::role))
+            .first(&*self.get()?)
+            .optional()
+            .context(format!(
+                "Couldn't find this user's ({}) deleted group ({})",
+                user_id, id
+            ))
+    }
+
+    fn find_by_user(&self, user: &User) -> anyhow::Result<Vec<Group>> {
+        groups::table
+            .inner_join(group_members::table)
+            .filter(group_members::user_id.eq(user.id))
+            .filter(groups::deleted_at.is_null())
+            .select(groups::all_columns)
+            .order(group_members::created_at)
+            .load(&*self.get()?)
             .context(format!("Couldn't find this user's ({}) groups", user.id))
     }
 
-    pub fn save(new_group: &NewGroup, pool: &PostgresPool) -> anyhow::Result<Group> {
-        diesel::insert_into(groups::table)
-            .values(new_group)
-            .get_result::<Group>(&pool.get()?)
-            .context("Couldn't save this group to the database")
+    fn find_deleted_by_user(&self, user: &User) -> anyhow::Result<Vec<Group>> {
+        groups::table
+            .inner_join(group_members::table)
+            .filter(group_members::user_id.eq(user.id))
+            .filter(groups::deleted_at.is_not_null())
+            .select(groups::all_columns)
+            .order(groups::deleted_at.desc())
+            .load(&*self.get()?)
+            .context(format!(
+                "Couldn't find this user's ({}) deleted groups",
+                user.id
+            ))
+    }
+
+    fn save(&self, new_group: &NewGroup) -> anyhow::Result<Group> {
+        let conn = self.get()?;
+        conn.transaction::<_, diesel::result::Error, _>(|| {
+            let group = diesel::insert_into(groups::table)
+                .values(new_group)
+                .get_result::<Group>(&*conn)?;
+            let member = diesel::insert_into(group_members::table)
+                .values((
+                    group_members::group_id.eq(group.id),
+                    group_members::user_id.eq(group.user_id),
+                    group_members::role.eq(Role::Owner.as_str()),
+                ))
+                .get_result::<GroupMember>(&*conn)?;
+            activity::record(&group.user_id, None, Some(&group), &conn)?;
+            activity::record(&group.user_id, None, Some(&member), &conn)?;
+            Ok(group)
+        })
+        .context("Couldn't save this group to the database")
     }
 
-    pub fn update_one(group: &UpdateGroup, pool: &PostgresPool) -> anyhow::Result<()> {
+    fn update_one(&self, group: &UpdateGroup, user_id: &uuid::Uuid) -> anyhow::Result<()> {
         if group.name.is_none() {
             return Ok(());
         }
 
-        diesel::update(groups::table.filter(groups::id.eq(group.id)))
-            .set(group)
-            .execute(&pool.get()?)
-            .context("Couldn't update this group to the database")
-            .map(|_| ())
+        let conn = self.get()?;
+        conn.transaction::<_, diesel::result::Error, _>(|| {
+            let before = groups::table
+                .find(group.id)
+                .for_update()
+                .first::<Group>(&*conn)
+                .optional()?;
+            let after = diesel::update(groups::table.filter(groups::id.eq(group.id)))
+                .set(group)
+                .get_result::<Group>(&*conn)
+                .optional()?;
+            activity::record(user_id, before.as_ref(), after.as_ref(), &conn)
+        })
+        .context("Couldn't update this group to the database")
     }
 
-    pub fn delete_one(id: &uuid::Uuid, pool: &PostgresPool) -> anyhow::Result<()> {
-        diesel::delete(groups::table)
-            .filter(groups::id.eq(id))
-            .execute(&pool.get()?)
-            .context(format!("Couldn't delete this group ({})", id))
-            .map(|_| ())
+    fn delete_one(&self, id: &uuid::Uuid, user_id: &uuid::Uuid) -> anyhow::Result<()> {
+        let conn = self.get()?;
+        conn.transaction::<_, diesel::result::Error, _>(|| {
+            let before = groups::table
+                .find(id)
+                .filter(groups::deleted_at.is_null())
+                .for_update()
+                .first::<Group>(&*conn)
+                .optional()?;
+            diesel::update(groups::table.find(id).filter(groups::deleted_at.is_null()))
+                .set(groups::deleted_at.eq(diesel::dsl::now))
+                .execute(&*conn)?;
+            activity::record(user_id, before.as_ref(), None, &conn)
+        })
+        .context(format!("Couldn't delete this group ({})", id))
+    }
+
+    fn restore_one(&self, id: &uuid::Uuid, user_id: &uuid::Uuid) -> anyhow::Result<()> {
+        let conn = self.get()?;
+        conn.transaction::<_, diesel::result::Error, _>(|| {
+            let before = groups::table
+                .find(id)
+                .filter(groups::deleted_at.is_not_null())
+                .for_update()
+                .first::<Group>(&*conn)
+                .optional()?;
+            let after = diesel::update(
+                groups::table
+                    .find(id)
+                    .filter(groups::deleted_at.is_not_null()),
+            )
+            .set(groups::deleted_at.eq(None::<chrono::DateTime<chrono::Utc>>))
+            .get_result::<Group>(&*conn)
+            .optional()?;
+            match (before, after) {
+                (Some(b), Some(a)) => activity::record_restore(user_id, &b, &a, &conn),
+                _ => Ok(()),
+            }
+        })
+        .context(format!("Couldn't restore this group ({})", id))
     }
 }
 
@@ -54,6 +246,7 @@ pub struct NewGroup {
     pub id: uuid::Uuid,
     pub user_id: uuid::Uuid,
     pub name: String,
+    pub currency: String,
 }
 
 #[derive(AsChangeset)]
@@ -0,0 +1,141 @@
+use super::{
+    activity::{self, Audited},
+    group::Group,
+    person::Person,
+    schema::{group_members, persons},
+    user::User,
+    Database,
+};
+use crate::domain::activity::Entity;
+use anyhow::Context;
+use diesel::prelude::*;
+use serde::Serialize;
+
+/// A user sharing a group.
+#[derive(Identifiable, Queryable, Associations, Serialize, PartialEq, Debug, Clone)]
+#[primary_key(group_id, user_id)]
+#[belongs_to(Group)]
+#[belongs_to(User)]
+pub struct GroupMember {
+    pub group_id: uuid::Uuid,
+    pub user_id: uuid::Uuid,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+    pub role: String,
+}
+
+impl Audited for GroupMember {
+    const ENTITY: Entity = Entity::Member;
+
+    fn entity_id(&self) -> uuid::Uuid {
+        self.user_id
+    }
+
+    fn group_id(&self) -> Option<uuid::Uuid> {
+        Some(self.group_id)
+    }
+}
+
+pub trait GroupMemberRepository {
+    fn find_by_group(&self, group: &Group) -> anyhow::Result<Vec<GroupMember>>;
+
+    /// Find the group's members and lock them until the end of the transaction,
+    /// so that the changes depending on the other members are made one at a time.
+    fn lock_by_group_id(&self, group_id: &uuid::Uuid) -> anyhow::Result<Vec<GroupMember>>;
+
+    fn update_role(
+        &self,
+        group_id: &uuid::Uuid,
+        user_id: &uuid::Uuid,
+        role: &str,
+        actor_id: &uuid::Uuid,
+    ) -> anyhow::Result<()>;
+
+    /// Remove a user from a group. The person they were in the group is kept but unlinked.
+    fn delete_one(
+        &self,
+        group_id: &uuid::Uuid,
+        user_id: &uuid::Uuid,
+        actor_id: &uuid::Uuid,
+    ) -> anyhow::Result<()>;
+}
+
+impl<D: Database> GroupMemberRepository for D {
+    fn find_by_group(&self, group: &Group) -> anyhow::Result<Vec<GroupMember>> {
+        GroupMember::belonging_to(group)
+            .order(group_members::created_at)
+            .load(&*self.get()?)
+            .context(format!("Couldn't find this group's ({}) members", group.id))
+    }
+
+    fn lock_by_group_id(&self, group_id: &uuid::Uuid) -> anyhow::Result<Vec<GroupMember>> {
+        group_members::table
+            .filter(group_members::group_id.eq(group_id))
+            .order(group_members::created_at)
+            .for_update()
+            .load(&*self.get()?)
+            .context(format!("Couldn't lock this group's ({}) members", group_id))
+    }
+
+    fn update_role(
+        &self,
+        group_id: &uuid::Uuid,
+        user_id: &uuid::Uuid,
+        role: &str,
+        actor_id: &uuid::Uuid,
+    ) -> anyhow::Result<()> {
+        let conn = self.get()?;
+        conn.transaction::<_, diesel::result::Error, _>(|| {
+            let before = group_members::table
+                .find((group_id, user_id))
+                .for_update()
+                .first::<GroupMember>(&*conn)
+                .optional()?;
+            let after = diesel::update(group_members::table.find((group_id, user_id)))
+                .set((
+                    group_members::role.eq(role),
+                    group_members::updated_at.eq(diesel::dsl::now),
+                ))
+                .get_result::<GroupMember>(&*conn)
+                .optional()?;
+            activity::record(actor_id, before.as_ref(), after.as_ref(), &conn)
+        })
+        .context(format!(
+            "Couldn't update this group's ({}) member ({})",
+            group_id, user_id
+        ))
+    }
+
+    fn delete_one(
+        &self,
+        group_id: &uuid::Uuid,
+        user_id: &uuid::Uuid,
+        actor_id: &uuid::Uuid,
+    ) -> anyhow::Result<()> {
+        let conn = self.get()?;
+        conn.transaction::<_, diesel::result::Error, _>(|| {
+            let linked = persons::table
+                .filter(persons::group_id.eq(group_id))
+                .filter(persons::user_id.eq(user_id))
+                .for_update()
+                .load::<Person>(&*conn)?;
+            for before in &linked {
+                let after = diesel::update(persons::table.find(before.id))
+                    .set((
+                        persons::user_id.eq(None::<uuid::Uuid>),
+                        persons::updated_at.eq(diesel::dsl::now),
+                    ))
+                    .get_result::<Person>(&*conn)?;
+                activity::record(actor_id, Some(before), Some(&after), &conn)?;
+            }
+            let before = diesel::delete(group_members::table.find((group_id, user_id)))
+                .get_result::<GroupMember>(&*conn)
+                .optional()?;
+            activity::record(actor_id, before.as_ref(), None, &conn)
+        })
+        .context(format!(
+            "Couldn't remove this group's ({}) member ({})",
+            group_id, user_id
+        ))
+    }
+}
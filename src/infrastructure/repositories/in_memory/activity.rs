@@ -0,0 +1,22 @@
+use super::InMemoryStore;
+use crate::infrastructure::repositories::{Activity, ActivityRepository, Group};
+
+impl ActivityRepository for InMemoryStore {
+    fn find_by_group(
+        &self,
+        group: &Group,
+        before: Option<i64>,
+        limit: i64,
+    ) -> anyhow::Result<Vec<Activity>> {
+        self.read(|t| {
+            t.activities
+                .iter()
+                .rev()
+                .filter(|a| a.group_id == group.id)
+                .filter(|a| before.is_none_or(|b| a.id < b))
+                .take(limit.max(0) as usize)
+                .cloned()
+                .collect()
+        })
+    }
+}
@@ -0,0 +1,107 @@
+use super::{unique_violation, InMemoryStore, Tables};
+use crate::infrastructure::repositories::{
+    Category, CategoryRepository, Group, NewCategory, UpdateCategory,
+};
+
+impl CategoryRepository for InMemoryStore {
+    fn find_one(&self, id: &uuid::Uuid) -> anyhow::Result<Option<Category>> {
+        self.read(|t| t.categories.iter().find(|c| c.id == *id).cloned())
+    }
+
+    fn find_one_by_user(
+        &self,
+        id: &uuid::Uuid,
+        user_id: &uuid::Uuid,
+    ) -> anyhow::Result<Option<(Category, String)>> {
+        self.read(|t| {
+            let category = t.categories.iter().find(|c| c.id == *id)?;
+            Some((category.clone(), t.role(&category.group_id?, user_id)?))
+        })
+    }
+
+    fn find_by_group(&self, group: &Group) -> anyhow::Result<Vec<Category>> {
+        self.read(|t| {
+            let mut categories = t
+                .categories
+                .iter()
+                .filter(|c| c.group_id.is_none_or(|id| id == group.id))
+                .cloned()
+                .collect::<Vec<_>>();
+            categories.sort_by(|a, b| {
+                (a.group_id.is_some(), &a.name).cmp(&(b.group_id.is_some(), &b.name))
+            });
+            categories
+        })
+    }
+
+    fn is_used(&self, id: &uuid::Uuid) -> anyhow::Result<bool> {
+        self.read(|t| t.expenses.iter().any(|e| e.category_id == Some(*id)))
+    }
+
+    fn save(&self, new_category: &NewCategory, user_id: &uuid::Uuid) -> anyhow::Result<Category> {
+        self.write(|t| {
+            let now = chrono::Utc::now();
+            let category = Category {
+                id: new_category.id,
+                group_id: Some(new_category.group_id),
+                name: new_category.name.clone(),
+                created_at: now,
+                updated_at: now,
+            };
+            check_name(t, &category)?;
+            t.categories.push(category.clone());
+            t.record_change(user_id, None, Some(&category))?;
+            Ok(category)
+        })
+    }
+
+    fn update_one(&self, category: &UpdateCategory, user_id: &uuid::Uuid) -> anyhow::Result<()> {
+        let name = match &category.name {
+            None => return Ok(()),
+            Some(n) => n,
+        };
+
+        self.write(|t| {
+            let row = match t.categories.iter_mut().find(|c| c.id == category.id) {
+                None => return Ok(()),
+                Some(c) => c,
+            };
+            let before = row.clone();
+            row.name = name.clone();
+            let after = row.clone();
+            check_name(t, &after)?;
+            t.record_change(user_id, Some(&before), Some(&after))
+        })
+    }
+
+    fn delete_one(&self, id: &uuid::Uuid, user_id: &uuid::Uuid) -> anyhow::Result<()> {
+        self.write(|t| {
+            // The expenses keep their categories
+            if t.expenses.iter().any(|e| e.category_id == Some(*id)) {
+                return Err(anyhow::anyhow!(
+                    "Couldn't delete this category ({}), some expenses belong to it",
+                    id
+                ));
+            }
+
+            match t.categories.iter().position(|c| c.id == *id) {
+                None => Ok(()),
+                Some(i) => {
+                    let before = t.categories.remove(i);
+                    t.record_change(user_id, Some(&before), None)
+                }
+            }
+        })
+    }
+}
+
+/// The categories of a group, as the built-in ones, have different names.
+fn check_name(t: &Tables, category: &Category) -> anyhow::Result<()> {
+    if t.categories
+        .iter()
+        .any(|c| c.id != category.id && c.group_id == category.group_id && c.name == category.name)
+    {
+        return Err(unique_violation("categories_group_id_name_idx"));
+    }
+    Ok(())
+}
@@ -0,0 +1,47 @@
+use super::InMemoryStore;
+use crate::infrastructure::repositories::{
+    EmailVerification, EmailVerificationRepository, NewEmailVerification,
+};
+
+impl EmailVerificationRepository for InMemoryStore {
+    fn find_one(&self, id: &uuid::Uuid) -> anyhow::Result<Option<EmailVerification>> {
+        self.read(|t| t.email_verifications.iter().find(|v| v.id == *id).cloned())
+    }
+
+    fn save(
+        &self,
+        new_email_verification: &NewEmailVerification,
+    ) -> anyhow::Result<EmailVerification> {
+        self.write(|t| {
+            let now = chrono::Utc::now();
+            let email_verification = EmailVerification {
+                id: new_email_verification.id,
+                user_id: new_email_verification.user_id,
+                email: new_email_verification.email.clone(),
+                secret_hash: new_email_verification.secret_hash.clone(),
+                expires_at: new_email_verification.expires_at,
+                used_at: None,
+                created_at: now,
+                updated_at: now,
+            };
+            t.email_verifications.push(email_verification.clone());
+            Ok(email_verification)
+        })
+    }
+
+    fn use_one(&self, id: &uuid::Uuid) -> anyhow::Result<bool> {
+        self.write(|t| {
+            let now = chrono::Utc::now();
+            let row = t
+                .email_verifications
+                .iter_mut()
+                .find(|v| v.id == *id && v.used_at.is_none() && v.expires_at > now);
+            Ok(row
+                .map(|v| {
+                    v.used_at = Some(now);
+                    v.updated_at = now;
+                })
+                .is_some())
+        })
+    }
+}
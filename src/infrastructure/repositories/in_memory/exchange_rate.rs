@@ -0,0 +1,60 @@
+use super::InMemoryStore;
+use crate::infrastructure::repositories::{ExchangeRate, ExchangeRateRepository, NewExchangeRate};
+
+impl ExchangeRateRepository for InMemoryStore {
+    fn find_all(&self) -> anyhow::Result<Vec<ExchangeRate>> {
+        self.read(|t| {
+            let mut rates = t.exchange_rates.clone();
+            rates.sort_by(|a, b| {
+                (&a.from_currency, &a.to_currency).cmp(&(&b.from_currency, &b.to_currency))
+            });
+            rates
+        })
+    }
+
+    fn find_between(&self, currency: &str, other: &str) -> anyhow::Result<Vec<ExchangeRate>> {
+        self.read(|t| {
+            t.exchange_rates
+                .iter()
+                .filter(|r| {
+                    (r.from_currency == currency && r.to_currency == other)
+                        || (r.from_currency == other && r.to_currency == currency)
+                })
+                .cloned()
+                .collect()
+        })
+    }
+
+    fn save_all(&self, new_rates: &[NewExchangeRate]) -> anyhow::Result<()> {
+        self.write(|t| {
+            let now = chrono::Utc::now();
+            for new in new_rates {
+                let existing = t.exchange_rates.iter_mut().find(|r| {
+                    r.from_currency == new.from_currency && r.to_currency == new.to_currency
+                });
+                match existing {
+                    Some(r) => {
+                        r.rate = new.rate;
+                        r.updated_at = now;
+                    }
+                    None => t.exchange_rates.push(ExchangeRate {
+                        from_currency: new.from_currency.clone(),
+                        to_currency: new.to_currency.clone(),
+                        rate: new.rate,
+                        created_at: now,
+                        updated_at: now,
+                    }),
+                }
+            }
+            Ok(())
+        })
+    }
+
+    fn delete_one(&self, from_currency: &str, to_currency: &str) -> anyhow::Result<()> {
+        self.write(|t| {
+            t.exchange_rates
+                .retain(|r| !(r.from_currency == from_currency && r.to_currency == to_currency));
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,188 @@
+use super::InMemoryStore;
+use crate::{
+    domain::expense::DateRange,
+    infrastructure::repositories::{
+        Expense, ExpenseRepository, Group, NewExpense, Person, UpdateExpense,
+    },
+};
+
+impl ExpenseRepository for InMemoryStore {
+    fn find_one(&self, id: &uuid::Uuid) -> anyhow::Result<Option<Expense>> {
+        self.read(|t| {
+            t.expenses
+                .iter()
+                .find(|e| e.id == *id && e.deleted_at.is_none())
+                .cloned()
+        })
+    }
+
+    fn find_one_by_user(
+        &self,
+        id: &uuid::Uuid,
+        user_id: &uuid::Uuid,
+    ) -> anyhow::Result<Option<(Expense, String)>> {
+        self.read(|t| {
+            let expense = t
+                .expenses
+                .iter()
+                .find(|e| e.id == *id && e.deleted_at.is_none())?;
+            Some((expense.clone(), t.role(&expense.group_id, user_id)?))
+        })
+    }
+
+    fn find_deleted_one_by_user(
+        &self,
+        id: &uuid::Uuid,
+        user_id: &uuid::Uuid,
+    ) -> anyhow::Result<Option<(Expense, String)>> {
+        self.read(|t| {
+            let expense = t
+                .expenses
+                .iter()
+                .find(|e| e.id == *id && e.deleted_at.is_some())?;
+            Some((expense.clone(), t.role(&expense.group_id, user_id)?))
+        })
+    }
+
+    fn find_by_person(&self, person: &Person) -> anyhow::Result<Vec<Expense>> {
+        self.read(|t| {
+            t.expenses
+                .iter()
+                .filter(|e| e.deleted_at.is_none())
+                .filter(|e| {
+                    t.expense_payers
+                        .iter()
+                        .any(|p| p.expense_id == e.id && p.person_id == person.id)
+                })
+                .cloned()
+                .collect()
+        })
+    }
+
+    fn find_by_group(&self, group: &Group, range: &DateRange) -> anyhow::Result<Vec<Expense>> {
+        self.read(|t| {
+            let mut expenses = t
+                .expenses
+                .iter()
+                .filter(|e| e.group_id == group.id && e.deleted_at.is_none())
+                .filter(|e| range.from.is_none_or(|from| e.occurred_on >= from))
+                .filter(|e| range.to.is_none_or(|to| e.occurred_on <= to))
+                .cloned()
+                .collect::<Vec<_>>();
+            expenses.sort_by_key(|e| (e.occurred_on, e.created_at));
+            expenses
+        })
+    }
+
+    fn find_deleted_by_group(&self, group: &Group) -> anyhow::Result<Vec<Expense>> {
+        self.read(|t| {
+            let mut expenses = t
+                .expenses
+                .iter()
+                .filter(|e| e.group_id == group.id && e.deleted_at.is_some())
+                .cloned()
+                .collect::<Vec<_>>();
+            expenses.sort_by_key(|e| std::cmp::Reverse(e.deleted_at));
+            expenses
+        })
+    }
+
+    fn save(&self, new_expense: &NewExpense, user_id: &uuid::Uuid) -> anyhow::Result<Expense> {
+        self.write(|t| {
+            let now = chrono::Utc::now();
+            let expense = Expense {
+                id: new_expense.id,
+                group_id: new_expense.group_id,
+                name: new_expense.name.clone(),
+                amount: new_expense.amount,
+                created_at: now,
+                updated_at: now,
+                split_mode: new_expense.split_mode.clone(),
+                currency: new_expense.currency.clone(),
+                exchange_rate: new_expense.exchange_rate,
+                occurred_on: new_expense.occurred_on,
+                category_id: new_expense.category_id,
+                deleted_at: None,
+            };
+            t.expenses.push(expense.clone());
+            t.record_change(user_id, None, Some(&expense))?;
+            Ok(expense)
+        })
+    }
+
+    fn update_one(&self, expense: &UpdateExpense, user_id: &uuid::Uuid) -> anyhow::Result<()> {
+        if expense.name.is_none()
+            && expense.amount.is_none()
+            && expense.split_mode.is_none()
+            && expense.currency.is_none()
+            && expense.occurred_on.is_none()
+            && expense.category_id.is_none()
+        {
+            return Ok(());
+        }
+
+        self.write(|t| {
+            let row = match t.expenses.iter_mut().find(|e| e.id == expense.id) {
+                None => return Ok(()),
+                Some(e) => e,
+            };
+            let before = row.clone();
+            if let Some(name) = &expense.name {
+                row.name = name.clone();
+            }
+            if let Some(amount) = expense.amount {
+                row.amount = amount;
+            }
+            if let Some(split_mode) = &expense.split_mode {
+                row.split_mode = split_mode.clone();
+            }
+            if let Some(currency) = &expense.currency {
+                row.currency = currency.clone();
+            }
+            if let Some(exchange_rate) = expense.exchange_rate {
+                row.exchange_rate = exchange_rate;
+            }
+            if let Some(occurred_on) = expense.occurred_on {
+                row.occurred_on = occurred_on;
+            }
+            if let Some(category_id) = expense.category_id {
+                row.category_id = Some(category_id);
+            }
+            let after = row.clone();
+            t.record_change(user_id, Some(&before), Some(&after))
+        })
+    }
+
+    fn delete_one(&self, id: &uuid::Uuid, user_id: &uuid::Uuid) -> anyhow::Result<()> {
+        self.write(|t| {
+            let row = match t
+                .expenses
+                .iter_mut()
+                .find(|e| e.id == *id && e.deleted_at.is_none())
+            {
+                None => return Ok(()),
+                Some(e) => e,
+            };
+            let before = row.clone();
+            row.deleted_at = Some(chrono::Utc::now());
+            t.record_change(user_id, Some(&before), None)
+        })
+    }
+
+    fn restore_one(&self, id: &uuid::Uuid, user_id: &uuid::Uuid) -> anyhow::Result<()> {
+        self.write(|t| {
+            let row = match t
+                .expenses
+                .iter_mut()
+                .find(|e| e.id == *id && e.deleted_at.is_some())
+            {
+                None => return Ok(()),
+                Some(e) => e,
+            };
+            let before = row.clone();
+            row.deleted_at = None;
+            let after = row.clone();
+            t.record_restore(user_id, &before, &after)
+        })
+    }
+}
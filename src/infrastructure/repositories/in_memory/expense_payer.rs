@@ -0,0 +1,60 @@
+use super::{unique_violation, InMemoryStore};
+use crate::infrastructure::repositories::{
+    Expense, ExpensePayer, ExpensePayerRepository, ExpensePayers, NewExpensePayer,
+};
+
+impl ExpensePayerRepository for InMemoryStore {
+    fn find_by_expense(&self, expense: &Expense) -> anyhow::Result<Vec<ExpensePayer>> {
+        self.read(|t| {
+            t.expense_payers
+                .iter()
+                .filter(|p| p.expense_id == expense.id)
+                .cloned()
+                .collect()
+        })
+    }
+
+    fn find_by_expenses(&self, expenses: &[Expense]) -> anyhow::Result<Vec<Vec<ExpensePayer>>> {
+        expenses.iter().map(|e| self.find_by_expense(e)).collect()
+    }
+
+    fn save_all(
+        &self,
+        expense_id: &uuid::Uuid,
+        new_payers: &[NewExpensePayer],
+        user_id: &uuid::Uuid,
+    ) -> anyhow::Result<()> {
+        self.write(|t| {
+            let group_id = t
+                .expenses
+                .iter()
+                .find(|e| e.id == *expense_id)
+                .map(|e| e.group_id)
+                .ok_or_else(|| anyhow::anyhow!("Couldn't find this expense ({})", expense_id))?;
+            let (before, kept) = t
+                .expense_payers
+                .drain(..)
+                .partition(|p| p.expense_id == *expense_id);
+            t.expense_payers = kept;
+
+            let now = chrono::Utc::now();
+            let mut after = Vec::<ExpensePayer>::new();
+            for p in new_payers {
+                if after.iter().any(|a| a.person_id == p.person_id) {
+                    return Err(unique_violation("expense_payers_pkey"));
+                }
+                after.push(ExpensePayer {
+                    expense_id: p.expense_id,
+                    person_id: p.person_id,
+                    amount: p.amount,
+                    created_at: now,
+                    updated_at: now,
+                });
+            }
+            t.expense_payers.extend(after.iter().cloned());
+
+            let (before, after) = ExpensePayers::change(group_id, *expense_id, before, after);
+            t.record_change(user_id, before.as_ref(), after.as_ref())
+        })
+    }
+}
@@ -0,0 +1,65 @@
+use super::{unique_violation, InMemoryStore};
+use crate::infrastructure::repositories::{
+    Expense, ExpenseShare, ExpenseShareRepository, ExpenseShares, NewExpenseShare,
+};
+
+impl ExpenseShareRepository for InMemoryStore {
+    fn find_by_expense(&self, expense: &Expense) -> anyhow::Result<Vec<ExpenseShare>> {
+        self.read(|t| {
+            t.expense_shares
+                .iter()
+                .filter(|s| s.expense_id == expense.id)
+                .filter(|s| {
+                    t.persons
+                        .iter()
+                        .any(|p| p.id == s.person_id && p.deleted_at.is_none())
+                })
+                .cloned()
+                .collect()
+        })
+    }
+
+    fn find_by_expenses(&self, expenses: &[Expense]) -> anyhow::Result<Vec<Vec<ExpenseShare>>> {
+        expenses.iter().map(|e| self.find_by_expense(e)).collect()
+    }
+
+    fn save_all(
+        &self,
+        expense_id: &uuid::Uuid,
+        new_shares: &[NewExpenseShare],
+        user_id: &uuid::Uuid,
+    ) -> anyhow::Result<()> {
+        self.write(|t| {
+            let group_id = t
+                .expenses
+                .iter()
+                .find(|e| e.id == *expense_id)
+                .map(|e| e.group_id)
+                .ok_or_else(|| anyhow::anyhow!("Couldn't find this expense ({})", expense_id))?;
+            let (before, kept) = t
+                .expense_shares
+                .drain(..)
+                .partition(|s| s.expense_id == *expense_id);
+            t.expense_shares = kept;
+
+            let now = chrono::Utc::now();
+            let mut after = Vec::<ExpenseShare>::new();
+            for s in new_shares {
+                if after.iter().any(|a| a.person_id == s.person_id) {
+                    return Err(unique_violation("expense_shares_pkey"));
+                }
+                after.push(ExpenseShare {
+                    expense_id: s.expense_id,
+                    person_id: s.person_id,
+                    value: s.value,
+                    created_at: now,
+                    updated_at: now,
+                });
+            }
+            t.expense_shares.extend(after.iter().cloned());
+
+            let (before, after) = ExpenseShares::change(group_id, *expense_id, before, after);
+            t.record_change(user_id, before.as_ref(), after.as_ref())
+        })
+    }
+}
@@ -0,0 +1,177 @@
+use super::{unique_violation, InMemoryStore, Tables};
+use crate::{
+    domain::member::Role,
+    infrastructure::repositories::{
+        Group, GroupMember, GroupRepository, NewGroup, UpdateGroup, User,
+    },
+};
+
+impl GroupRepository for InMemoryStore {
+    fn find_one(&self, id: &uuid::Uuid) -> anyhow::Result<Option<Group>> {
+        self.read(|t| {
+            t.groups
+                .iter()
+                .find(|g| g.id == *id && g.deleted_at.is_none())
+                .cloned()
+        })
+    }
+
+    fn find_one_including_deleted(&self, id: &uuid::Uuid) -> anyhow::Result<Option<Group>> {
+        self.read(|t| t.groups.iter().find(|g| g.id == *id).cloned())
+    }
+
+    fn find_one_by_user(
+        &self,
+        id: &uuid::Uuid,
+        user_id: &uuid::Uuid,
+    ) -> anyhow::Result<Option<(Group, String)>> {
+        self.read(|t| {
+            let group = t
+                .groups
+                .iter()
+                .find(|g| g.id == *id && g.deleted_at.is_none())?;
+            Some((group.clone(), t.role(id, user_id)?))
+        })
+    }
+
+    fn find_deleted_one_by_user(
+        &self,
+        id: &uuid::Uuid,
+        user_id: &uuid::Uuid,
+    ) -> anyhow::Result<Option<(Group, String)>> {
+        self.read(|t| {
+            let group = t
+                .groups
+                .iter()
+                .find(|g| g.id == *id && g.deleted_at.is_some())?;
+            let member = t
+                .group_members
+                .iter()
+                .find(|m| m.group_id == *id && m.user_id == *user_id)?;
+            Some((group.clone(), member.role.clone()))
+        })
+    }
+
+    fn find_by_user(&self, user: &User) -> anyhow::Result<Vec<Group>> {
+        self.read(|t| {
+            member_groups(t, &user.id)
+                .filter(|g| g.deleted_at.is_none())
+                .cloned()
+                .collect()
+        })
+    }
+
+    fn find_deleted_by_user(&self, user: &User) -> anyhow::Result<Vec<Group>> {
+        self.read(|t| {
+            let mut groups = member_groups(t, &user.id)
+                .filter(|g| g.deleted_at.is_some())
+                .cloned()
+                .collect::<Vec<_>>();
+            groups.sort_by_key(|g| std::cmp::Reverse(g.deleted_at));
+            groups
+        })
+    }
+
+    fn save(&self, new_group: &NewGroup) -> anyhow::Result<Group> {
+        self.write(|t| {
+            check_name(t, &new_group.id, &new_group.user_id, &new_group.name)?;
+
+            let now = chrono::Utc::now();
+            let group = Group {
+                id: new_group.id,
+                user_id: new_group.user_id,
+                name: new_group.name.clone(),
+                created_at: now,
+                updated_at: now,
+                currency: new_group.currency.clone(),
+                deleted_at: None,
+            };
+            let member = GroupMember {
+                group_id: group.id,
+                user_id: group.user_id,
+                created_at: now,
+                updated_at: now,
+                role: Role::Owner.as_str().to_string(),
+            };
+            t.groups.push(group.clone());
+            t.group_members.push(member.clone());
+            t.record_change(&group.user_id, None, Some(&group))?;
+            t.record_change(&group.user_id, None, Some(&member))?;
+            Ok(group)
+        })
+    }
+
+    fn update_one(&self, group: &UpdateGroup, user_id: &uuid::Uuid) -> anyhow::Result<()> {
+        let name = match &group.name {
+            None => return Ok(()),
+            Some(n) => n,
+        };
+
+        self.write(|t| {
+            let row = match t.groups.iter_mut().find(|g| g.id == group.id) {
+                None => return Ok(()),
+                Some(g) => g,
+            };
+            let before = row.clone();
+            row.name = name.clone();
+            let after = row.clone();
+            if after.deleted_at.is_none() {
+                check_name(t, &after.id, &after.user_id, &after.name)?;
+            }
+            t.record_change(user_id, Some(&before), Some(&after))
+        })
+    }
+
+    fn delete_one(&self, id: &uuid::Uuid, user_id: &uuid::Uuid) -> anyhow::Result<()> {
+        self.write(|t| {
+            let row = match t
+                .groups
+                .iter_mut()
+                .find(|g| g.id == *id && g.deleted_at.is_none())
+            {
+                None => return Ok(()),
+                Some(g) => g,
+            };
+            let before = row.clone();
+            row.deleted_at = Some(chrono::Utc::now());
+            t.record_change(user_id, Some(&before), None)
+        })
+    }
+
+    fn restore_one(&self, id: &uuid::Uuid, user_id: &uuid::Uuid) -> anyhow::Result<()> {
+        self.write(|t| {
+            let row = match t
+                .groups
+                .iter_mut()
+                .find(|g| g.id == *id && g.deleted_at.is_some())
+            {
+                None => return Ok(()),
+                Some(g) => g,
+            };
+            let before = row.clone();
+            row.deleted_at = None;
+            let after = row.clone();
+            check_name(t, &after.id, &after.user_id, &after.name)?;
+            t.record_restore(user_id, &before, &after)
+        })
+    }
+}
+
+/// The groups a user is a member of, in the order they joined them.
+fn member_groups<'a>(t: &'a Tables, user_id: &'a uuid::Uuid) -> impl Iterator<Item = &'a Group> {
+    t.group_members
+        .iter()
+        .filter(move |m| m.user_id == *user_id)
+        .filter_map(move |m| t.groups.iter().find(|g| g.id == m.group_id))
+}
+
+/// The groups out of the trash a user created have different names.
+fn check_name(t: &Tables, id: &uuid::Uuid, user_id: &uuid::Uuid, name: &str) -> anyhow::Result<()> {
+    if t.groups
+        .iter()
+        .any(|g| g.id != *id && g.user_id == *user_id && g.name == name && g.deleted_at.is_none())
+    {
+        return Err(unique_violation("groups_user_id_name_idx"));
+    }
+    Ok(())
+}
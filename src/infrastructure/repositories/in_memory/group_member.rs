@@ -0,0 +1,80 @@
+use super::InMemoryStore;
+use crate::infrastructure::repositories::{Group, GroupMember, GroupMemberRepository};
+
+impl GroupMemberRepository for InMemoryStore {
+    fn find_by_group(&self, group: &Group) -> anyhow::Result<Vec<GroupMember>> {
+        self.lock_by_group_id(&group.id)
+    }
+
+    // The transactions already run one at a time
+    fn lock_by_group_id(&self, group_id: &uuid::Uuid) -> anyhow::Result<Vec<GroupMember>> {
+        self.read(|t| {
+            t.group_members
+                .iter()
+                .filter(|m| m.group_id == *group_id)
+                .cloned()
+                .collect()
+        })
+    }
+
+    fn update_role(
+        &self,
+        group_id: &uuid::Uuid,
+        user_id: &uuid::Uuid,
+        role: &str,
+        actor_id: &uuid::Uuid,
+    ) -> anyhow::Result<()> {
+        self.write(|t| {
+            let row = match t
+                .group_members
+                .iter_mut()
+                .find(|m| m.group_id == *group_id && m.user_id == *user_id)
+            {
+                None => return Ok(()),
+                Some(m) => m,
+            };
+            let before = row.clone();
+            row.role = role.to_string();
+            row.updated_at = chrono::Utc::now();
+            let after = row.clone();
+            t.record_change(actor_id, Some(&before), Some(&after))
+        })
+    }
+
+    fn delete_one(
+        &self,
+        group_id: &uuid::Uuid,
+        user_id: &uuid::Uuid,
+        actor_id: &uuid::Uuid,
+    ) -> anyhow::Result<()> {
+        self.write(|t| {
+            let now = chrono::Utc::now();
+            let mut unlinked = Vec::new();
+            for p in t
+                .persons
+                .iter_mut()
+                .filter(|p| p.group_id == *group_id && p.user_id == Some(*user_id))
+            {
+                let before = p.clone();
+                p.user_id = None;
+                p.updated_at = now;
+                unlinked.push((before, p.clone()));
+            }
+            for (before, after) in &unlinked {
+                t.record_change(actor_id, Some(before), Some(after))?;
+            }
+
+            let i = t
+                .group_members
+                .iter()
+                .position(|m| m.group_id == *group_id && m.user_id == *user_id);
+            match i {
+                None => Ok(()),
+                Some(i) => {
+                    let before = t.group_members.remove(i);
+                    t.record_change(actor_id, Some(&before), None)
+                }
+            }
+        })
+    }
+}
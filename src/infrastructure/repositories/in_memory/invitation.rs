@@ -0,0 +1,137 @@
+use super::InMemoryStore;
+use crate::infrastructure::repositories::{
+    Group, GroupMember, Invitation, InvitationRepository, NewInvitation,
+};
+
+impl InvitationRepository for InMemoryStore {
+    fn find_one(&self, id: &uuid::Uuid) -> anyhow::Result<Option<Invitation>> {
+        self.read(|t| t.invitations.iter().find(|i| i.id == *id).cloned())
+    }
+
+    fn find_one_by_user(
+        &self,
+        id: &uuid::Uuid,
+        user_id: &uuid::Uuid,
+    ) -> anyhow::Result<Option<(Invitation, String)>> {
+        self.read(|t| {
+            let invitation = t.invitations.iter().find(|i| i.id == *id)?;
+            Some((invitation.clone(), t.role(&invitation.group_id, user_id)?))
+        })
+    }
+
+    fn find_pending_by_group(&self, group: &Group) -> anyhow::Result<Vec<Invitation>> {
+        let now = chrono::Utc::now();
+        self.read(|t| {
+            t.invitations
+                .iter()
+                .filter(|i| i.group_id == group.id)
+                .filter(|i| i.accepted_at.is_none() && i.revoked_at.is_none())
+                .filter(|i| i.expires_at > now)
+                .cloned()
+                .collect()
+        })
+    }
+
+    fn save(&self, new_invitation: &NewInvitation) -> anyhow::Result<Invitation> {
+        self.write(|t| {
+            let now = chrono::Utc::now();
+            let invitation = Invitation {
+                id: new_invitation.id,
+                group_id: new_invitation.group_id,
+                created_by: new_invitation.created_by,
+                secret_hash: new_invitation.secret_hash.clone(),
+                expires_at: new_invitation.expires_at,
+                accepted_by: None,
+                accepted_at: None,
+                revoked_at: None,
+                created_at: now,
+                updated_at: now,
+                role: new_invitation.role.clone(),
+            };
+            t.invitations.push(invitation.clone());
+            t.record_change(&invitation.created_by, None, Some(&invitation))?;
+            Ok(invitation)
+        })
+    }
+
+    fn accept(
+        &self,
+        invitation: &Invitation,
+        user_id: &uuid::Uuid,
+        person_id: Option<&uuid::Uuid>,
+    ) -> anyhow::Result<bool> {
+        self.write(|t| {
+            let pending = t.invitations.iter().position(|i| {
+                i.id == invitation.id && i.accepted_at.is_none() && i.revoked_at.is_none()
+            });
+            let claimed = person_id.map(|id| {
+                t.persons.iter().position(|p| {
+                    p.id == *id && p.group_id == invitation.group_id && p.user_id.is_none()
+                })
+            });
+            // Nothing changes when the invitation or the person is already taken
+            let (i, claimed) = match (pending, claimed) {
+                (None, _) | (_, Some(None)) => return Ok(false),
+                (Some(i), claimed) => (i, claimed.flatten()),
+            };
+
+            let now = chrono::Utc::now();
+            let used = &mut t.invitations[i];
+            used.accepted_by = Some(*user_id);
+            used.accepted_at = Some(now);
+            used.updated_at = now;
+            let used = used.clone();
+            t.record_change(user_id, Some(invitation), Some(&used))?;
+
+            if !t
+                .group_members
+                .iter()
+                .any(|m| m.group_id == invitation.group_id && m.user_id == *user_id)
+            {
+                let member = GroupMember {
+                    group_id: invitation.group_id,
+                    user_id: *user_id,
+                    created_at: now,
+                    updated_at: now,
+                    role: invitation.role.clone(),
+                };
+                t.group_members.push(member.clone());
+                t.record_change(user_id, None, Some(&member))?;
+            }
+            if let Some(p) = claimed {
+                let before = t.persons[p].clone();
+                if t.persons
+                    .iter()
+                    .any(|o| o.group_id == before.group_id && o.user_id == Some(*user_id))
+                {
+                    return Err(super::unique_violation("persons_group_id_user_id_idx"));
+                }
+                t.persons[p].user_id = Some(*user_id);
+                t.persons[p].updated_at = now;
+                let after = t.persons[p].clone();
+                t.record_change(user_id, Some(&before), Some(&after))?;
+            }
+
+            Ok(true)
+        })
+    }
+
+    fn revoke(&self, id: &uuid::Uuid, user_id: &uuid::Uuid) -> anyhow::Result<()> {
+        self.write(|t| {
+            let row = match t
+                .invitations
+                .iter_mut()
+                .find(|i| i.id == *id && i.accepted_at.is_none() && i.revoked_at.is_none())
+            {
+                None => return Ok(()),
+                Some(i) => i,
+            };
+            let before = row.clone();
+            let now = chrono::Utc::now();
+            row.revoked_at = Some(now);
+            row.updated_at = now;
+            let after = row.clone();
+            t.record_change(user_id, Some(&before), Some(&after))
+        })
+    }
+}
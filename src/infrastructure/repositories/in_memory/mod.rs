@@ -0,0 +1,257 @@
+//! A store keeping everything in memory, for the tests which shouldn't need a database.
+//! It behaves like the Postgres one, down to its unique constraints and its cascading deletes,
+//! and runs its transactions one at a time.
+//! That makes the races Postgres has to settle impossible, so the suites running on it
+//! (IN_MEMORY_STORE) don't replace the runs on Postgres, which the CI must keep doing.
+
+mod activity;
+mod category;
+mod email_verification;
+mod exchange_rate;
+mod expense;
+mod expense_payer;
+mod expense_share;
+mod group;
+mod group_member;
+mod invitation;
+#[cfg(test)]
+mod parity;
+mod password_reset;
+mod payment;
+mod person;
+mod session;
+mod statistics;
+mod trash;
+mod user;
+
+use super::{
+    activity::{Activity, Audited, NewActivity},
+    Category, EmailVerification, ExchangeRate, Expense, ExpensePayer, ExpenseShare, Group,
+    GroupMember, Invitation, PasswordReset, Payment, Person, Session, Store, User,
+};
+use diesel::result::{DatabaseErrorKind, Error as DieselError};
+use std::sync::{Mutex, MutexGuard};
+
+/// The rows of every table, in the order they were inserted.
+#[derive(Clone, Default)]
+struct Tables {
+    users: Vec<User>,
+    groups: Vec<Group>,
+    group_members: Vec<GroupMember>,
+    invitations: Vec<Invitation>,
+    persons: Vec<Person>,
+    categories: Vec<Category>,
+    expenses: Vec<Expense>,
+    expense_payers: Vec<ExpensePayer>,
+    expense_shares: Vec<ExpenseShare>,
+    payments: Vec<Payment>,
+    exchange_rates: Vec<ExchangeRate>,
+    activities: Vec<Activity>,
+    sessions: Vec<Session>,
+    password_resets: Vec<PasswordReset>,
+    email_verifications: Vec<EmailVerification>,
+}
+
+impl Tables {
+    /// The role of a user in a group out of the trash, if they're one of its members.
+    fn role(&self, group_id: &uuid::Uuid, user_id: &uuid::Uuid) -> Option<String> {
+        let group = self
+            .groups
+            .iter()
+            .find(|g| g.id == *group_id && g.deleted_at.is_none())?;
+        self.group_members
+            .iter()
+            .find(|m| m.group_id == group.id && m.user_id == *user_id)
+            .map(|m| m.role.clone())
+    }
+
+    /// Record a change made to a row of a group, as the Postgres repositories do.
+    fn record(&mut self, entry: serde_json::Result<Option<NewActivity>>) -> anyhow::Result<()> {
+        let entry = match entry? {
+            Some(e) => e,
+            None => return Ok(()),
+        };
+
+        let id = self.activities.last().map_or(1, |a| a.id + 1);
+        self.activities.push(Activity {
+            id,
+            group_id: entry.group_id,
+            user_id: Some(entry.user_id),
+            action: entry.action,
+            entity_type: entry.entity_type,
+            entity_id: entry.entity_id,
+            before: entry.before,
+            after: entry.after,
+            created_at: chrono::Utc::now(),
+        });
+        Ok(())
+    }
+
+    fn record_change<T: Audited>(
+        &mut self,
+        user_id: &uuid::Uuid,
+        before: Option<&T>,
+        after: Option<&T>,
+    ) -> anyhow::Result<()> {
+        self.record(NewActivity::change(user_id, before, after))
+    }
+
+    fn record_restore<T: Audited>(
+        &mut self,
+        user_id: &uuid::Uuid,
+        before: &T,
+        after: &T,
+    ) -> anyhow::Result<()> {
+        self.record(NewActivity::restore(user_id, before, after))
+    }
+}
+
+pub struct InMemoryStore {
+    tables: Mutex<Tables>,
+}
+
+impl InMemoryStore {
+    /// An empty store, but for the built-in categories.
+    pub fn new() -> Self {
+        let now = chrono::Utc::now();
+        let built_in = BUILT_IN_CATEGORIES
+            .iter()
+            .enumerate()
+            .map(|(i, name)| Category {
+                id: uuid::Uuid::parse_str(&format!("00000000-0000-0000-0000-{:012}", i + 1))
+                    .expect("Invalid built-in category id"),
+                group_id: None,
+                name: name.to_string(),
+                created_at: now,
+                updated_at: now,
+            })
+            .collect();
+
+        InMemoryStore {
+            tables: Mutex::new(Tables {
+                categories: built_in,
+                ..Tables::default()
+            }),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Tables> {
+        self.tables
+            .lock()
+            .expect("A thread panicked while changing the in-memory store")
+    }
+
+    fn read<T>(&self, f: impl FnOnce(&Tables) -> T) -> anyhow::Result<T> {
+        Ok(f(&self.lock()))
+    }
+
+    /// Change the tables all at once, or not at all when it fails.
+    fn write<T>(&self, f: impl FnOnce(&mut Tables) -> anyhow::Result<T>) -> anyhow::Result<T> {
+        let mut tables = self.lock();
+        let mut changed = tables.clone();
+        let res = f(&mut changed)?;
+        *tables = changed;
+        Ok(res)
+    }
+
+    /// Make a user's password look hashed with the former global salt, as the migrated ones.
+    #[cfg(test)]
+    pub(crate) fn forget_password_hashed_at(&self, user_id: &uuid::Uuid) {
+        self.lock()
+            .users
+            .iter_mut()
+            .filter(|u| u.id == *user_id)
+            .for_each(|u| u.password_hashed_at = None);
+    }
+}
+
+impl Default for InMemoryStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Store for InMemoryStore {
+    fn run_transaction(
+        &self,
+        f: &mut dyn FnMut(&dyn Store) -> anyhow::Result<()>,
+    ) -> anyhow::Result<()> {
+        // The other transactions wait for this one to end
+        let mut tables = self.lock();
+        let tx = InMemoryStore {
+            tables: Mutex::new(tables.clone()),
+        };
+        f(&tx)?;
+        *tables = tx.tables.into_inner().expect("The transaction panicked");
+        Ok(())
+    }
+}
+
+/// The categories every group can use, as inserted by their migration.
+const BUILT_IN_CATEGORIES: [&str; 7] = [
+    "Food",
+    "Transport",
+    "Housing",
+    "Leisure",
+    "Health",
+    "Shopping",
+    "Other",
+];
+
+/// The error Postgres gives when a unique constraint fails.
+fn unique_violation(constraint: &str) -> anyhow::Error {
+    DieselError::DatabaseError(
+        DatabaseErrorKind::UniqueViolation,
+        Box::new(format!(
+            "duplicate key value violates unique constraint \"{}\"",
+            constraint
+        )),
+    )
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::repositories::{self, NewUser, UserRepository};
+
+    fn new_user(email: &str) -> NewUser {
+        NewUser {
+            id: uuid::Uuid::new_v4(),
+            email: email.to_string(),
+            password: "password".to_string(),
+        }
+    }
+
+    #[test]
+    fn transactions_should_be_rolled_back_when_they_fail() {
+        let db = InMemoryStore::new();
+
+        let res = repositories::transaction(&db, |tx| {
+            UserRepository::save(tx, &new_user("first@test.com"))?;
+            Err::<(), _>(anyhow::anyhow!("Failed"))
+        });
+        repositories::transaction(&db, |tx| {
+            UserRepository::save(tx, &new_user("second@test.com")).map(|_| ())
+        })
+        .unwrap();
+
+        assert!(res.is_err());
+        assert!(UserRepository::find_one_by_email(&db, "first@test.com")
+            .unwrap()
+            .is_none());
+        assert!(UserRepository::find_one_by_email(&db, "second@test.com")
+            .unwrap()
+            .is_some());
+    }
+
+    #[test]
+    fn unique_constraints_should_be_reported_as_postgres_does() {
+        let db = InMemoryStore::new();
+
+        UserRepository::save(&db, &new_user("user@test.com")).unwrap();
+        let res = UserRepository::save(&db, &new_user("user@test.com"));
+
+        assert!(repositories::is_unique_violation(&res.unwrap_err()));
+    }
+}
@@ -0,0 +1,512 @@
+//! The same scenarios run on the in-memory store and on Postgres, which it must behave like.
+//! Postgres is skipped along with the integration suite's when IN_MEMORY_STORE is set,
+//! which the CI must not do: only there are the two stores compared.
+
+use super::InMemoryStore;
+use crate::{
+    domain::{
+        expense::{DateRange, Participant, Payer, SplitMode},
+        member::Role,
+        services::{
+            AddExpense, CategoryService, ExpenseService, GroupService, MemberService, Money,
+        },
+    },
+    infrastructure::{
+        config::Settings,
+        repositories::{self, PostgresPool, Store},
+    },
+};
+use chrono::NaiveDate;
+use std::{collections::HashMap, sync::Mutex};
+
+lazy_static::lazy_static! {
+    /// The scenarios take turns on Postgres, as the trash purge empties the whole trash.
+    static ref POSTGRES: Option<Mutex<PostgresPool>> = {
+        if std::env::var("IN_MEMORY_STORE").is_ok() {
+            return None;
+        }
+        let pool = repositories::get_pool(&Settings::for_tests()).expect("Failed to connect to Postgres.");
+        diesel_migrations::run_pending_migrations(&*pool.get().expect("Failed to get a connection."))
+            .expect("Failed to run the migrations.");
+        Some(Mutex::new(pool))
+    };
+}
+
+/// Run a scenario on a fresh in-memory store, then on Postgres.
+fn stores(scenario: impl Fn(&str, &dyn Store)) {
+    scenario("in-memory", &InMemoryStore::new());
+    if let Some(pool) = &*POSTGRES {
+        let pool = pool.lock().unwrap_or_else(|e| e.into_inner());
+        scenario("postgres", &*pool);
+    }
+}
+
+fn name() -> String {
+    uuid::Uuid::new_v4().to_string()[..8].to_string()
+}
+
+fn user(db: &dyn Store) -> uuid::Uuid {
+    let email = format!("{}@test.com", uuid::Uuid::new_v4());
+    let new_user = repositories::NewUser {
+        id: uuid::Uuid::new_v4(),
+        email: email.clone(),
+        password: "password".to_string(),
+    };
+    let id = repositories::UserRepository::save(db, &new_user)
+        .expect("Failed to save the user.")
+        .id;
+    repositories::UserRepository::verify_email(db, &id, &email).expect("Failed to verify.");
+    id
+}
+
+fn person(
+    group: &repositories::Group,
+    name: &str,
+    owner: &uuid::Uuid,
+    db: &dyn Store,
+) -> uuid::Uuid {
+    let resources = Money {
+        amount: 100,
+        currency: None,
+    };
+    GroupService::add_person(&group.id, name.to_string(), &resources, owner, db)
+        .expect("Failed to add the person.")
+        .id
+}
+
+/// An expense split equally between the payers.
+fn expense(
+    group: &repositories::Group,
+    (amount, currency): (i64, &str),
+    payers: &[(uuid::Uuid, i64)],
+    occurred_on: (i32, u32, u32),
+    category_id: Option<uuid::Uuid>,
+    owner: &uuid::Uuid,
+    db: &dyn Store,
+) -> uuid::Uuid {
+    let new = AddExpense {
+        group_id: group.id,
+        name: name(),
+        amount,
+        currency: Some(currency.to_string()),
+        payers: payers
+            .iter()
+            .map(|&(person_id, amount)| Payer { person_id, amount })
+            .collect(),
+        split_mode: SplitMode::Equal,
+        participants: payers
+            .iter()
+            .map(|&(person_id, _)| Participant {
+                person_id,
+                value: 0,
+            })
+            .collect(),
+        occurred_on: NaiveDate::from_ymd_opt(occurred_on.0, occurred_on.1, occurred_on.2),
+        category_id,
+    };
+    ExpenseService::add_expense(new, owner, db)
+        .expect("Failed to add the expense.")
+        .id
+}
+
+#[test]
+fn statistics_should_be_the_same_in_both_stores() {
+    stores(|store, db| {
+        // Arrange
+        let owner = user(db);
+        let group = GroupService::add_group(name(), Some("CHF".to_string()), &owner, db)
+            .expect("Failed to add the group.");
+        let alice = person(&group, "Alice", &owner, db);
+        let bob = person(&group, "Bob", &owner, db);
+        let gifts = CategoryService::add_category(&group.id, "Gifts".to_string(), &owner, db)
+            .expect("Failed to add the category.")
+            .id;
+        repositories::ExchangeRateRepository::save_all(
+            db,
+            &[repositories::NewExchangeRate {
+                from_currency: "GBP".to_string(),
+                to_currency: "CHF".to_string(),
+                rate: 1.25,
+            }],
+        )
+        .expect("Failed to save the rate.");
+        let names: HashMap<_, _> = vec![(alice, "Alice"), (bob, "Bob"), (gifts, "Gifts")]
+            .into_iter()
+            .collect();
+
+        expense(
+            &group,
+            (3000, "CHF"),
+            &[(alice, 3000)],
+            (2026, 1, 15),
+            Some(gifts),
+            &owner,
+            db,
+        );
+        expense(
+            &group,
+            (1000, "CHF"),
+            &[(bob, 1000)],
+            (2026, 1, 20),
+            None,
+            &owner,
+            db,
+        );
+        expense(
+            &group,
+            (5000, "GBP"),
+            &[(alice, 2000), (bob, 3000)],
+            (2026, 2, 3),
+            Some(gifts),
+            &owner,
+            db,
+        );
+        // The expenses in the trash don't count
+        let trashed = expense(
+            &group,
+            (7000, "CHF"),
+            &[(alice, 7000)],
+            (2026, 3, 1),
+            None,
+            &owner,
+            db,
+        );
+        ExpenseService::remove_expense(&trashed, &owner, db).expect("Failed to remove.");
+
+        let all = DateRange::default();
+        let since = DateRange {
+            from: NaiveDate::from_ymd_opt(2026, 1, 16),
+            to: None,
+        };
+
+        // Act
+        let by_category = repositories::StatisticsRepository::by_category(db, &group, &all)
+            .expect("Failed to sum by category.");
+        let by_person = repositories::StatisticsRepository::by_person(db, &group, &all)
+            .expect("Failed to sum by person.");
+        let by_month = repositories::StatisticsRepository::by_month(db, &group, &all)
+            .expect("Failed to sum by month.");
+        let by_month_since = repositories::StatisticsRepository::by_month(db, &group, &since)
+            .expect("Failed to sum by month.");
+
+        // Assert
+        let mut categories = by_category
+            .iter()
+            .map(|s| {
+                let name = s.category_id.map(|id| names[&id]);
+                (name, s.currency.as_str(), s.exchange_rate, s.amount)
+            })
+            .collect::<Vec<_>>();
+        // The uncategorized ones come last
+        assert_eq!(None, categories.last().unwrap().0, "{}", store);
+        categories.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert_eq!(
+            vec![
+                (None, "CHF", 1.0, 1000),
+                (Some("Gifts"), "CHF", 1.0, 3000),
+                (Some("Gifts"), "GBP", 1.25, 5000),
+            ],
+            categories,
+            "{}",
+            store
+        );
+
+        let mut persons = by_person
+            .iter()
+            .map(|s| (names[&s.person_id], s.currency.as_str(), s.amount))
+            .collect::<Vec<_>>();
+        persons.sort();
+        assert_eq!(
+            vec![
+                ("Alice", "CHF", 3000),
+                ("Alice", "GBP", 2000),
+                ("Bob", "CHF", 1000),
+                ("Bob", "GBP", 3000),
+            ],
+            persons,
+            "{}",
+            store
+        );
+
+        let months = |subtotals: &[repositories::MonthSubtotal]| {
+            subtotals
+                .iter()
+                .map(|s| (s.month.to_string(), s.currency.clone(), s.amount))
+                .collect::<Vec<_>>()
+        };
+        let month = |m: &str, c: &str, a| (m.to_string(), c.to_string(), a);
+        assert_eq!(
+            vec![
+                month("2026-01-01", "CHF", 4000),
+                month("2026-02-01", "GBP", 5000)
+            ],
+            months(&by_month),
+            "{}",
+            store
+        );
+        assert_eq!(
+            vec![
+                month("2026-01-01", "CHF", 1000),
+                month("2026-02-01", "GBP", 5000)
+            ],
+            months(&by_month_since),
+            "{}",
+            store
+        );
+    });
+}
+
+#[test]
+fn the_trash_purge_should_be_the_same_in_both_stores() {
+    stores(|store, db| {
+        // Arrange
+        let owner = user(db);
+        let group =
+            GroupService::add_group(name(), None, &owner, db).expect("Failed to add the group.");
+        let alice = person(&group, "Alice", &owner, db);
+        let bob = person(&group, "Bob", &owner, db);
+        let carol = person(&group, "Carol", &owner, db);
+        let shared = expense(
+            &group,
+            (900, "EUR"),
+            &[(alice, 450), (carol, 450)],
+            (2026, 1, 1),
+            None,
+            &owner,
+            db,
+        );
+        let kept = expense(
+            &group,
+            (300, "EUR"),
+            &[(alice, 300)],
+            (2026, 1, 1),
+            None,
+            &owner,
+            db,
+        );
+        let removed = expense(
+            &group,
+            (300, "EUR"),
+            &[(bob, 300)],
+            (2026, 1, 1),
+            None,
+            &owner,
+            db,
+        );
+        let payment = ExpenseService::add_payment(
+            &group.id,
+            &bob,
+            &carol,
+            &Money {
+                amount: 100,
+                currency: None,
+            },
+            &owner,
+            db,
+        )
+        .expect("Failed to add the payment.")
+        .id;
+        let trashed_group =
+            GroupService::add_group(name(), None, &owner, db).expect("Failed to add the group.");
+        let dave = person(&trashed_group, "Dave", &owner, db);
+        let gifts =
+            CategoryService::add_category(&trashed_group.id, "Gifts".to_string(), &owner, db)
+                .expect("Failed to add the category.")
+                .id;
+
+        // Carol goes with the expense she paid for
+        GroupService::remove_person(&carol, &owner, db).expect("Failed to remove.");
+        ExpenseService::remove_expense(&removed, &owner, db).expect("Failed to remove.");
+        GroupService::remove_group(&trashed_group.id, &owner, db).expect("Failed to remove.");
+
+        // Act
+        let purged = repositories::TrashRepository::purge(db, &chrono::Utc::now())
+            .expect("Failed to purge.");
+
+        // Assert
+        // Postgres purges the whole suite's trash, which holds at least this one
+        assert!(purged.groups >= 1, "{}", store);
+        assert!(purged.persons >= 1, "{}", store);
+        assert!(purged.expenses >= 2, "{}", store);
+
+        let found = |id: &uuid::Uuid| repositories::ExpenseRepository::find_one(db, id).unwrap();
+        assert!(found(&kept).is_some(), "{}", store);
+        assert!(found(&shared).is_none(), "{}", store);
+        assert!(found(&removed).is_none(), "{}", store);
+        assert!(
+            repositories::ExpenseRepository::find_deleted_one_by_user(db, &shared, &owner)
+                .unwrap()
+                .is_none(),
+            "{}",
+            store
+        );
+        let persons = |id: &uuid::Uuid| repositories::PersonRepository::find_one(db, id).unwrap();
+        assert!(persons(&alice).is_some(), "{}", store);
+        assert!(persons(&carol).is_none(), "{}", store);
+        assert!(persons(&dave).is_none(), "{}", store);
+        assert!(
+            repositories::PersonRepository::find_deleted_one_by_user(db, &carol, &owner)
+                .unwrap()
+                .is_none(),
+            "{}",
+            store
+        );
+        // Everything the purged rows held goes with them
+        assert!(
+            repositories::PaymentRepository::find_one(db, &payment)
+                .unwrap()
+                .is_none(),
+            "{}",
+            store
+        );
+        assert!(
+            repositories::CategoryRepository::find_one(db, &gifts)
+                .unwrap()
+                .is_none(),
+            "{}",
+            store
+        );
+        assert!(
+            repositories::GroupRepository::find_deleted_one_by_user(db, &trashed_group.id, &owner)
+                .unwrap()
+                .is_none(),
+            "{}",
+            store
+        );
+        assert!(
+            repositories::GroupRepository::find_one(db, &group.id)
+                .unwrap()
+                .is_some(),
+            "{}",
+            store
+        );
+    });
+}
+
+#[test]
+fn rows_should_be_found_with_the_viewer_role_in_both_stores() {
+    let config = Settings::for_tests();
+    stores(|store, db| {
+        // Arrange
+        let owner = user(db);
+        let viewer = user(db);
+        let stranger = user(db);
+        let group =
+            GroupService::add_group(name(), None, &owner, db).expect("Failed to add the group.");
+        let token = MemberService::create_invitation(
+            &group.id,
+            3600,
+            Role::Viewer,
+            &owner,
+            config.security(),
+            db,
+        )
+        .expect("Failed to invite.");
+        MemberService::accept_invitation(&token, None, &viewer, config.security(), db)
+            .expect("Failed to accept.");
+        MemberService::create_invitation(
+            &group.id,
+            3600,
+            Role::Editor,
+            &owner,
+            config.security(),
+            db,
+        )
+        .expect("Failed to invite.");
+        let alice = person(&group, "Alice", &owner, db);
+        let bob = person(&group, "Bob", &owner, db);
+        let spent = expense(
+            &group,
+            (300, "EUR"),
+            &[(alice, 300)],
+            (2026, 1, 1),
+            None,
+            &owner,
+            db,
+        );
+        let payment = ExpenseService::add_payment(
+            &group.id,
+            &bob,
+            &alice,
+            &Money {
+                amount: 100,
+                currency: None,
+            },
+            &owner,
+            db,
+        )
+        .expect("Failed to add the payment.")
+        .id;
+        let gifts = CategoryService::add_category(&group.id, "Gifts".to_string(), &owner, db)
+            .expect("Failed to add the category.")
+            .id;
+        let invitation = repositories::InvitationRepository::find_pending_by_group(db, &group)
+            .expect("Failed to find the invitations.")
+            .pop()
+            .expect("No pending invitation.")
+            .id;
+
+        // Act
+        let roles = |user: &uuid::Uuid| {
+            vec![
+                repositories::GroupRepository::find_one_by_user(db, &group.id, user)
+                    .unwrap()
+                    .map(|(_, r)| r),
+                repositories::PersonRepository::find_one_by_user(db, &alice, user)
+                    .unwrap()
+                    .map(|(_, r)| r),
+                repositories::ExpenseRepository::find_one_by_user(db, &spent, user)
+                    .unwrap()
+                    .map(|(_, r)| r),
+                repositories::PaymentRepository::find_one_by_user(db, &payment, user)
+                    .unwrap()
+                    .map(|(_, r)| r),
+                repositories::CategoryRepository::find_one_by_user(db, &gifts, user)
+                    .unwrap()
+                    .map(|(_, r)| r),
+                repositories::InvitationRepository::find_one_by_user(db, &invitation, user)
+                    .unwrap()
+                    .map(|(_, r)| r),
+            ]
+        };
+
+        // Assert
+        let role = |r: Role| Some(r.to_string());
+        assert_eq!(vec![role(Role::Owner); 6], roles(&owner), "{}", store);
+        assert_eq!(vec![role(Role::Viewer); 6], roles(&viewer), "{}", store);
+        assert_eq!(vec![None; 6], roles(&stranger), "{}", store);
+
+        /* --- The rows in the trash are only found as deleted ones --- */
+        GroupService::remove_person(&bob, &owner, db).expect("Failed to remove.");
+        ExpenseService::remove_expense(&spent, &owner, db).expect("Failed to remove.");
+
+        assert!(
+            repositories::PersonRepository::find_one_by_user(db, &bob, &viewer)
+                .unwrap()
+                .is_none()
+        );
+        assert_eq!(
+            Some(Role::Viewer.to_string()),
+            repositories::PersonRepository::find_deleted_one_by_user(db, &bob, &viewer)
+                .unwrap()
+                .map(|(_, r)| r),
+            "{}",
+            store
+        );
+        assert_eq!(
+            Some(Role::Viewer.to_string()),
+            repositories::ExpenseRepository::find_deleted_one_by_user(db, &spent, &viewer)
+                .unwrap()
+                .map(|(_, r)| r),
+            "{}",
+            store
+        );
+        assert!(
+            repositories::ExpenseRepository::find_deleted_one_by_user(db, &spent, &stranger)
+                .unwrap()
+                .is_none(),
+            "{}",
+            store
+        );
+    });
+}
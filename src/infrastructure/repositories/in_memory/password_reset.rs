@@ -0,0 +1,71 @@
+use super::InMemoryStore;
+use crate::infrastructure::repositories::{
+    NewPasswordReset, PasswordReset, PasswordResetRepository,
+};
+
+impl PasswordResetRepository for InMemoryStore {
+    fn find_one(&self, id: &uuid::Uuid) -> anyhow::Result<Option<PasswordReset>> {
+        self.read(|t| t.password_resets.iter().find(|r| r.id == *id).cloned())
+    }
+
+    fn save(&self, new_password_reset: &NewPasswordReset) -> anyhow::Result<PasswordReset> {
+        self.write(|t| {
+            let now = chrono::Utc::now();
+            let password_reset = PasswordReset {
+                id: new_password_reset.id,
+                user_id: new_password_reset.user_id,
+                secret_hash: new_password_reset.secret_hash.clone(),
+                expires_at: new_password_reset.expires_at,
+                used_at: None,
+                created_at: now,
+                updated_at: now,
+            };
+            t.password_resets.push(password_reset.clone());
+            Ok(password_reset)
+        })
+    }
+
+    fn use_one(&self, id: &uuid::Uuid) -> anyhow::Result<bool> {
+        self.write(|t| {
+            let now = chrono::Utc::now();
+            let row = t
+                .password_resets
+                .iter_mut()
+                .find(|r| r.id == *id && r.used_at.is_none() && r.expires_at > now);
+            Ok(row
+                .map(|r| {
+                    r.used_at = Some(now);
+                    r.updated_at = now;
+                })
+                .is_some())
+        })
+    }
+
+    fn expire_by_user(&self, user_id: &uuid::Uuid) -> anyhow::Result<()> {
+        self.write(|t| {
+            let now = chrono::Utc::now();
+            t.password_resets
+                .iter_mut()
+                .filter(|r| r.user_id == *user_id && r.used_at.is_none() && r.expires_at > now)
+                .for_each(|r| {
+                    r.expires_at = now;
+                    r.updated_at = now;
+                });
+            Ok(())
+        })
+    }
+
+    fn count_by_email_since(
+        &self,
+        email: &str,
+        since: &chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<i64> {
+        self.read(|t| {
+            let user = t.users.iter().find(|u| u.email == email);
+            t.password_resets
+                .iter()
+                .filter(|r| user.is_some_and(|u| u.id == r.user_id) && r.created_at >= *since)
+                .count() as i64
+        })
+    }
+}
@@ -0,0 +1,63 @@
+use super::InMemoryStore;
+use crate::infrastructure::repositories::{Group, NewPayment, Payment, PaymentRepository};
+
+impl PaymentRepository for InMemoryStore {
+    fn find_one(&self, id: &uuid::Uuid) -> anyhow::Result<Option<Payment>> {
+        self.read(|t| t.payments.iter().find(|p| p.id == *id).cloned())
+    }
+
+    fn find_one_by_user(
+        &self,
+        id: &uuid::Uuid,
+        user_id: &uuid::Uuid,
+    ) -> anyhow::Result<Option<(Payment, String)>> {
+        self.read(|t| {
+            let payment = t.payments.iter().find(|p| p.id == *id)?;
+            Some((payment.clone(), t.role(&payment.group_id, user_id)?))
+        })
+    }
+
+    fn find_by_group(&self, group: &Group) -> anyhow::Result<Vec<Payment>> {
+        self.read(|t| {
+            let deleted = |id: &uuid::Uuid| {
+                t.persons
+                    .iter()
+                    .any(|p| p.id == *id && p.deleted_at.is_some())
+            };
+            t.payments
+                .iter()
+                .filter(|p| p.group_id == group.id)
+                .filter(|p| !deleted(&p.from_person_id) && !deleted(&p.to_person_id))
+                .cloned()
+                .collect()
+        })
+    }
+
+    fn save(&self, new_payment: &NewPayment, user_id: &uuid::Uuid) -> anyhow::Result<Payment> {
+        self.write(|t| {
+            let now = chrono::Utc::now();
+            let payment = Payment {
+                id: new_payment.id,
+                group_id: new_payment.group_id,
+                from_person_id: new_payment.from_person_id,
+                to_person_id: new_payment.to_person_id,
+                amount: new_payment.amount,
+                created_at: now,
+                updated_at: now,
+            };
+            t.payments.push(payment.clone());
+            t.record_change(user_id, None, Some(&payment))?;
+            Ok(payment)
+        })
+    }
+
+    fn delete_one(&self, id: &uuid::Uuid, user_id: &uuid::Uuid) -> anyhow::Result<()> {
+        self.write(|t| match t.payments.iter().position(|p| p.id == *id) {
+            None => Ok(()),
+            Some(i) => {
+                let before = t.payments.remove(i);
+                t.record_change(user_id, Some(&before), None)
+            }
+        })
+    }
+}
@@ -0,0 +1,206 @@
+use super::{unique_violation, InMemoryStore, Tables};
+use crate::infrastructure::repositories::{
+    Group, NewPerson, Person, PersonRepository, UpdatePerson,
+};
+
+impl PersonRepository for InMemoryStore {
+    fn find_one(&self, id: &uuid::Uuid) -> anyhow::Result<Option<Person>> {
+        self.read(|t| {
+            t.persons
+                .iter()
+                .find(|p| p.id == *id && p.deleted_at.is_none())
+                .cloned()
+        })
+    }
+
+    fn find_one_by_user(
+        &self,
+        id: &uuid::Uuid,
+        user_id: &uuid::Uuid,
+    ) -> anyhow::Result<Option<(Person, String)>> {
+        self.read(|t| {
+            let person = t
+                .persons
+                .iter()
+                .find(|p| p.id == *id && p.deleted_at.is_none())?;
+            Some((person.clone(), t.role(&person.group_id, user_id)?))
+        })
+    }
+
+    fn find_deleted_one_by_user(
+        &self,
+        id: &uuid::Uuid,
+        user_id: &uuid::Uuid,
+    ) -> anyhow::Result<Option<(Person, String)>> {
+        self.read(|t| {
+            let person = t
+                .persons
+                .iter()
+                .find(|p| p.id == *id && p.deleted_at.is_some())?;
+            Some((person.clone(), t.role(&person.group_id, user_id)?))
+        })
+    }
+
+    fn find_by_group(&self, group: &Group) -> anyhow::Result<Vec<Person>> {
+        self.find_by_group_id(&group.id)
+    }
+
+    fn find_deleted_by_group(&self, group: &Group) -> anyhow::Result<Vec<Person>> {
+        self.read(|t| {
+            let mut persons = t
+                .persons
+                .iter()
+                .filter(|p| p.group_id == group.id && p.deleted_at.is_some())
+                .cloned()
+                .collect::<Vec<_>>();
+            persons.sort_by_key(|p| std::cmp::Reverse(p.deleted_at));
+            persons
+        })
+    }
+
+    fn find_by_group_id(&self, group_id: &uuid::Uuid) -> anyhow::Result<Vec<Person>> {
+        self.read(|t| {
+            t.persons
+                .iter()
+                .filter(|p| p.group_id == *group_id && p.deleted_at.is_none())
+                .cloned()
+                .collect()
+        })
+    }
+
+    fn save(&self, new_person: &NewPerson, user_id: &uuid::Uuid) -> anyhow::Result<Person> {
+        self.write(|t| {
+            let now = chrono::Utc::now();
+            let person = Person {
+                id: new_person.id,
+                group_id: new_person.group_id,
+                name: new_person.name.clone(),
+                resources: new_person.resources,
+                created_at: now,
+                updated_at: now,
+                user_id: None,
+                deleted_at: None,
+            };
+            check_unique(t, &person)?;
+            t.persons.push(person.clone());
+            t.record_change(user_id, None, Some(&person))?;
+            Ok(person)
+        })
+    }
+
+    fn update_one(&self, person: &UpdatePerson, user_id: &uuid::Uuid) -> anyhow::Result<()> {
+        if person.name.is_none() && person.resources.is_none() {
+            return Ok(());
+        }
+
+        self.write(|t| {
+            let row = match t.persons.iter_mut().find(|p| p.id == person.id) {
+                None => return Ok(()),
+                Some(p) => p,
+            };
+            let before = row.clone();
+            if let Some(name) = &person.name {
+                row.name = name.clone();
+            }
+            if let Some(resources) = person.resources {
+                row.resources = resources;
+            }
+            let after = row.clone();
+            check_unique(t, &after)?;
+            t.record_change(user_id, Some(&before), Some(&after))
+        })
+    }
+
+    fn delete_one(&self, id: &uuid::Uuid, user_id: &uuid::Uuid) -> anyhow::Result<()> {
+        self.write(|t| {
+            let i = match t
+                .persons
+                .iter()
+                .position(|p| p.id == *id && p.deleted_at.is_none())
+            {
+                None => return Ok(()),
+                Some(i) => i,
+            };
+            // They share the person's deletion time, which tells them apart when restoring
+            let now = chrono::Utc::now();
+            let paid = paid_by(t, id);
+            let mut deleted = Vec::new();
+            for e in t
+                .expenses
+                .iter_mut()
+                .filter(|e| paid.contains(&e.id) && e.deleted_at.is_none())
+            {
+                deleted.push(e.clone());
+                e.deleted_at = Some(now);
+            }
+            for e in &deleted {
+                t.record_change(user_id, Some(e), None)?;
+            }
+            let before = t.persons[i].clone();
+            t.persons[i].deleted_at = Some(now);
+            t.record_change(user_id, Some(&before), None)
+        })
+    }
+
+    fn restore_one(&self, id: &uuid::Uuid, user_id: &uuid::Uuid) -> anyhow::Result<()> {
+        self.write(|t| {
+            let i = match t
+                .persons
+                .iter()
+                .position(|p| p.id == *id && p.deleted_at.is_some())
+            {
+                None => return Ok(()),
+                Some(i) => i,
+            };
+            let before = t.persons[i].clone();
+            t.persons[i].deleted_at = None;
+            let after = t.persons[i].clone();
+            check_unique(t, &after)?;
+            t.record_restore(user_id, &before, &after)?;
+
+            let paid = paid_by(t, id);
+            let mut restored = Vec::new();
+            for e in t
+                .expenses
+                .iter_mut()
+                .filter(|e| paid.contains(&e.id) && e.deleted_at == before.deleted_at)
+            {
+                let before = e.clone();
+                e.deleted_at = None;
+                restored.push((before, e.clone()));
+            }
+            for (before, after) in &restored {
+                t.record_restore(user_id, before, after)?;
+            }
+
+            Ok(())
+        })
+    }
+}
+
+/// The expenses a person helped pay for.
+fn paid_by(t: &Tables, person_id: &uuid::Uuid) -> Vec<uuid::Uuid> {
+    t.expense_payers
+        .iter()
+        .filter(|p| p.person_id == *person_id)
+        .map(|p| p.expense_id)
+        .collect()
+}
+
+/// The persons out of the trash of a group have different names,
+/// and a user is at most one of them.
+fn check_unique(t: &Tables, person: &Person) -> anyhow::Result<()> {
+    let others = t
+        .persons
+        .iter()
+        .filter(|p| p.id != person.id && p.group_id == person.group_id);
+    for p in others {
+        if p.deleted_at.is_none() && person.deleted_at.is_none() && p.name == person.name {
+            return Err(unique_violation("persons_group_id_name_idx"));
+        }
+        if person.user_id.is_some() && p.user_id == person.user_id {
+            return Err(unique_violation("persons_group_id_user_id_idx"));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,79 @@
+use super::InMemoryStore;
+use crate::infrastructure::repositories::{NewSession, Session, SessionRepository};
+
+impl SessionRepository for InMemoryStore {
+    fn find_one(&self, id: &uuid::Uuid) -> anyhow::Result<Option<Session>> {
+        self.read(|t| t.sessions.iter().find(|s| s.id == *id).cloned())
+    }
+
+    fn save(&self, new_session: &NewSession) -> anyhow::Result<Session> {
+        self.write(|t| {
+            let now = chrono::Utc::now();
+            let session = Session {
+                id: new_session.id,
+                user_id: new_session.user_id,
+                secret_hash: new_session.secret_hash.clone(),
+                previous_secret_hash: None,
+                expires_at: new_session.expires_at,
+                revoked_at: None,
+                created_at: now,
+                updated_at: now,
+            };
+            t.sessions.push(session.clone());
+            Ok(session)
+        })
+    }
+
+    fn rotate(
+        &self,
+        session: &Session,
+        secret_hash: &str,
+        expires_at: &chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<Option<Session>> {
+        self.write(|t| {
+            let row = t.sessions.iter_mut().find(|s| {
+                s.id == session.id && s.secret_hash == session.secret_hash && s.revoked_at.is_none()
+            });
+            Ok(row.map(|s| {
+                s.previous_secret_hash = Some(s.secret_hash.clone());
+                s.secret_hash = secret_hash.to_string();
+                s.expires_at = *expires_at;
+                s.updated_at = chrono::Utc::now();
+                s.clone()
+            }))
+        })
+    }
+
+    fn revoke(&self, id: &uuid::Uuid) -> anyhow::Result<()> {
+        self.write(|t| {
+            let now = chrono::Utc::now();
+            t.sessions
+                .iter_mut()
+                .filter(|s| s.id == *id && s.revoked_at.is_none())
+                .for_each(|s| {
+                    s.revoked_at = Some(now);
+                    s.updated_at = now;
+                });
+            Ok(())
+        })
+    }
+
+    fn revoke_by_user(
+        &self,
+        user_id: &uuid::Uuid,
+        except: Option<&uuid::Uuid>,
+    ) -> anyhow::Result<()> {
+        self.write(|t| {
+            let now = chrono::Utc::now();
+            t.sessions
+                .iter_mut()
+                .filter(|s| s.user_id == *user_id && Some(&s.id) != except)
+                .filter(|s| s.revoked_at.is_none())
+                .for_each(|s| {
+                    s.revoked_at = Some(now);
+                    s.updated_at = now;
+                });
+            Ok(())
+        })
+    }
+}
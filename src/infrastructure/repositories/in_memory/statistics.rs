@@ -0,0 +1,100 @@
+use super::{InMemoryStore, Tables};
+use crate::{
+    domain::expense::DateRange,
+    infrastructure::repositories::{
+        CategorySubtotal, Expense, Group, MonthSubtotal, PersonSubtotal, StatisticsRepository,
+    },
+};
+use chrono::Datelike;
+
+impl StatisticsRepository for InMemoryStore {
+    fn by_category(
+        &self,
+        group: &Group,
+        range: &DateRange,
+    ) -> anyhow::Result<Vec<CategorySubtotal>> {
+        self.read(|t| {
+            let mut subtotals = Vec::<CategorySubtotal>::new();
+            for e in expenses_in_range(t, group, range) {
+                match subtotals.iter_mut().find(|s| {
+                    s.category_id == e.category_id
+                        && s.currency == e.currency
+                        && s.exchange_rate == e.exchange_rate
+                }) {
+                    Some(s) => s.amount += e.amount,
+                    None => subtotals.push(CategorySubtotal {
+                        category_id: e.category_id,
+                        currency: e.currency.clone(),
+                        exchange_rate: e.exchange_rate,
+                        amount: e.amount,
+                    }),
+                }
+            }
+            // The uncategorized ones last, as Postgres sorts the nulls
+            subtotals.sort_by_key(|s| (s.category_id.is_none(), s.category_id));
+            subtotals
+        })
+    }
+
+    fn by_person(&self, group: &Group, range: &DateRange) -> anyhow::Result<Vec<PersonSubtotal>> {
+        self.read(|t| {
+            let mut subtotals = Vec::<PersonSubtotal>::new();
+            for e in expenses_in_range(t, group, range) {
+                for p in t.expense_payers.iter().filter(|p| p.expense_id == e.id) {
+                    match subtotals.iter_mut().find(|s| {
+                        s.person_id == p.person_id
+                            && s.currency == e.currency
+                            && s.exchange_rate == e.exchange_rate
+                    }) {
+                        Some(s) => s.amount += p.amount,
+                        None => subtotals.push(PersonSubtotal {
+                            person_id: p.person_id,
+                            currency: e.currency.clone(),
+                            exchange_rate: e.exchange_rate,
+                            amount: p.amount,
+                        }),
+                    }
+                }
+            }
+            subtotals.sort_by_key(|s| s.person_id);
+            subtotals
+        })
+    }
+
+    fn by_month(&self, group: &Group, range: &DateRange) -> anyhow::Result<Vec<MonthSubtotal>> {
+        self.read(|t| {
+            let mut subtotals = Vec::<MonthSubtotal>::new();
+            for e in expenses_in_range(t, group, range) {
+                let month = e.occurred_on.with_day(1).unwrap_or(e.occurred_on);
+                match subtotals.iter_mut().find(|s| {
+                    s.month == month
+                        && s.currency == e.currency
+                        && s.exchange_rate == e.exchange_rate
+                }) {
+                    Some(s) => s.amount += e.amount,
+                    None => subtotals.push(MonthSubtotal {
+                        month,
+                        currency: e.currency.clone(),
+                        exchange_rate: e.exchange_rate,
+                        amount: e.amount,
+                    }),
+                }
+            }
+            subtotals.sort_by_key(|s| s.month);
+            subtotals
+        })
+    }
+}
+
+/// The group's expenses which occurred in the range, out of the trash.
+fn expenses_in_range<'a>(
+    t: &'a Tables,
+    group: &'a Group,
+    range: &'a DateRange,
+) -> impl Iterator<Item = &'a Expense> {
+    t.expenses
+        .iter()
+        .filter(move |e| e.group_id == group.id && e.deleted_at.is_none())
+        .filter(move |e| range.from.is_none_or(|from| e.occurred_on >= from))
+        .filter(move |e| range.to.is_none_or(|to| e.occurred_on <= to))
+}
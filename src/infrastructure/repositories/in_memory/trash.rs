@@ -0,0 +1,51 @@
+use super::InMemoryStore;
+use crate::infrastructure::repositories::{Purged, TrashRepository};
+
+impl TrashRepository for InMemoryStore {
+    fn purge(&self, before: &chrono::DateTime<chrono::Utc>) -> anyhow::Result<Purged> {
+        self.write(|t| {
+            let purged = |deleted_at: Option<chrono::DateTime<chrono::Utc>>| {
+                deleted_at.is_some_and(|d| d < *before)
+            };
+
+            let n = t.expenses.len();
+            t.expenses.retain(|e| !purged(e.deleted_at));
+            let expenses = n - t.expenses.len();
+            let n = t.persons.len();
+            t.persons.retain(|p| !purged(p.deleted_at));
+            let persons = n - t.persons.len();
+            let n = t.groups.len();
+            t.groups.retain(|g| !purged(g.deleted_at));
+            let groups = n - t.groups.len();
+
+            // Everything they held goes with them, as the foreign keys cascade
+            let groups_left = t.groups.iter().map(|g| g.id).collect::<Vec<_>>();
+            let in_group = |id: &uuid::Uuid| groups_left.contains(id);
+            t.group_members.retain(|m| in_group(&m.group_id));
+            t.invitations.retain(|i| in_group(&i.group_id));
+            t.categories
+                .retain(|c| c.group_id.is_none_or(|id| in_group(&id)));
+            t.persons.retain(|p| in_group(&p.group_id));
+            t.expenses.retain(|e| in_group(&e.group_id));
+            let persons_left = t.persons.iter().map(|p| p.id).collect::<Vec<_>>();
+            let expenses_left = t.expenses.iter().map(|e| e.id).collect::<Vec<_>>();
+            t.payments.retain(|p| {
+                in_group(&p.group_id)
+                    && persons_left.contains(&p.from_person_id)
+                    && persons_left.contains(&p.to_person_id)
+            });
+            t.expense_payers.retain(|p| {
+                expenses_left.contains(&p.expense_id) && persons_left.contains(&p.person_id)
+            });
+            t.expense_shares.retain(|s| {
+                expenses_left.contains(&s.expense_id) && persons_left.contains(&s.person_id)
+            });
+
+            Ok(Purged {
+                groups,
+                persons,
+                expenses,
+            })
+        })
+    }
+}
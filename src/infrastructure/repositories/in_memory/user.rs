@@ -0,0 +1,73 @@
+use super::{unique_violation, InMemoryStore};
+use crate::infrastructure::repositories::{NewUser, User, UserRepository};
+
+impl UserRepository for InMemoryStore {
+    fn find_one(&self, id: &uuid::Uuid) -> anyhow::Result<Option<User>> {
+        self.read(|t| t.users.iter().find(|u| u.id == *id).cloned())
+    }
+
+    fn find_one_by_email(&self, email: &str) -> anyhow::Result<Option<User>> {
+        self.read(|t| t.users.iter().find(|u| u.email == email).cloned())
+    }
+
+    fn save(&self, new_user: &NewUser) -> anyhow::Result<User> {
+        self.write(|t| {
+            if t.users.iter().any(|u| u.email == new_user.email) {
+                return Err(unique_violation("users_email_key"));
+            }
+
+            let now = chrono::Utc::now();
+            let user = User {
+                id: new_user.id,
+                email: new_user.email.clone(),
+                password: new_user.password.clone(),
+                created_at: now,
+                updated_at: now,
+                email_verified_at: None,
+                password_hashed_at: Some(now),
+            };
+            t.users.push(user.clone());
+            Ok(user)
+        })
+    }
+
+    fn update_password(&self, id: &uuid::Uuid, password: &str) -> anyhow::Result<()> {
+        self.write(|t| {
+            if let Some(u) = t.users.iter_mut().find(|u| u.id == *id) {
+                let now = chrono::Utc::now();
+                u.password = password.to_string();
+                u.password_hashed_at = Some(now);
+                u.updated_at = now;
+            }
+            Ok(())
+        })
+    }
+
+    fn update_email(&self, id: &uuid::Uuid, email: &str) -> anyhow::Result<()> {
+        self.write(|t| {
+            if t.users.iter().any(|u| u.id != *id && u.email == email) {
+                return Err(unique_violation("users_email_key"));
+            }
+
+            if let Some(u) = t.users.iter_mut().find(|u| u.id == *id) {
+                u.email = email.to_string();
+                u.email_verified_at = None;
+                u.updated_at = chrono::Utc::now();
+            }
+            Ok(())
+        })
+    }
+
+    fn verify_email(&self, id: &uuid::Uuid, email: &str) -> anyhow::Result<bool> {
+        self.write(|t| {
+            let now = chrono::Utc::now();
+            let row = t.users.iter_mut().find(|u| u.id == *id && u.email == email);
+            Ok(row
+                .map(|u| {
+                    u.email_verified_at = Some(now);
+                    u.updated_at = now;
+                })
+                .is_some())
+        })
+    }
+}
@@ -0,0 +1,228 @@
+use super::{
+    activity::{self, Audited},
+    group::Group,
+    group_member::GroupMember,
+    person::Person,
+    schema::{group_members, groups, invitations, persons},
+    Database,
+};
+use crate::domain::activity::Entity;
+use anyhow::Context;
+use diesel::prelude::*;
+use serde::Serialize;
+
+#[derive(Identifiable, Queryable, Associations, Serialize, PartialEq, Debug, Clone)]
+#[belongs_to(Group)]
+pub struct Invitation {
+    pub id: uuid::Uuid,
+    pub group_id: uuid::Uuid,
+    pub created_by: uuid::Uuid,
+    /// The invitation's secret is only known by the person it was given to.
+    #[serde(skip)]
+    pub secret_hash: String,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+    pub accepted_by: Option<uuid::Uuid>,
+    pub accepted_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub revoked_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+    /// The role given to whoever accepts the invitation.
+    pub role: String,
+}
+
+impl Audited for Invitation {
+    const ENTITY: Entity = Entity::Invitation;
+
+    fn entity_id(&self) -> uuid::Uuid {
+        self.id
+    }
+
+    fn group_id(&self) -> Option<uuid::Uuid> {
+        Some(self.group_id)
+    }
+}
+
+pub trait InvitationRepository {
+    fn find_one(&self, id: &uuid::Uuid) -> anyhow::Result<Option<Invitation>>;
+
+    /// Find an invitation if this user is a member of its group, along with their role.
+    fn find_one_by_user(
+        &self,
+        id: &uuid::Uuid,
+        user_id: &uuid::Uuid,
+    ) -> anyhow::Result<Option<(Invitation, String)>>;
+
+    /// Find the group's invitations which can still be accepted, the oldest first.
+    fn find_pending_by_group(&self, group: &Group) -> anyhow::Result<Vec<Invitation>>;
+
+    fn save(&self, new_invitation: &NewInvitation) -> anyhow::Result<Invitation>;
+
+    /// Use an invitation: add the user to its group and link them to the person they claimed.
+    /// Returns false, changing nothing, when the invitation was used or revoked in the meantime
+    /// or when the person was already claimed.
+    fn accept(
+        &self,
+        invitation: &Invitation,
+        user_id: &uuid::Uuid,
+        person_id: Option<&uuid::Uuid>,
+    ) -> anyhow::Result<bool>;
+
+    /// Make an invitation unusable. Accepted invitations are left untouched.
+    fn revoke(&self, id: &uuid::Uuid, user_id: &uuid::Uuid) -> anyhow::Result<()>;
+}
+
+impl<D: Database> InvitationRepository for D {
+    fn find_one(&self, id: &uuid::Uuid) -> anyhow::Result<Option<Invitation>> {
+        invitations::table
+            .find(id)
+            .first(&*self.get()?)
+            .optional()
+            .context(format!("Couldn't find this invitation ({})", id))
+    }
+
+    fn find_one_by_user(
+        &self,
+        id: &uuid::Uuid,
+        user_id: &uuid::Uuid,
+    ) -> anyhow::Result<Option<(Invitation, String)>> {
+        invitations::table
+            .inner_join(groups::table)
+            .inner_join(group_members::table.on(group_members::group_id.eq(invitations::group_id)))
+            .filter(invitations::id.eq(id))
+            .filter(groups::deleted_at.is_null())
+            .filter(group_members::user_id.eq(user_id))
+            .select((invitations::all_columns, group_members::role))
+            .first(&*self.get()?)
+            .optional()
+            .context(format!(
+                "Couldn't find this user's ({}) invitation ({})",
+                user_id, id
+            ))
+    }
+
+    fn find_pending_by_group(&self, group: &Group) -> anyhow::Result<Vec<Invitation>> {
+        Invitation::belonging_to(group)
+            .filter(invitations::accepted_at.is_null())
+            .filter(invitations::revoked_at.is_null())
+            .filter(invitations::expires_at.gt(diesel::dsl::now))
+            .order(invitations::created_at)
+            .load(&*self.get()?)
+            .context(format!(
+                "Couldn't find this group's ({}) invitations",
+                group.id
+            ))
+    }
+
+    fn save(&self, new_invitation: &NewInvitation) -> anyhow::Result<Invitation> {
+        let conn = self.get()?;
+        conn.transaction::<_, diesel::result::Error, _>(|| {
+            let invitation = diesel::insert_into(invitations::table)
+                .values(new_invitation)
+                .get_result::<Invitation>(&*conn)?;
+            activity::record(&invitation.created_by, None, Some(&invitation), &conn)?;
+            Ok(invitation)
+        })
+        .context("Couldn't save this invitation to the database")
+    }
+
+    fn accept(
+        &self,
+        invitation: &Invitation,
+        user_id: &uuid::Uuid,
+        person_id: Option<&uuid::Uuid>,
+    ) -> anyhow::Result<bool> {
+        let conn = self.get()?;
+        conn.transaction::<_, diesel::result::Error, _>(|| {
+            let used = diesel::update(
+                invitations::table
+                    .filter(invitations::id.eq(invitation.id))
+                    .filter(invitations::accepted_at.is_null())
+                    .filter(invitations::revoked_at.is_null()),
+            )
+            .set((
+                invitations::accepted_by.eq(user_id),
+                invitations::accepted_at.eq(diesel::dsl::now),
+                invitations::updated_at.eq(diesel::dsl::now),
+            ))
+            .get_result::<Invitation>(&*conn)
+            .optional()?;
+            let used = match used {
+                None => return Ok(false),
+                Some(i) => i,
+            };
+            activity::record(user_id, Some(invitation), Some(&used), &conn)?;
+
+            let member = diesel::insert_into(group_members::table)
+                .values((
+                    group_members::group_id.eq(invitation.group_id),
+                    group_members::user_id.eq(user_id),
+                    group_members::role.eq(&invitation.role),
+                ))
+                .on_conflict_do_nothing()
+                .get_result::<GroupMember>(&*conn)
+                .optional()?;
+            activity::record(user_id, None, member.as_ref(), &conn)?;
+            if let Some(person_id) = person_id {
+                let before = persons::table
+                    .filter(persons::id.eq(person_id))
+                    .filter(persons::group_id.eq(invitation.group_id))
+                    .filter(persons::user_id.is_null())
+                    .for_update()
+                    .first::<Person>(&*conn)
+                    .optional()?
+                    .ok_or(diesel::result::Error::RollbackTransaction)?;
+                let after = diesel::update(persons::table.find(person_id))
+                    .set((
+                        persons::user_id.eq(user_id),
+                        persons::updated_at.eq(diesel::dsl::now),
+                    ))
+                    .get_result::<Person>(&*conn)?;
+                activity::record(user_id, Some(&before), Some(&after), &conn)?;
+            }
+
+            Ok(true)
+        })
+        .or_else(|e| match e {
+            diesel::result::Error::RollbackTransaction => Ok(false),
+            e => Err(e),
+        })
+        .context(format!(
+            "Couldn't accept this invitation ({})",
+            invitation.id
+        ))
+    }
+
+    fn revoke(&self, id: &uuid::Uuid, user_id: &uuid::Uuid) -> anyhow::Result<()> {
+        let conn = self.get()?;
+        conn.transaction::<_, diesel::result::Error, _>(|| {
+            let pending = invitations::table
+                .filter(invitations::id.eq(id))
+                .filter(invitations::accepted_at.is_null())
+                .filter(invitations::revoked_at.is_null());
+            let before = pending
+                .for_update()
+                .first::<Invitation>(&*conn)
+                .optional()?;
+            let after = diesel::update(pending)
+                .set((
+                    invitations::revoked_at.eq(diesel::dsl::now),
+                    invitations::updated_at.eq(diesel::dsl::now),
+                ))
+                .get_result::<Invitation>(&*conn)
+                .optional()?;
+            activity::record(user_id, before.as_ref(), after.as_ref(), &conn)
+        })
+        .context(format!("Couldn't revoke this invitation ({})", id))
+    }
+}
+
+#[derive(Insertable)]
+#[table_name = "invitations"]
+pub struct NewInvitation {
+    pub id: uuid::Uuid,
+    pub group_id: uuid::Uuid,
+    pub created_by: uuid::Uuid,
+    pub secret_hash: String,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+    pub role: String,
+}
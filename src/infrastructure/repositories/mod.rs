@@ -1,14 +1,39 @@
+mod activity;
+mod category;
+mod email_verification;
+mod exchange_rate;
 mod expense;
+mod expense_payer;
+mod expense_share;
 mod group;
+mod group_member;
+mod in_memory;
+mod invitation;
+mod password_reset;
+mod payment;
 mod person;
 mod schema;
+mod session;
+mod statistics;
+mod trash;
 mod user;
 
-pub(super) use self::{expense::*, group::*, person::*, user::*};
+pub use self::in_memory::InMemoryStore;
+pub(crate) use self::{
+    activity::*, category::*, email_verification::*, exchange_rate::*, expense::*,
+    expense_payer::*, expense_share::*, group::*, group_member::*, invitation::*,
+    password_reset::*, payment::*, person::*, session::*, statistics::*, trash::*, user::*,
+};
 use crate::infrastructure::config;
 use anyhow::Context;
-use diesel::{pg::PgConnection, r2d2::ConnectionManager};
+use diesel::{
+    pg::PgConnection,
+    r2d2::{ConnectionManager, PooledConnection},
+    result::{DatabaseErrorKind, Error as DieselError},
+    Connection as _,
+};
 use r2d2::Pool;
+use std::ops::Deref;
 
 /// The Postgres-specific connection pool managing all database connections.
 pub type PostgresPool = Pool<ConnectionManager<PgConnection>>;
@@ -20,3 +45,124 @@ pub fn get_pool(config: &config::Settings) -> anyhow::Result<PostgresPool> {
         .build(mgr)
         .context("Couldn't build the postgres connection pool")
 }
+
+/// Everything the application stores, whatever it's stored in.
+/// The repositories are called through it, e.g. `UserRepository::find_one(db, &id)`.
+pub trait Store:
+    ActivityRepository
+    + CategoryRepository
+    + EmailVerificationRepository
+    + ExchangeRateRepository
+    + ExpenseRepository
+    + ExpensePayerRepository
+    + ExpenseShareRepository
+    + GroupRepository
+    + GroupMemberRepository
+    + InvitationRepository
+    + PasswordResetRepository
+    + PaymentRepository
+    + PersonRepository
+    + SessionRepository
+    + StatisticsRepository
+    + TrashRepository
+    + UserRepository
+{
+    /// Run a closure inside one transaction, committed when it succeeds.
+    /// Use [`transaction`] instead, which gives back the closure's own result.
+    fn run_transaction(
+        &self,
+        f: &mut dyn FnMut(&dyn Store) -> anyhow::Result<()>,
+    ) -> anyhow::Result<()>;
+}
+
+/// Where the Postgres repositories run their queries:
+/// on any connection of the pool, or on the one of a transaction in progress.
+pub trait Database {
+    fn get(&self) -> anyhow::Result<Connection<'_>>;
+}
+
+impl<D: Database> Store for D {
+    fn run_transaction(
+        &self,
+        f: &mut dyn FnMut(&dyn Store) -> anyhow::Result<()>,
+    ) -> anyhow::Result<()> {
+        let conn = self.get()?;
+        conn.transaction(|| f(&*conn))
+    }
+}
+
+impl Database for PostgresPool {
+    fn get(&self) -> anyhow::Result<Connection<'_>> {
+        Pool::get(self)
+            .map(Connection::Pooled)
+            .context("Couldn't get a database connection")
+    }
+}
+
+impl Database for PgConnection {
+    fn get(&self) -> anyhow::Result<Connection<'_>> {
+        Ok(Connection::Borrowed(self))
+    }
+}
+
+pub enum Connection<'a> {
+    Pooled(PooledConnection<ConnectionManager<PgConnection>>),
+    Borrowed(&'a PgConnection),
+}
+
+impl Deref for Connection<'_> {
+    type Target = PgConnection;
+
+    fn deref(&self) -> &PgConnection {
+        match self {
+            Connection::Pooled(conn) => conn,
+            Connection::Borrowed(conn) => conn,
+        }
+    }
+}
+
+/// Run a closure inside one transaction.
+/// The repositories called with the store it's given see and take part in the same transaction,
+/// which is committed when the closure succeeds and rolled back when it fails.
+pub fn transaction<T, E, F>(db: &dyn Store, f: F) -> Result<T, E>
+where
+    F: FnOnce(&dyn Store) -> Result<T, E>,
+    E: From<anyhow::Error>,
+{
+    let mut f = Some(f);
+    let mut result = None;
+    let committed = db.run_transaction(&mut |tx| {
+        let f = f.take().expect("A transaction runs its closure once");
+        let res = f(tx);
+        let failed = res.is_err();
+        result = Some(res);
+        // The closure's error is given back as it is, the store only needs to roll back
+        if failed {
+            Err(anyhow::anyhow!("The transaction's closure failed"))
+        } else {
+            Ok(())
+        }
+    });
+
+    match (result, committed) {
+        (Some(Err(e)), _) => Err(e),
+        (Some(Ok(v)), Ok(())) => Ok(v),
+        (_, Err(e)) => Err(E::from(
+            e.context("Couldn't commit or roll back the transaction"),
+        )),
+        (None, Ok(())) => unreachable!("The store committed a transaction without running it"),
+    }
+}
+
+/// Whether a repository failed because of a unique constraint,
+/// meaning another row already holds the value.
+/// The in-memory store reports its own unique constraints the same way.
+pub fn is_unique_violation(e: &anyhow::Error) -> bool {
+    matches!(
+        e.downcast_ref::<DieselError>(),
+        Some(DieselError::DatabaseError(
+            DatabaseErrorKind::UniqueViolation,
+            _
+        ))
+    )
+}
@@ -0,0 +1,113 @@
+use super::{
+    schema::{password_resets, users},
+    Database,
+};
+use anyhow::Context;
+use diesel::prelude::*;
+
+#[derive(Identifiable, Queryable, PartialEq, Debug, Clone)]
+pub struct PasswordReset {
+    pub id: uuid::Uuid,
+    pub user_id: uuid::Uuid,
+    /// The token's secret is only known by whoever reads the user's emails.
+    pub secret_hash: String,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+    pub used_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+pub trait PasswordResetRepository {
+    fn find_one(&self, id: &uuid::Uuid) -> anyhow::Result<Option<PasswordReset>>;
+
+    fn save(&self, new_password_reset: &NewPasswordReset) -> anyhow::Result<PasswordReset>;
+
+    /// Mark a password reset as used.
+    /// Returns false, changing nothing, when it was already used or has expired.
+    fn use_one(&self, id: &uuid::Uuid) -> anyhow::Result<bool>;
+
+    /// Make the user's unused password resets expire now.
+    fn expire_by_user(&self, user_id: &uuid::Uuid) -> anyhow::Result<()>;
+
+    /// How many password resets were issued since then to the user with this email address.
+    fn count_by_email_since(
+        &self,
+        email: &str,
+        since: &chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<i64>;
+}
+
+impl<D: Database> PasswordResetRepository for D {
+    fn find_one(&self, id: &uuid::Uuid) -> anyhow::Result<Option<PasswordReset>> {
+        password_resets::table
+            .find(id)
+            .first(&*self.get()?)
+            .optional()
+            .context(format!("Couldn't find this password reset ({})", id))
+    }
+
+    fn save(&self, new_password_reset: &NewPasswordReset) -> anyhow::Result<PasswordReset> {
+        diesel::insert_into(password_resets::table)
+            .values(new_password_reset)
+            .get_result::<PasswordReset>(&*self.get()?)
+            .context("Couldn't save this password reset to the database")
+    }
+
+    fn use_one(&self, id: &uuid::Uuid) -> anyhow::Result<bool> {
+        diesel::update(
+            password_resets::table
+                .filter(password_resets::id.eq(id))
+                .filter(password_resets::used_at.is_null())
+                .filter(password_resets::expires_at.gt(diesel::dsl::now)),
+        )
+        .set((
+            password_resets::used_at.eq(diesel::dsl::now),
+            password_resets::updated_at.eq(diesel::dsl::now),
+        ))
+        .execute(&*self.get()?)
+        .map(|n| n > 0)
+        .context(format!("Couldn't use this password reset ({})", id))
+    }
+
+    fn expire_by_user(&self, user_id: &uuid::Uuid) -> anyhow::Result<()> {
+        diesel::update(
+            password_resets::table
+                .filter(password_resets::user_id.eq(user_id))
+                .filter(password_resets::used_at.is_null())
+                .filter(password_resets::expires_at.gt(diesel::dsl::now)),
+        )
+        .set((
+            password_resets::expires_at.eq(diesel::dsl::now),
+            password_resets::updated_at.eq(diesel::dsl::now),
+        ))
+        .execute(&*self.get()?)
+        .map(|_| ())
+        .context(format!(
+            "Couldn't expire this user's ({}) password resets",
+            user_id
+        ))
+    }
+
+    fn count_by_email_since(
+        &self,
+        email: &str,
+        since: &chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<i64> {
+        password_resets::table
+            .inner_join(users::table)
+            .filter(users::email.eq(email))
+            .filter(password_resets::created_at.ge(since))
+            .count()
+            .get_result(&*self.get()?)
+            .context("Couldn't count the password resets of this email address")
+    }
+}
+
+#[derive(Insertable)]
+#[table_name = "password_resets"]
+pub struct NewPasswordReset {
+    pub id: uuid::Uuid,
+    pub user_id: uuid::Uuid,
+    pub secret_hash: String,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+}
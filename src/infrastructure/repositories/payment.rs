@@ -0,0 +1,133 @@
+use super::{
+    activity::{self, Audited},
+    group::Group,
+    schema::{group_members, groups, payments, persons},
+    Database,
+};
+use crate::domain::activity::Entity;
+use anyhow::Context;
+use diesel::prelude::*;
+use serde::Serialize;
+
+#[derive(Identifiable, Queryable, Associations, Serialize, PartialEq, Debug, Clone)]
+#[belongs_to(Group)]
+pub struct Payment {
+    pub id: uuid::Uuid,
+    pub group_id: uuid::Uuid,
+    pub from_person_id: uuid::Uuid,
+    pub to_person_id: uuid::Uuid,
+    pub amount: i64,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl Audited for Payment {
+    const ENTITY: Entity = Entity::Payment;
+
+    fn entity_id(&self) -> uuid::Uuid {
+        self.id
+    }
+
+    fn group_id(&self) -> Option<uuid::Uuid> {
+        Some(self.group_id)
+    }
+}
+
+pub trait PaymentRepository {
+    fn find_one(&self, id: &uuid::Uuid) -> anyhow::Result<Option<Payment>>;
+
+    /// Find a payment if this user is a member of its group, along with their role.
+    fn find_one_by_user(
+        &self,
+        id: &uuid::Uuid,
+        user_id: &uuid::Uuid,
+    ) -> anyhow::Result<Option<(Payment, String)>>;
+
+    /// Find the group's payments, except the ones of the persons in the trash.
+    fn find_by_group(&self, group: &Group) -> anyhow::Result<Vec<Payment>>;
+
+    fn save(&self, new_payment: &NewPayment, user_id: &uuid::Uuid) -> anyhow::Result<Payment>;
+
+    fn delete_one(&self, id: &uuid::Uuid, user_id: &uuid::Uuid) -> anyhow::Result<()>;
+}
+
+impl<D: Database> PaymentRepository for D {
+    fn find_one(&self, id: &uuid::Uuid) -> anyhow::Result<Option<Payment>> {
+        payments::table
+            .find(id)
+            .first(&*self.get()?)
+            .optional()
+            .context(format!("Couldn't find this payment ({})", id))
+    }
+
+    fn find_one_by_user(
+        &self,
+        id: &uuid::Uuid,
+        user_id: &uuid::Uuid,
+    ) -> anyhow::Result<Option<(Payment, String)>> {
+        payments::table
+            .inner_join(groups::table)
+            .inner_join(group_members::table.on(group_members::group_id.eq(payments::group_id)))
+            .filter(payments::id.eq(id))
+            .filter(groups::deleted_at.is_null())
+            .filter(group_members::user_id.eq(user_id))
+            .select((payments::all_columns, group_members::role))
+            .first(&*self.get()?)
+            .optional()
+            .context(format!(
+                "Couldn't find this user's ({}) payment ({})",
+                user_id, id
+            ))
+    }
+
+    fn find_by_group(&self, group: &Group) -> anyhow::Result<Vec<Payment>> {
+        let deleted = || {
+            persons::table
+                .filter(persons::group_id.eq(group.id))
+                .filter(persons::deleted_at.is_not_null())
+                .select(persons::id)
+        };
+        Payment::belonging_to(group)
+            .filter(diesel::dsl::not(payments::from_person_id.eq_any(deleted())))
+            .filter(diesel::dsl::not(payments::to_person_id.eq_any(deleted())))
+            .load(&*self.get()?)
+            .context(format!(
+                "Couldn't find this group's ({}) payments",
+                group.id
+            ))
+    }
+
+    fn save(&self, new_payment: &NewPayment, user_id: &uuid::Uuid) -> anyhow::Result<Payment> {
+        let conn = self.get()?;
+        conn.transaction::<_, diesel::result::Error, _>(|| {
+            let payment = diesel::insert_into(payments::table)
+                .values(new_payment)
+                .get_result::<Payment>(&*conn)?;
+            activity::record(user_id, None, Some(&payment), &conn)?;
+            Ok(payment)
+        })
+        .context("Couldn't save this payment to the database")
+    }
+
+    fn delete_one(&self, id: &uuid::Uuid, user_id: &uuid::Uuid) -> anyhow::Result<()> {
+        let conn = self.get()?;
+        conn.transaction::<_, diesel::result::Error, _>(|| {
+            let before = diesel::delete(payments::table)
+                .filter(payments::id.eq(id))
+                .get_result::<Payment>(&*conn)
+                .optional()?;
+            activity::record(user_id, before.as_ref(), None, &conn)
+        })
+        .context(format!("Couldn't delete this payment ({})", id))
+    }
+}
+
+#[derive(Insertable)]
+#[table_name = "payments"]
+pub struct NewPayment {
+    pub id: uuid::Uuid,
+    pub group_id: uuid::Uuid,
+    pub from_person_id: uuid::Uuid,
+    pub to_person_id: uuid::Uuid,
+    pub amount: i64,
+}
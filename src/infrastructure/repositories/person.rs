@@ -1,51 +1,262 @@
-use super::{group::Group, schema::persons, PostgresPool};
+use super::{
+    activity::{self, Audited},
+    expense::Expense,
+    group::Group,
+    schema::{expense_payers, expenses, group_members, groups, persons},
+    Database,
+};
+use crate::domain::activity::Entity;
 use anyhow::Context;
 use diesel::prelude::*;
+use serde::Serialize;
 
-#[derive(Identifiable, Queryable, Associations, PartialEq, Debug)]
+#[derive(Identifiable, Queryable, Associations, Serialize, PartialEq, Debug, Clone)]
 #[belongs_to(Group)]
 pub struct Person {
     pub id: uuid::Uuid,
     pub group_id: uuid::Uuid,
     pub name: String,
-    pub resources: i32,
+    pub resources: i64,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub updated_at: chrono::DateTime<chrono::Utc>,
+    /// The account of this person, if they have one.
+    pub user_id: Option<uuid::Uuid>,
+    /// When the person was moved to the trash, if they were.
+    pub deleted_at: Option<chrono::DateTime<chrono::Utc>>,
 }
 
-pub struct PersonRepository;
-impl PersonRepository {
-    pub fn find_by_group(group: &Group, pool: &PostgresPool) -> anyhow::Result<Vec<Person>> {
+impl Audited for Person {
+    const ENTITY: Entity = Entity::Person;
+
+    fn entity_id(&self) -> uuid::Uuid {
+        self.id
+    }
+
+    fn group_id(&self) -> Option<uuid::Uuid> {
+        Some(self.group_id)
+    }
+}
+
+pub trait PersonRepository {
+    fn find_one(&self, id: &uuid::Uuid) -> anyhow::Result<Option<Person>>;
+
+    /// Find a person if their group belongs to this user.
+    fn find_one_by_user(
+        &self,
+        id: &uuid::Uuid,
+        user_id: &uuid::Uuid,
+    ) -> anyhow::Result<Option<(Person, String)>>;
+
+    /// Find a person in the trash if their group, out of it, belongs to this user.
+    fn find_deleted_one_by_user(
+        &self,
+        id: &uuid::Uuid,
+        user_id: &uuid::Uuid,
+    ) -> anyhow::Result<Option<(Person, String)>>;
+
+    /// Find the group's persons, the oldest first.
+    fn find_by_group(&self, group: &Group) -> anyhow::Result<Vec<Person>>;
+
+    /// Find the group's persons in the trash, the latest deleted first.
+    fn find_deleted_by_group(&self, group: &Group) -> anyhow::Result<Vec<Person>>;
+
+    /// Find the group's persons, the oldest first.
+    fn find_by_group_id(&self, group_id: &uuid::Uuid) -> anyhow::Result<Vec<Person>>;
+
+    fn save(&self, new_person: &NewPerson, user_id: &uuid::Uuid) -> anyhow::Result<Person>;
+
+    fn update_one(&self, person: &UpdatePerson, user_id: &uuid::Uuid) -> anyhow::Result<()>;
+
+    /// Move a person to the trash along with every expense they helped pay for.
+    fn delete_one(&self, id: &uuid::Uuid, user_id: &uuid::Uuid) -> anyhow::Result<()>;
+
+    /// Move a person back from the trash along with the expenses deleted with them.
+    fn restore_one(&self, id: &uuid::Uuid, user_id: &uuid::Uuid) -> anyhow::Result<()>;
+}
+
+impl<D: Database> PersonRepository for D {
+    fn find_one(&self, id: &uuid::Uuid) -> anyhow::Result<Option<Person>> {
+        persons::table
+            .find(id)
+            .filter(persons::deleted_at.is_null())
+            .first(&*self.get()?)
+            .optional()
+            .context(format!("Couldn't find this person ({})", id))
+    }
+
+    fn find_one_by_user(
+        &self,
+        id: &uuid::Uuid,
+        user_id: &uuid::Uuid,
+    ) -> anyhow::Result<Option<(Person, String)>> {
+        persons::table
+            .inner_join(groups::table)
+            .inner_join(group_members::table.on(group_members::group_id.eq(persons::group_id)))
+            .filter(persons::id.eq(id))
+            .filter(persons::deleted_at.is_null())
+            .filter(groups::deleted_at.is_null())
+            .filter(group_members::user_id.eq(user_id))
+            .select((persons::all_columns, group_members::role))
+            .first(&*self.get()?)
+            .optional()
+            .context(format!(
+                "Couldn't find this user's ({}) person ({})",
+                user_id, id
+            ))
+    }
+
+    fn find_deleted_one_by_user(
+        &self,
+        id: &uuid::Uuid,
+        user_id: &uuid::Uuid,
+    ) -> anyhow::Result<Option<(Person, String)>> {
+        persons::table
+            .inner_join(groups::table)
+            .inner_join(group_members::table.on(group_members::group_id.eq(persons::group_id)))
+            .filter(persons::id.eq(id))
+            .filter(persons::deleted_at.is_not_null())
+            .filter(groups::deleted_at.is_null())
+            .filter(group_members::user_id.eq(user_id))
+            .select((persons::all_columns, group_members::role))
+            .first(&*self.get()?)
+            .optional()
+            .context(format!(
+                "Couldn't find this user's ({}) deleted person ({})",
+                user_id, id
+            ))
+    }
+
+    fn find_by_group(&self, group: &Group) -> anyhow::Result<Vec<Person>> {
         Person::belonging_to(group)
-            .load(&pool.get()?)
+            .filter(persons::deleted_at.is_null())
+            .order(persons::created_at)
+            .load(&*self.get()?)
             .context(format!("Couldn't find this group's ({}) persons", group.id))
     }
 
-    pub fn save(new_person: &NewPerson, pool: &PostgresPool) -> anyhow::Result<Person> {
-        diesel::insert_into(persons::table)
-            .values(new_person)
-            .get_result::<Person>(&pool.get()?)
-            .context("Couldn't save this person to the database")
+    fn find_deleted_by_group(&self, group: &Group) -> anyhow::Result<Vec<Person>> {
+        Person::belonging_to(group)
+            .filter(persons::deleted_at.is_not_null())
+            .order(persons::deleted_at.desc())
+            .load(&*self.get()?)
+            .context(format!(
+                "Couldn't find this group's ({}) deleted persons",
+                group.id
+            ))
     }
 
-    pub fn update_one(person: &UpdatePerson, pool: &PostgresPool) -> anyhow::Result<()> {
+    fn find_by_group_id(&self, group_id: &uuid::Uuid) -> anyhow::Result<Vec<Person>> {
+        persons::table
+            .filter(persons::group_id.eq(group_id))
+            .filter(persons::deleted_at.is_null())
+            .order(persons::created_at)
+            .load(&*self.get()?)
+            .context(format!("Couldn't find this group's ({}) persons", group_id))
+    }
+
+    fn save(&self, new_person: &NewPerson, user_id: &uuid::Uuid) -> anyhow::Result<Person> {
+        let conn = self.get()?;
+        conn.transaction::<_, diesel::result::Error, _>(|| {
+            let person = diesel::insert_into(persons::table)
+                .values(new_person)
+                .get_result::<Person>(&*conn)?;
+            activity::record(user_id, None, Some(&person), &conn)?;
+            Ok(person)
+        })
+        .context("Couldn't save this person to the database")
+    }
+
+    fn update_one(&self, person: &UpdatePerson, user_id: &uuid::Uuid) -> anyhow::Result<()> {
         if person.name.is_none() && person.resources.is_none() {
             return Ok(());
         }
 
-        diesel::update(persons::table.filter(persons::id.eq(person.id)))
-            .set(person)
-            .execute(&pool.get()?)
-            .context("Couldn't update this person to the database")
-            .map(|_| ())
+        let conn = self.get()?;
+        conn.transaction::<_, diesel::result::Error, _>(|| {
+            let before = persons::table
+                .find(person.id)
+                .for_update()
+                .first::<Person>(&*conn)
+                .optional()?;
+            let after = diesel::update(persons::table.filter(persons::id.eq(person.id)))
+                .set(person)
+                .get_result::<Person>(&*conn)
+                .optional()?;
+            activity::record(user_id, before.as_ref(), after.as_ref(), &conn)
+        })
+        .context("Couldn't update this person to the database")
     }
 
-    pub fn delete_one(id: &uuid::Uuid, pool: &PostgresPool) -> anyhow::Result<()> {
-        diesel::delete(persons::table)
-            .filter(persons::id.eq(id))
-            .execute(&pool.get()?)
-            .context(format!("Couldn't delete this person ({})", id))
-            .map(|_| ())
+    fn delete_one(&self, id: &uuid::Uuid, user_id: &uuid::Uuid) -> anyhow::Result<()> {
+        let conn = self.get()?;
+        conn.transaction::<_, diesel::result::Error, _>(|| {
+            let before = persons::table
+                .find(id)
+                .filter(persons::deleted_at.is_null())
+                .for_update()
+                .first::<Person>(&*conn)
+                .optional()?;
+            let before = match before {
+                None => return Ok(()),
+                Some(p) => p,
+            };
+            // They share the person's deletion time, which tells them apart when restoring
+            let paid = expense_payers::table
+                .filter(expense_payers::person_id.eq(id))
+                .select(expense_payers::expense_id);
+            let expenses = diesel::update(
+                expenses::table
+                    .filter(expenses::id.eq_any(paid))
+                    .filter(expenses::deleted_at.is_null()),
+            )
+            .set(expenses::deleted_at.eq(diesel::dsl::now))
+            .get_results::<Expense>(&*conn)?;
+            for e in &expenses {
+                activity::record(user_id, Some(e), None, &conn)?;
+            }
+            diesel::update(persons::table.find(id))
+                .set(persons::deleted_at.eq(diesel::dsl::now))
+                .execute(&*conn)?;
+            activity::record(user_id, Some(&before), None, &conn)
+        })
+        .context(format!("Couldn't delete this person ({})", id))
+    }
+
+    fn restore_one(&self, id: &uuid::Uuid, user_id: &uuid::Uuid) -> anyhow::Result<()> {
+        let conn = self.get()?;
+        conn.transaction::<_, diesel::result::Error, _>(|| {
+            let before = persons::table
+                .find(id)
+                .filter(persons::deleted_at.is_not_null())
+                .for_update()
+                .first::<Person>(&*conn)
+                .optional()?;
+            let before = match before {
+                None => return Ok(()),
+                Some(p) => p,
+            };
+            let after = diesel::update(persons::table.find(id))
+                .set(persons::deleted_at.eq(None::<chrono::DateTime<chrono::Utc>>))
+                .get_result::<Person>(&*conn)?;
+            activity::record_restore(user_id, &before, &after, &conn)?;
+            let paid = expense_payers::table
+                .filter(expense_payers::person_id.eq(id))
+                .select(expense_payers::expense_id);
+            let deleted = expenses::table
+                .filter(expenses::id.eq_any(paid))
+                .filter(expenses::deleted_at.eq(before.deleted_at))
+                .for_update()
+                .load::<Expense>(&*conn)?;
+            for e in &deleted {
+                let restored = diesel::update(expenses::table.find(e.id))
+                    .set(expenses::deleted_at.eq(None::<chrono::DateTime<chrono::Utc>>))
+                    .get_result::<Expense>(&*conn)?;
+                activity::record_restore(user_id, e, &restored, &conn)?;
+            }
+
+            Ok(())
+        })
+        .context(format!("Couldn't restore this person ({})", id))
     }
 }
 
@@ -55,7 +266,7 @@ pub struct NewPerson {
     pub id: uuid::Uuid,
     pub group_id: uuid::Uuid,
     pub name: String,
-    pub resources: i32,
+    pub resources: i64,
 }
 
 #[derive(AsChangeset)]
@@ -63,5 +274,5 @@ pub struct NewPerson {
 pub struct UpdatePerson {
     pub id: uuid::Uuid,
     pub name: Option<String>,
-    pub resources: Option<i32>,
+    pub resources: Option<i64>,
 }
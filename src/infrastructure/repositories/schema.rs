@@ -1,15 +1,97 @@
+table! {
+    activities (id) {
+        id -> Int8,
+        group_id -> Uuid,
+        user_id -> Nullable<Uuid>,
+        action -> Varchar,
+        entity_type -> Varchar,
+        entity_id -> Uuid,
+        before -> Nullable<Jsonb>,
+        after -> Nullable<Jsonb>,
+        created_at -> Timestamptz,
+    }
+}
+
+table! {
+    expense_payers (expense_id, person_id) {
+        expense_id -> Uuid,
+        person_id -> Uuid,
+        amount -> Int8,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+table! {
+    expense_shares (expense_id, person_id) {
+        expense_id -> Uuid,
+        person_id -> Uuid,
+        value -> Int8,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
 table! {
     expenses (id) {
         id -> Uuid,
         group_id -> Uuid,
-        person_id -> Uuid,
         name -> Varchar,
-        amount -> Int4,
+        amount -> Int8,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+        split_mode -> Varchar,
+        currency -> Varchar,
+        exchange_rate -> Float8,
+        occurred_on -> Date,
+        category_id -> Nullable<Uuid>,
+        deleted_at -> Nullable<Timestamptz>,
+    }
+}
+
+table! {
+    categories (id) {
+        id -> Uuid,
+        group_id -> Nullable<Uuid>,
+        name -> Varchar,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+table! {
+    email_verifications (id) {
+        id -> Uuid,
+        user_id -> Uuid,
+        email -> Varchar,
+        secret_hash -> Varchar,
+        expires_at -> Timestamptz,
+        used_at -> Nullable<Timestamptz>,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+table! {
+    exchange_rates (from_currency, to_currency) {
+        from_currency -> Varchar,
+        to_currency -> Varchar,
+        rate -> Float8,
         created_at -> Timestamptz,
         updated_at -> Timestamptz,
     }
 }
 
+table! {
+    group_members (group_id, user_id) {
+        group_id -> Uuid,
+        user_id -> Uuid,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+        role -> Varchar,
+    }
+}
+
 table! {
     groups (id) {
         id -> Uuid,
@@ -17,6 +99,48 @@ table! {
         name -> Varchar,
         created_at -> Timestamptz,
         updated_at -> Timestamptz,
+        currency -> Varchar,
+        deleted_at -> Nullable<Timestamptz>,
+    }
+}
+
+table! {
+    invitations (id) {
+        id -> Uuid,
+        group_id -> Uuid,
+        created_by -> Uuid,
+        secret_hash -> Varchar,
+        expires_at -> Timestamptz,
+        accepted_by -> Nullable<Uuid>,
+        accepted_at -> Nullable<Timestamptz>,
+        revoked_at -> Nullable<Timestamptz>,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+        role -> Varchar,
+    }
+}
+
+table! {
+    password_resets (id) {
+        id -> Uuid,
+        user_id -> Uuid,
+        secret_hash -> Varchar,
+        expires_at -> Timestamptz,
+        used_at -> Nullable<Timestamptz>,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+table! {
+    payments (id) {
+        id -> Uuid,
+        group_id -> Uuid,
+        from_person_id -> Uuid,
+        to_person_id -> Uuid,
+        amount -> Int8,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
     }
 }
 
@@ -25,7 +149,22 @@ table! {
         id -> Uuid,
         group_id -> Uuid,
         name -> Varchar,
-        resources -> Int4,
+        resources -> Int8,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+        user_id -> Nullable<Uuid>,
+        deleted_at -> Nullable<Timestamptz>,
+    }
+}
+
+table! {
+    sessions (id) {
+        id -> Uuid,
+        user_id -> Uuid,
+        secret_hash -> Varchar,
+        previous_secret_hash -> Nullable<Varchar>,
+        expires_at -> Timestamptz,
+        revoked_at -> Nullable<Timestamptz>,
         created_at -> Timestamptz,
         updated_at -> Timestamptz,
     }
@@ -38,12 +177,44 @@ table! {
         password -> Varchar,
         created_at -> Timestamptz,
         updated_at -> Timestamptz,
+        email_verified_at -> Nullable<Timestamptz>,
+        password_hashed_at -> Nullable<Timestamptz>,
     }
 }
 
+joinable!(activities -> users (user_id));
+joinable!(categories -> groups (group_id));
+joinable!(email_verifications -> users (user_id));
+joinable!(expense_payers -> expenses (expense_id));
+joinable!(expense_payers -> persons (person_id));
+joinable!(expense_shares -> expenses (expense_id));
+joinable!(expense_shares -> persons (person_id));
+joinable!(expenses -> categories (category_id));
 joinable!(expenses -> groups (group_id));
-joinable!(expenses -> persons (person_id));
+joinable!(group_members -> groups (group_id));
+joinable!(group_members -> users (user_id));
 joinable!(groups -> users (user_id));
+joinable!(invitations -> groups (group_id));
+joinable!(password_resets -> users (user_id));
+joinable!(payments -> groups (group_id));
 joinable!(persons -> groups (group_id));
+joinable!(persons -> users (user_id));
+joinable!(sessions -> users (user_id));
 
-allow_tables_to_appear_in_same_query!(expenses, groups, persons, users,);
+allow_tables_to_appear_in_same_query!(
+    activities,
+    categories,
+    email_verifications,
+    expense_payers,
+    expense_shares,
+    expenses,
+    exchange_rates,
+    group_members,
+    groups,
+    invitations,
+    password_resets,
+    payments,
+    persons,
+    sessions,
+    users,
+);
@@ -0,0 +1,132 @@
+use super::{schema::sessions, Database};
+use anyhow::Context;
+use diesel::prelude::*;
+
+#[derive(Identifiable, Queryable, PartialEq, Debug, Clone)]
+pub struct Session {
+    pub id: uuid::Uuid,
+    pub user_id: uuid::Uuid,
+    /// The refresh token's secret is only known by the client it was given to.
+    pub secret_hash: String,
+    /// The secret replaced by the last rotation, which only a stolen refresh token still has.
+    pub previous_secret_hash: Option<String>,
+    /// When the refresh token can't be used anymore, unless it's rotated before.
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+    pub revoked_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+pub trait SessionRepository {
+    fn find_one(&self, id: &uuid::Uuid) -> anyhow::Result<Option<Session>>;
+
+    fn save(&self, new_session: &NewSession) -> anyhow::Result<Session>;
+
+    /// Replace a session's secret, keeping the previous one, and push back its expiration.
+    /// Returns None, changing nothing, when the session was rotated or revoked in the meantime.
+    fn rotate(
+        &self,
+        session: &Session,
+        secret_hash: &str,
+        expires_at: &chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<Option<Session>>;
+
+    /// Make a session unusable, along with the access tokens issued for it.
+    fn revoke(&self, id: &uuid::Uuid) -> anyhow::Result<()>;
+
+    /// Make all the sessions of a user unusable, but the one given.
+    fn revoke_by_user(
+        &self,
+        user_id: &uuid::Uuid,
+        except: Option<&uuid::Uuid>,
+    ) -> anyhow::Result<()>;
+}
+
+impl<D: Database> SessionRepository for D {
+    fn find_one(&self, id: &uuid::Uuid) -> anyhow::Result<Option<Session>> {
+        sessions::table
+            .find(id)
+            .first(&*self.get()?)
+            .optional()
+            .context(format!("Couldn't find this session ({})", id))
+    }
+
+    fn save(&self, new_session: &NewSession) -> anyhow::Result<Session> {
+        diesel::insert_into(sessions::table)
+            .values(new_session)
+            .get_result::<Session>(&*self.get()?)
+            .context("Couldn't save this session to the database")
+    }
+
+    fn rotate(
+        &self,
+        session: &Session,
+        secret_hash: &str,
+        expires_at: &chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<Option<Session>> {
+        diesel::update(
+            sessions::table
+                .filter(sessions::id.eq(session.id))
+                .filter(sessions::secret_hash.eq(&session.secret_hash))
+                .filter(sessions::revoked_at.is_null()),
+        )
+        .set((
+            sessions::secret_hash.eq(secret_hash),
+            sessions::previous_secret_hash.eq(&session.secret_hash),
+            sessions::expires_at.eq(expires_at),
+            sessions::updated_at.eq(diesel::dsl::now),
+        ))
+        .get_result::<Session>(&*self.get()?)
+        .optional()
+        .context(format!("Couldn't rotate this session ({})", session.id))
+    }
+
+    fn revoke(&self, id: &uuid::Uuid) -> anyhow::Result<()> {
+        diesel::update(
+            sessions::table
+                .filter(sessions::id.eq(id))
+                .filter(sessions::revoked_at.is_null()),
+        )
+        .set((
+            sessions::revoked_at.eq(diesel::dsl::now),
+            sessions::updated_at.eq(diesel::dsl::now),
+        ))
+        .execute(&*self.get()?)
+        .map(|_| ())
+        .context(format!("Couldn't revoke this session ({})", id))
+    }
+
+    fn revoke_by_user(
+        &self,
+        user_id: &uuid::Uuid,
+        except: Option<&uuid::Uuid>,
+    ) -> anyhow::Result<()> {
+        // No session has the nil id
+        let except = except.copied().unwrap_or_else(uuid::Uuid::nil);
+        diesel::update(
+            sessions::table
+                .filter(sessions::user_id.eq(user_id))
+                .filter(sessions::id.ne(except))
+                .filter(sessions::revoked_at.is_null()),
+        )
+        .set((
+            sessions::revoked_at.eq(diesel::dsl::now),
+            sessions::updated_at.eq(diesel::dsl::now),
+        ))
+        .execute(&*self.get()?)
+        .map(|_| ())
+        .context(format!(
+            "Couldn't revoke this user's ({}) sessions",
+            user_id
+        ))
+    }
+}
+
+#[derive(Insertable)]
+#[table_name = "sessions"]
+pub struct NewSession {
+    pub id: uuid::Uuid,
+    pub user_id: uuid::Uuid,
+    pub secret_hash: String,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+}
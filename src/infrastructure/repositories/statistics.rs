@@ -0,0 +1,132 @@
+use super::{group::Group, Database};
+use crate::domain::expense::DateRange;
+use anyhow::Context;
+use diesel::{
+    prelude::*,
+    sql_types::{BigInt, Date, Double, Nullable, Text, Uuid},
+};
+
+/// The sum of a group's expenses of a category in a currency. None for the uncategorized ones.
+#[derive(QueryableByName, PartialEq, Debug)]
+pub struct CategorySubtotal {
+    #[sql_type = "Nullable<Uuid>"]
+    pub category_id: Option<uuid::Uuid>,
+    #[sql_type = "Text"]
+    pub currency: String,
+    #[sql_type = "Double"]
+    pub exchange_rate: f64,
+    #[sql_type = "BigInt"]
+    pub amount: i64,
+}
+
+/// The sum a person paid for a group's expenses in a currency.
+#[derive(QueryableByName, PartialEq, Debug)]
+pub struct PersonSubtotal {
+    #[sql_type = "Uuid"]
+    pub person_id: uuid::Uuid,
+    #[sql_type = "Text"]
+    pub currency: String,
+    #[sql_type = "Double"]
+    pub exchange_rate: f64,
+    #[sql_type = "BigInt"]
+    pub amount: i64,
+}
+
+/// The sum of a group's expenses which occurred in a month in a currency.
+#[derive(QueryableByName, PartialEq, Debug)]
+pub struct MonthSubtotal {
+    /// The first day of the month.
+    #[sql_type = "Date"]
+    pub month: chrono::NaiveDate,
+    #[sql_type = "Text"]
+    pub currency: String,
+    #[sql_type = "Double"]
+    pub exchange_rate: f64,
+    #[sql_type = "BigInt"]
+    pub amount: i64,
+}
+
+/// Only keep the expenses `e` of the group which occurred in the range, out of the trash.
+const GROUP_EXPENSES_IN_RANGE: &str = "e.group_id = $1
+    AND e.deleted_at IS NULL
+    AND ($2::DATE IS NULL OR e.occurred_on >= $2)
+    AND ($3::DATE IS NULL OR e.occurred_on <= $3)";
+
+/// Aggregates a group's expenses in the database, by currency and exchange rate
+/// since the conversion into the group's currency depends on them.
+pub trait StatisticsRepository {
+    fn by_category(
+        &self,
+        group: &Group,
+        range: &DateRange,
+    ) -> anyhow::Result<Vec<CategorySubtotal>>;
+
+    fn by_person(&self, group: &Group, range: &DateRange) -> anyhow::Result<Vec<PersonSubtotal>>;
+
+    fn by_month(&self, group: &Group, range: &DateRange) -> anyhow::Result<Vec<MonthSubtotal>>;
+}
+
+impl<D: Database> StatisticsRepository for D {
+    fn by_category(
+        &self,
+        group: &Group,
+        range: &DateRange,
+    ) -> anyhow::Result<Vec<CategorySubtotal>> {
+        diesel::sql_query(format!(
+            "SELECT e.category_id, e.currency, e.exchange_rate, SUM(e.amount)::BIGINT AS amount
+            FROM expenses e
+            WHERE {}
+            GROUP BY e.category_id, e.currency, e.exchange_rate
+            ORDER BY e.category_id",
+            GROUP_EXPENSES_IN_RANGE
+        ))
+        .bind::<Uuid, _>(group.id)
+        .bind::<Nullable<Date>, _>(range.from)
+        .bind::<Nullable<Date>, _>(range.to)
+        .load(&*self.get()?)
+        .context(format!(
+            "Couldn't sum this group's ({}) expenses by category",
+            group.id
+        ))
+    }
+
+    fn by_person(&self, group: &Group, range: &DateRange) -> anyhow::Result<Vec<PersonSubtotal>> {
+        diesel::sql_query(format!(
+            "SELECT p.person_id, e.currency, e.exchange_rate, SUM(p.amount)::BIGINT AS amount
+            FROM expense_payers p
+            INNER JOIN expenses e ON e.id = p.expense_id
+            WHERE {}
+            GROUP BY p.person_id, e.currency, e.exchange_rate
+            ORDER BY p.person_id",
+            GROUP_EXPENSES_IN_RANGE
+        ))
+        .bind::<Uuid, _>(group.id)
+        .bind::<Nullable<Date>, _>(range.from)
+        .bind::<Nullable<Date>, _>(range.to)
+        .load(&*self.get()?)
+        .context(format!(
+            "Couldn't sum this group's ({}) expenses by person",
+            group.id
+        ))
+    }
+
+    fn by_month(&self, group: &Group, range: &DateRange) -> anyhow::Result<Vec<MonthSubtotal>> {
+        diesel::sql_query(format!(
+            "SELECT DATE_TRUNC('month', e.occurred_on)::DATE AS month, e.currency, e.exchange_rate,
+                SUM(e.amount)::BIGINT AS amount
+            FROM expenses e
+            WHERE {}
+            GROUP BY month, e.currency, e.exchange_rate
+            ORDER BY month",
+            GROUP_EXPENSES_IN_RANGE
+        ))
+        .bind::<Uuid, _>(group.id)
+        .bind::<Nullable<Date>, _>(range.from)
+        .bind::<Nullable<Date>, _>(range.to)
+        .load(&*self.get()?)
+        .context(format!(
+            "Couldn't sum this group's ({}) expenses by month",
+            group.id
+        ))
+    }
+}
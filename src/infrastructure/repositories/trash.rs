@@ -0,0 +1,40 @@
+use super::{
+    schema::{expenses, groups, persons},
+    Database,
+};
+use anyhow::Context;
+use diesel::prelude::*;
+
+/// The number of rows purged from the trash, by table.
+#[derive(PartialEq, Debug, Default)]
+pub struct Purged {
+    pub groups: usize,
+    pub persons: usize,
+    pub expenses: usize,
+}
+
+pub trait TrashRepository {
+    /// Permanently delete the groups, persons and expenses moved to the trash before this date.
+    /// Everything they hold goes with them.
+    fn purge(&self, before: &chrono::DateTime<chrono::Utc>) -> anyhow::Result<Purged>;
+}
+
+impl<D: Database> TrashRepository for D {
+    fn purge(&self, before: &chrono::DateTime<chrono::Utc>) -> anyhow::Result<Purged> {
+        let conn = self.get()?;
+        conn.transaction::<_, diesel::result::Error, _>(|| {
+            let expenses = diesel::delete(expenses::table.filter(expenses::deleted_at.lt(before)))
+                .execute(&*conn)?;
+            let persons = diesel::delete(persons::table.filter(persons::deleted_at.lt(before)))
+                .execute(&*conn)?;
+            let groups = diesel::delete(groups::table.filter(groups::deleted_at.lt(before)))
+                .execute(&*conn)?;
+            Ok(Purged {
+                groups,
+                persons,
+                expenses,
+            })
+        })
+        .context(format!("Couldn't purge the trash before {}", before))
+    }
+}
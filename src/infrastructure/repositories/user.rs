@@ -1,41 +1,101 @@
-use super::{schema::users, PostgresPool};
+use super::{schema::users, Database};
 use anyhow::Context;
 use diesel::prelude::*;
 
-#[derive(Identifiable, Queryable, PartialEq, Debug)]
+#[derive(Identifiable, Queryable, PartialEq, Debug, Clone)]
 pub struct User {
     pub id: uuid::Uuid,
     pub email: String,
     pub password: String,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub updated_at: chrono::DateTime<chrono::Utc>,
+    /// When the user proved they read the emails sent to their current address.
+    pub email_verified_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// When the password was last hashed, with a salt of its own.
+    /// None for the passwords hashed with the former global salt.
+    pub password_hashed_at: Option<chrono::DateTime<chrono::Utc>>,
 }
 
-// FIXME: Create a singleton with a pool field to avoid having to pass it to every methods.
-pub struct UserRepository;
-impl UserRepository {
-    pub fn find_one(id: &uuid::Uuid, pool: &PostgresPool) -> anyhow::Result<Option<User>> {
+pub trait UserRepository {
+    fn find_one(&self, id: &uuid::Uuid) -> anyhow::Result<Option<User>>;
+
+    fn find_one_by_email(&self, email: &str) -> anyhow::Result<Option<User>>;
+
+    fn save(&self, new_user: &NewUser) -> anyhow::Result<User>;
+
+    /// Replace a user's password hash.
+    fn update_password(&self, id: &uuid::Uuid, password: &str) -> anyhow::Result<()>;
+
+    /// Replace a user's email, which has to be verified again.
+    fn update_email(&self, id: &uuid::Uuid, email: &str) -> anyhow::Result<()>;
+
+    /// Mark a user's email as verified.
+    /// Returns false, changing nothing, when the user's email isn't this one anymore.
+    fn verify_email(&self, id: &uuid::Uuid, email: &str) -> anyhow::Result<bool>;
+}
+
+impl<D: Database> UserRepository for D {
+    fn find_one(&self, id: &uuid::Uuid) -> anyhow::Result<Option<User>> {
         users::table
             .find(id)
-            .first(&pool.get()?)
+            .first(&*self.get()?)
             .optional()
             .context("Couldn't find one user")
     }
 
-    pub fn find_one_by_email(email: &str, pool: &PostgresPool) -> anyhow::Result<Option<User>> {
+    fn find_one_by_email(&self, email: &str) -> anyhow::Result<Option<User>> {
         users::table
             .filter(users::email.eq(email))
-            .first(&pool.get()?)
+            .first(&*self.get()?)
             .optional()
             .context("Couldn't query to find one user by email")
     }
 
-    pub fn save(new_user: &NewUser, pool: &PostgresPool) -> anyhow::Result<User> {
+    fn save(&self, new_user: &NewUser) -> anyhow::Result<User> {
         diesel::insert_into(users::table)
             .values(new_user)
-            .get_result::<User>(&pool.get()?)
+            .get_result::<User>(&*self.get()?)
             .context("Couldn't save this user to the database")
     }
+
+    fn update_password(&self, id: &uuid::Uuid, password: &str) -> anyhow::Result<()> {
+        diesel::update(users::table.find(id))
+            .set((
+                users::password.eq(password),
+                users::password_hashed_at.eq(diesel::dsl::now),
+                users::updated_at.eq(diesel::dsl::now),
+            ))
+            .execute(&*self.get()?)
+            .map(|_| ())
+            .context(format!("Couldn't update this user's ({}) password", id))
+    }
+
+    fn update_email(&self, id: &uuid::Uuid, email: &str) -> anyhow::Result<()> {
+        diesel::update(users::table.find(id))
+            .set((
+                users::email.eq(email),
+                users::email_verified_at.eq(None::<chrono::DateTime<chrono::Utc>>),
+                users::updated_at.eq(diesel::dsl::now),
+            ))
+            .execute(&*self.get()?)
+            .map(|_| ())
+            .context(format!("Couldn't update this user's ({}) email", id))
+    }
+
+    fn verify_email(&self, id: &uuid::Uuid, email: &str) -> anyhow::Result<bool> {
+        diesel::update(
+            users::table
+                .filter(users::id.eq(id))
+                .filter(users::email.eq(email)),
+        )
+        .set((
+            users::email_verified_at.eq(diesel::dsl::now),
+            users::updated_at.eq(diesel::dsl::now),
+        ))
+        .execute(&*self.get()?)
+        .map(|n| n > 0)
+        .context(format!("Couldn't verify this user's ({}) email", id))
+    }
 }
 
 #[derive(Insertable)]
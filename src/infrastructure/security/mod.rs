@@ -1,16 +1,23 @@
+use crate::infrastructure::config::HashSettings;
 use anyhow::Context;
 use chrono::serde::ts_seconds;
 use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
 use serde::{Deserialize, Serialize};
 
 // FIXME: Keep the config to avoid repeating it in the methods
+/// Sign an access token for a user's session, which can be revoked before the token expires.
 pub fn sign_token(
     sub: uuid::Uuid,
+    session_id: uuid::Uuid,
     expiration_time: i64,
     secret_key: &[u8],
 ) -> anyhow::Result<String> {
     let exp = chrono::Utc::now() + chrono::Duration::seconds(expiration_time);
-    let claims = Claims { sub, exp };
+    let claims = Claims {
+        sub,
+        jti: session_id,
+        exp,
+    };
     encode(
         &Header::default(),
         &claims,
@@ -27,11 +34,14 @@ pub fn verify_token(token: &str, secret_key: &[u8]) -> anyhow::Result<Viewer> {
     )?;
     Ok(Viewer {
         id: token.claims.sub,
+        session_id: token.claims.jti,
     })
 }
 
-pub fn hash_password(pwd: &[u8], salt: &[u8]) -> anyhow::Result<String> {
-    argon2::hash_encoded(pwd, salt, &argon2::Config::default())
+/// Hash a password, or a secret, with a random salt of its own.
+pub fn hash_password(pwd: &[u8], settings: &HashSettings) -> anyhow::Result<String> {
+    let salt = rand::random::<[u8; 16]>();
+    argon2::hash_encoded(pwd, &salt, &argon2_config(settings)?)
         .context("Couldn't hash this password")
 }
 
@@ -39,9 +49,56 @@ pub fn verify_password(pwd: &[u8], hash: &str) -> anyhow::Result<bool> {
     argon2::verify_encoded(hash, pwd).context("Couldn't verify this password")
 }
 
+/// Whether a hash was made with other parameters than the current ones.
+pub fn needs_rehash(hash: &str, settings: &HashSettings) -> anyhow::Result<bool> {
+    let config = argon2_config(settings)?;
+    // The parameters are encoded before the salt
+    let params = format!(
+        "${}$v={}$m={},t={},p={}$",
+        config.variant, config.version, config.mem_cost, config.time_cost, config.lanes
+    );
+    Ok(!hash.starts_with(&params))
+}
+
+fn argon2_config(settings: &HashSettings) -> anyhow::Result<argon2::Config<'static>> {
+    Ok(argon2::Config {
+        variant: argon2::Variant::from_str(settings.variant())
+            .context(format!("Unknown argon2 variant {}", settings.variant()))?,
+        mem_cost: settings.memory_cost(),
+        time_cost: settings.iterations(),
+        lanes: settings.parallelism(),
+        thread_mode: argon2::ThreadMode::from_threads(settings.parallelism()),
+        ..argon2::Config::default()
+    })
+}
+
+/// Generate a random secret, to be handed out once and only stored hashed.
+pub fn generate_secret() -> String {
+    format!(
+        "{}{}",
+        uuid::Uuid::new_v4().simple(),
+        uuid::Uuid::new_v4().simple()
+    )
+}
+
+/// Build an opaque token from the id of what it grants access to and from a secret.
+pub fn opaque_token(id: &uuid::Uuid, secret: &str) -> String {
+    format!("{}.{}", id.simple(), secret)
+}
+
+/// Split an opaque token into its id and its secret.
+pub fn parse_opaque_token(token: &str) -> Option<(uuid::Uuid, &str)> {
+    let mut parts = token.splitn(2, '.');
+    let id = uuid::Uuid::parse_str(parts.next()?).ok()?;
+    let secret = parts.next().filter(|s| !s.is_empty())?;
+    Some((id, secret))
+}
+
 #[derive(Serialize, Deserialize)]
 struct Claims {
     sub: uuid::Uuid,
+    /// The session the token was issued for.
+    jti: uuid::Uuid,
     #[serde(with = "ts_seconds")]
     exp: chrono::DateTime<chrono::Utc>,
 }
@@ -49,12 +106,17 @@ struct Claims {
 #[derive(Debug, Clone, Default)]
 pub struct Viewer {
     id: uuid::Uuid,
+    session_id: uuid::Uuid,
 }
 
 impl Viewer {
     pub fn id(&self) -> &uuid::Uuid {
         &self.id
     }
+
+    pub fn session_id(&self) -> &uuid::Uuid {
+        &self.session_id
+    }
 }
 
 #[cfg(test)]
@@ -64,16 +126,51 @@ mod tests {
     #[test]
     fn should_create_a_valid_token() {
         let sub = uuid::Uuid::new_v4();
-        let token = sign_token(sub, 3600, b"mysupersecretkey").unwrap();
+        let session_id = uuid::Uuid::new_v4();
+        let token = sign_token(sub, session_id, 3600, b"mysupersecretkey").unwrap();
         let viewer = verify_token(&token[..], b"mysupersecretkey").unwrap();
 
         assert_eq!(sub, *viewer.id());
+        assert_eq!(session_id, *viewer.session_id());
+    }
+
+    #[test]
+    fn should_parse_opaque_tokens() {
+        let id = uuid::Uuid::new_v4();
+        let secret = generate_secret();
+        let token = opaque_token(&id, &secret);
+
+        assert_eq!(Some((id, &secret[..])), parse_opaque_token(&token));
+        assert_eq!(None, parse_opaque_token(&secret));
+        assert_eq!(None, parse_opaque_token(&format!("{}.", id)));
     }
 
     #[test]
     fn should_hash_a_password_correctly() {
         let pwd = "453cR37";
-        let hash = hash_password(pwd.as_bytes(), b"randomsalt").unwrap();
+        let hash = hash_password(pwd.as_bytes(), &HashSettings::default()).unwrap();
         assert!(verify_password(pwd.as_bytes(), &hash[..]).unwrap())
     }
+
+    #[test]
+    fn identical_passwords_should_get_different_hashes() {
+        let pwd = "453cR37";
+        let settings = HashSettings::default();
+
+        assert_ne!(
+            hash_password(pwd.as_bytes(), &settings).unwrap(),
+            hash_password(pwd.as_bytes(), &settings).unwrap()
+        );
+    }
+
+    #[test]
+    fn outdated_hashes_should_need_a_rehash() {
+        let pwd = b"453cR37";
+        let settings = HashSettings::default();
+        let outdated =
+            argon2::hash_encoded(pwd, b"randomsalt", &argon2::Config::default()).unwrap();
+
+        assert!(needs_rehash(&outdated, &settings).unwrap());
+        assert!(!needs_rehash(&hash_password(pwd, &settings).unwrap(), &settings).unwrap());
+    }
 }
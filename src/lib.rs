@@ -1,10 +1,13 @@
 #[macro_use]
 extern crate diesel;
 
+pub mod domain;
 pub mod infrastructure;
 
 pub use infrastructure::{
     config::*,
     http::run,
-    repositories::{get_pool, PostgresPool},
+    jobs::{purge_trash, spawn_trash_purge},
+    mail::{get_mailer, Email, Mailer, OutboxMailer},
+    repositories::{get_pool, InMemoryStore, PostgresPool, Store},
 };
@@ -9,13 +9,22 @@ async fn main() -> anyhow::Result<()> {
 
     let configuration = group_expenses::Settings::new()?;
     let db_pool = group_expenses::get_pool(&configuration).expect("Failed to connect to Postgres.");
+    let mailer = group_expenses::get_mailer(&configuration)?;
 
     // Setup the database
     embedded_migrations::run_with_output(&db_pool.get()?, &mut std::io::stdout())?;
 
+    group_expenses::spawn_trash_purge(configuration.clone(), db_pool.clone());
+
     let address = format!("0.0.0.0:{}", &configuration.application_port());
     let listener = std::net::TcpListener::bind(address)?;
-    group_expenses::run(listener, configuration, db_pool)?.await?;
+    group_expenses::run(
+        listener,
+        configuration,
+        std::sync::Arc::new(db_pool),
+        mailer,
+    )?
+    .await?;
 
     Ok(())
 }
@@ -21,9 +21,22 @@ fn should_use_the_default_application_port_without_env_var() {
     env::remove_var("APPLICATION_PORT");
     let settings = group_expenses::Settings::new();
 
-    assert!(settings.is_ok(), settings.err().unwrap().to_string());
+    assert!(settings.is_ok(), "{}", settings.as_ref().err().unwrap());
     assert_eq!(
         group_expenses::Settings::new().unwrap().application_port(),
         settings.unwrap().application_port()
     );
 }
+
+#[test]
+fn should_send_the_emails_through_smtp_without_env_var() {
+    helpers::initialize();
+
+    env::remove_var("MAIL_TRANSPORT");
+    let settings = group_expenses::Settings::new().unwrap();
+
+    assert_eq!(
+        group_expenses::MailTransport::Smtp,
+        settings.mail().transport()
+    );
+}
@@ -39,6 +39,335 @@ impl GraphQLClient {
 
         res.json::<GraphQLResponse<T>>().await
     }
+
+    /// Send an authenticated request and return its data, failing on any GraphQL error.
+    async fn execute<T>(&self, body: serde_json::Value, token: &str) -> T
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let input = GraphQLRequestInput::WithToken { body: &body, token };
+        let res = self
+            .send::<T>(&input)
+            .await
+            .expect("Failed to convert response to json");
+
+        assert!(res.errors.is_none(), "{:?}", res.errors);
+        res.data.unwrap()
+    }
+
+    /// Send an authenticated request expected to fail and return its first error's code.
+    async fn error_code(&self, body: serde_json::Value, token: &str) -> serde_json::Value {
+        let input = GraphQLRequestInput::WithToken { body: &body, token };
+        let res = self
+            .send::<serde_json::Value>(&input)
+            .await
+            .expect("Failed to convert response to json");
+
+        let errors = res.errors.expect("The request should have failed");
+        errors[0]["extensions"]["code"].clone()
+    }
+
+    /// Send an authenticated request and return its HTTP status, whatever its outcome.
+    async fn status(&self, body: serde_json::Value, token: &str) -> reqwest::StatusCode {
+        self.client
+            .post(&self.url)
+            .header(reqwest::header::AUTHORIZATION, format!("Bearer {}", token))
+            .json(&body)
+            .send()
+            .await
+            .expect("Failed to execute request")
+            .status()
+    }
+
+    /// Signup a new random user and return their token.
+    async fn signup(&self) -> String {
+        self.signup_as(&format!("{}@htest.com", helpers::rand_string()))
+            .await
+    }
+
+    /// Signup a new random user, verify their email with the link they were sent,
+    /// and return their token.
+    async fn verified_signup(&self) -> String {
+        let email = format!("{}@htest.com", helpers::rand_string());
+        let token = self.signup_as(&email).await;
+        let body = json!({
+            "query": r#"
+                mutation IT_VERIFY_EMAIL($token: String!) {
+                    verifyEmail(token: $token)
+                }
+            "#,
+            "variables": {
+                "token": verification_token(&email)
+            }
+        });
+        self.execute::<serde_json::Value>(body, &token).await;
+        token
+    }
+
+    async fn signup_as(&self, email: &str) -> String {
+        let body = json!({
+            "query": r#"
+                mutation IT_SIGNUP($input: SignupInput!) {
+                    signup(input: $input)
+                }
+            "#,
+            "variables": {
+                "input": {
+                    "email": email,
+                    "password": "hihihihi"
+                }
+            }
+        });
+        let input = GraphQLRequestInput::WithoutToken { body: &body };
+        let res = self
+            .send::<Signup>(&input)
+            .await
+            .expect("Failed to convert response to json");
+
+        assert!(res.errors.is_none(), "{:?}", res.errors);
+        res.data.unwrap().signup
+    }
+
+    /// Signup the admin user, or login if they already exist, verify their email if it isn't,
+    /// and return their token.
+    async fn admin(&self) -> String {
+        let token = self.signup_or_login_admin().await;
+        // The admin rights need a verified email
+        let body = json!({ "query": "query IT_VIEWER { viewer { emailVerifiedAt } }" });
+        let viewer = self.execute::<serde_json::Value>(body, &token).await;
+        if viewer["viewer"]["emailVerifiedAt"].is_null() {
+            let body = json!({
+                "query": "mutation IT_SEND_VERIFICATION_EMAIL { sendVerificationEmail }"
+            });
+            self.execute::<serde_json::Value>(body, &token).await;
+            let body = json!({
+                "query": r#"
+                    mutation IT_VERIFY_EMAIL($token: String!) {
+                        verifyEmail(token: $token)
+                    }
+                "#,
+                "variables": {
+                    "token": verification_token(helpers::ADMIN_EMAIL)
+                }
+            });
+            // Another test may have verified it meanwhile
+            self.send::<serde_json::Value>(&GraphQLRequestInput::WithToken {
+                body: &body,
+                token: &token,
+            })
+            .await
+            .expect("Failed to convert response to json");
+        }
+        token
+    }
+
+    async fn signup_or_login_admin(&self) -> String {
+        let body = json!({
+            "query": r#"
+                mutation IT_SIGNUP($input: SignupInput!) {
+                    signup(input: $input)
+                }
+            "#,
+            "variables": {
+                "input": {
+                    "email": helpers::ADMIN_EMAIL,
+                    "password": "hihihihi"
+                }
+            }
+        });
+        let input = GraphQLRequestInput::WithoutToken { body: &body };
+        let res = self
+            .send::<Signup>(&input)
+            .await
+            .expect("Failed to convert response to json");
+        if let Some(data) = res.data {
+            return data.signup;
+        }
+
+        let body = json!({
+            "query": r#"
+                query IT_LOGIN($email: String!, $password: String!) {
+                    login(email: $email, password: $password)
+                }
+            "#,
+            "variables": {
+                "email": helpers::ADMIN_EMAIL,
+                "password": "hihihihi"
+            }
+        });
+        let input = GraphQLRequestInput::WithoutToken { body: &body };
+        let res = self
+            .send::<Login>(&input)
+            .await
+            .expect("Failed to convert response to json");
+
+        assert!(res.errors.is_none(), "{:?}", res.errors);
+        res.data.unwrap().login
+    }
+
+    /// Add a group with the given persons to the viewer and return it.
+    async fn add_group(&self, token: &str, persons: &[&str]) -> Group {
+        let name = helpers::rand_string();
+        let body = json!({
+            "query": r#"
+                mutation IT_ADD_GROUP($input: AddGroupInput!) {
+                    addGroup(input: $input)
+                }
+            "#,
+            "variables": {
+                "input": {
+                    "name": name
+                }
+            }
+        });
+        self.execute::<AddGroup>(body, token).await;
+        let body = json!({
+            "query": r#"
+                query IT_VIEWER {
+                    viewer {
+                        groups {
+                            id
+                            name
+                            persons {
+                                id
+                                name
+                            }
+                            expenses {
+                                id
+                                name
+                            }
+                        }
+                    }
+                }
+            "#
+        });
+        let group_id = self
+            .execute::<Viewer>(body, token)
+            .await
+            .viewer
+            .groups
+            .into_iter()
+            .find(|g| g.name == name)
+            .unwrap()
+            .id;
+
+        for person in persons {
+            let body = json!({
+                "query": r#"
+                    mutation IT_ADD_PERSON($input: AddPersonInput!) {
+                        addPerson(input: $input)
+                    }
+                "#,
+                "variables": {
+                    "input": {
+                        "groupId": group_id,
+                        "name": person,
+                        "resources": { "amount": 0 },
+                    }
+                }
+            });
+            self.execute::<AddPerson>(body, token).await;
+        }
+
+        self.group(token, &group_id).await
+    }
+
+    /// Query one of the viewer's groups.
+    async fn group(&self, token: &str, id: &uuid::Uuid) -> Group {
+        let body = json!({
+            "query": r#"
+                query IT_GROUP($id: String!) {
+                    group(id: $id) {
+                        id
+                        name
+                        currency
+                        total {
+                            amount
+                        }
+                        persons {
+                            id
+                            name
+                            resources {
+                                amount
+                            }
+                            expenses {
+                                id
+                                name
+                                amount {
+                                    amount
+                                }
+                            }
+                        }
+                        expenses {
+                            id
+                            name
+                            amount {
+                                amount
+                                currency
+                            }
+                            exchangeRate
+                            payers {
+                                person {
+                                    id
+                                }
+                                amount {
+                                    amount
+                                }
+                            }
+                        }
+                        payments {
+                            id
+                            from {
+                                id
+                            }
+                            to {
+                                id
+                            }
+                            amount {
+                                amount
+                            }
+                        }
+                        balances {
+                            person {
+                                id
+                            }
+                            paid {
+                                amount
+                            }
+                            owed {
+                                amount
+                            }
+                            sent {
+                                amount
+                            }
+                            received {
+                                amount
+                            }
+                            net {
+                                amount
+                            }
+                        }
+                        settlementPlan {
+                            from {
+                                id
+                            }
+                            to {
+                                id
+                            }
+                            amount {
+                                amount
+                            }
+                        }
+                    }
+                }
+            "#,
+            "variables": {
+                "id": id
+            }
+        });
+
+        self.execute::<GroupQuery>(body, token).await.group
+    }
 }
 
 #[actix_rt::test]
@@ -72,7 +401,7 @@ async fn graphql_api_should_work() {
         .expect("Failed to convert response to json");
 
     // Assert
-    assert!(res.errors.is_none(), format!("{:?}", res.errors));
+    assert!(res.errors.is_none(), "{:?}", res.errors);
     let data = res.data.unwrap();
     assert!(!data.signup.is_empty());
 
@@ -98,7 +427,7 @@ async fn graphql_api_should_work() {
         .expect("Failed to convert response to json");
 
     // Assert
-    assert!(res.errors.is_none(), format!("{:?}", res.errors));
+    assert!(res.errors.is_none(), "{:?}", res.errors);
     let data = res.data.unwrap();
     assert!(!data.login.is_empty());
 
@@ -138,7 +467,7 @@ async fn graphql_api_should_work() {
         .expect("Failed to convert response to json");
 
     // Assert
-    assert!(res.errors.is_none(), format!("{:?}", res.errors));
+    assert!(res.errors.is_none(), "{:?}", res.errors);
     let data = res.data.unwrap();
     assert!(data.viewer.groups.is_empty());
 
@@ -169,7 +498,7 @@ async fn graphql_api_should_work() {
         .expect("Failed to convert response to json");
 
     // Assert
-    assert!(res.errors.is_none(), format!("{:?}", res.errors));
+    assert!(res.errors.is_none(), "{:?}", res.errors);
 
     /* --- Check Mutation result --- */
     let res = client
@@ -178,7 +507,7 @@ async fn graphql_api_should_work() {
         .expect("Failed to convert response to json");
 
     // Assert
-    assert!(res.errors.is_none(), format!("{:?}", res.errors));
+    assert!(res.errors.is_none(), "{:?}", res.errors);
     let data = res.data.unwrap();
     assert!(data.viewer.groups.len() == 1);
     assert_eq!(group_name, data.viewer.groups[0].name);
@@ -211,7 +540,7 @@ async fn graphql_api_should_work() {
         .expect("Failed to convert response to json");
 
     // Assert
-    assert!(res.errors.is_none(), format!("{:?}", res.errors));
+    assert!(res.errors.is_none(), "{:?}", res.errors);
 
     /* --- addPerson --- */
     // Arrange
@@ -225,7 +554,7 @@ async fn graphql_api_should_work() {
             "input": {
                 "groupId": group_id,
                 "name": "Mary",
-                "resources": 0,
+                "resources": { "amount": 0 },
             }
         }
     });
@@ -241,7 +570,7 @@ async fn graphql_api_should_work() {
         .expect("Failed to convert response to json");
 
     // Assert
-    assert!(res.errors.is_none(), format!("{:?}", res.errors));
+    assert!(res.errors.is_none(), "{:?}", res.errors);
 
     /* --- Shouldn't be able to create duplicate person --- */
     // Act
@@ -264,17 +593,48 @@ async fn graphql_api_should_work() {
                     persons {
                         id
                         name
-                        resources
+                        resources {
+                            amount
+                        }
                         expenses {
                             id
                             name
-                            amount
+                            amount {
+                                amount
+                            }
                         }
                     }
                     expenses {
                         id
                         name
-                        amount
+                        amount {
+                            amount
+                        }
+                    }
+                    balances {
+                        person {
+                            id
+                        }
+                        paid {
+                            amount
+                        }
+                        owed {
+                            amount
+                        }
+                        net {
+                            amount
+                        }
+                    }
+                    settlementPlan {
+                        from {
+                            id
+                        }
+                        to {
+                            id
+                        }
+                        amount {
+                            amount
+                        }
                     }
                 }
             }
@@ -295,7 +655,7 @@ async fn graphql_api_should_work() {
         .expect("Failed to convert response to json");
 
     // Assert
-    assert!(res.errors.is_none(), format!("{:?}", res.errors));
+    assert!(res.errors.is_none(), "{:?}", res.errors);
     let data = res.data.unwrap();
     assert!(!data.group.persons.is_empty());
     assert!(data.group.persons.len() == 1);
@@ -314,7 +674,7 @@ async fn graphql_api_should_work() {
         "variables": {
             "input": {
                 "personId": person_id,
-                "resources": new_resources
+                "resources": { "amount": new_resources }
             }
         }
     });
@@ -330,7 +690,7 @@ async fn graphql_api_should_work() {
         .expect("Failed to convert response to json");
 
     // Assert
-    assert!(res.errors.is_none(), format!("{:?}", res.errors));
+    assert!(res.errors.is_none(), "{:?}", res.errors);
 
     /* --- addExpense --- */
     // Arrange
@@ -345,7 +705,7 @@ async fn graphql_api_should_work() {
                 "groupId": group_id,
                 "personId": person_id,
                 "name": "Burger King",
-                "amount": 20
+                "amount": { "amount": 20 }
             }
         }
     });
@@ -361,7 +721,7 @@ async fn graphql_api_should_work() {
         .expect("Failed to convert response to json");
 
     // Assert
-    assert!(res.errors.is_none(), format!("{:?}", res.errors));
+    assert!(res.errors.is_none(), "{:?}", res.errors);
 
     /* --- Check mutation results --- */
     let res = client
@@ -370,10 +730,16 @@ async fn graphql_api_should_work() {
         .expect("Failed to convert response to json");
 
     // Assert
-    assert!(res.errors.is_none(), format!("{:?}", res.errors));
+    assert!(res.errors.is_none(), "{:?}", res.errors);
     let data = res.data.unwrap();
     assert_eq!(data.group.persons[0].resources, new_resources);
     assert!(!data.group.persons[0].expenses.is_empty());
+    assert_eq!(data.group.balances.len(), 1);
+    assert_eq!(data.group.balances[0].person.id, person_id);
+    assert_eq!(data.group.balances[0].paid, 20);
+    assert_eq!(data.group.balances[0].owed, 20);
+    assert_eq!(data.group.balances[0].net, 0);
+    assert!(data.group.settlement_plan.is_empty());
 
     /* --- removePerson --- */
     // Arrange
@@ -401,7 +767,7 @@ async fn graphql_api_should_work() {
         .expect("Failed to convert response to json");
 
     // Assert
-    assert!(res.errors.is_none(), format!("{:?}", res.errors));
+    assert!(res.errors.is_none(), "{:?}", res.errors);
 
     /* --- Check mutation results --- */
     // Act
@@ -411,7 +777,7 @@ async fn graphql_api_should_work() {
         .expect("Failed to convert response to json");
 
     // Assert
-    assert!(res.errors.is_none(), format!("{:?}", res.errors));
+    assert!(res.errors.is_none(), "{:?}", res.errors);
     let data = res.data.unwrap();
     assert!(data.group.persons.is_empty());
     assert!(data.group.expenses.is_empty());
@@ -442,7 +808,7 @@ async fn graphql_api_should_work() {
         .expect("Failed to convert response to json");
 
     // Assert
-    assert!(res.errors.is_none(), format!("{:?}", res.errors));
+    assert!(res.errors.is_none(), "{:?}", res.errors);
 
     /* --- Check Mutation result --- */
     let res = client
@@ -451,126 +817,2974 @@ async fn graphql_api_should_work() {
         .expect("Failed to convert response to json");
 
     // Assert
-    assert!(res.errors.is_none(), format!("{:?}", res.errors));
+    assert!(res.errors.is_none(), "{:?}", res.errors);
     let data = res.data.unwrap();
     assert!(data.viewer.groups.is_empty());
 }
 
 #[actix_rt::test]
-async fn non_auth_operations_should_be_protected() {
+async fn payments_should_settle_balances() {
     let app = helpers::spawn_app();
-    let client = reqwest::Client::new();
+    let client = GraphQLClient::new(format!("{}/graphql", app.address));
+    let token = client.signup().await;
+    let group = client.add_group(&token, &["Alice", "Bob"]).await;
+    let (alice, bob) = (group.persons[0].id, group.persons[1].id);
 
+    /* --- addExpense --- */
     // Arrange
     let body = json!({
         "query": r#"
-            query NON_AUTH {
-                nonAuth {
-                    myfield
-                }
+            mutation IT_ADD_EXPENSE($input: AddExpenseInput!) {
+                addExpense(input: $input)
             }
-        "#
+        "#,
+        "variables": {
+            "input": {
+                "groupId": group.id,
+                "personId": alice,
+                "name": "Restaurant",
+                "amount": { "amount": 80 }
+            }
+        }
     });
 
     // Act
+    client.execute::<AddExpense>(body, &token).await;
+    let group = client.group(&token, &group.id).await;
+
+    // Assert
+    assert_eq!(1, group.settlement_plan.len());
+    assert_eq!(bob, group.settlement_plan[0].from.id);
+    assert_eq!(40, group.settlement_plan[0].amount);
+
+    /* --- addPayment --- */
+    // Arrange
+    let add_payment = |from: uuid::Uuid, to: uuid::Uuid| {
+        json!({
+            "query": r#"
+                mutation IT_ADD_PAYMENT($input: AddPaymentInput!) {
+                    addPayment(input: $input)
+                }
+            "#,
+            "variables": {
+                "input": {
+                    "groupId": group.id,
+                    "fromPersonId": from,
+                    "toPersonId": to,
+                    "amount": { "amount": 40 }
+                }
+            }
+        })
+    };
+
+    // Act
+    client
+        .execute::<AddPayment>(add_payment(bob, alice), &token)
+        .await;
+    let group = client.group(&token, &group.id).await;
+
+    // Assert
+    assert_eq!(1, group.payments.len());
+    assert_eq!(bob, group.payments[0].from.id);
+    assert!(group.balances.iter().all(|b| b.net == 0));
+    assert!(group.settlement_plan.is_empty());
+
+    /* --- Shouldn't be able to pay oneself back --- */
+    // Act
+    let body = add_payment(bob, bob);
+    let input = GraphQLRequestInput::WithToken {
+        body: &body,
+        token: &token,
+    };
     let res = client
-        .post(&format!("{}/graphql", app.address))
-        .json(&body)
-        .send()
+        .send::<AddPayment>(&input)
         .await
-        .expect("Failed to execute request");
+        .expect("Failed to convert response to json");
 
-    assert_eq!(401, res.status());
-}
+    // Assert
+    assert!(res.errors.is_some());
 
-#[derive(serde::Deserialize)]
-struct GraphQLResponse<T> {
-    data: Option<T>,
-    errors: Option<Vec<serde_json::Value>>,
-}
+    /* --- Shouldn't be able to remove another user's payment --- */
+    // Arrange
+    let body = json!({
+        "query": r#"
+            mutation IT_REMOVE_PAYMENT($input: RemovePaymentInput!) {
+                removePayment(input: $input)
+            }
+        "#,
+        "variables": {
+            "input": {
+                "paymentId": group.payments[0].id
+            }
+        }
+    });
+    let other_token = client.signup().await;
+    let input = GraphQLRequestInput::WithToken {
+        body: &body,
+        token: &other_token,
+    };
 
-#[derive(serde::Deserialize)]
-struct Signup {
-    signup: String,
-}
+    // Act
+    let res = client
+        .send::<RemovePayment>(&input)
+        .await
+        .expect("Failed to convert response to json");
 
-#[derive(serde::Deserialize)]
-struct Login {
-    login: String,
-}
+    // Assert
+    assert!(res.errors.is_some());
 
-#[derive(serde::Deserialize)]
-struct Viewer {
-    viewer: User,
-}
+    /* --- removePayment --- */
+    // Act
+    client.execute::<RemovePayment>(body, &token).await;
+    let group = client.group(&token, &group.id).await;
 
-#[derive(serde::Deserialize)]
-struct GroupQuery {
-    group: Group,
+    // Assert
+    assert!(group.payments.is_empty());
+    assert_eq!(1, group.settlement_plan.len());
 }
 
-#[derive(serde::Deserialize)]
-struct User {
-    groups: Vec<Group>,
-}
+#[actix_rt::test]
+async fn expenses_should_be_split_between_their_participants() {
+    let app = helpers::spawn_app();
+    let client = GraphQLClient::new(format!("{}/graphql", app.address));
+    let token = client.signup().await;
+    let group = client.add_group(&token, &["Alice", "Bob", "Carol"]).await;
+    let (alice, bob) = (group.persons[0].id, group.persons[1].id);
+    let add_expense = |split_mode: &str, shares: serde_json::Value| {
+        json!({
+            "query": r#"
+                mutation IT_ADD_EXPENSE($input: AddExpenseInput!) {
+                    addExpense(input: $input)
+                }
+            "#,
+            "variables": {
+                "input": {
+                    "groupId": group.id,
+                    "personId": alice,
+                    "name": "Cinema",
+                    "amount": { "amount": 30 },
+                    "splitMode": split_mode,
+                    "shares": shares
+                }
+            }
+        })
+    };
 
-#[allow(dead_code)]
-#[derive(serde::Deserialize)]
-struct Group {
-    id: uuid::Uuid,
-    name: String,
-    persons: Vec<Person>,
-    expenses: Vec<Expense>,
-}
+    /* --- addExpense with exact amounts --- */
+    // Arrange
+    let body = add_expense(
+        "EXACT",
+        json!([
+            { "personId": alice, "value": 10 },
+            { "personId": bob, "value": 20 }
+        ]),
+    );
 
-#[allow(dead_code)]
-#[derive(serde::Deserialize)]
-struct Person {
-    id: uuid::Uuid,
-    name: String,
-    resources: i32,
-    expenses: Vec<Expense>,
-}
+    // Act
+    client.execute::<AddExpense>(body, &token).await;
+    let group = client.group(&token, &group.id).await;
 
-#[allow(dead_code)]
-#[derive(serde::Deserialize)]
-struct Expense {
-    id: uuid::Uuid,
-    name: String,
-    amount: i32,
-}
+    // Assert
+    let owed = group.balances.iter().map(|b| b.owed).collect::<Vec<_>>();
+    assert_eq!(vec![10, 20, 0], owed);
 
-#[allow(dead_code)]
-#[derive(serde::Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct AddGroup {
-    add_group: bool,
-}
+    /* --- Percentages must add up to 100 --- */
+    // Arrange
+    let body = add_expense(
+        "PERCENTAGE",
+        json!([
+            { "personId": alice, "value": 50 },
+            { "personId": bob, "value": 40 }
+        ]),
+    );
+    let input = GraphQLRequestInput::WithToken {
+        body: &body,
+        token: &token,
+    };
 
-#[allow(dead_code)]
-#[derive(serde::Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct UpdateGroup {
-    update_group: bool,
-}
+    // Act
+    let res = client
+        .send::<AddExpense>(&input)
+        .await
+        .expect("Failed to convert response to json");
 
-#[allow(dead_code)]
-#[derive(serde::Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct AddPerson {
-    add_person: bool,
+    // Assert
+    let errors = res.errors.expect("The percentages should be refused");
+    assert_eq!("INVALID_PERCENTAGES", errors[0]["extensions"]["code"]);
 }
 
-#[allow(dead_code)]
-#[derive(serde::Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct UpdatePerson {
-    update_person: bool,
-}
+#[actix_rt::test]
+async fn expenses_can_be_paid_by_several_persons() {
+    let app = helpers::spawn_app();
+    let client = GraphQLClient::new(format!("{}/graphql", app.address));
+    let token = client.signup().await;
+    let group = client.add_group(&token, &["Alice", "Bob", "Carol"]).await;
+    let (alice, bob) = (group.persons[0].id, group.persons[1].id);
+    let add_expense = |payers: serde_json::Value| {
+        json!({
+            "query": r#"
+                mutation IT_ADD_EXPENSE($input: AddExpenseInput!) {
+                    addExpense(input: $input)
+                }
+            "#,
+            "variables": {
+                "input": {
+                    "groupId": group.id,
+                    "name": "Restaurant",
+                    "amount": { "amount": 90 },
+                    "splitMode": "EQUAL",
+                    "payers": payers
+                }
+            }
+        })
+    };
 
-#[allow(dead_code)]
-#[derive(serde::Deserialize)]
-#[serde(rename_all = "camelCase")]
+    /* --- addExpense with several payers --- */
+    // Arrange
+    let body = add_expense(json!([
+        { "personId": alice, "amount": 60 },
+        { "personId": bob, "amount": 30 }
+    ]));
+
+    // Act
+    client.execute::<AddExpense>(body, &token).await;
+    let group = client.group(&token, &group.id).await;
+
+    // Assert
+    assert_eq!(2, group.expenses[0].payers.len());
+    let paid = group.balances.iter().map(|b| b.paid).collect::<Vec<_>>();
+    assert_eq!(vec![60, 30, 0], paid);
+    let nets = group.balances.iter().map(|b| b.net).collect::<Vec<_>>();
+    assert_eq!(vec![30, 0, -30], nets);
+    assert_eq!(1, group.persons[0].expenses.len());
+    assert_eq!(1, group.persons[1].expenses.len());
+    assert!(group.persons[2].expenses.is_empty());
+
+    /* --- Payers must pay the whole amount --- */
+    // Arrange
+    let body = add_expense(json!([
+        { "personId": alice, "amount": 60 },
+        { "personId": bob, "amount": 20 }
+    ]));
+    let input = GraphQLRequestInput::WithToken {
+        body: &body,
+        token: &token,
+    };
+
+    // Act
+    let res = client
+        .send::<AddExpense>(&input)
+        .await
+        .expect("Failed to convert response to json");
+
+    // Assert
+    let errors = res.errors.expect("The payers should be refused");
+    assert_eq!("INVALID_PAYERS", errors[0]["extensions"]["code"]);
+}
+
+#[actix_rt::test]
+async fn expenses_should_be_converted_into_the_group_currency() {
+    let app = helpers::spawn_app();
+    let client = GraphQLClient::new(format!("{}/graphql", app.address));
+    let token = client.signup().await;
+    let admin = client.admin().await;
+    let group = client.add_group(&token, &["Alice", "Bob"]).await;
+    let alice = group.persons[0].id;
+    let set_rate = |rate: f64| {
+        json!({
+            "query": r#"
+                mutation IT_SET_EXCHANGE_RATE($input: ExchangeRateInput!) {
+                    setExchangeRate(input: $input)
+                }
+            "#,
+            "variables": {
+                "input": {
+                    "from": "XTS",
+                    "to": "EUR",
+                    "rate": rate
+                }
+            }
+        })
+    };
+    let add_expense = |currency: &str| {
+        json!({
+            "query": r#"
+                mutation IT_ADD_EXPENSE($input: AddExpenseInput!) {
+                    addExpense(input: $input)
+                }
+            "#,
+            "variables": {
+                "input": {
+                    "groupId": group.id,
+                    "personId": alice,
+                    "name": "Souvenirs",
+                    "amount": { "amount": 30, "currency": currency }
+                }
+            }
+        })
+    };
+
+    /* --- Only admins can set exchange rates --- */
+    // Arrange
+    let body = set_rate(2.0);
+    let input = GraphQLRequestInput::WithToken {
+        body: &body,
+        token: &token,
+    };
+
+    // Act
+    let res = client
+        .send::<SetExchangeRate>(&input)
+        .await
+        .expect("Failed to convert response to json");
+
+    // Assert
+    let errors = res.errors.expect("The viewer shouldn't be an admin");
+    assert_eq!("FORBIDDEN", errors[0]["extensions"]["code"]);
+
+    /* --- Admins should have verified their email --- */
+    // Arrange
+    let unverified_admin = client.signup_as(&helpers::UNVERIFIED_ADMIN_EMAIL).await;
+    let body = set_rate(2.0);
+    let input = GraphQLRequestInput::WithToken {
+        body: &body,
+        token: &unverified_admin,
+    };
+
+    // Act
+    let res = client
+        .send::<SetExchangeRate>(&input)
+        .await
+        .expect("Failed to convert response to json");
+
+    // Assert
+    let errors = res.errors.expect("The viewer shouldn't be an admin yet");
+    assert_eq!("FORBIDDEN", errors[0]["extensions"]["code"]);
+
+    /* --- addExpense in another currency --- */
+    // Arrange
+    client
+        .execute::<SetExchangeRate>(set_rate(2.0), &admin)
+        .await;
+
+    // Act
+    client
+        .execute::<AddExpense>(add_expense("XTS"), &token)
+        .await;
+    let group = client.group(&token, &group.id).await;
+
+    // Assert
+    assert_eq!("EUR", group.currency);
+    assert_eq!("XTS", group.expenses[0].amount.currency);
+    assert_eq!(60, group.total);
+    let paid = group.balances.iter().map(|b| b.paid).collect::<Vec<_>>();
+    assert_eq!(vec![60, 0], paid);
+
+    /* --- A new rate doesn't change the existing expenses --- */
+    // Arrange
+    let body = json!({
+        "query": r#"
+            mutation IT_IMPORT_EXCHANGE_RATES($csv: String!) {
+                importExchangeRates(csv: $csv)
+            }
+        "#,
+        "variables": {
+            "csv": "from,to,rate\nXTS,EUR,3\n"
+        }
+    });
+
+    // Act
+    let imported = client.execute::<ImportExchangeRates>(body, &admin).await;
+    let group = client.group(&token, &group.id).await;
+
+    // Assert
+    assert_eq!(1, imported.import_exchange_rates);
+    assert_eq!(2.0, group.expenses[0].exchange_rate);
+    assert_eq!(60, group.total);
+
+    /* --- An expense needs a rate into the group currency --- */
+    // Arrange
+    let body = add_expense("XXX");
+    let input = GraphQLRequestInput::WithToken {
+        body: &body,
+        token: &token,
+    };
+
+    // Act
+    let res = client
+        .send::<AddExpense>(&input)
+        .await
+        .expect("Failed to convert response to json");
+
+    // Assert
+    let errors = res.errors.expect("There shouldn't be any rate for XXX");
+    assert_eq!("EXCHANGE_RATE_NOT_FOUND", errors[0]["extensions"]["code"]);
+}
+
+#[actix_rt::test]
+async fn expenses_should_be_filtered_by_date() {
+    let app = helpers::spawn_app();
+    let client = GraphQLClient::new(format!("{}/graphql", app.address));
+    let token = client.signup().await;
+    let group = client.add_group(&token, &["Alice", "Bob"]).await;
+    let (alice, bob) = (group.persons[0].id, group.persons[1].id);
+    for (person, amount, occurred_on) in &[(alice, 10, "2020-09-01"), (bob, 30, "2020-09-15")] {
+        let body = json!({
+            "query": r#"
+                mutation IT_ADD_EXPENSE($input: AddExpenseInput!) {
+                    addExpense(input: $input)
+                }
+            "#,
+            "variables": {
+                "input": {
+                    "groupId": group.id,
+                    "personId": person,
+                    "name": "Groceries",
+                    "amount": { "amount": amount },
+                    "splitMode": "EQUAL",
+                    "occurredOn": occurred_on
+                }
+            }
+        });
+        client.execute::<AddExpense>(body, &token).await;
+    }
+    let ranged_group = |from: &str, to: &str| {
+        json!({
+            "query": r#"
+                query IT_GROUP($id: String!, $from: NaiveDate, $to: NaiveDate) {
+                    group(id: $id) {
+                        id
+                        name
+                        persons {
+                            id
+                            name
+                            resources {
+                                amount
+                            }
+                            expenses {
+                                id
+                                name
+                                amount {
+                                    amount
+                                }
+                            }
+                        }
+                        expenses(from: $from, to: $to) {
+                            id
+                            name
+                            amount {
+                                amount
+                            }
+                        }
+                        total(from: $from, to: $to) {
+                            amount
+                        }
+                        balances(from: $from, to: $to) {
+                            person {
+                                id
+                            }
+                            paid {
+                                amount
+                            }
+                            owed {
+                                amount
+                            }
+                            net {
+                                amount
+                            }
+                        }
+                    }
+                }
+            "#,
+            "variables": {
+                "id": group.id,
+                "from": from,
+                "to": to
+            }
+        })
+    };
+
+    /* --- Only the expenses in the range are counted --- */
+    // Act
+    let group = client
+        .execute::<GroupQuery>(ranged_group("2020-09-10", "2020-09-30"), &token)
+        .await
+        .group;
+
+    // Assert
+    assert_eq!(1, group.expenses.len());
+    assert_eq!(30, group.total);
+    let nets = group.balances.iter().map(|b| b.net).collect::<Vec<_>>();
+    assert_eq!(vec![-15, 15], nets);
+
+    /* --- The range can't end before it starts --- */
+    // Arrange
+    let body = ranged_group("2020-09-30", "2020-09-10");
+    let input = GraphQLRequestInput::WithToken {
+        body: &body,
+        token: &token,
+    };
+
+    // Act
+    let res = client
+        .send::<serde_json::Value>(&input)
+        .await
+        .expect("Failed to convert response to json");
+
+    // Assert
+    let errors = res.errors.expect("The range should be refused");
+    assert_eq!("INVALID_DATE_RANGE", errors[0]["extensions"]["code"]);
+}
+
+#[actix_rt::test]
+async fn expenses_should_be_categorized() {
+    let app = helpers::spawn_app();
+    let client = GraphQLClient::new(format!("{}/graphql", app.address));
+    let token = client.signup().await;
+    let group = client.add_group(&token, &["Alice", "Bob"]).await;
+    let categories = json!({
+        "query": r#"
+            query IT_GROUP($id: String!) {
+                group(id: $id) {
+                    categories {
+                        id
+                        name
+                        builtIn
+                    }
+                    expenses {
+                        category {
+                            name
+                        }
+                    }
+                }
+            }
+        "#,
+        "variables": {
+            "id": group.id
+        }
+    });
+
+    /* --- A custom category comes after the built-in ones --- */
+    // Arrange
+    let body = json!({
+        "query": r#"
+            mutation IT_ADD_CATEGORY($input: AddCategoryInput!) {
+                addCategory(input: $input)
+            }
+        "#,
+        "variables": {
+            "input": {
+                "groupId": group.id,
+                "name": "Gifts"
+            }
+        }
+    });
+
+    // Act
+    client.execute::<serde_json::Value>(body, &token).await;
+    let res = client
+        .execute::<serde_json::Value>(categories.clone(), &token)
+        .await;
+
+    // Assert
+    let list = res["group"]["categories"].as_array().unwrap();
+    assert!(list.len() > 1, "{:?}", list);
+    assert_eq!(true, list[0]["builtIn"]);
+    let gifts = list.last().unwrap();
+    assert_eq!("Gifts", gifts["name"]);
+    assert_eq!(false, gifts["builtIn"]);
+
+    /* --- Expenses can belong to a category --- */
+    // Arrange
+    let body = json!({
+        "query": r#"
+            mutation IT_ADD_EXPENSE($input: AddExpenseInput!) {
+                addExpense(input: $input)
+            }
+        "#,
+        "variables": {
+            "input": {
+                "groupId": group.id,
+                "personId": group.persons[0].id,
+                "name": "Flowers",
+                "amount": { "amount": 20 },
+                "categoryId": gifts["id"]
+            }
+        }
+    });
+
+    // Act
+    client.execute::<serde_json::Value>(body, &token).await;
+    let res = client
+        .execute::<serde_json::Value>(categories, &token)
+        .await;
+
+    // Assert
+    assert_eq!("Gifts", res["group"]["expenses"][0]["category"]["name"]);
+
+    /* --- A category can't be removed while it's in use --- */
+    // Arrange
+    let remove_category = |id: &serde_json::Value| {
+        json!({
+            "query": r#"
+                mutation IT_REMOVE_CATEGORY($input: RemoveCategoryInput!) {
+                    removeCategory(input: $input)
+                }
+            "#,
+            "variables": {
+                "input": {
+                    "categoryId": id
+                }
+            }
+        })
+    };
+    let body = remove_category(&gifts["id"]);
+    let input = GraphQLRequestInput::WithToken {
+        body: &body,
+        token: &token,
+    };
+
+    // Act
+    let res = client
+        .send::<serde_json::Value>(&input)
+        .await
+        .expect("Failed to convert response to json");
+
+    // Assert
+    let errors = res.errors.expect("The category should still be in use");
+    assert_eq!("CATEGORY_IN_USE", errors[0]["extensions"]["code"]);
+
+    /* --- Built-in categories can't be removed --- */
+    // Arrange
+    let body = remove_category(&list[0]["id"]);
+    let input = GraphQLRequestInput::WithToken {
+        body: &body,
+        token: &token,
+    };
+
+    // Act
+    let res = client
+        .send::<serde_json::Value>(&input)
+        .await
+        .expect("Failed to convert response to json");
+
+    // Assert
+    let errors = res.errors.expect("Built-in categories should be kept");
+    assert_eq!("CATEGORY_NOT_FOUND", errors[0]["extensions"]["code"]);
+}
+
+#[actix_rt::test]
+async fn group_statistics_should_sum_the_expenses() {
+    let app = helpers::spawn_app();
+    let client = GraphQLClient::new(format!("{}/graphql", app.address));
+    let token = client.signup().await;
+    let group = client.add_group(&token, &["Alice", "Bob"]).await;
+    let (alice, bob) = (group.persons[0].id, group.persons[1].id);
+    let food = "00000000-0000-0000-0000-000000000001";
+    for (person, amount, occurred_on, category) in &[
+        (alice, 10, "2020-09-01", Some(food)),
+        (bob, 30, "2020-09-15", None),
+        (alice, 5, "2020-10-02", Some(food)),
+    ] {
+        let body = json!({
+            "query": r#"
+                mutation IT_ADD_EXPENSE($input: AddExpenseInput!) {
+                    addExpense(input: $input)
+                }
+            "#,
+            "variables": {
+                "input": {
+                    "groupId": group.id,
+                    "personId": person,
+                    "name": "Groceries",
+                    "amount": { "amount": amount },
+                    "occurredOn": occurred_on,
+                    "categoryId": category
+                }
+            }
+        });
+        client.execute::<AddExpense>(body, &token).await;
+    }
+    let statistics = |to: Option<&str>| {
+        json!({
+            "query": r#"
+                query IT_GROUP($id: String!, $to: NaiveDate) {
+                    group(id: $id) {
+                        statistics(to: $to) {
+                            byCategory {
+                                category {
+                                    name
+                                }
+                                total {
+                                    amount
+                                }
+                            }
+                            byPerson {
+                                person {
+                                    id
+                                }
+                                total {
+                                    amount
+                                }
+                            }
+                            byMonth {
+                                month
+                                total {
+                                    amount
+                                }
+                            }
+                        }
+                    }
+                }
+            "#,
+            "variables": {
+                "id": group.id,
+                "to": to
+            }
+        })
+    };
+
+    /* --- The expenses are summed by category, person and month --- */
+    // Act
+    let res = client
+        .execute::<serde_json::Value>(statistics(None), &token)
+        .await;
+
+    // Assert
+    let totals = &res["group"]["statistics"];
+    let by_category = &totals["byCategory"];
+    assert_eq!(serde_json::Value::Null, by_category[0]["category"]);
+    assert_eq!("30", by_category[0]["total"]["amount"]);
+    assert_eq!("Food", by_category[1]["category"]["name"]);
+    assert_eq!("15", by_category[1]["total"]["amount"]);
+    let by_person = &totals["byPerson"];
+    assert_eq!(bob.to_string(), by_person[0]["person"]["id"]);
+    assert_eq!("30", by_person[0]["total"]["amount"]);
+    assert_eq!(alice.to_string(), by_person[1]["person"]["id"]);
+    assert_eq!("15", by_person[1]["total"]["amount"]);
+    let by_month = &totals["byMonth"];
+    assert_eq!("2020-09-01", by_month[0]["month"]);
+    assert_eq!("40", by_month[0]["total"]["amount"]);
+    assert_eq!("2020-10-01", by_month[1]["month"]);
+    assert_eq!("5", by_month[1]["total"]["amount"]);
+
+    /* --- Only the expenses in the range are summed --- */
+    // Act
+    let res = client
+        .execute::<serde_json::Value>(statistics(Some("2020-09-30")), &token)
+        .await;
+
+    // Assert
+    let by_month = res["group"]["statistics"]["byMonth"].as_array().unwrap();
+    assert_eq!(1, by_month.len());
+    assert_eq!("40", by_month[0]["total"]["amount"]);
+}
+
+#[actix_rt::test]
+async fn other_users_data_should_be_out_of_reach() {
+    let app = helpers::spawn_app();
+    let client = GraphQLClient::new(format!("{}/graphql", app.address));
+    let token = client.signup().await;
+    let group = client.add_group(&token, &["Alice", "Bob"]).await;
+    let alice = group.persons[0].id;
+    let bob = group.persons[1].id;
+    let body = json!({
+        "query": r#"
+            mutation IT_ADD_EXPENSE($input: AddExpenseInput!) {
+                addExpense(input: $input)
+            }
+        "#,
+        "variables": {
+            "input": {
+                "groupId": group.id,
+                "personId": alice,
+                "name": "Groceries",
+                "amount": { "amount": 10 }
+            }
+        }
+    });
+    client.execute::<AddExpense>(body, &token).await;
+    let expense_id = client.group(&token, &group.id).await.expenses[0].id;
+    let body = json!({
+        "query": r#"
+            mutation IT_ADD_PAYMENT($input: AddPaymentInput!) {
+                addPaymentV2(input: $input) {
+                    payment {
+                        id
+                    }
+                }
+            }
+        "#,
+        "variables": {
+            "input": {
+                "groupId": group.id,
+                "fromPersonId": bob,
+                "toPersonId": alice,
+                "amount": { "amount": 5 }
+            }
+        }
+    });
+    let payment_id = client.execute::<serde_json::Value>(body, &token).await["addPaymentV2"]
+        ["payment"]["id"]
+        .clone();
+    let body = json!({
+        "query": r#"
+            mutation IT_ADD_CATEGORY($input: AddCategoryInput!) {
+                addCategory(input: $input)
+            }
+        "#,
+        "variables": {
+            "input": {
+                "groupId": group.id,
+                "name": "Gifts"
+            }
+        }
+    });
+    client.execute::<serde_json::Value>(body, &token).await;
+    let body = json!({
+        "query": r#"
+            query IT_CATEGORIES($id: String!) {
+                group(id: $id) {
+                    categories {
+                        id
+                        builtIn
+                    }
+                }
+            }
+        "#,
+        "variables": {
+            "id": group.id
+        }
+    });
+    let category_id = client.execute::<serde_json::Value>(body, &token).await["group"]
+        ["categories"]
+        .as_array()
+        .unwrap()
+        .last()
+        .unwrap()["id"]
+        .clone();
+    let other_token = client.signup().await;
+    let mutation = |name: &str, input: serde_json::Value| {
+        json!({
+            "query": format!(
+                "mutation IT_{0}($input: {1}Input!) {{ {2}(input: $input) }}",
+                name.to_uppercase(),
+                name[..1].to_uppercase() + &name[1..],
+                name
+            ),
+            "variables": {
+                "input": input
+            }
+        })
+    };
+
+    /* --- Another user's rows are reported as not found --- */
+    // Arrange
+    let attempts = vec![
+        (
+            mutation(
+                "updateGroup",
+                json!({ "personId": group.id, "name": "Mine" }),
+            ),
+            "GROUP_NOT_FOUND",
+        ),
+        (
+            mutation(
+                "addPerson",
+                json!({ "groupId": group.id, "name": "Eve", "resources": { "amount": 0 } }),
+            ),
+            "GROUP_NOT_FOUND",
+        ),
+        (
+            mutation("updatePerson", json!({ "personId": alice, "name": "Eve" })),
+            "PERSON_NOT_FOUND",
+        ),
+        (
+            mutation(
+                "updateExpense",
+                json!({ "expenseId": expense_id, "name": "Mine" }),
+            ),
+            "EXPENSE_NOT_FOUND",
+        ),
+        (
+            mutation("removeExpense", json!({ "expenseId": expense_id })),
+            "EXPENSE_NOT_FOUND",
+        ),
+        (
+            mutation("removePayment", json!({ "paymentId": payment_id })),
+            "PAYMENT_NOT_FOUND",
+        ),
+        (
+            mutation("removeCategory", json!({ "categoryId": category_id })),
+            "CATEGORY_NOT_FOUND",
+        ),
+        (
+            mutation("removePerson", json!({ "personId": alice })),
+            "PERSON_NOT_FOUND",
+        ),
+        (
+            mutation("removeGroup", json!({ "groupId": group.id })),
+            "GROUP_NOT_FOUND",
+        ),
+    ];
+
+    for (body, code) in &attempts {
+        let input = GraphQLRequestInput::WithToken {
+            body,
+            token: &other_token,
+        };
+
+        // Act
+        let res = client
+            .send::<serde_json::Value>(&input)
+            .await
+            .expect("Failed to convert response to json");
+
+        // Assert
+        let errors = res.errors.expect("Another user's rows should be refused");
+        assert_eq!(*code, errors[0]["extensions"]["code"], "{}", body);
+    }
+
+    /* --- Another user's group can't be read --- */
+    // Arrange
+    let body = json!({
+        "query": r#"
+            query IT_GROUP($id: String!) {
+                group(id: $id) {
+                    id
+                }
+            }
+        "#,
+        "variables": {
+            "id": group.id
+        }
+    });
+
+    // Act
+    let res = client
+        .execute::<serde_json::Value>(body, &other_token)
+        .await;
+
+    // Assert
+    assert_eq!(serde_json::Value::Null, res["group"]);
+
+    /* --- Nothing was changed --- */
+    // Act
+    let group = client.group(&token, &group.id).await;
+
+    // Assert
+    assert_eq!(2, group.persons.len());
+    assert_eq!("Alice", group.persons[0].name);
+    assert_eq!(1, group.expenses.len());
+    assert_eq!("Groceries", group.expenses[0].name);
+    let body = json!({
+        "query": r#"
+            query IT_GROUP($id: String!) {
+                group(id: $id) {
+                    payments {
+                        id
+                    }
+                    categories {
+                        name
+                    }
+                }
+            }
+        "#,
+        "variables": {
+            "id": group.id
+        }
+    });
+    let res = client.execute::<serde_json::Value>(body, &token).await;
+    assert_eq!(1, res["group"]["payments"].as_array().unwrap().len());
+    assert_eq!(
+        "Gifts",
+        res["group"]["categories"]
+            .as_array()
+            .unwrap()
+            .last()
+            .unwrap()["name"]
+    );
+}
+
+#[actix_rt::test]
+async fn group_creators_should_be_members() {
+    let app = helpers::spawn_app();
+    let client = GraphQLClient::new(format!("{}/graphql", app.address));
+    let token = client.signup().await;
+    client.add_group(&token, &["Alice"]).await;
+    let body = json!({
+        "query": r#"
+            query IT_VIEWER {
+                viewer {
+                    email
+                    groups {
+                        members {
+                            user {
+                                email
+                            }
+                        }
+                        persons {
+                            account {
+                                id
+                            }
+                        }
+                    }
+                }
+            }
+        "#
+    });
+
+    // Act
+    let res = client.execute::<serde_json::Value>(body, &token).await;
+
+    // Assert
+    let viewer = &res["viewer"];
+    let members = viewer["groups"][0]["members"].as_array().unwrap();
+    assert_eq!(1, members.len());
+    assert_eq!(viewer["email"], members[0]["user"]["email"]);
+    assert_eq!(
+        serde_json::Value::Null,
+        viewer["groups"][0]["persons"][0]["account"]
+    );
+}
+
+#[actix_rt::test]
+async fn invitations_should_share_groups() {
+    let app = helpers::spawn_app();
+    let client = GraphQLClient::new(format!("{}/graphql", app.address));
+    let token = client.verified_signup().await;
+    let group = client.add_group(&token, &["Alice", "Bob"]).await;
+    let bob = group.persons[1].id;
+    let create_invitation = |expires_in: i32| {
+        json!({
+            "query": r#"
+                mutation IT_CREATE_INVITATION($input: CreateInvitationInput!) {
+                    createInvitation(input: $input)
+                }
+            "#,
+            "variables": {
+                "input": {
+                    "groupId": group.id,
+                    "expiresIn": expires_in
+                }
+            }
+        })
+    };
+    let accept_invitation = |token: &serde_json::Value, person_id: Option<uuid::Uuid>| {
+        json!({
+            "query": r#"
+                mutation IT_ACCEPT_INVITATION($input: AcceptInvitationInput!) {
+                    acceptInvitation(input: $input)
+                }
+            "#,
+            "variables": {
+                "input": {
+                    "token": token,
+                    "personId": person_id
+                }
+            }
+        })
+    };
+    let group_query = json!({
+        "query": r#"
+            query IT_GROUP($id: String!) {
+                group(id: $id) {
+                    members {
+                        user {
+                            id
+                        }
+                    }
+                    persons {
+                        account {
+                            id
+                        }
+                    }
+                    invitations {
+                        id
+                    }
+                }
+            }
+        "#,
+        "variables": {
+            "id": group.id
+        }
+    });
+
+    /* --- An invitation adds its user to the group as the person they claimed --- */
+    // Arrange
+    let invitation = client
+        .execute::<serde_json::Value>(create_invitation(3600), &token)
+        .await["createInvitation"]
+        .clone();
+    let other_token = client.verified_signup().await;
+
+    // Act
+    client
+        .execute::<serde_json::Value>(accept_invitation(&invitation, Some(bob)), &other_token)
+        .await;
+    let res = client
+        .execute::<serde_json::Value>(group_query.clone(), &other_token)
+        .await;
+
+    // Assert
+    let members = res["group"]["members"].as_array().unwrap();
+    assert_eq!(2, members.len());
+    assert_eq!(members[1]["user"], res["group"]["persons"][1]["account"]);
+    assert_eq!(
+        serde_json::Value::Null,
+        res["group"]["persons"][0]["account"]
+    );
+
+    /* --- An invitation can only be used once --- */
+    // Arrange
+    let third_token = client.verified_signup().await;
+
+    // Act
+    let code = client
+        .error_code(accept_invitation(&invitation, None), &third_token)
+        .await;
+
+    // Assert
+    assert_eq!("INVITATION_ALREADY_USED", code);
+
+    /* --- A person can only be claimed once --- */
+    // Arrange
+    let invitation = client
+        .execute::<serde_json::Value>(create_invitation(3600), &token)
+        .await["createInvitation"]
+        .clone();
+
+    // Act
+    let code = client
+        .error_code(accept_invitation(&invitation, Some(bob)), &third_token)
+        .await;
+
+    // Assert
+    assert_eq!("PERSON_ALREADY_CLAIMED", code);
+
+    /* --- A revoked invitation can't be used --- */
+    // Arrange
+    let res = client
+        .execute::<serde_json::Value>(group_query, &token)
+        .await;
+    assert_eq!(1, res["group"]["invitations"].as_array().unwrap().len());
+    let body = json!({
+        "query": r#"
+            mutation IT_REVOKE_INVITATION($input: RevokeInvitationInput!) {
+                revokeInvitation(input: $input)
+            }
+        "#,
+        "variables": {
+            "input": {
+                "invitationId": res["group"]["invitations"][0]["id"]
+            }
+        }
+    });
+    client.execute::<serde_json::Value>(body, &token).await;
+
+    // Act
+    let code = client
+        .error_code(accept_invitation(&invitation, None), &third_token)
+        .await;
+
+    // Assert
+    assert_eq!("INVITATION_NOT_FOUND", code);
+
+    /* --- Unknown tokens and invalid expirations are refused --- */
+    // Act
+    let unknown = client
+        .error_code(accept_invitation(&json!("unknown"), None), &third_token)
+        .await;
+    let expiration = client.error_code(create_invitation(0), &token).await;
+
+    // Assert
+    assert_eq!("INVITATION_NOT_FOUND", unknown);
+    assert_eq!("INVALID_EXPIRATION", expiration);
+}
+
+#[actix_rt::test]
+async fn member_roles_should_restrict_mutations() {
+    let app = helpers::spawn_app();
+    let client = GraphQLClient::new(format!("{}/graphql", app.address));
+    let token = client.verified_signup().await;
+    let group = client.add_group(&token, &["Alice"]).await;
+    let body = json!({
+        "query": r#"
+            mutation IT_CREATE_INVITATION($input: CreateInvitationInput!) {
+                createInvitation(input: $input)
+            }
+        "#,
+        "variables": {
+            "input": {
+                "groupId": group.id,
+                "expiresIn": 3600,
+                "role": "VIEWER"
+            }
+        }
+    });
+    let invitation = client.execute::<serde_json::Value>(body, &token).await;
+    let member_token = client.verified_signup().await;
+    let body = json!({
+        "query": r#"
+            mutation IT_ACCEPT_INVITATION($input: AcceptInvitationInput!) {
+                acceptInvitation(input: $input)
+            }
+        "#,
+        "variables": {
+            "input": {
+                "token": invitation["createInvitation"]
+            }
+        }
+    });
+    client
+        .execute::<serde_json::Value>(body, &member_token)
+        .await;
+    let members = json!({
+        "query": r#"
+            query IT_GROUP($id: String!) {
+                group(id: $id) {
+                    members {
+                        user {
+                            id
+                        }
+                        role
+                    }
+                }
+            }
+        "#,
+        "variables": {
+            "id": group.id
+        }
+    });
+    let res = client
+        .execute::<serde_json::Value>(members.clone(), &member_token)
+        .await;
+    let owner_id = res["group"]["members"][0]["user"]["id"].clone();
+    let member_id = res["group"]["members"][1]["user"]["id"].clone();
+    let add_person = json!({
+        "query": r#"
+            mutation IT_ADD_PERSON($input: AddPersonInput!) {
+                addPerson(input: $input)
+            }
+        "#,
+        "variables": {
+            "input": {
+                "groupId": group.id,
+                "name": "Bob",
+                "resources": { "amount": 0 }
+            }
+        }
+    });
+    let remove_group = json!({
+        "query": r#"
+            mutation IT_REMOVE_GROUP($input: RemoveGroupInput!) {
+                removeGroup(input: $input)
+            }
+        "#,
+        "variables": {
+            "input": {
+                "groupId": group.id
+            }
+        }
+    });
+    let change_role = |user_id: &serde_json::Value, role: &str| {
+        json!({
+            "query": r#"
+                mutation IT_CHANGE_MEMBER_ROLE($input: ChangeMemberRoleInput!) {
+                    changeMemberRole(input: $input)
+                }
+            "#,
+            "variables": {
+                "input": {
+                    "groupId": group.id,
+                    "userId": user_id,
+                    "role": role
+                }
+            }
+        })
+    };
+
+    /* --- Viewers can only read the group --- */
+    // Act
+    let code = client.error_code(add_person.clone(), &member_token).await;
+
+    // Assert
+    assert_eq!("OWNER", res["group"]["members"][0]["role"]);
+    assert_eq!("VIEWER", res["group"]["members"][1]["role"]);
+    assert_eq!("FORBIDDEN", code);
+
+    /* --- Editors can change the group's content but not the group itself --- */
+    // Act
+    client
+        .execute::<serde_json::Value>(change_role(&member_id, "EDITOR"), &token)
+        .await;
+    client
+        .execute::<serde_json::Value>(add_person, &member_token)
+        .await;
+    let code = client.error_code(remove_group, &member_token).await;
+
+    // Assert
+    assert_eq!(2, client.group(&token, &group.id).await.persons.len());
+    assert_eq!("FORBIDDEN", code);
+
+    /* --- A group can't lose its last owner --- */
+    // Act
+    let code = client
+        .error_code(change_role(&owner_id, "VIEWER"), &token)
+        .await;
+
+    // Assert
+    assert_eq!("LAST_OWNER", code);
+
+    /* --- Owners can remove members --- */
+    // Arrange
+    let body = json!({
+        "query": r#"
+            mutation IT_REMOVE_MEMBER($input: RemoveMemberInput!) {
+                removeMember(input: $input)
+            }
+        "#,
+        "variables": {
+            "input": {
+                "groupId": group.id,
+                "userId": member_id
+            }
+        }
+    });
+
+    // Act
+    client.execute::<serde_json::Value>(body, &token).await;
+    let res = client
+        .execute::<serde_json::Value>(members, &member_token)
+        .await;
+
+    // Assert
+    assert_eq!(serde_json::Value::Null, res["group"]);
+}
+
+#[actix_rt::test]
+async fn group_activity_should_record_the_changes() {
+    let app = helpers::spawn_app();
+    let client = GraphQLClient::new(format!("{}/graphql", app.address));
+    let token = client.signup().await;
+    let group = client.add_group(&token, &["Alice"]).await;
+    let alice = group.persons[0].id;
+    let body = json!({
+        "query": r#"
+            mutation IT_ADD_EXPENSE($input: AddExpenseInput!) {
+                addExpense(input: $input)
+            }
+        "#,
+        "variables": {
+            "input": {
+                "groupId": group.id,
+                "personId": alice,
+                "name": "Restaurant",
+                "amount": { "amount": 80 }
+            }
+        }
+    });
+    client.execute::<serde_json::Value>(body, &token).await;
+    let expense_id = client.group(&token, &group.id).await.expenses[0].id;
+    let body = json!({
+        "query": r#"
+            mutation IT_REMOVE_EXPENSE($input: RemoveExpenseInput!) {
+                removeExpense(input: $input)
+            }
+        "#,
+        "variables": {
+            "input": {
+                "expenseId": expense_id
+            }
+        }
+    });
+    client.execute::<serde_json::Value>(body, &token).await;
+    let activity = |after: Option<String>| {
+        json!({
+            "query": r#"
+                query IT_GROUP_ACTIVITY($id: String!, $after: String) {
+                    viewer {
+                        email
+                    }
+                    group(id: $id) {
+                        activity(first: 2, after: $after) {
+                            entries {
+                                actor {
+                                    email
+                                }
+                                action
+                                entityType
+                                entityId
+                                before
+                                after
+                            }
+                            endCursor
+                            hasNextPage
+                        }
+                    }
+                }
+            "#,
+            "variables": {
+                "id": group.id,
+                "after": after
+            }
+        })
+    };
+
+    /* --- The latest changes come first --- */
+    // Act
+    let res = client
+        .execute::<serde_json::Value>(activity(None), &token)
+        .await;
+
+    // Assert
+    let page = &res["group"]["activity"];
+    let entries = page["entries"].as_array().unwrap();
+    assert_eq!(2, entries.len());
+    assert_eq!(true, page["hasNextPage"]);
+    let removal = &entries[0];
+    assert_eq!(res["viewer"]["email"], removal["actor"]["email"]);
+    assert_eq!("DELETE", removal["action"]);
+    assert_eq!("EXPENSE", removal["entityType"]);
+    assert_eq!(expense_id.to_string(), removal["entityId"]);
+    assert_eq!(serde_json::Value::Null, removal["after"]);
+    let before: serde_json::Value =
+        serde_json::from_str(removal["before"].as_str().unwrap()).unwrap();
+    assert_eq!("Restaurant", before["name"]);
+    assert_eq!(80, before["amount"]);
+    assert_eq!("CREATE", entries[1]["action"]);
+    assert_eq!("EXPENSE_PAYERS", entries[1]["entityType"]);
+
+    /* --- The next pages start after the previous ones --- */
+    // Act
+    let mut cursor = page["endCursor"].as_str().map(String::from);
+    let mut older = Vec::new();
+    while let Some(after) = cursor.take() {
+        let res = client
+            .execute::<serde_json::Value>(activity(Some(after)), &token)
+            .await;
+        let page = &res["group"]["activity"];
+        older.extend(page["entries"].as_array().unwrap().clone());
+        if page["hasNextPage"] == true {
+            cursor = page["endCursor"].as_str().map(String::from);
+        }
+    }
+
+    // Assert
+    let older = older
+        .iter()
+        .map(|e| {
+            (
+                e["action"].as_str().unwrap(),
+                e["entityType"].as_str().unwrap(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        vec![
+            ("CREATE", "EXPENSE"),
+            ("CREATE", "PERSON"),
+            ("CREATE", "MEMBER"),
+            ("CREATE", "GROUP")
+        ],
+        older
+    );
+}
+
+#[actix_rt::test]
+async fn removed_rows_should_be_restorable_from_the_trash() {
+    let app = helpers::spawn_app();
+    let client = GraphQLClient::new(format!("{}/graphql", app.address));
+    let token = client.signup().await;
+    let group = client.add_group(&token, &["Alice", "Bob"]).await;
+    let (alice, bob) = (group.persons[0].id, group.persons[1].id);
+    for (person_id, name) in &[(alice, "Restaurant"), (bob, "Cinema")] {
+        let body = json!({
+            "query": r#"
+                mutation IT_ADD_EXPENSE($input: AddExpenseInput!) {
+                    addExpense(input: $input)
+                }
+            "#,
+            "variables": {
+                "input": {
+                    "groupId": group.id,
+                    "personId": person_id,
+                    "name": name,
+                    "amount": { "amount": 20 }
+                }
+            }
+        });
+        client.execute::<serde_json::Value>(body, &token).await;
+    }
+    let expenses = client.group(&token, &group.id).await.expenses;
+    let restaurant = expenses.iter().find(|e| e.name == "Restaurant").unwrap().id;
+    let cinema = expenses.iter().find(|e| e.name == "Cinema").unwrap().id;
+    let mutation = |name: &str, input: serde_json::Value| {
+        json!({
+            "query": format!(
+                "mutation IT_{}($input: {}Input!) {{ {}(input: $input) }}",
+                name.to_uppercase(),
+                name[..1].to_uppercase() + &name[1..],
+                name
+            ),
+            "variables": {
+                "input": input
+            }
+        })
+    };
+    let trash = json!({
+        "query": r#"
+            query IT_TRASH($id: String!) {
+                group(id: $id) {
+                    persons {
+                        name
+                    }
+                    expenses {
+                        name
+                    }
+                    trash {
+                        persons {
+                            name
+                            deletedAt
+                        }
+                        expenses {
+                            name
+                            deletedAt
+                        }
+                    }
+                }
+            }
+        "#,
+        "variables": {
+            "id": group.id
+        }
+    });
+    let names = |v: &serde_json::Value| {
+        let mut names = v
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| r["name"].as_str().unwrap().to_string())
+            .collect::<Vec<_>>();
+        names.sort();
+        names
+    };
+
+    /* --- Removing a person trashes the expenses they paid --- */
+    // Act
+    client
+        .execute::<serde_json::Value>(
+            mutation("removeExpense", json!({ "expenseId": restaurant })),
+            &token,
+        )
+        .await;
+    client
+        .execute::<serde_json::Value>(mutation("removePerson", json!({ "personId": bob })), &token)
+        .await;
+    let res = client
+        .execute::<serde_json::Value>(trash.clone(), &token)
+        .await;
+
+    // Assert
+    let group_res = &res["group"];
+    assert_eq!(vec!["Alice"], names(&group_res["persons"]));
+    assert!(names(&group_res["expenses"]).is_empty());
+    assert_eq!(vec!["Bob"], names(&group_res["trash"]["persons"]));
+    assert_eq!(
+        vec!["Cinema", "Restaurant"],
+        names(&group_res["trash"]["expenses"])
+    );
+    assert!(group_res["trash"]["persons"][0]["deletedAt"].is_string());
+
+    /* --- An expense can't be restored before its payers --- */
+    // Act
+    let code = client
+        .error_code(
+            mutation("restoreExpense", json!({ "expenseId": cinema })),
+            &token,
+        )
+        .await;
+
+    // Assert
+    assert_eq!("PERSON_NOT_FOUND", code);
+
+    /* --- Restoring a person restores the expenses deleted with them --- */
+    // Act
+    client
+        .execute::<serde_json::Value>(
+            mutation("restorePerson", json!({ "personId": bob })),
+            &token,
+        )
+        .await;
+    client
+        .execute::<serde_json::Value>(
+            mutation("restoreExpense", json!({ "expenseId": restaurant })),
+            &token,
+        )
+        .await;
+    let res = client
+        .execute::<serde_json::Value>(trash.clone(), &token)
+        .await;
+
+    // Assert
+    let group_res = &res["group"];
+    assert_eq!(vec!["Alice", "Bob"], names(&group_res["persons"]));
+    assert_eq!(vec!["Cinema", "Restaurant"], names(&group_res["expenses"]));
+    assert!(names(&group_res["trash"]["persons"]).is_empty());
+    assert!(names(&group_res["trash"]["expenses"]).is_empty());
+
+    /* --- Trashed groups are only listed as deleted --- */
+    // Arrange
+    let groups = json!({
+        "query": r#"
+            query IT_VIEWER {
+                viewer {
+                    groups {
+                        id
+                    }
+                    deletedGroups {
+                        id
+                        deletedAt
+                    }
+                }
+            }
+        "#
+    });
+
+    // Act
+    client
+        .execute::<serde_json::Value>(
+            mutation("removeGroup", json!({ "groupId": group.id })),
+            &token,
+        )
+        .await;
+    let deleted = client
+        .execute::<serde_json::Value>(groups.clone(), &token)
+        .await;
+    client
+        .execute::<serde_json::Value>(
+            mutation("restoreGroup", json!({ "groupId": group.id })),
+            &token,
+        )
+        .await;
+    let restored = client.execute::<serde_json::Value>(groups, &token).await;
+
+    // Assert
+    let id = json!(group.id.to_string());
+    assert!(!deleted["viewer"]["groups"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|g| g["id"] == id));
+    assert_eq!(id, deleted["viewer"]["deletedGroups"][0]["id"]);
+    assert!(restored["viewer"]["groups"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|g| g["id"] == id));
+    assert!(restored["viewer"]["deletedGroups"]
+        .as_array()
+        .unwrap()
+        .is_empty());
+
+    /* --- The purge empties the trash for good --- */
+    // Act
+    client
+        .execute::<serde_json::Value>(
+            mutation("removeExpense", json!({ "expenseId": cinema })),
+            &token,
+        )
+        .await;
+    group_expenses::purge_trash(chrono::Duration::zero(), &*app.db).expect("Failed to purge.");
+    let res = client.execute::<serde_json::Value>(trash, &token).await;
+    let code = client
+        .error_code(
+            mutation("restoreExpense", json!({ "expenseId": cinema })),
+            &token,
+        )
+        .await;
+
+    // Assert
+    assert!(names(&res["group"]["trash"]["expenses"]).is_empty());
+    assert_eq!("EXPENSE_NOT_FOUND", code);
+}
+
+#[actix_rt::test]
+async fn mutations_should_return_the_changed_rows() {
+    let app = helpers::spawn_app();
+    let client = GraphQLClient::new(format!("{}/graphql", app.address));
+    let token = client.signup().await;
+
+    /* --- addGroupV2 --- */
+    // Arrange
+    let name = helpers::rand_string();
+    let body = json!({
+        "query": r#"
+            mutation IT_ADD_GROUP($input: AddGroupInput!) {
+                addGroupV2(input: $input) {
+                    group {
+                        id
+                        name
+                    }
+                }
+            }
+        "#,
+        "variables": {
+            "input": {
+                "name": name
+            }
+        }
+    });
+
+    // Act
+    let res = client.execute::<serde_json::Value>(body, &token).await;
+    let group = &res["addGroupV2"]["group"];
+
+    // Assert
+    assert_eq!(name, group["name"]);
+    let group_id = group["id"].as_str().unwrap().to_string();
+
+    /* --- addPersonV2 --- */
+    // Arrange
+    let add_person = |name: &str| {
+        json!({
+            "query": r#"
+                mutation IT_ADD_PERSON($input: AddPersonInput!) {
+                    addPersonV2(input: $input) {
+                        person {
+                            id
+                            name
+                        }
+                        group {
+                            persons {
+                                id
+                            }
+                        }
+                    }
+                }
+            "#,
+            "variables": {
+                "input": {
+                    "groupId": group_id,
+                    "name": name,
+                    "resources": { "amount": 0 },
+                }
+            }
+        })
+    };
+
+    // Act
+    let alice = client
+        .execute::<serde_json::Value>(add_person("Alice"), &token)
+        .await;
+    let bob = client
+        .execute::<serde_json::Value>(add_person("Bob"), &token)
+        .await;
+
+    // Assert
+    assert_eq!("Alice", alice["addPersonV2"]["person"]["name"]);
+    assert_eq!(
+        2,
+        bob["addPersonV2"]["group"]["persons"]
+            .as_array()
+            .unwrap()
+            .len()
+    );
+    let alice = alice["addPersonV2"]["person"]["id"].clone();
+    let bob = bob["addPersonV2"]["person"]["id"].clone();
+
+    /* --- addExpenseV2 --- */
+    // Arrange
+    let body = json!({
+        "query": r#"
+            mutation IT_ADD_EXPENSE($input: AddExpenseInput!) {
+                addExpenseV2(input: $input) {
+                    expense {
+                        id
+                        name
+                    }
+                    group {
+                        settlementPlan {
+                            from {
+                                id
+                            }
+                            amount {
+                                amount
+                            }
+                        }
+                    }
+                }
+            }
+        "#,
+        "variables": {
+            "input": {
+                "groupId": group_id,
+                "personId": alice,
+                "name": "Restaurant",
+                "amount": { "amount": 80 }
+            }
+        }
+    });
+
+    // Act
+    let res = client.execute::<serde_json::Value>(body, &token).await;
+    let payload = &res["addExpenseV2"];
+
+    // Assert
+    assert_eq!("Restaurant", payload["expense"]["name"]);
+    assert_eq!(bob, payload["group"]["settlementPlan"][0]["from"]["id"]);
+    assert_eq!(
+        "40",
+        payload["group"]["settlementPlan"][0]["amount"]["amount"]
+    );
+    let expense_id = payload["expense"]["id"].clone();
+
+    /* --- updateExpenseV2 --- */
+    // Arrange
+    let body = json!({
+        "query": r#"
+            mutation IT_UPDATE_EXPENSE($input: UpdateExpenseInput!) {
+                updateExpenseV2(input: $input) {
+                    expense {
+                        id
+                        name
+                    }
+                }
+            }
+        "#,
+        "variables": {
+            "input": {
+                "expenseId": expense_id,
+                "name": "Dinner"
+            }
+        }
+    });
+
+    // Act
+    let res = client.execute::<serde_json::Value>(body, &token).await;
+
+    // Assert
+    assert_eq!(expense_id, res["updateExpenseV2"]["expense"]["id"]);
+    assert_eq!("Dinner", res["updateExpenseV2"]["expense"]["name"]);
+
+    /* --- addPaymentV2 --- */
+    // Arrange
+    let body = json!({
+        "query": r#"
+            mutation IT_ADD_PAYMENT($input: AddPaymentInput!) {
+                addPaymentV2(input: $input) {
+                    payment {
+                        id
+                        amount {
+                            amount
+                        }
+                    }
+                    group {
+                        settlementPlan {
+                            amount {
+                                amount
+                            }
+                        }
+                    }
+                }
+            }
+        "#,
+        "variables": {
+            "input": {
+                "groupId": group_id,
+                "fromPersonId": bob,
+                "toPersonId": alice,
+                "amount": { "amount": 40 }
+            }
+        }
+    });
+
+    // Act
+    let res = client.execute::<serde_json::Value>(body, &token).await;
+    let payload = &res["addPaymentV2"];
+
+    // Assert
+    assert_eq!("40", payload["payment"]["amount"]["amount"]);
+    assert!(payload["group"]["settlementPlan"]
+        .as_array()
+        .unwrap()
+        .is_empty());
+
+    /* --- removeExpenseV2 --- */
+    // Arrange
+    let body = json!({
+        "query": r#"
+            mutation IT_REMOVE_EXPENSE($input: RemoveExpenseInput!) {
+                removeExpenseV2(input: $input) {
+                    deletedId
+                }
+            }
+        "#,
+        "variables": {
+            "input": {
+                "expenseId": expense_id
+            }
+        }
+    });
+
+    // Act
+    let res = client.execute::<serde_json::Value>(body, &token).await;
+
+    // Assert
+    assert_eq!(expense_id, res["removeExpenseV2"]["deletedId"]);
+
+    /* --- restoreExpenseV2 --- */
+    // Arrange
+    let body = json!({
+        "query": r#"
+            mutation IT_RESTORE_EXPENSE($input: RestoreExpenseInput!) {
+                restoreExpenseV2(input: $input) {
+                    expense {
+                        id
+                    }
+                    group {
+                        expenses {
+                            id
+                        }
+                    }
+                }
+            }
+        "#,
+        "variables": {
+            "input": {
+                "expenseId": expense_id
+            }
+        }
+    });
+
+    // Act
+    let res = client.execute::<serde_json::Value>(body, &token).await;
+    let payload = &res["restoreExpenseV2"];
+
+    // Assert
+    assert_eq!(expense_id, payload["expense"]["id"]);
+    assert_eq!(expense_id, payload["group"]["expenses"][0]["id"]);
+
+    /* --- addCategoryV2 --- */
+    // Arrange
+    let body = json!({
+        "query": r#"
+            mutation IT_ADD_CATEGORY($input: AddCategoryInput!) {
+                addCategoryV2(input: $input) {
+                    category {
+                        id
+                        name
+                        builtIn
+                    }
+                }
+            }
+        "#,
+        "variables": {
+            "input": {
+                "groupId": group_id,
+                "name": "Gifts"
+            }
+        }
+    });
+
+    // Act
+    let res = client.execute::<serde_json::Value>(body, &token).await;
+    let category = &res["addCategoryV2"]["category"];
+
+    // Assert
+    assert_eq!("Gifts", category["name"]);
+    assert_eq!(false, category["builtIn"]);
+    let category_id = category["id"].clone();
+
+    /* --- updateCategoryV2 --- */
+    // Arrange
+    let body = json!({
+        "query": r#"
+            mutation IT_UPDATE_CATEGORY($input: UpdateCategoryInput!) {
+                updateCategoryV2(input: $input) {
+                    category {
+                        id
+                        name
+                    }
+                }
+            }
+        "#,
+        "variables": {
+            "input": {
+                "categoryId": category_id,
+                "name": "Presents"
+            }
+        }
+    });
+
+    // Act
+    let res = client.execute::<serde_json::Value>(body, &token).await;
+
+    // Assert
+    assert_eq!(category_id, res["updateCategoryV2"]["category"]["id"]);
+    assert_eq!("Presents", res["updateCategoryV2"]["category"]["name"]);
+
+    /* --- removeCategoryV2 --- */
+    // Arrange
+    let body = json!({
+        "query": r#"
+            mutation IT_REMOVE_CATEGORY($input: RemoveCategoryInput!) {
+                removeCategoryV2(input: $input) {
+                    deletedId
+                }
+            }
+        "#,
+        "variables": {
+            "input": {
+                "categoryId": category_id
+            }
+        }
+    });
+
+    // Act
+    let res = client.execute::<serde_json::Value>(body, &token).await;
+
+    // Assert
+    assert_eq!(category_id, res["removeCategoryV2"]["deletedId"]);
+}
+
+/// Only meaningful on Postgres: the in-memory store runs its transactions one at a time.
+#[actix_rt::test]
+async fn concurrent_mutations_should_keep_names_unique() {
+    let app = helpers::spawn_app();
+    let client = GraphQLClient::new(format!("{}/graphql", app.address));
+    let token = client.signup().await;
+    let group = client.add_group(&token, &["Alice"]).await;
+    let mutation = |name: &str, input: serde_json::Value| {
+        json!({
+            "query": format!(
+                "mutation IT_{0}($input: {1}Input!) {{ {2}(input: $input) }}",
+                name.to_uppercase(),
+                name[..1].to_uppercase() + &name[1..],
+                name
+            ),
+            "variables": { "input": input }
+        })
+    };
+    // Send the same mutation several times at once and return the codes of the failed ones
+    let race = |body: serde_json::Value| {
+        let client = &client;
+        let token = &token;
+        async move {
+            let inputs = (0..5)
+                .map(|_| GraphQLRequestInput::WithToken { body: &body, token })
+                .collect::<Vec<_>>();
+            let responses = futures::future::join_all(
+                inputs.iter().map(|i| client.send::<serde_json::Value>(i)),
+            )
+            .await;
+            responses
+                .into_iter()
+                .map(|r| r.expect("Failed to convert response to json"))
+                .filter_map(|r| r.errors)
+                .map(|e| e[0]["extensions"]["code"].clone())
+                .collect::<Vec<_>>()
+        }
+    };
+
+    /* --- Only one of the persons added at once gets the name --- */
+    // Act
+    let codes = race(mutation(
+        "addPerson",
+        json!({ "groupId": group.id, "name": "Bob", "resources": { "amount": 0 } }),
+    ))
+    .await;
+    let persons = client.group(&token, &group.id).await.persons;
+
+    // Assert
+    assert_eq!(vec![json!("NAME_NOT_UNIQUE"); 4], codes);
+    assert_eq!(
+        1,
+        persons.iter().filter(|p| p.name == "Bob").count(),
+        "The name should only be taken once"
+    );
+
+    /* --- Only one of the groups added at once gets the name --- */
+    // Arrange
+    let name = helpers::rand_string();
+
+    // Act
+    let codes = race(mutation("addGroup", json!({ "name": name }))).await;
+    let res = client
+        .execute::<serde_json::Value>(json!({ "query": "{ viewer { groups { name } } }" }), &token)
+        .await;
+
+    // Assert
+    assert_eq!(vec![json!("NAME_NOT_UNIQUE"); 4], codes);
+    assert_eq!(
+        1,
+        res["viewer"]["groups"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|g| g["name"] == name.as_str())
+            .count()
+    );
+
+    /* --- A person can't be renamed after another one --- */
+    // Act
+    let alice = &persons.iter().find(|p| p.name == "Alice").unwrap().id;
+    let code = client
+        .error_code(
+            mutation("updatePerson", json!({ "personId": alice, "name": "Bob" })),
+            &token,
+        )
+        .await;
+
+    // Assert
+    assert_eq!("NAME_NOT_UNIQUE", code);
+}
+
+#[actix_rt::test]
+async fn non_auth_operations_should_be_protected() {
+    let app = helpers::spawn_app();
+    let client = reqwest::Client::new();
+
+    // Arrange
+    let body = json!({
+        "query": r#"
+            query NON_AUTH {
+                nonAuth {
+                    myfield
+                }
+            }
+        "#
+    });
+
+    // Act
+    let res = client
+        .post(&format!("{}/graphql", app.address))
+        .json(&body)
+        .send()
+        .await
+        .expect("Failed to execute request");
+
+    assert_eq!(401, res.status());
+}
+
+#[actix_rt::test]
+async fn sessions_should_be_refreshed_and_revoked() {
+    let app = helpers::spawn_app();
+    let client = GraphQLClient::new(format!("{}/graphql", app.address));
+    let email = format!("{}@htest.com", helpers::rand_string());
+    let viewer = || {
+        json!({
+            "query": r#"
+                query IT_VIEWER {
+                    viewer {
+                        email
+                    }
+                }
+            "#
+        })
+    };
+    let refresh = |token: &serde_json::Value| {
+        json!({
+            "query": r#"
+                mutation IT_REFRESH_TOKEN($refreshToken: String!) {
+                    refreshToken(refreshToken: $refreshToken) {
+                        accessToken
+                        refreshToken
+                    }
+                }
+            "#,
+            "variables": {
+                "refreshToken": token
+            }
+        })
+    };
+    let login = || {
+        json!({
+            "query": r#"
+                mutation IT_LOGIN($email: String!, $password: String!) {
+                    loginV2(email: $email, password: $password) {
+                        accessToken
+                        refreshToken
+                    }
+                }
+            "#,
+            "variables": {
+                "email": email,
+                "password": "hihihihi"
+            }
+        })
+    };
+    let logout = |everywhere: bool| {
+        let query = if everywhere {
+            "mutation IT_LOGOUT_EVERYWHERE { logoutEverywhere }"
+        } else {
+            "mutation IT_LOGOUT { logout }"
+        };
+        json!({ "query": query })
+    };
+
+    /* --- signupV2 --- */
+    // Arrange
+    let body = json!({
+        "query": r#"
+            mutation IT_SIGNUP($input: SignupInput!) {
+                signupV2(input: $input) {
+                    accessToken
+                    refreshToken
+                }
+            }
+        "#,
+        "variables": {
+            "input": {
+                "email": email,
+                "password": "hihihihi"
+            }
+        }
+    });
+
+    // Act
+    let input = GraphQLRequestInput::WithoutToken { body: &body };
+    let res = client
+        .send::<serde_json::Value>(&input)
+        .await
+        .expect("Failed to convert response to json");
+    let tokens = res.data.unwrap()["signupV2"].clone();
+
+    // Assert
+    let access_token = tokens["accessToken"].as_str().unwrap();
+    client
+        .execute::<serde_json::Value>(viewer(), access_token)
+        .await;
+
+    /* --- refreshToken --- */
+    // Act
+    let body = refresh(&tokens["refreshToken"]);
+    let input = GraphQLRequestInput::WithoutToken { body: &body };
+    let res = client
+        .send::<serde_json::Value>(&input)
+        .await
+        .expect("Failed to convert response to json");
+    let refreshed = res.data.unwrap()["refreshToken"].clone();
+
+    // Assert
+    assert_ne!(tokens["refreshToken"], refreshed["refreshToken"]);
+    let access_token = refreshed["accessToken"].as_str().unwrap();
+    client
+        .execute::<serde_json::Value>(viewer(), access_token)
+        .await;
+
+    /* --- A wrong secret shouldn't revoke the session --- */
+    // Arrange
+    let session_id = refreshed["refreshToken"]
+        .as_str()
+        .unwrap()
+        .split('.')
+        .next()
+        .unwrap();
+    let forged = refresh(&json!(format!("{}.guessed", session_id)));
+
+    // Act
+    let res = client
+        .send::<serde_json::Value>(&GraphQLRequestInput::WithoutToken { body: &forged })
+        .await
+        .expect("Failed to convert response to json");
+
+    // Assert
+    let errors = res.errors.expect("The request should have failed");
+    assert_eq!("INVALID_TOKEN", errors[0]["extensions"]["code"]);
+    assert_eq!(200, client.status(viewer(), access_token).await);
+
+    /* --- A refresh token used twice should revoke its session --- */
+    // Act
+    let res = client
+        .send::<serde_json::Value>(&input)
+        .await
+        .expect("Failed to convert response to json");
+
+    // Assert
+    let errors = res.errors.expect("The request should have failed");
+    assert_eq!("INVALID_TOKEN", errors[0]["extensions"]["code"]);
+    assert_eq!(401, client.status(viewer(), access_token).await);
+    let body = refresh(&refreshed["refreshToken"]);
+    let input = GraphQLRequestInput::WithoutToken { body: &body };
+    let res = client
+        .send::<serde_json::Value>(&input)
+        .await
+        .expect("Failed to convert response to json");
+    assert!(res.errors.is_some());
+
+    /* --- logout --- */
+    // Arrange
+    let mut sessions = Vec::new();
+    for _ in 0..3 {
+        let body = login();
+        let input = GraphQLRequestInput::WithoutToken { body: &body };
+        let res = client
+            .send::<serde_json::Value>(&input)
+            .await
+            .expect("Failed to convert response to json");
+        sessions.push(res.data.unwrap()["loginV2"].clone());
+    }
+    let access_tokens = sessions
+        .iter()
+        .map(|s| s["accessToken"].as_str().unwrap())
+        .collect::<Vec<_>>();
+
+    // Act
+    client
+        .execute::<serde_json::Value>(logout(false), access_tokens[0])
+        .await;
+
+    // Assert
+    assert_eq!(401, client.status(viewer(), access_tokens[0]).await);
+    let body = refresh(&sessions[0]["refreshToken"]);
+    let input = GraphQLRequestInput::WithoutToken { body: &body };
+    let res = client
+        .send::<serde_json::Value>(&input)
+        .await
+        .expect("Failed to convert response to json");
+    assert!(res.errors.is_some());
+    client
+        .execute::<serde_json::Value>(viewer(), access_tokens[1])
+        .await;
+
+    /* --- logoutEverywhere --- */
+    // Act
+    client
+        .execute::<serde_json::Value>(logout(true), access_tokens[1])
+        .await;
+
+    // Assert
+    assert_eq!(401, client.status(viewer(), access_tokens[1]).await);
+    assert_eq!(401, client.status(viewer(), access_tokens[2]).await);
+}
+
+#[actix_rt::test]
+async fn users_should_change_their_password_and_email() {
+    let app = helpers::spawn_app();
+    let client = GraphQLClient::new(format!("{}/graphql", app.address));
+    let email = format!("{}@htest.com", helpers::rand_string());
+    let login = |password: &str| {
+        json!({
+            "query": r#"
+                mutation IT_LOGIN($email: String!, $password: String!) {
+                    loginV2(email: $email, password: $password) {
+                        accessToken
+                    }
+                }
+            "#,
+            "variables": {
+                "email": email,
+                "password": password
+            }
+        })
+    };
+    let viewer = || json!({ "query": "query IT_VIEWER { viewer { email } }" });
+    let signup = json!({
+        "query": r#"
+            mutation IT_SIGNUP($input: SignupInput!) {
+                signup(input: $input)
+            }
+        "#,
+        "variables": {
+            "input": {
+                "email": email,
+                "password": "hihihihi"
+            }
+        }
+    });
+    let input = GraphQLRequestInput::WithoutToken { body: &signup };
+    let token = client
+        .send::<Signup>(&input)
+        .await
+        .expect("Failed to convert response to json")
+        .data
+        .unwrap()
+        .signup;
+    let input_login = login("hihihihi");
+    let input = GraphQLRequestInput::WithoutToken { body: &input_login };
+    let other_token = client
+        .send::<serde_json::Value>(&input)
+        .await
+        .expect("Failed to convert response to json")
+        .data
+        .unwrap()["loginV2"]["accessToken"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let change_password = |current: &str, new: &str| {
+        json!({
+            "query": r#"
+                mutation IT_CHANGE_PASSWORD($currentPassword: String!, $newPassword: String!) {
+                    changePassword(currentPassword: $currentPassword, newPassword: $newPassword)
+                }
+            "#,
+            "variables": {
+                "currentPassword": current,
+                "newPassword": new
+            }
+        })
+    };
+
+    /* --- changePassword should check both passwords --- */
+    // Act
+    let wrong = client
+        .error_code(change_password("hohohoho", "hahahaha"), &token)
+        .await;
+    let invalid = client
+        .error_code(change_password("hihihihi", "haha"), &token)
+        .await;
+
+    // Assert
+    assert_eq!("INVALID_CREDENTIALS", wrong);
+    assert_eq!("INVALID_PASSWORD", invalid);
+
+    /* --- changePassword --- */
+    // Act
+    client
+        .execute::<serde_json::Value>(change_password("hihihihi", "hahahaha"), &token)
+        .await;
+
+    // Assert
+    client.execute::<serde_json::Value>(viewer(), &token).await;
+    assert_eq!(401, client.status(viewer(), &other_token).await);
+    let body = login("hihihihi");
+    let input = GraphQLRequestInput::WithoutToken { body: &body };
+    let res = client
+        .send::<serde_json::Value>(&input)
+        .await
+        .expect("Failed to convert response to json");
+    assert!(res.errors.is_some());
+    let body = login("hahahaha");
+    let input = GraphQLRequestInput::WithoutToken { body: &body };
+    let res = client
+        .send::<serde_json::Value>(&input)
+        .await
+        .expect("Failed to convert response to json");
+    assert!(res.errors.is_none());
+
+    /* --- changeEmail --- */
+    // Arrange
+    let change_email = |password: &str, new_email: &str| {
+        json!({
+            "query": r#"
+                mutation IT_CHANGE_EMAIL($password: String!, $newEmail: String!) {
+                    changeEmail(password: $password, newEmail: $newEmail)
+                }
+            "#,
+            "variables": {
+                "password": password,
+                "newEmail": new_email
+            }
+        })
+    };
+    let new_email = format!("{}@htest.com", helpers::rand_string());
+
+    // Act
+    let invalid = client
+        .error_code(change_email("hahahaha", "someone.htest.com"), &token)
+        .await;
+    let wrong = client
+        .error_code(change_email("hihihihi", &new_email), &token)
+        .await;
+    client
+        .execute::<serde_json::Value>(change_email("hahahaha", &new_email), &token)
+        .await;
+    let other_token = client.signup().await;
+    let taken = client
+        .error_code(change_email("hihihihi", &new_email), &other_token)
+        .await;
+
+    // Assert
+    assert_eq!("INVALID_EMAIL_ADDRESS", invalid);
+    assert_eq!("INVALID_CREDENTIALS", wrong);
+    assert_eq!("ALREADY_USED_EMAIL", taken);
+    let res = client.execute::<serde_json::Value>(viewer(), &token).await;
+    assert_eq!(new_email, res["viewer"]["email"]);
+}
+
+#[actix_rt::test]
+async fn users_should_reset_their_password() {
+    let app = helpers::spawn_app();
+    let client = GraphQLClient::new(format!("{}/graphql", app.address));
+    let email = format!("{}@htest.com", helpers::rand_string());
+    let unknown_email = format!("{}@htest.com", helpers::rand_string());
+    let send = |body: serde_json::Value| {
+        let client = &client;
+        async move {
+            let input = GraphQLRequestInput::WithoutToken { body: &body };
+            client
+                .send::<serde_json::Value>(&input)
+                .await
+                .expect("Failed to convert response to json")
+        }
+    };
+    let request_password_reset = |email: &str| {
+        json!({
+            "query": r#"
+                mutation IT_REQUEST_PASSWORD_RESET($email: String!) {
+                    requestPasswordReset(email: $email)
+                }
+            "#,
+            "variables": {
+                "email": email
+            }
+        })
+    };
+    let reset_password = |token: &str, new_password: &str| {
+        json!({
+            "query": r#"
+                mutation IT_RESET_PASSWORD($token: String!, $newPassword: String!) {
+                    resetPassword(token: $token, newPassword: $newPassword)
+                }
+            "#,
+            "variables": {
+                "token": token,
+                "newPassword": new_password
+            }
+        })
+    };
+    let login = |password: &str| {
+        json!({
+            "query": r#"
+                mutation IT_LOGIN($email: String!, $password: String!) {
+                    loginV2(email: $email, password: $password) {
+                        accessToken
+                    }
+                }
+            "#,
+            "variables": {
+                "email": email,
+                "password": password
+            }
+        })
+    };
+    let viewer = || json!({ "query": "query IT_VIEWER { viewer { email } }" });
+    let signup = json!({
+        "query": r#"
+            mutation IT_SIGNUP($input: SignupInput!) {
+                signup(input: $input)
+            }
+        "#,
+        "variables": {
+            "input": {
+                "email": email,
+                "password": "hihihihi"
+            }
+        }
+    });
+    let token = send(signup).await.data.unwrap()["signup"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    /* --- requestPasswordReset shouldn't tell whether the email exists --- */
+    // Act
+    let unknown = send(request_password_reset(&unknown_email)).await;
+    let known = send(request_password_reset(&email)).await;
+
+    // Assert
+    assert!(unknown.errors.is_none(), "{:?}", unknown.errors);
+    assert!(known.errors.is_none(), "{:?}", known.errors);
+    assert_eq!(unknown.data, known.data);
+    let reset_token = password_reset_token(&app.outbox, &email, 1).await;
+    assert!(app.outbox.emails_to(&unknown_email).unwrap().is_empty());
+
+    /* --- Only the last token emailed should be usable --- */
+    // Act
+    send(request_password_reset(&email)).await;
+    let earlier_token = reset_token;
+    let reset_token = password_reset_token(&app.outbox, &email, 2).await;
+    let earlier = send(reset_password(&earlier_token, "hahahaha")).await;
+
+    // Assert
+    assert_eq!(
+        "INVALID_TOKEN",
+        earlier.errors.unwrap()[0]["extensions"]["code"]
+    );
+
+    /* --- resetPassword should check the token and the password --- */
+    // Act
+    let invalid = send(reset_password(&reset_token, "haha")).await;
+    let forged = send(reset_password(&format!("{}x", reset_token), "hahahaha")).await;
+
+    // Assert
+    assert_eq!(
+        "INVALID_PASSWORD",
+        invalid.errors.unwrap()[0]["extensions"]["code"]
+    );
+    assert_eq!(
+        "INVALID_TOKEN",
+        forged.errors.unwrap()[0]["extensions"]["code"]
+    );
+
+    /* --- resetPassword --- */
+    // Act
+    let res = send(reset_password(&reset_token, "hahahaha")).await;
+    let reused = send(reset_password(&reset_token, "hohohoho")).await;
+
+    // Assert
+    assert!(res.errors.is_none(), "{:?}", res.errors);
+    assert_eq!(
+        "INVALID_TOKEN",
+        reused.errors.unwrap()[0]["extensions"]["code"]
+    );
+    assert_eq!(401, client.status(viewer(), &token).await);
+    assert!(send(login("hihihihi")).await.errors.is_some());
+    assert!(send(login("hahahaha")).await.errors.is_none());
+}
+
+#[actix_rt::test]
+async fn invitations_should_need_verified_emails() {
+    let app = helpers::spawn_app();
+    let client = GraphQLClient::new(format!("{}/graphql", app.address));
+    let email = format!("{}@htest.com", helpers::rand_string());
+    let token = client.signup_as(&email).await;
+    let group = client.add_group(&token, &["Alice"]).await;
+    let create_invitation = || {
+        json!({
+            "query": r#"
+                mutation IT_CREATE_INVITATION($input: CreateInvitationInput!) {
+                    createInvitation(input: $input)
+                }
+            "#,
+            "variables": {
+                "input": {
+                    "groupId": group.id,
+                    "expiresIn": 3600
+                }
+            }
+        })
+    };
+    let verify_email = |token: &str| {
+        json!({
+            "query": r#"
+                mutation IT_VERIFY_EMAIL($token: String!) {
+                    verifyEmail(token: $token)
+                }
+            "#,
+            "variables": {
+                "token": token
+            }
+        })
+    };
+    let viewer = || json!({ "query": "query IT_VIEWER { viewer { emailVerifiedAt } }" });
+
+    /* --- Unverified users can't invite --- */
+    // Act
+    let code = client.error_code(create_invitation(), &token).await;
+
+    // Assert
+    assert_eq!("UNVERIFIED_EMAIL", code);
+    let res = client.execute::<serde_json::Value>(viewer(), &token).await;
+    assert_eq!(serde_json::Value::Null, res["viewer"]["emailVerifiedAt"]);
+
+    /* --- verifyEmail --- */
+    // Act
+    client
+        .execute::<serde_json::Value>(
+            json!({ "query": "mutation IT_SEND_VERIFICATION_EMAIL { sendVerificationEmail }" }),
+            &token,
+        )
+        .await;
+    let forged = client
+        .error_code(
+            verify_email(&format!("{}x", verification_token(&email))),
+            &token,
+        )
+        .await;
+    client
+        .execute::<serde_json::Value>(verify_email(&verification_token(&email)), &token)
+        .await;
+
+    // Assert
+    assert_eq!(2, app.outbox.emails_to(&email).unwrap().len());
+    assert_eq!("INVALID_TOKEN", forged);
+    let res = client.execute::<serde_json::Value>(viewer(), &token).await;
+    assert!(res["viewer"]["emailVerifiedAt"].is_string());
+
+    /* --- Unverified users can't be invited --- */
+    // Arrange
+    let invitation = client
+        .execute::<serde_json::Value>(create_invitation(), &token)
+        .await["createInvitation"]
+        .clone();
+    let accept_invitation = json!({
+        "query": r#"
+            mutation IT_ACCEPT_INVITATION($input: AcceptInvitationInput!) {
+                acceptInvitation(input: $input)
+            }
+        "#,
+        "variables": {
+            "input": {
+                "token": invitation
+            }
+        }
+    });
+    let other_email = format!("{}@htest.com", helpers::rand_string());
+    let other_token = client.signup_as(&other_email).await;
+
+    // Act
+    let code = client
+        .error_code(accept_invitation.clone(), &other_token)
+        .await;
+    client
+        .execute::<serde_json::Value>(
+            verify_email(&verification_token(&other_email)),
+            &other_token,
+        )
+        .await;
+
+    // Assert
+    assert_eq!("UNVERIFIED_EMAIL", code);
+    client
+        .execute::<serde_json::Value>(accept_invitation, &other_token)
+        .await;
+
+    /* --- A new email has to be verified again --- */
+    // Arrange
+    let new_email = format!("{}@htest.com", helpers::rand_string());
+
+    // Act
+    client
+        .execute::<serde_json::Value>(
+            json!({
+                "query": r#"
+                    mutation IT_CHANGE_EMAIL($password: String!, $newEmail: String!) {
+                        changeEmail(password: $password, newEmail: $newEmail)
+                    }
+                "#,
+                "variables": {
+                    "password": "hihihihi",
+                    "newEmail": new_email
+                }
+            }),
+            &token,
+        )
+        .await;
+
+    // Assert
+    let res = client.execute::<serde_json::Value>(viewer(), &token).await;
+    assert_eq!(serde_json::Value::Null, res["viewer"]["emailVerifiedAt"]);
+    assert_eq!(
+        "UNVERIFIED_EMAIL",
+        client.error_code(create_invitation(), &token).await
+    );
+    assert_eq!(1, app.outbox.emails_to(&new_email).unwrap().len());
+}
+
+/// The token of the last password reset email sent to an address, once it was sent this many.
+/// They're sent in the background, after the request was answered.
+async fn password_reset_token(
+    outbox: &group_expenses::OutboxMailer,
+    email: &str,
+    count: usize,
+) -> String {
+    for _ in 0..100 {
+        // The user was also sent a verification email when signing up
+        let emails = outbox
+            .emails_to(email)
+            .unwrap()
+            .into_iter()
+            .filter(|e| e.subject == "Reset your password")
+            .collect::<Vec<_>>();
+        if emails.len() >= count {
+            assert_eq!(count, emails.len());
+            // The token is alone on its line
+            return emails[count - 1]
+                .body
+                .lines()
+                .find(|l| !l.is_empty() && !l.contains(' '))
+                .expect("The email should contain a token")
+                .to_string();
+        }
+        actix_rt::time::delay_for(std::time::Duration::from_millis(50)).await;
+    }
+    panic!("No password reset email was sent to this address");
+}
+
+/// The token of the last email verification link sent to an address.
+fn verification_token(email: &str) -> String {
+    let emails = helpers::spawn_app().outbox.emails_to(email).unwrap();
+    let body = &emails
+        .last()
+        .expect("No email was sent to this address")
+        .body;
+    body.split("token=")
+        .nth(1)
+        .and_then(|t| t.split_whitespace().next())
+        .expect("The email should contain a verification link")
+        .to_string()
+}
+
+#[derive(serde::Deserialize)]
+struct GraphQLResponse<T> {
+    data: Option<T>,
+    errors: Option<Vec<serde_json::Value>>,
+}
+
+#[derive(serde::Deserialize)]
+struct Signup {
+    signup: String,
+}
+
+#[derive(serde::Deserialize)]
+struct Login {
+    login: String,
+}
+
+#[derive(serde::Deserialize)]
+struct Viewer {
+    viewer: User,
+}
+
+#[derive(serde::Deserialize)]
+struct GroupQuery {
+    group: Group,
+}
+
+#[derive(serde::Deserialize)]
+struct User {
+    groups: Vec<Group>,
+}
+
+#[allow(dead_code)]
+#[derive(serde::Deserialize)]
+struct Group {
+    id: uuid::Uuid,
+    name: String,
+    #[serde(default)]
+    currency: String,
+    #[serde(default, deserialize_with = "minor_units")]
+    total: i64,
+    persons: Vec<Person>,
+    expenses: Vec<Expense>,
+    #[serde(default)]
+    balances: Vec<Balance>,
+    #[serde(default, rename = "settlementPlan")]
+    settlement_plan: Vec<Transfer>,
+    #[serde(default)]
+    payments: Vec<Payment>,
+}
+
+#[allow(dead_code)]
+#[derive(serde::Deserialize)]
+struct Person {
+    id: uuid::Uuid,
+    name: String,
+    #[serde(deserialize_with = "minor_units")]
+    resources: i64,
+    expenses: Vec<Expense>,
+}
+
+#[allow(dead_code)]
+#[derive(serde::Deserialize)]
+struct Balance {
+    person: PersonRef,
+    #[serde(deserialize_with = "minor_units")]
+    paid: i64,
+    #[serde(deserialize_with = "minor_units")]
+    owed: i64,
+    #[serde(default, deserialize_with = "minor_units")]
+    sent: i64,
+    #[serde(default, deserialize_with = "minor_units")]
+    received: i64,
+    #[serde(deserialize_with = "minor_units")]
+    net: i64,
+}
+
+#[allow(dead_code)]
+#[derive(serde::Deserialize)]
+struct Money {
+    amount: String,
+    #[serde(default)]
+    currency: String,
+}
+
+/// Read a `Money` object as its amount in minor units.
+fn minor_units<'de, D>(deserializer: D) -> Result<i64, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let money = <Money as serde::Deserialize>::deserialize(deserializer)?;
+    money.amount.parse().map_err(serde::de::Error::custom)
+}
+
+#[derive(serde::Deserialize)]
+struct PersonRef {
+    id: uuid::Uuid,
+}
+
+#[allow(dead_code)]
+#[derive(serde::Deserialize)]
+struct Transfer {
+    from: PersonRef,
+    to: PersonRef,
+    #[serde(deserialize_with = "minor_units")]
+    amount: i64,
+}
+
+#[allow(dead_code)]
+#[derive(serde::Deserialize)]
+struct Payment {
+    id: uuid::Uuid,
+    from: PersonRef,
+    to: PersonRef,
+    #[serde(deserialize_with = "minor_units")]
+    amount: i64,
+}
+
+#[allow(dead_code)]
+#[derive(serde::Deserialize)]
+struct Expense {
+    id: uuid::Uuid,
+    name: String,
+    amount: Money,
+    #[serde(default, rename = "exchangeRate")]
+    exchange_rate: f64,
+    #[serde(default)]
+    payers: Vec<ExpensePayer>,
+}
+
+#[allow(dead_code)]
+#[derive(serde::Deserialize)]
+struct ExpensePayer {
+    person: PersonRef,
+    #[serde(deserialize_with = "minor_units")]
+    amount: i64,
+}
+
+#[allow(dead_code)]
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AddGroup {
+    add_group: bool,
+}
+
+#[allow(dead_code)]
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UpdateGroup {
+    update_group: bool,
+}
+
+#[allow(dead_code)]
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AddPerson {
+    add_person: bool,
+}
+
+#[allow(dead_code)]
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UpdatePerson {
+    update_person: bool,
+}
+
+#[allow(dead_code)]
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
 struct AddExpense {
     add_expense: bool,
 }
@@ -588,3 +3802,30 @@ struct RemovePerson {
 struct RemoveGroup {
     remove_group: bool,
 }
+
+#[allow(dead_code)]
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AddPayment {
+    add_payment: bool,
+}
+
+#[allow(dead_code)]
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RemovePayment {
+    remove_payment: bool,
+}
+
+#[allow(dead_code)]
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SetExchangeRate {
+    set_exchange_rate: bool,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ImportExchangeRates {
+    import_exchange_rates: i32,
+}